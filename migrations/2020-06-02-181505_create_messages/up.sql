@@ -0,0 +1,13 @@
+-- Player-to-player messages
+
+CREATE TABLE messages (
+    id BIGSERIAL PRIMARY KEY,
+    sender_id BIGINT NULL REFERENCES players(id) ON DELETE SET NULL,
+    receiver_id BIGINT NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    sent TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    read BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX messages_receiver_idx ON messages (receiver_id);
+CREATE INDEX messages_sender_sent_idx ON messages (sender_id, sent);
@@ -0,0 +1,2 @@
+DROP INDEX players_karma_idx;
+DROP TABLE player_season_stats;
@@ -0,0 +1,13 @@
+-- Scores for seasonal leaderboards and indices for ranking queries
+
+CREATE TABLE player_season_stats (
+    player_id BIGINT NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    season INT NOT NULL,
+    karma BIGINT NOT NULL DEFAULT 0,
+    visitors_welcomed BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (player_id, season)
+);
+
+CREATE INDEX player_season_stats_karma_idx ON player_season_stats (season, karma);
+CREATE INDEX player_season_stats_visitors_idx ON player_season_stats (season, visitors_welcomed);
+CREATE INDEX players_karma_idx ON players (karma);
@@ -0,0 +1,11 @@
+-- Chunks of the map that have been generated already.
+-- A row is inserted before the streams of a chunk are generated, which
+-- prevents generating the same chunk twice.
+CREATE TABLE map_chunks (
+    id INTEGER PRIMARY KEY,
+    generated TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Maps generated before had a fixed size, they are registered as chunks of width 20
+INSERT INTO map_chunks (id)
+SELECT DISTINCT FLOOR(start_x / 20.0)::INTEGER FROM streams;
@@ -0,0 +1,2 @@
+DROP INDEX players_last_active_idx;
+ALTER TABLE players DROP COLUMN last_active;
@@ -0,0 +1,2 @@
+ALTER TABLE players ADD COLUMN last_active TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP;
+CREATE INDEX players_last_active_idx ON players (last_active);
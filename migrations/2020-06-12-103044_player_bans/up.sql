@@ -0,0 +1 @@
+ALTER TABLE players ADD COLUMN banned BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+DROP TABLE completed_quests;
+DROP TYPE QUEST_NAME;
@@ -0,0 +1,13 @@
+CREATE TYPE QUEST_NAME AS ENUM (
+  'plant_trees',
+  'welcome_visitors',
+  'build_saw_mill',
+  'gather_karma'
+);
+
+CREATE TABLE completed_quests (
+    player_id BIGINT NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    quest QUEST_NAME NOT NULL,
+    completed TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (player_id, quest)
+);
@@ -0,0 +1,4 @@
+DROP TABLE replay_buildings;
+DROP TABLE replay_effects;
+DROP TABLE replay_hobos;
+DROP TABLE visit_replays;
@@ -0,0 +1,39 @@
+-- Archive of finished visits, to watch them again later
+
+CREATE TABLE visit_replays (
+    id BIGSERIAL PRIMARY KEY,
+    village_id BIGINT NOT NULL REFERENCES villages(id) ON DELETE CASCADE,
+    arrival TIMESTAMP NOT NULL,
+    finished TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE replay_hobos (
+    id BIGSERIAL PRIMARY KEY,
+    replay_id BIGINT NOT NULL REFERENCES visit_replays(id) ON DELETE CASCADE,
+    color UNIT_COLOR,
+    speed REAL NOT NULL,
+    hp BIGINT NOT NULL,
+    hurried BOOLEAN NOT NULL,
+    released TIMESTAMP,
+    satisfied BOOLEAN NOT NULL
+);
+
+CREATE TABLE replay_effects (
+    id BIGSERIAL PRIMARY KEY,
+    replay_hobo_id BIGINT NOT NULL REFERENCES replay_hobos(id) ON DELETE CASCADE,
+    attribute HOBO_ATTRIBUTE_TYPE NOT NULL,
+    strength INT,
+    start_time TIMESTAMP NOT NULL
+);
+
+CREATE TABLE replay_buildings (
+    id BIGSERIAL PRIMARY KEY,
+    replay_id BIGINT NOT NULL REFERENCES visit_replays(id) ON DELETE CASCADE,
+    x INT NOT NULL,
+    y INT NOT NULL,
+    building_type BUILDING_TYPE NOT NULL,
+    building_range REAL,
+    attack_power INT,
+    attacks_per_cycle INT,
+    creation TIMESTAMP NOT NULL
+);
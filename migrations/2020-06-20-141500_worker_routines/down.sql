@@ -0,0 +1,3 @@
+DROP TABLE routine_tasks;
+DROP TABLE worker_routines;
+DROP TYPE TASK_CONDITION;
@@ -0,0 +1,20 @@
+-- Routines are cycles of tasks that workers repeat until they get new orders
+
+CREATE TYPE TASK_CONDITION AS ENUM ('resting_visitor', 'unwelcomed_visitor');
+
+CREATE TABLE worker_routines (
+    worker_id BIGINT PRIMARY KEY REFERENCES workers(id) ON DELETE CASCADE,
+    next_step INT NOT NULL DEFAULT 0,
+    next_step_time TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE routine_tasks (
+    worker_id BIGINT NOT NULL REFERENCES worker_routines(worker_id) ON DELETE CASCADE,
+    step INT NOT NULL,
+    task_type TASK_TYPE NOT NULL,
+    x INT NOT NULL,
+    y INT NOT NULL,
+    duration BIGINT,
+    task_condition TASK_CONDITION,
+    PRIMARY KEY (worker_id, step)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE hobos
+    DROP COLUMN last_production;
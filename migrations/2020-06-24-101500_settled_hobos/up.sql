@@ -0,0 +1,2 @@
+ALTER TABLE hobos
+ADD COLUMN last_production TIMESTAMP NULL DEFAULT NULL;
@@ -0,0 +1,3 @@
+DELETE FROM pg_enum
+    WHERE pg_enum.enumtypid = 'task_type'::regtype::oid
+    AND pg_enum.enumlabel = 'plant_tree';
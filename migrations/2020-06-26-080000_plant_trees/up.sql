@@ -0,0 +1,3 @@
+INSERT INTO pg_enum (enumtypid, enumlabel, enumsortorder)
+    SELECT 'task_type'::regtype::oid, 'plant_tree', 
+    ( SELECT MAX(enumsortorder) + 1 FROM pg_enum WHERE enumtypid = 'task_type'::regtype );
@@ -0,0 +1 @@
+CREATE UNIQUE INDEX villages_position_idx ON villages (x, y);
@@ -0,0 +1,2 @@
+DROP INDEX players_referral_code_idx;
+ALTER TABLE players DROP COLUMN referral_code;
@@ -0,0 +1,4 @@
+ALTER TABLE players ADD COLUMN referral_code VARCHAR;
+UPDATE players SET referral_code = upper(substr(md5(random()::text || id::text), 1, 12));
+ALTER TABLE players ALTER COLUMN referral_code SET NOT NULL;
+CREATE UNIQUE INDEX players_referral_code_idx ON players (referral_code);
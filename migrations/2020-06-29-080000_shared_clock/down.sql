@@ -0,0 +1 @@
+DROP TABLE shared_clock;
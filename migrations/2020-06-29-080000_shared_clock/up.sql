@@ -0,0 +1,5 @@
+CREATE TABLE shared_clock (
+    id INT PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    advanced_us BIGINT NOT NULL DEFAULT 0
+);
+INSERT INTO shared_clock DEFAULT VALUES;
@@ -0,0 +1,6 @@
+DROP INDEX player_season_stats_karma_idx;
+DROP INDEX player_season_stats_visitors_idx;
+DROP INDEX players_karma_idx;
+CREATE INDEX player_season_stats_karma_idx ON player_season_stats (season, karma);
+CREATE INDEX player_season_stats_visitors_idx ON player_season_stats (season, visitors_welcomed);
+CREATE INDEX players_karma_idx ON players (karma);
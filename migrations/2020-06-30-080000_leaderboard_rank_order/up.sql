@@ -0,0 +1,9 @@
+-- Indices in the order of the leaderboards, ties are broken by player id.
+-- Ranks are counted on these without sorting the entire leaderboard.
+
+DROP INDEX player_season_stats_karma_idx;
+DROP INDEX player_season_stats_visitors_idx;
+DROP INDEX players_karma_idx;
+CREATE INDEX player_season_stats_karma_idx ON player_season_stats (season, karma DESC, player_id);
+CREATE INDEX player_season_stats_visitors_idx ON player_season_stats (season, visitors_welcomed DESC, player_id);
+CREATE INDEX players_karma_idx ON players (karma DESC, id);
@@ -5,6 +5,7 @@ use chrono::prelude::NaiveDateTime;
 use juniper;
 use juniper::FieldResult;
 use paddlers_shared_lib::graphql_types::*;
+use paddlers_shared_lib::leaderboard::*;
 use paddlers_shared_lib::prelude::*;
 use paddlers_shared_lib::user_authentication::PadlUser;
 use std::sync::Arc;
@@ -35,16 +36,26 @@ pub enum Context {
 impl juniper::Context for Context {}
 
 impl Context {
-    pub fn new(db: DbConn, user: Option<PadlUser>) -> Option<Self> {
+    pub fn new(db: DbConn, user: Option<PadlUser>) -> Result<Self, PadlApiError> {
         let conn = Arc::new(db);
         if let Some(user) = user {
-            let player = conn.player_by_uuid(user.uuid)?;
-            let vids = conn
+            let player = conn
+                .player_by_uuid(user.uuid)
+                .ok_or(PadlApiError::PlayerNotCreated)?;
+            if player.banned {
+                return Err(PadlApiError::PlayerBanned);
+            }
+            conn.record_activity(&player);
+            let vids: Vec<VillageKey> = conn
                 .player_villages(player.key())
                 .into_iter()
                 .map(|v| v.key())
                 .collect();
-            Some(Context::Authenticated(AuthenticatedContext {
+            // Players whose villages have been abandoned need to be settled again
+            if vids.is_empty() {
+                return Err(PadlApiError::PlayerNotCreated);
+            }
+            Ok(Context::Authenticated(AuthenticatedContext {
                 db: conn,
                 user: player,
                 villages: vids,
@@ -53,11 +64,11 @@ impl Context {
             #[cfg(feature = "local")]
             {
                 let tester_context = Self::tester_context(conn.clone());
-                if tester_context.is_some() {
-                    return tester_context;
+                if let Some(tester_context) = tester_context {
+                    return Ok(tester_context);
                 }
             }
-            Some(Context::Public(UnauthenticatedContext { db: conn }))
+            Ok(Context::Public(UnauthenticatedContext { db: conn }))
         }
     }
     #[cfg(feature = "local")]
@@ -153,11 +164,48 @@ impl Query {
     // Returns up to 100 players starting from the given rank upwards
     fn scoreboard(ctx: &Context, rank_offset: i32) -> Vec<GqlPlayer> {
         ctx.db()
-            .players_sorted_by_karma(rank_offset as i64, 100)
+            .players_sorted_by_karma(rank_offset.max(0) as i64, 100)
             .into_iter()
             .map(GqlPlayer)
             .collect()
     }
+    // Object Visibility: public
+    // Returns a page of the leaderboard of the current season, starting from the given rank upwards
+    fn leaderboard(
+        ctx: &Context,
+        category: LeaderboardCategory,
+        rank_offset: i32,
+        limit: Option<i32>,
+    ) -> Vec<GqlLeaderboardEntry> {
+        // Negative values are not accepted by SQL
+        let rank_offset = i64::from(rank_offset.max(0));
+        let limit = limit
+            .map(i64::from)
+            .unwrap_or(LEADERBOARD_PAGE_SIZE)
+            .max(0)
+            .min(LEADERBOARD_MAX_PAGE_SIZE);
+        let season = season_of(paddlers_shared_lib::game_clock::now());
+        ctx.db()
+            .leaderboard(category, season, rank_offset, limit)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (player, score))| GqlLeaderboardEntry {
+                rank: rank_offset + i as i64 + 1,
+                player: GqlPlayer(player),
+                score,
+            })
+            .collect()
+    }
+    // Object Visibility: user
+    // Rank of the authenticated player in the current season, null if the player has no score
+    fn my_rank(ctx: &Context, category: LeaderboardCategory) -> FieldResult<Option<i32>> {
+        let player = ctx.authenticated()?.user.key();
+        let season = season_of(paddlers_shared_lib::game_clock::now());
+        Ok(ctx
+            .db()
+            .leaderboard_rank(category, season, player)
+            .map(|rank| rank as i32))
+    }
 }
 
 #[juniper::object(
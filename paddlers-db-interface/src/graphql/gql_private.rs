@@ -5,6 +5,7 @@
 use super::*;
 use juniper;
 use juniper::FieldResult;
+use paddlers_shared_lib::story::quest::QuestName;
 
 #[juniper::object (Context = Context)]
 impl GqlAttack {
@@ -94,6 +95,135 @@ impl GqlAttackReport {
     }
 }
 
+#[juniper::object (Context = Context)]
+impl GqlMessage {
+    fn id(&self) -> juniper::ID {
+        self.0.id.to_string().into()
+    }
+    fn sender(&self, ctx: &Context) -> Option<GqlPlayer> {
+        self.0
+            .sender()
+            .and_then(|pid| ctx.db().player(pid))
+            .map(GqlPlayer)
+    }
+    fn body(&self) -> &str {
+        &self.0.body
+    }
+    fn sent(&self) -> FieldResult<GqlTimestamp> {
+        datetime(&self.0.sent)
+    }
+    fn read(&self) -> bool {
+        self.0.read
+    }
+}
+
+#[juniper::object (Context = Context)]
+impl GqlQuest {
+    fn name(&self) -> QuestName {
+        self.name
+    }
+    fn completed(&self) -> bool {
+        self.completed
+    }
+    fn objectives(&self) -> Vec<GqlQuestObjective> {
+        self.objectives.clone()
+    }
+}
+
+#[juniper::object (Context = Context)]
+impl GqlVisitReplay {
+    fn id(&self) -> juniper::ID {
+        self.0.id.to_string().into()
+    }
+    fn arrival(&self) -> FieldResult<GqlTimestamp> {
+        datetime(&self.0.arrival)
+    }
+    fn finished(&self) -> FieldResult<GqlTimestamp> {
+        datetime(&self.0.finished)
+    }
+    fn hobos(&self, ctx: &Context) -> Vec<GqlReplayHobo> {
+        ctx.db()
+            .replay_hobos(self.0.key())
+            .into_iter()
+            .map(GqlReplayHobo::authorized) // Inherited authorization
+            .collect()
+    }
+    fn buildings(&self, ctx: &Context) -> Vec<GqlReplayBuilding> {
+        ctx.db()
+            .replay_buildings(self.0.key())
+            .into_iter()
+            .map(GqlReplayBuilding::authorized) // Inherited authorization
+            .collect()
+    }
+}
+
+#[juniper::object (Context = Context)]
+impl GqlReplayHobo {
+    fn color(&self) -> &Option<paddlers_shared_lib::models::UnitColor> {
+        &self.0.color
+    }
+    fn hp(&self) -> i32 {
+        self.0.hp as i32
+    }
+    fn speed(&self) -> f64 {
+        self.0.speed as f64
+    }
+    fn hurried(&self) -> bool {
+        self.0.hurried
+    }
+    fn released(&self) -> Option<GqlTimestamp> {
+        self.0.released.as_ref().map(GqlTimestamp::from_chrono)
+    }
+    fn satisfied(&self) -> bool {
+        self.0.satisfied
+    }
+    fn effects(&self, ctx: &Context) -> Vec<GqlReplayEffect> {
+        ctx.db()
+            .replay_effects(self.0.key())
+            .into_iter()
+            .map(GqlReplayEffect::authorized) // Inherited authorization
+            .collect()
+    }
+}
+
+#[juniper::object (Context = Context)]
+impl GqlReplayEffect {
+    fn attribute(&self) -> &paddlers_shared_lib::models::HoboAttributeType {
+        &self.0.attribute
+    }
+    fn start_time(&self) -> GqlTimestamp {
+        GqlTimestamp::from_chrono(&self.0.start_time)
+    }
+    fn strength(&self) -> Option<i32> {
+        self.0.strength
+    }
+}
+
+#[juniper::object (Context = Context)]
+impl GqlReplayBuilding {
+    fn x(&self) -> i32 {
+        self.0.x
+    }
+    fn y(&self) -> i32 {
+        self.0.y
+    }
+    fn building_type(&self) -> &paddlers_shared_lib::models::BuildingType {
+        &self.0.building_type
+    }
+    fn building_range(&self) -> Option<f64> {
+        self.0.building_range.map(f64::from)
+    }
+    fn attack_power(&self) -> Option<f64> {
+        self.0.attack_power.map(f64::from)
+    }
+    fn attacks_per_cycle(&self) -> Option<i32> {
+        self.0.attacks_per_cycle
+    }
+    fn creation(&self) -> FieldResult<GqlTimestamp> {
+        datetime(&self.0.creation)
+    }
+}
+
 #[juniper::object (Context = Context)]
 impl GqlTask {
     fn id(&self) -> juniper::ID {
@@ -21,7 +21,9 @@ use super::*;
 use juniper;
 use juniper::FieldResult;
 use paddlers_shared_lib::sql_db::keys::SqlKey;
+use paddlers_shared_lib::story::quest::{QuestName, QuestProgress};
 use paddlers_shared_lib::story::story_state::StoryState;
+use paddlers_shared_lib::strum::IntoEnumIterator;
 
 // Complete list of fully public objects without private sub fields.
 pub struct GqlMapSlice {
@@ -31,6 +33,18 @@ pub struct GqlMapSlice {
 pub struct GqlStream(pub paddlers_shared_lib::models::Stream);
 pub struct GqlHobo(pub paddlers_shared_lib::models::Hobo);
 pub struct GqlAttackUnit(pub GqlHobo, pub GqlHoboAttackInfo);
+/// A single row of a leaderboard
+pub struct GqlLeaderboardEntry {
+    pub rank: i64,
+    pub player: GqlPlayer,
+    pub score: i64,
+}
+/// Progress towards a single objective of a quest
+#[derive(Clone)]
+pub struct GqlQuestObjective {
+    pub current: i64,
+    pub target: i64,
+}
 /// Additional information for a hobo that is currently attacking
 pub struct GqlHoboAttackInfo(pub paddlers_shared_lib::models::AttackToHobo);
 
@@ -53,7 +67,18 @@ pub struct GqlAttackReport {
     _priv: PrivacyGuard,
 }
 pub struct GqlEffect(pub paddlers_shared_lib::models::Effect, PrivacyGuard);
+pub struct GqlMessage(pub paddlers_shared_lib::models::PlayerMessage, PrivacyGuard);
+pub struct GqlReplayBuilding(pub paddlers_shared_lib::models::ReplayBuilding, PrivacyGuard);
+pub struct GqlReplayEffect(pub paddlers_shared_lib::models::ReplayEffect, PrivacyGuard);
+pub struct GqlReplayHobo(pub paddlers_shared_lib::models::ReplayHobo, PrivacyGuard);
+pub struct GqlQuest {
+    pub name: QuestName,
+    pub completed: bool,
+    pub objectives: Vec<GqlQuestObjective>,
+    _priv: PrivacyGuard,
+}
 pub struct GqlTask(pub paddlers_shared_lib::models::Task, PrivacyGuard);
+pub struct GqlVisitReplay(pub paddlers_shared_lib::models::VisitReplay, PrivacyGuard);
 pub struct GqlWorker(pub paddlers_shared_lib::models::Worker, PrivacyGuard);
 
 #[juniper::object (Context = Context)]
@@ -90,6 +115,43 @@ impl GqlPlayer {
         ctx.check_user_key(self.0.key())?;
         Ok(self.0.story_state)
     }
+    #[graphql(arguments(min_id(
+        description = "Response only contains messages with id >= min_id",
+    )))]
+    /// Messages received from other players
+    /// Field Visibility: user
+    fn messages(&self, ctx: &Context, min_id: Option<i32>) -> FieldResult<Vec<GqlMessage>> {
+        ctx.check_user_key(self.0.key())?;
+        Ok(ctx
+            .db()
+            .messages(self.0.key(), min_id.map(i64::from))
+            .into_iter()
+            .map(GqlMessage::authorized)
+            .collect())
+    }
+    /// Field Visibility: user
+    fn unread_message_count(&self, ctx: &Context) -> FieldResult<i32> {
+        ctx.check_user_key(self.0.key())?;
+        Ok(ctx.db().unread_messages_count(self.0.key()) as i32)
+    }
+    /// Quests which are available to the player or have been completed already
+    /// Field Visibility: user
+    fn quests(&self, ctx: &Context) -> FieldResult<Vec<GqlQuest>> {
+        ctx.check_user_key(self.0.key())?;
+        let db = ctx.db();
+        let completed = db.completed_quests(self.0.key());
+        let progress = db.quest_progress(&self.0);
+        Ok(QuestName::iter()
+            .filter(|q| completed.contains(q) || q.is_available(self.0.story_state, &completed))
+            .map(|q| GqlQuest::authorized(q, completed.contains(&q), &progress))
+            .collect())
+    }
+    /// Code to invite other players, their villages are placed close to the own villages
+    /// Field Visibility: user
+    fn referral_code(&self, ctx: &Context) -> FieldResult<String> {
+        ctx.check_user_key(self.0.key())?;
+        Ok(self.0.referral_code.clone())
+    }
 }
 
 #[juniper::object (Context = Context)]
@@ -194,6 +256,17 @@ impl GqlVillage {
             })
             .collect())
     }
+    /// Archived visits that can be watched again, latest first
+    /// Field Visibility: user
+    fn replays(&self, ctx: &Context) -> FieldResult<Vec<GqlVisitReplay>> {
+        ctx.check_village_key(self.0.key())?;
+        Ok(ctx
+            .db()
+            .visit_replays(self.0.key())
+            .into_iter()
+            .map(GqlVisitReplay::authorized)
+            .collect())
+    }
 }
 
 #[juniper::object (Context = Context)]
@@ -269,6 +342,34 @@ impl GqlHobo {
     }
 }
 
+#[juniper::object (Context = Context)]
+impl GqlLeaderboardEntry {
+    /// Field Visibility: public
+    fn rank(&self) -> i32 {
+        self.rank as i32
+    }
+    /// Field Visibility: public
+    fn player(&self) -> &GqlPlayer {
+        &self.player
+    }
+    /// Field Visibility: public
+    fn score(&self) -> GqlBigInt {
+        GqlBigInt(self.score)
+    }
+}
+
+#[juniper::object (Context = Context)]
+impl GqlQuestObjective {
+    /// Field Visibility: public
+    fn current(&self) -> i32 {
+        self.current as i32
+    }
+    /// Field Visibility: public
+    fn target(&self) -> i32 {
+        self.target as i32
+    }
+}
+
 /**
  * Map data
  */
@@ -324,11 +425,55 @@ impl GqlEffect {
         GqlEffect(inner, PrivacyGuard)
     }
 }
+impl GqlMessage {
+    pub(super) fn authorized(inner: paddlers_shared_lib::models::PlayerMessage) -> Self {
+        GqlMessage(inner, PrivacyGuard)
+    }
+}
+impl GqlQuest {
+    pub(super) fn authorized(name: QuestName, completed: bool, progress: &QuestProgress) -> Self {
+        let objectives = name
+            .definition()
+            .objectives
+            .iter()
+            .map(|o| GqlQuestObjective {
+                current: o.current(progress),
+                target: o.target(),
+            })
+            .collect();
+        GqlQuest {
+            name,
+            completed,
+            objectives,
+            _priv: PrivacyGuard,
+        }
+    }
+}
+impl GqlReplayBuilding {
+    pub(super) fn authorized(inner: paddlers_shared_lib::models::ReplayBuilding) -> Self {
+        GqlReplayBuilding(inner, PrivacyGuard)
+    }
+}
+impl GqlReplayEffect {
+    pub(super) fn authorized(inner: paddlers_shared_lib::models::ReplayEffect) -> Self {
+        GqlReplayEffect(inner, PrivacyGuard)
+    }
+}
+impl GqlReplayHobo {
+    pub(super) fn authorized(inner: paddlers_shared_lib::models::ReplayHobo) -> Self {
+        GqlReplayHobo(inner, PrivacyGuard)
+    }
+}
 impl GqlTask {
     pub(super) fn authorized(inner: paddlers_shared_lib::models::Task) -> Self {
         GqlTask(inner, PrivacyGuard)
     }
 }
+impl GqlVisitReplay {
+    pub(super) fn authorized(inner: paddlers_shared_lib::models::VisitReplay) -> Self {
+        GqlVisitReplay(inner, PrivacyGuard)
+    }
+}
 impl GqlWorker {
     pub(in crate::graphql) fn authorized(inner: paddlers_shared_lib::models::Worker) -> Self {
         GqlWorker(inner, PrivacyGuard)
@@ -9,6 +9,7 @@ use rocket::Outcome;
 use rocket::State;
 
 use crate::graphql::Schema;
+use paddlers_shared_lib::game_clock;
 use paddlers_shared_lib::prelude::{Config, PadlApiError};
 use paddlers_shared_lib::user_authentication::*;
 
@@ -53,20 +54,23 @@ fn generic_graphql_handler(
     schema: State<Schema>,
     user_info: UserInfo,
 ) -> GraphQLResponse {
-    if let Some(player_ctx) = crate::graphql::Context::new(connection, user_info.user) {
-        request.execute(&schema, &player_ctx)
-    } else {
-        // Lookup error code from shared lib that frontend understands
-        let n = PadlApiError::PlayerNotCreated as i32;
-        // Create a GQL error
-        let err = FieldError::new("Player is not in DB", graphql_value!({ "padlcode": n }));
-        // Pack GQL Error into a GQL response
-        // Note: Juniper will send this as BadRequest, although I think
-        //       the standard for GQL would be 200 OK
-        //       Either way, the HTTP code should not be considered by
-        //       the frontend too much, the errors in the response are
-        //       what really counts.
-        juniper_rocket::GraphQLResponse::error(err)
+    // The game master may have advanced the clock since the last request
+    game_clock::sync_server_clock(&connection);
+    match crate::graphql::Context::new(connection, user_info.user) {
+        Ok(player_ctx) => request.execute(&schema, &player_ctx),
+        Err(padl_err) => {
+            // Lookup error code from shared lib that frontend understands
+            let n = padl_err as i32;
+            // Create a GQL error
+            let err = FieldError::new(padl_err.to_string(), graphql_value!({ "padlcode": n }));
+            // Pack GQL Error into a GQL response
+            // Note: Juniper will send this as BadRequest, although I think
+            //       the standard for GQL would be 200 OK
+            //       Either way, the HTTP code should not be considered by
+            //       the frontend too much, the errors in the response are
+            //       what really counts.
+            juniper_rocket::GraphQLResponse::error(err)
+        }
     }
 }
 
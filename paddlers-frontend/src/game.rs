@@ -14,6 +14,7 @@ pub(crate) mod map;
 pub(crate) mod movement;
 pub(crate) mod net_receiver;
 pub(crate) mod player_info;
+pub(crate) mod settings;
 pub(crate) mod status_effects;
 pub(crate) mod story;
 pub(crate) mod town;
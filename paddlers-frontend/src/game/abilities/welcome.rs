@@ -35,7 +35,7 @@ pub fn use_welcome_ability<'a>(
     let strength = a.apply().1;
     let mana_cost = a.mana_cost();
 
-    h.make_happy(strength as i64, target, ep);
+    h.welcome(strength as i64, target, ep);
     se.add_health_reduction(strength);
     m.mana -= mana_cost;
 
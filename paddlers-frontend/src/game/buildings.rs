@@ -3,16 +3,19 @@ use crate::game::{
     components::*,
     fight::{Aura, Range},
     forestry::ForestComponent,
+    game_event_manager::GameEvent,
     input::Clickable,
     movement::Position,
     town::{TileIndex, Town},
 };
 use crate::gui::{render::Renderable, sprites::*, utils::*, z::Z_BUILDINGS};
 use crate::prelude::*;
+use paddlers_shared_lib::game_mechanics::worker::basic_worker_cost;
 use paddlers_shared_lib::prelude::*;
 use paddlers_shared_lib::{game_mechanics::attributes::Attributes, graphql_types::*};
 use specs::prelude::*;
 use specs::world::EntitiesRes;
+use std::collections::HashMap;
 
 #[derive(Debug, Component)]
 #[storage(HashMapStorage)]
@@ -91,6 +94,14 @@ impl Town {
             BuildingType::Tree => {
                 builder = builder.with(ForestComponent::new(created));
             }
+            BuildingType::Shack => {
+                // Hired workers come with the work ability, its icon stands for them
+                builder = builder.with(UiMenu::new_shop_menu().with_shop_item(
+                    GameEvent::HttpHireWorker,
+                    SpriteSet::Simple(SingleSprite::NewOrder),
+                    basic_worker_cost(),
+                ));
+            }
             _ => {}
         }
 
@@ -122,6 +133,40 @@ impl buildings_query::ResponseData {
             .map(|u| u.create_entity(town_context))
             .collect()
     }
+    /// Trees take the planting time recorded by the game-master, which has moved forward if they were chopped.
+    /// Trees that have been chopped down completely are removed.
+    pub(crate) fn update_forest(&self, world: &World) -> PadlResult<()> {
+        let planted: HashMap<TileIndex, Timestamp> = self
+            .village
+            .buildings
+            .iter()
+            .filter(|b| match b.building_type {
+                buildings_query::BuildingType::TREE => true,
+                _ => false,
+            })
+            .map(|b| {
+                let created = GqlTimestamp::from_string(&b.creation).unwrap().into();
+                ((b.x as usize, b.y as usize), created)
+            })
+            .collect();
+        let entities = world.entities();
+        let mut forest = world.write_storage::<ForestComponent>();
+        let position = world.read_storage::<Position>();
+        let mut town = world.fetch_mut::<Town>();
+        for (e, tree, pos) in (&entities, &mut forest, &position).join() {
+            let tile = town.resolution.tile(pos.area.pos);
+            match planted.get(&tile) {
+                Some(created) => tree.planted = *created,
+                None => {
+                    town.remove_building(tile);
+                    entities
+                        .delete(e)
+                        .map_err(|_| PadlError::dev_err(PadlErrorCode::EcsError("Delete tree")))?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl buildings_query::BuildingsQueryVillageBuildings {
@@ -140,6 +185,7 @@ impl buildings_query::BuildingsQueryVillageBuildings {
             buildings_query::BuildingType::TEMPLE => BuildingType::Temple,
             buildings_query::BuildingType::SINGLE_NEST => BuildingType::SingleNest,
             buildings_query::BuildingType::TRIPLE_NEST => BuildingType::TripleNest,
+            buildings_query::BuildingType::SHACK => BuildingType::Shack,
             buildings_query::BuildingType::Other(_) => panic!("Unexpected BuildingType"),
         };
         let created = GqlTimestamp::from_string(&self.creation).unwrap().into();
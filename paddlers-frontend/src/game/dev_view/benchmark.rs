@@ -58,7 +58,7 @@ impl TestData {
         game.world.maintain();
 
         println!("Starting {:?} test", setting);
-        let now = utc_now();
+        let now = real_now();
         let dt_seconds = chrono::Duration::seconds(10);
         TestData {
             kind: setting,
@@ -75,21 +75,21 @@ impl TestData {
         }
     }
     pub fn record_start_of_update(&mut self) {
-        self.current_update_start = utc_now();
+        self.current_update_start = real_now();
     }
     pub fn record_end_of_update(&mut self) {
         if self.current_update_start.micros() > 0 {
-            let now = utc_now();
+            let now = real_now();
             let dt = now - self.current_update_start;
             self.update_intervals.push(dt);
         }
     }
     pub fn record_start_of_frame(&mut self) {
-        self.current_frame_start = utc_now();
+        self.current_frame_start = real_now();
     }
     pub fn record_end_of_frame(&mut self) {
         self.total_frames += 1;
-        let now = utc_now();
+        let now = real_now();
         let dt = now - self.prev_interval;
         self.prev_interval = now;
         self.intervals.push(dt);
@@ -99,7 +99,7 @@ impl TestData {
         }
     }
     pub fn result(&self) -> Option<String> {
-        let now = utc_now();
+        let now = real_now();
         if self.end < now {
             Some(self.evaluate())
         } else {
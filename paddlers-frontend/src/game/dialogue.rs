@@ -8,13 +8,16 @@ use crate::gui::{
     ui_state::Now, utils::colors::LIGHT_BLUE, utils::*, z::*,
 };
 use crate::init::quicksilver_integration::Signal;
+use crate::net::NetMsg;
 use crate::prelude::*;
 use crate::view::{ExperimentalSignalChannel, Frame};
+use paddlers_shared_lib::story::scene::SceneDefinition;
 use quicksilver::graphics::{Mesh, ShapeRenderer};
 use quicksilver::lyon::{math::point, path::Path, tessellation::*};
 use quicksilver::prelude::Window as QuicksilverWindow;
 use quicksilver::prelude::{Col, Rectangle, Transform};
 use specs::WorldExt;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
 pub(crate) struct DialogueFrame<'a, 'b> {
@@ -26,6 +29,8 @@ pub(crate) struct DialogueFrame<'a, 'b> {
     text_provider: TableTextProvider,
     text_bubble: Mesh,
     current_scene: Option<Scene>,
+    /// Scenes already loaded from the game master
+    scene_definitions: HashMap<SceneIndex, SceneDefinition>,
     phantom: PhantomData<(&'a (), &'b ())>,
 }
 
@@ -52,6 +57,7 @@ impl<'a, 'b> DialogueFrame<'a, 'b> {
             text_provider,
             text_bubble,
             current_scene: None,
+            scene_definitions: HashMap::new(),
             phantom: PhantomData,
         };
 
@@ -62,6 +68,24 @@ impl<'a, 'b> DialogueFrame<'a, 'b> {
         self.current_scene = Some(scene);
         self.reload(locale);
     }
+    /// Shows the scene right away if it has been loaded before, otherwise requests it from the game master
+    fn open_scene(
+        &mut self,
+        index: SceneIndex,
+        slide: SlideIndex,
+        locale: &TextDb,
+    ) -> PadlResult<()> {
+        if let Some(definition) = self.scene_definitions.get(&index) {
+            let scene = Scene::from_definition(index, definition, slide)?;
+            self.load_scene(scene, locale);
+        } else {
+            self.current_scene = None;
+            self.buttons.clear();
+            self.text_lines.clear();
+            crate::net::request_scene(index, slide);
+        }
+        Ok(())
+    }
     #[inline(always)]
     fn scene_mut(&mut self) -> PadlResult<&mut Scene> {
         self.current_scene
@@ -118,7 +142,7 @@ impl<'a, 'b> DialogueFrame<'a, 'b> {
     fn load_slide_buttons(&mut self, texts: &TextDb) {
         for b in self.current_scene.as_ref().unwrap().slide_buttons() {
             let button = UiElement::new(ClickOutput::SlideAction(b.action.clone()))
-                .with_text(texts.gettext(b.text_key).to_owned())
+                .with_text(texts.gettext(&b.text_key).to_owned())
                 .with_background_color(LIGHT_GREEN);
             self.buttons.add(button);
         }
@@ -197,7 +221,11 @@ impl<'a, 'b> Frame for DialogueFrame<'a, 'b> {
     fn event(&mut self, state: &mut Self::State, e: &Self::Event) -> Result<(), Self::Error> {
         match e {
             PadlEvent::Signal(Signal::Scene(scene, slide)) => {
-                self.load_scene(scene.load_scene(*slide), &state.locale);
+                self.open_scene(*scene, *slide, &state.locale)?;
+            }
+            PadlEvent::Network(NetMsg::Scene(index, slide, definition)) => {
+                self.scene_definitions.insert(*index, definition.clone());
+                self.open_scene(*index, *slide, &state.locale)?;
             }
             PadlEvent::Signal(Signal::NewStoryState(s)) => {
                 state.set_story_state(*s);
@@ -5,6 +5,8 @@ use crate::game::{
     town::Town,
 };
 use crate::prelude::ScreenResolution;
+use paddlers_shared_lib::game_mechanics::hobos::HoboColorStats;
+use paddlers_shared_lib::game_mechanics::town::defence::order_touched_together;
 use specs::prelude::*;
 use specs::storage::BTreeStorage;
 use specs::world::Index;
@@ -47,6 +49,11 @@ pub struct Health {
     pub hp: i64,
     // Used for effects that affect the unit once per defender
     pub aura_effects: Vec<Index>,
+    // Number of auras still to be passed without effect
+    pub ignored_auras: usize,
+    // Sum of all welcoming so far, before the color modifier is applied
+    welcome: i64,
+    welcome_divisor: i64,
 }
 impl Health {
     pub fn new(hp: i64, hp_left: i64, aura_effects: Vec<Index>) -> Self {
@@ -54,15 +61,32 @@ impl Health {
             max_hp: hp,
             hp: hp_left,
             aura_effects,
+            ignored_auras: 0,
+            welcome: 0,
+            welcome_divisor: 1,
         }
     }
     #[allow(dead_code)]
     pub fn new_full_health(hp: i64) -> Self {
-        Health {
-            max_hp: hp,
-            hp: hp,
-            aura_effects: vec![],
-        }
+        Self::new(hp, hp, vec![])
+    }
+    /// Applies the modifiers of the unit's color, given how many auras it has already passed and how much welcome it has received
+    pub fn with_color_stats(
+        mut self,
+        stats: HoboColorStats,
+        auras_passed: usize,
+        welcome: i64,
+    ) -> Self {
+        self.ignored_auras = stats.ignored_auras.saturating_sub(auras_passed);
+        self.welcome = welcome;
+        self.welcome_divisor = stats.welcome_divisor as i64;
+        self
+    }
+    pub fn welcome(&mut self, amount: i64, id: Entity, ep: &EventPool) {
+        let before = self.welcome / self.welcome_divisor;
+        self.welcome += amount;
+        let after = self.welcome / self.welcome_divisor;
+        self.make_happy(after - before, id, ep);
     }
     pub fn make_happy(&mut self, amount: i64, id: Entity, ep: &EventPool) {
         let new_hp = 0.max(self.hp - amount);
@@ -111,21 +135,26 @@ impl<'a> System<'a> for FightSystem {
         // n can be arbitrarily large in late game
         // m will most likely remain limited by the map size
         // t is always smaller than the map lane size
-        for (aid, a) in (&entities, &aura).join() {
-            // m
-            for (hid, p, h) in (&entities, &position, &mut health).join() {
-                // n
-                let tile = Town::find_tile(p.area.pos, ul);
-                if a.affected_tiles.binary_search(&tile).is_ok() {
-                    // log t
-                    match h.aura_effects.binary_search(&aid.id()) {
-                        // log m
-                        Ok(_) => { /* Aura already active*/ }
-                        Err(i) => {
-                            (*h).make_happy(a.effect, hid, &self.event_pool);
-                            (*h).aura_effects.insert(i, aid.id()); // [Theoretically O(m) but not considered above]
-                        }
-                    }
+        for (hid, p, h) in (&entities, &position, &mut health).join() {
+            // n
+            let tile = Town::find_tile(p.area.pos, ul);
+            let mut new_auras: Vec<(Index, i32)> = (&entities, &aura)
+                .join()
+                // m
+                .filter(|(_, a)| a.affected_tiles.binary_search(&tile).is_ok()) // log t
+                .filter(|(aid, _)| h.aura_effects.binary_search(&aid.id()).is_err()) // log m
+                .map(|(aid, a)| (aid.id(), a.effect as i32))
+                .collect();
+            // Ignored auras are picked the same way as in the shared computation of the game master
+            order_touched_together(&mut new_auras);
+            for (aid, effect) in new_auras {
+                if h.ignored_auras > 0 {
+                    h.ignored_auras -= 1;
+                } else {
+                    h.make_happy(effect as i64, hid, &self.event_pool);
+                }
+                if let Err(i) = h.aura_effects.binary_search(&aid) {
+                    h.aura_effects.insert(i, aid); // [Theoretically O(m) but not considered above]
                 }
             }
         }
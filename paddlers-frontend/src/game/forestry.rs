@@ -1,9 +1,11 @@
+use crate::game::components::EntityContainer;
+use crate::game::movement::Position;
 use crate::game::town::Town;
 use crate::gui::render::Renderable;
 use crate::gui::sprites::*;
 use crate::gui::utils::RenderVariant;
 use crate::prelude::*;
-use paddlers_shared_lib::game_mechanics::forestry::tree_size;
+use paddlers_shared_lib::game_mechanics::forestry::{tree_size, Forest};
 use specs::prelude::*;
 
 #[derive(Component, Debug, Clone)]
@@ -13,31 +15,58 @@ pub struct ForestComponent {
     pub score: usize,
 }
 
+/// How often the trees are loaded again while they are being chopped, in microseconds
+const CHOPPING_SYNC_INTERVAL: i64 = 60_000_000;
+
 #[derive(Default, Clone, Copy)]
-pub struct ForestrySystem;
+pub struct ForestrySystem {
+    last_sync: Timestamp,
+}
 
 impl<'a> System<'a> for ForestrySystem {
     type SystemData = (
+        Entities<'a>,
         WriteStorage<'a, ForestComponent>,
         WriteStorage<'a, Renderable>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, EntityContainer>,
         WriteExpect<'a, Town>,
     );
 
-    fn run(&mut self, (mut forest, mut rend, mut town): Self::SystemData) {
+    fn run(
+        &mut self,
+        (entities, mut forest, mut rend, position, containers, mut town): Self::SystemData,
+    ) {
         let now = utc_now();
-        let mut total = 0;
-        for (tree, r) in (&mut forest, &mut rend).join() {
+        // Chopping only happens on the game-master, the trees have to be loaded from there
+        let chopping = containers
+            .join()
+            .any(|c| c.task == TaskType::ChopTree && !c.children.is_empty());
+        if chopping && (now - self.last_sync).micros() >= CHOPPING_SYNC_INTERVAL {
+            self.last_sync = now;
+            crate::net::request_forest_update();
+        }
+        let mut model = Forest::new();
+        for (e, tree, r) in (&entities, &mut forest, &mut rend).join() {
             let before = tree.score;
             let t = chrono::Duration::microseconds((now - tree.planted).micros());
             tree.score = tree_size(t);
-            if tree.score != before {
+            if tree.score != before && tree.score > 0 {
                 if let RenderVariant::ImgWithImgBackground(ref mut img, _bkg) = r.kind {
                     *img = tree_sprite(tree.score);
                 }
             }
-            total += tree.score;
+            model.add_tree(e, t);
+        }
+        // Trees that died of old age are removed by the game-master as well
+        for e in model.dead_trees() {
+            if let Some(pos) = position.get(e) {
+                let tile = town.resolution.tile(pos.area.pos);
+                town.remove_building(tile);
+            }
+            entities.delete(e).expect("Deleting dead tree");
         }
-        town.update_forest_size(total);
+        town.update_forest_size(model.size());
     }
 }
 
@@ -10,7 +10,7 @@
 //! Try to keep computations in here short and simple.
 
 use crate::game::{
-    components::*, player_info::PlayerInfo, story::StoryAction, units::attackers::Visitor,
+    components::*, player_info::PlayerInfo, story::scene::StoryAction, units::attackers::Visitor,
     units::attackers::*,
 };
 use crate::gui::input::UiView;
@@ -18,7 +18,10 @@ use crate::gui::ui_state::Now;
 use crate::init::quicksilver_integration::{GameState, Signal};
 use crate::net::game_master_api::RestApiState;
 use crate::net::request_foreign_town;
+use crate::net::state::current_village;
 use crate::prelude::*;
+use paddlers_shared_lib::api::hobos::{HoboEviction, HoboOrdination};
+use paddlers_shared_lib::api::shop::WorkerPurchase;
 use paddlers_shared_lib::api::story::StoryStateTransition;
 use paddlers_shared_lib::prelude::*;
 use specs::prelude::*;
@@ -36,13 +39,18 @@ pub type VillageCoordinate = (i32, i32);
 /// If anything should go between frames, than it should use the signal + notification publish-subscriber system that is to be created
 /// (But how, frames in the same view need to communicate a lot)
 pub enum GameEvent {
+    ComposeMessage(VillageKey, String),
     HoboSatisfied(Entity),
     HttpBuyProphet,
+    HttpEvictHobo(HoboKey),
+    HttpHireWorker,
+    HttpOrdainHobo(HoboKey),
     LoadHomeVillage,
     LoadVillage(VillageKey),
     SendProphetAttack(VillageCoordinate),
     StoryActions(Vec<StoryAction>),
     SwitchToView(UiView),
+    WatchReplay(VisitReplayKey),
 }
 
 impl GameState {
@@ -73,29 +81,52 @@ impl GameState {
                             let moving = release_and_move_visitor(hobo, resolution, now);
                             v_store.insert(id, moving)?;
                         }
-                        // Tell backend that release might be required
-                        let net_store = town_world.read_storage::<NetObj>();
-                        let net_id = net_store.get(id).ok_or(PadlError::dev_err(
-                            PadlErrorCode::MissingComponent("NetObj"),
-                        ))?;
-                        RestApiState::get().http_notify_visitor_satisfied(HoboKey(net_id.id))?;
                     }
                 }
             }
+            GameEvent::ComposeMessage(village, player_name) => {
+                self.game
+                    .switch_view(UiView::Visitors(VisitorViewTab::Messages));
+                self.viewer.global_event(
+                    &mut self.game,
+                    &PadlEvent::Signal(Signal::ComposeMessage(village, player_name)),
+                )?;
+            }
             GameEvent::HttpBuyProphet => {
                 let player: PlayerInfo = *self.game.player().clone();
                 crate::game::town::purchase_prophet(&player)?;
             }
+            GameEvent::HttpEvictHobo(hobo) => {
+                RestApiState::get().http_evict_hobo(HoboEviction { hobo })?;
+            }
+            GameEvent::HttpHireWorker => {
+                RestApiState::get().http_hire_worker(WorkerPurchase {
+                    village: current_village(),
+                })?;
+            }
+            GameEvent::HttpOrdainHobo(hobo) => {
+                RestApiState::get().http_ordain_hobo(HoboOrdination { hobo })?;
+            }
             GameEvent::SendProphetAttack((x, y)) => {
                 self.game.send_prophet_attack((x, y))?;
                 // TODO: Only confirm if HTTP OK is returned
                 // (Probably do this after cleaning pu network and promise handling)
-                self.game
-                    .confirm_to_user(format!("Attacking village <{}:{}>", x, y))?;
+                let msg = self.game.locale.gettext_args(
+                    "visit-sent",
+                    &[("x", &x.to_string()), ("y", &y.to_string())],
+                );
+                self.game.confirm_to_user(msg)?;
             }
             GameEvent::SwitchToView(view) => {
                 self.game.switch_view(view);
             }
+            GameEvent::WatchReplay(replay) => {
+                self.game.switch_view(UiView::Replay);
+                self.viewer.global_event(
+                    &mut self.game,
+                    &PadlEvent::Signal(Signal::WatchReplay(replay)),
+                )?;
+            }
             GameEvent::StoryActions(actions) => {
                 for a in actions {
                     self.try_handle_story_action(a)?;
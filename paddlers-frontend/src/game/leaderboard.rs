@@ -8,18 +8,33 @@ use crate::gui::z::*;
 use crate::init::quicksilver_integration::Signal;
 use crate::net::NetMsg;
 use crate::view::Frame;
+use paddlers_shared_lib::leaderboard::*;
+use paddlers_shared_lib::strum::IntoEnumIterator;
 use quicksilver::prelude::Window as QuicksilverWindow;
 use quicksilver::prelude::{Col, Rectangle, Transform};
+use std::cell::Cell;
 use std::marker::PhantomData;
+use std::rc::Rc;
 
 pub(crate) struct LeaderboardFrame<'a, 'b> {
     pane: panes::PaneHandle,
     table: Node,
+    info: Element,
+    /// The page currently displayed, shared with DOM event listeners
+    page: Rc<Cell<LeaderboardPage>>,
     phantom: PhantomData<(&'a (), &'b ())>,
 }
 
+#[derive(Clone, Copy)]
+struct LeaderboardPage {
+    category: LeaderboardCategory,
+    offset: usize,
+    /// A full page has been received, there might be more entries
+    full: bool,
+}
+
 impl LeaderboardFrame<'_, '_> {
-    pub fn new(area: &Rectangle) -> PadlResult<Self> {
+    pub fn new(area: &Rectangle, locale: &TextDb) -> PadlResult<Self> {
         let pane = panes::new_styled_pane(
             area.x() as u32,
             area.y() as u32,
@@ -31,33 +46,66 @@ impl LeaderboardFrame<'_, '_> {
         )?;
         let node = pane.first_inner_node()?;
 
-        // TODO Debug why this didn't work:
+        let page = Rc::new(Cell::new(LeaderboardPage {
+            category: LeaderboardCategory::Karma,
+            offset: 0,
+            full: false,
+        }));
 
-        // js! {
-        //     console.log(@{node.as_ref()})
-        // }
+        let categories = document().create_element("div").unwrap();
+        categories
+            .set_attribute("class", "leaderboard-categories")
+            .unwrap();
+        for category in LeaderboardCategory::iter() {
+            let button = new_button(locale.gettext(category_label(category)));
+            let _handle = button.add_event_listener::<event::ClickEvent, _>(move |_| {
+                crate::net::request_leaderboard(category, 0);
+            });
+            categories.append_child(&button);
+        }
+        node.append_child(&categories);
 
-        // let el : HtmlElement = node.clone().try_into().map_err(
-        //     |_| PadlError::dev_err(PadlErrorCode::InvalidDom("Not html"))
-        // )?;
+        let info = document().create_element("p").unwrap();
+        info.set_attribute("class", "leaderboard-info").unwrap();
+        node.append_child(&info);
 
-        // el.append_html(
-        //     &format!(r#"<h3>{}</h3>
-        //     <h3>{}</h3>
-        //     <h3>{}</h3>
-        //     "#,
-        //     "#", "Name", "Karma")
-        // ).expect("append html");
+        let table = document().create_element("div").unwrap();
+        table.set_attribute("class", "leaderboard-table").unwrap();
+        node.append_child(&table);
 
-        // insert_h3(&node, "#");
-        // insert_h3(&node, "Name");
-        // insert_h3(&node, "Karma");
+        let pager = document().create_element("div").unwrap();
+        pager.set_attribute("class", "leaderboard-pager").unwrap();
+        let prev = new_button(locale.gettext("leaderboard-previous"));
+        let page_ref = page.clone();
+        let _handle = prev.add_event_listener::<event::ClickEvent, _>(move |_| {
+            let current = page_ref.get();
+            if current.offset > 0 {
+                let offset = current
+                    .offset
+                    .saturating_sub(LEADERBOARD_PAGE_SIZE as usize);
+                crate::net::request_leaderboard(current.category, offset);
+            }
+        });
+        pager.append_child(&prev);
+        let next = new_button(locale.gettext("leaderboard-next"));
+        let page_ref = page.clone();
+        let _handle = next.add_event_listener::<event::ClickEvent, _>(move |_| {
+            let current = page_ref.get();
+            if current.full {
+                let offset = current.offset + LEADERBOARD_PAGE_SIZE as usize;
+                crate::net::request_leaderboard(current.category, offset);
+            }
+        });
+        pager.append_child(&next);
+        node.append_child(&pager);
 
         pane.hide()?;
 
         Ok(LeaderboardFrame {
             pane,
-            table: node,
+            table: table.into(),
+            info,
+            page,
             phantom: PhantomData,
         })
     }
@@ -66,7 +114,7 @@ impl LeaderboardFrame<'_, '_> {
         Ok(())
     }
 
-    pub fn insert_row(&self, rank: usize, name: &str, karma: i64) -> PadlResult<()> {
+    pub fn insert_row(&self, rank: usize, name: &str, score: i64) -> PadlResult<()> {
         let node = document().create_element("div").unwrap();
         node.set_text_content(&rank.to_string());
         self.table.append_child(&node);
@@ -76,11 +124,57 @@ impl LeaderboardFrame<'_, '_> {
         self.table.append_child(&node);
 
         let node = document().create_element("div").unwrap();
-        node.set_text_content(&karma.to_string());
+        node.set_text_content(&score.to_string());
         self.table.append_child(&node);
 
         Ok(())
     }
+    fn insert_header(&self, category: LeaderboardCategory, locale: &TextDb) {
+        let titles = [
+            "#",
+            locale.gettext("leaderboard-name"),
+            locale.gettext(category_label(category)),
+        ];
+        for title in &titles {
+            let node = document().create_element("h3").unwrap();
+            node.set_text_content(title);
+            self.table.append_child(&node);
+        }
+    }
+    fn update_info(&self, category: LeaderboardCategory, my_rank: Option<usize>, locale: &TextDb) {
+        let mut text = match my_rank {
+            Some(rank) => {
+                locale.gettext_args("leaderboard-my-rank", &[("rank", &rank.to_string())])
+            }
+            None => locale.gettext("leaderboard-not-ranked").to_owned(),
+        };
+        if category.is_seasonal() {
+            let now = utc_now();
+            let now = chrono::NaiveDateTime::from_timestamp(now.millis() / 1000, 0);
+            let end = season_end(season_of(now));
+            let end = end.format("%d.%m.%Y %H:%M UTC").to_string();
+            text += " ";
+            text += &locale.gettext_args("leaderboard-season-end", &[("date", &end)]);
+        }
+        self.info.set_text_content(&text);
+    }
+}
+
+fn new_button(text: &str) -> Element {
+    let button = document().create_element("div").unwrap();
+    button.set_attribute("class", "leaderboard-button").unwrap();
+    button.set_text_content(text);
+    button
+}
+
+fn category_label(category: LeaderboardCategory) -> TextKey {
+    match category {
+        LeaderboardCategory::Karma => "leaderboard-karma",
+        LeaderboardCategory::SeasonKarma => "leaderboard-season-karma",
+        LeaderboardCategory::Villages => "leaderboard-villages",
+        LeaderboardCategory::VisitorsWelcomed => "leaderboard-visitors-welcomed",
+        LeaderboardCategory::Prophets => "leaderboard-prophets",
+    }
 }
 
 impl<'a, 'b> Frame for LeaderboardFrame<'a, 'b> {
@@ -89,13 +183,20 @@ impl<'a, 'b> Frame for LeaderboardFrame<'a, 'b> {
     type Graphics = QuicksilverWindow;
     type Event = PadlEvent;
     type Signal = Signal;
-    fn event(&mut self, _state: &mut Self::State, e: &Self::Event) -> Result<(), Self::Error> {
+    fn event(&mut self, state: &mut Self::State, e: &Self::Event) -> Result<(), Self::Error> {
         match e {
-            PadlEvent::Network(NetMsg::Leaderboard(offset, list)) => {
+            PadlEvent::Network(NetMsg::Leaderboard(category, first_rank, list, my_rank)) => {
+                self.page.set(LeaderboardPage {
+                    category: *category,
+                    offset: first_rank - 1,
+                    full: list.len() >= LEADERBOARD_PAGE_SIZE as usize,
+                });
                 self.clear()?;
-                for (i, (name, karma)) in list.into_iter().enumerate() {
-                    self.insert_row(offset + i, &name, *karma)?;
+                self.insert_header(*category, &state.locale);
+                for (i, (name, score)) in list.into_iter().enumerate() {
+                    self.insert_row(first_rank + i, &name, *score)?;
                 }
+                self.update_info(*category, *my_rank, &state.locale);
             }
             _ => {}
         }
@@ -127,10 +228,3 @@ impl<'a, 'b> Frame for LeaderboardFrame<'a, 'b> {
         Ok(())
     }
 }
-
-// fn insert_h3(node: &Node, text: &str) {
-//     let inner = document().create_element("h3").unwrap();
-//     inner.set_text_content(text);
-//     node.append_child(&inner);
-//     std::mem::drop(inner);
-// }
@@ -9,9 +9,11 @@ use crate::net::authentication::read_jwt_preferred_username;
 use map_position::*;
 use map_segment::MapSegment;
 use map_tesselation::*;
+use paddlers_shared_lib::game_mechanics::map::*;
 use quicksilver::graphics::Mesh;
 use quicksilver::prelude::{Col, Rectangle, Transform, Vector, Window};
 use specs::prelude::*;
+use std::collections::HashSet;
 
 pub(crate) use map_frame::MapFrame;
 pub use map_position::MapPosition;
@@ -32,16 +34,24 @@ pub struct GlobalMapPrivateState {
     segments: Vec<MapSegment>,
     villages: Vec<VillageMetaInfo>,
     view_width: i32,
-    loaded: (i32, i32),
+    /// Chunks of the map that have been requested from the server
+    loaded_chunks: HashSet<i32>,
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy)]
 pub struct GlobalMapSharedState {
     /// Offset in map coordinates (1.0 = one village width)
     x_offset: f32,
+    /// Vertical offset in map coordinates, only non-zero while zoomed in
+    y_offset: f32,
     scaling: f32,
+    /// Factor applied on top of the scaling that fits the map height to the view
+    zoom: f32,
 }
 
+const MIN_ZOOM: f32 = 1.0;
+const MAX_ZOOM: f32 = 3.0;
+
 impl<'a> GlobalMap<'a> {
     pub fn combined<'b>(
         private: &'b mut GlobalMapPrivateState,
@@ -50,8 +60,8 @@ impl<'a> GlobalMap<'a> {
         GlobalMap::<'b> { private, shared }
     }
     pub fn new(view_size: Vector) -> (GlobalMapPrivateState, GlobalMapSharedState) {
-        let scaling = Self::calculate_scaling(view_size);
-        let (w, h) = Self::display_shape();
+        let scaling = Self::calculate_scaling(view_size, MIN_ZOOM);
+        let (w, h) = Self::display_shape(view_size, scaling);
         let view_port = Rectangle::new((0, 0), Vector::new(w, h) * scaling);
         let grid_mesh = tesselate_map_background(view_port, w, h);
 
@@ -60,11 +70,13 @@ impl<'a> GlobalMap<'a> {
             segments: vec![],
             villages: vec![],
             view_width: w,
-            loaded: (0, -1),
+            loaded_chunks: HashSet::new(),
         };
         let shared = GlobalMapSharedState {
             x_offset: 0.0,
+            y_offset: 0.0,
             scaling,
+            zoom: MIN_ZOOM,
         };
         (map, shared)
     }
@@ -84,29 +96,19 @@ impl<'a> GlobalMap<'a> {
 
         Ok(())
     }
-    const LOAD_AHEAD: i32 = 10;
-    const LOAD_STEP: i32 = 10;
+    /// Distance to the visible area (in map coordinates) from which on chunks are loaded
+    const LOAD_AHEAD: f32 = 10.0;
+    /// Requests all chunks of the map that are visible or will be soon after dragging the map
     pub fn update(&mut self) {
-        let x = -self.shared.x_offset as i32;
-        if self.private.loaded.0 > x - Self::LOAD_AHEAD {
-            let (low, high) = (
-                self.private.loaded.0 - 1 - Self::LOAD_STEP,
-                self.private.loaded.0 - 1,
-            );
-            crate::net::request_map_read(low, high);
-
-            self.private.loaded.0 = self.private.loaded.0.min(low);
-            self.private.loaded.1 = self.private.loaded.1.max(high);
-        }
-        if self.private.loaded.1 < x + self.private.view_width + Self::LOAD_AHEAD {
-            let (low, high) = (
-                self.private.loaded.1 + 1,
-                self.private.loaded.1 + 1 + Self::LOAD_STEP,
-            );
-            crate::net::request_map_read(low, high);
-
-            self.private.loaded.0 = self.private.loaded.0.min(low);
-            self.private.loaded.1 = self.private.loaded.1.max(high);
+        let x = -self.shared.x_offset;
+        // Nothing is ever generated left of x = 0
+        let first = map_chunk_of(x - Self::LOAD_AHEAD).max(0);
+        let last = map_chunk_of(x + self.private.view_width as f32 + Self::LOAD_AHEAD);
+        for chunk in first..=last {
+            if self.private.loaded_chunks.insert(chunk) {
+                let (low, high) = map_chunk_range(chunk);
+                crate::net::request_map_read(low as i32, high as i32 - 1);
+            }
         }
     }
     fn draw_grid(&mut self, window: &mut Window) {
@@ -114,12 +116,15 @@ impl<'a> GlobalMap<'a> {
         if x > 0.0 {
             x -= 1.0
         }
-        let t = Transform::translate((x * self.shared.scaling, 0));
+        let t = Transform::translate((
+            x * self.shared.scaling,
+            self.shared.y_offset * self.shared.scaling,
+        ));
         extend_transformed(window.mesh(), &self.private.grid_mesh, t);
     }
     fn draw_water(&mut self, window: &mut Window, area: &Rectangle) {
         let visible_frame = Rectangle::new(
-            (-self.shared.x_offset, 0),
+            (-self.shared.x_offset, -self.shared.y_offset),
             area.size() / self.shared.scaling,
         );
         let t = self.view_transform();
@@ -162,26 +167,34 @@ impl<'a> GlobalMap<'a> {
         Ok(())
     }
 
-    fn display_shape() -> (i32, i32) {
-        let w = 15; // TODO: determine dynamically what fits the viewport
-        let h = paddlers_shared_lib::game_mechanics::map::MAP_H as i32;
+    /// Number of columns and rows of the grid that fill the view
+    fn display_shape(view_size: Vector, scaling: f32) -> (i32, i32) {
+        let h = MAP_H as i32;
+        // One additional column to cover the view while the grid is shifted by the offset
+        let w = (view_size.x / scaling).ceil() as i32 + 1;
         (w, h)
     }
-    pub fn calculate_scaling(view_size: Vector) -> f32 {
-        let (w, h) = Self::display_shape();
-        let rx = view_size.x / w as f32;
-        let ry = view_size.y / h as f32;
-        rx.min(ry)
+    pub fn calculate_scaling(view_size: Vector, zoom: f32) -> f32 {
+        view_size.y / MAP_H as f32 * zoom
     }
     fn apply_scaling(&mut self, size: Vector) {
-        let r = Self::calculate_scaling(size);
-        if self.shared.scaling != r {
+        let r = Self::calculate_scaling(size, self.shared.zoom);
+        let (w, h) = Self::display_shape(size, r);
+        if self.private.view_width != w {
+            let view_port = Rectangle::new((0, 0), Vector::new(w, h) * r);
+            self.private.grid_mesh = tesselate_map_background(view_port, w, h);
+            self.private.view_width = w;
+            self.shared.scaling = r;
+        } else if self.shared.scaling != r {
             scale_mesh(&mut self.private.grid_mesh, r / self.shared.scaling);
             self.shared.scaling = r;
         }
     }
     fn view_transform(&self) -> Transform {
-        Transform::translate((self.shared.x_offset * self.shared.scaling, 0))
+        Transform::translate((
+            self.shared.x_offset * self.shared.scaling,
+            self.shared.y_offset * self.shared.scaling,
+        ))
     }
 
     #[cfg(feature = "dev_view")]
@@ -193,7 +206,7 @@ impl<'a> GlobalMap<'a> {
                     let area = Rectangle::new(
                         (
                             (self.shared.x_offset + x) * self.shared.scaling - pt / 2.0,
-                            y * self.shared.scaling - pt / 2.0,
+                            (self.shared.y_offset + y) * self.shared.scaling - pt / 2.0,
                         ),
                         (pt, pt),
                     );
@@ -213,13 +226,14 @@ impl GlobalMapPrivateState {
     pub fn add_segment(
         &mut self,
         world: &mut World,
+        locale: &TextDb,
         streams: Vec<Vec<(f32, f32)>>,
         villages: Vec<VillageMetaInfo>,
         min_x: i32,
         max_x: i32,
     ) {
         let w = max_x - min_x;
-        let h = paddlers_shared_lib::game_mechanics::map::MAP_H as i32;
+        let h = MAP_H as i32;
         let mut segment = MapSegment::new(min_x, 0, w, h, streams);
         segment.tesselate_rivers();
         self.segments.push(segment);
@@ -237,7 +251,7 @@ impl GlobalMapPrivateState {
                 )))
                 .with(Clickable)
                 .with((*village).clone())
-                .with(village.new_village_menu(is_mine))
+                .with(village.new_village_menu(is_mine, locale))
                 .build();
         }
 
@@ -245,9 +259,39 @@ impl GlobalMapPrivateState {
     }
 }
 
+impl Default for GlobalMapSharedState {
+    fn default() -> Self {
+        GlobalMapSharedState {
+            x_offset: 0.0,
+            y_offset: 0.0,
+            scaling: 1.0,
+            zoom: MIN_ZOOM,
+        }
+    }
+}
+
 impl GlobalMapSharedState {
     pub fn drag(&mut self, v: Vector) {
         self.x_offset += v.x;
+        self.y_offset += v.y;
+        self.clamp_y_offset();
+    }
+    /// Zooms in or out by a factor, keeping the map point under `center` in place
+    pub fn zoom(&mut self, center: Vector, factor: f32) {
+        let zoom = (self.zoom * factor).max(MIN_ZOOM).min(MAX_ZOOM);
+        // The new scaling is applied on the next render, the offsets have to be adjusted already now
+        let scaling = self.scaling * zoom / self.zoom;
+        let fixed_point = center / self.scaling - Vector::new(self.x_offset, self.y_offset);
+        let offset = center / scaling - fixed_point;
+        self.x_offset = offset.x;
+        self.y_offset = offset.y;
+        self.zoom = zoom;
+        self.clamp_y_offset();
+    }
+    /// The map fills the view vertically, it can only be moved up and down as far as it is zoomed in
+    fn clamp_y_offset(&mut self) {
+        let hidden_rows = MAP_H as f32 * (1.0 - 1.0 / self.zoom);
+        self.y_offset = self.y_offset.min(0.0).max(-hidden_rows);
     }
     pub fn left_click_on_main_area<'a>(
         &mut self,
@@ -258,7 +302,10 @@ impl GlobalMapSharedState {
         clickable: ReadStorage<'a, Clickable>,
     ) {
         let r = self.scaling;
-        let map_coordinates = Vector::new(mouse_pos.x / r, mouse_pos.y / r);
+        let map_coordinates = Vector::new(
+            mouse_pos.x / r - self.x_offset,
+            mouse_pos.y / r - self.y_offset,
+        );
 
         ui_state.selected_entity =
             map_position_lookup(map_coordinates, entities, position, clickable);
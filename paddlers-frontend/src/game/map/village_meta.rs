@@ -7,6 +7,7 @@ use crate::gui::{
     sprites::*,
     utils::*,
 };
+use crate::prelude::*;
 use paddlers_shared_lib::prelude::VillageKey;
 use specs::prelude::*;
 use specs::storage::BTreeStorage;
@@ -45,28 +46,34 @@ impl VillageMetaInfo {
     pub fn player_name(&self) -> Option<&str> {
         self.player.as_ref().map(|s| s.name.as_str())
     }
-    pub fn village_details<'a>(&self) -> Vec<TableRow<'a>> {
-        let text = format!("Village <{}:{}>", self.coordinates.0, self.coordinates.1);
+    pub fn village_details<'a>(&self, locale: &TextDb) -> Vec<TableRow<'a>> {
+        let text = locale.gettext_args(
+            "village-coordinates",
+            &[
+                ("x", &self.coordinates.0.to_string()),
+                ("y", &self.coordinates.1.to_string()),
+            ],
+        );
         let row0 = TableRow::Text(text);
-        let row1 = self.player_info_row();
+        let row1 = self.player_info_row(locale);
         vec![row0, row1]
     }
-    fn player_info_row<'a>(&self) -> TableRow<'a> {
+    fn player_info_row<'a>(&self, locale: &TextDb) -> TableRow<'a> {
         let text = if let Some(p) = &self.player {
             format!("{} ({})", p.name, p.karma)
         } else {
-            "Anarchists".to_owned()
+            locale.gettext("village-without-owner").to_owned()
         };
         TableRow::Text(text)
     }
-    pub fn new_village_menu(&self, owned: bool) -> UiMenu {
+    pub fn new_village_menu(&self, owned: bool, locale: &TextDb) -> UiMenu {
         let mut menu = UiMenu {
             ui: UiBox::new(2, 2, 10.0, 2.0),
         };
         if !owned {
             menu.ui.add(
                 UiElement::new(ClickOutput::Event(GameEvent::LoadVillage(self.id)))
-                    .with_text("Descend".to_owned())
+                    .with_text(locale.gettext("village-visit").to_owned())
                     .with_background_color(LIGHT_BLUE),
             );
             menu.ui.add(
@@ -76,6 +83,16 @@ impl VillageMetaInfo {
                 .with_image(SpriteSet::Simple(SingleSprite::Prophet))
                 .with_background_color(RED),
             );
+            if let Some(player) = &self.player {
+                menu.ui.add(
+                    UiElement::new(ClickOutput::Event(GameEvent::ComposeMessage(
+                        self.id,
+                        player.name.clone(),
+                    )))
+                    .with_text(locale.gettext("village-write-message").to_owned())
+                    .with_background_color(LIGHT_BLUE),
+                );
+            }
         }
         menu
     }
@@ -56,9 +56,8 @@ impl LoadingState {
                     self.progress.report_progress_for(&response, 1);
                     self.game_data.village_info = Some(response);
                 }
-                NetMsg::Leaderboard(offset, list) => {
-                    self.viewer_data
-                        .push(PadlEvent::Network(NetMsg::Leaderboard(offset, list)));
+                msg @ NetMsg::Leaderboard(..) => {
+                    self.viewer_data.push(PadlEvent::Network(msg));
                     self.progress
                         .report_progress::<PadlEvent>(self.viewer_data.len());
                 }
@@ -94,15 +93,16 @@ impl GameState {
                     NetMsg::Buildings(response) => {
                         self.game.load_buildings_from_net_response(response)?;
                     }
+                    NetMsg::Forest(response) => {
+                        self.game.update_forest_from_net_response(response)?;
+                    }
                     NetMsg::Hobos(hobos, vid) => {
                         let ctx = self.game.maybe_town_context_mut(vid, "villages")?;
                         load_hobos_from_net_response(ctx, hobos)?;
                     }
-                    NetMsg::Leaderboard(offset, list) => {
-                        self.viewer.global_event(
-                            &mut self.game,
-                            &PadlEvent::Network(NetMsg::Leaderboard(offset, list)),
-                        )?;
+                    msg @ NetMsg::Leaderboard(..) | msg @ NetMsg::Scene(..) => {
+                        self.viewer
+                            .global_event(&mut self.game, &PadlEvent::Network(msg))?;
                     }
                     NetMsg::Map(response, min, max) => {
                         if let Some(data) = response.data {
@@ -123,8 +123,14 @@ impl GameState {
                                 .into_iter()
                                 .map(VillageMetaInfo::from)
                                 .collect();
-                            let (map, world) = (self.game.map.as_mut(), &mut self.game.world);
-                            map.map(|map| map.add_segment(world, streams, villages, min, max));
+                            let (map, world, locale) = (
+                                self.game.map.as_mut(),
+                                &mut self.game.world,
+                                &self.game.locale,
+                            );
+                            map.map(|map| {
+                                map.add_segment(world, locale, streams, villages, min, max)
+                            });
                         } else {
                             println!("No map data available");
                         }
@@ -176,6 +182,24 @@ impl GameState {
                             &PadlEvent::Network(NetMsg::Reports(data)),
                         )?;
                     }
+                    NetMsg::Replays(data) => {
+                        self.viewer.global_event(
+                            &mut self.game,
+                            &PadlEvent::Network(NetMsg::Replays(data)),
+                        )?;
+                    }
+                    NetMsg::Messages(data) => {
+                        self.viewer.global_event(
+                            &mut self.game,
+                            &PadlEvent::Network(NetMsg::Messages(data)),
+                        )?;
+                    }
+                    NetMsg::Quests(data) => {
+                        self.viewer.global_event(
+                            &mut self.game,
+                            &PadlEvent::Network(NetMsg::Quests(data)),
+                        )?;
+                    }
                 }
             }
             Err(TryRecvError::Disconnected) => {
@@ -205,6 +229,22 @@ impl<'a, 'b> Game<'a, 'b> {
         }
         Ok(())
     }
+    /// Keeps the buildings and only updates the trees, which may have been chopped since they were loaded
+    pub fn update_forest_from_net_response(
+        &mut self,
+        response: BuildingsResponse,
+    ) -> PadlResult<()> {
+        if let Some(data) = response.data {
+            if let Some(ctx) = self.town_context.context_by_key_mut(data.village_id()) {
+                let world = ctx.world_mut();
+                data.update_forest(world)?;
+                world.maintain();
+            } else {
+                return PadlErrorCode::DataForInactiveTownReceived("forest").dev();
+            }
+        }
+        Ok(())
+    }
     pub fn load_attacking_hobos(&mut self, response: AttacksResponse) -> PadlResult<()> {
         if let Some(data) = response.data {
             for atk in data.village.attacks {
@@ -0,0 +1,179 @@
+use crate::game::Game;
+use crate::gui::input::key_bindings::{key_label, KeyAction, KeyBindings};
+use crate::gui::utils::colors::DARK_BLUE;
+use crate::gui::z::*;
+use crate::i18n::{key_action_key, Locale};
+use crate::init::quicksilver_integration::Signal;
+use crate::prelude::*;
+use crate::view::Frame;
+use quicksilver::prelude::Window as QuicksilverWindow;
+use quicksilver::prelude::{Col, Rectangle, Transform};
+use specs::WorldExt;
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use stdweb::web::*;
+use strum::IntoEnumIterator;
+
+/// Lets the player change the key bindings and the language
+pub(crate) struct SettingsFrame<'a, 'b> {
+    pane: panes::PaneHandle,
+    table: Element,
+    /// Requests from DOM event listeners, applied on the next update
+    requests: Rc<Cell<Option<SettingsRequest>>>,
+    /// Bindings version and captured action currently displayed
+    displayed: Option<(u32, Option<KeyAction>)>,
+    phantom: PhantomData<(&'a (), &'b ())>,
+}
+
+#[derive(Clone, Copy)]
+enum SettingsRequest {
+    Rebind(KeyAction),
+    Reset,
+    SelectLocale(Locale),
+}
+
+impl SettingsFrame<'_, '_> {
+    pub fn new(area: &Rectangle, locale: &TextDb) -> PadlResult<Self> {
+        let pane = panes::new_styled_pane(
+            area.x() as u32,
+            area.y() as u32,
+            area.width() as u32,
+            area.height() as u32,
+            r#"<section class="settings"></section>"#,
+            &[""],
+            &[("color", "white")],
+        )?;
+        let node = pane.first_inner_node()?;
+
+        let title = document().create_element("h2").unwrap();
+        title.set_text_content(locale.gettext("settings-title"));
+        node.append_child(&title);
+
+        let subtitle = document().create_element("h3").unwrap();
+        subtitle.set_text_content(locale.gettext("settings-key-bindings"));
+        node.append_child(&subtitle);
+
+        let table = document().create_element("div").unwrap();
+        table.set_attribute("class", "settings-table").unwrap();
+        node.append_child(&table);
+
+        let requests = Rc::new(Cell::new(None));
+        let reset = new_button(locale.gettext("settings-reset"));
+        let requests_ref = requests.clone();
+        let _handle = reset.add_event_listener::<event::ClickEvent, _>(move |_| {
+            requests_ref.set(Some(SettingsRequest::Reset));
+        });
+        node.append_child(&reset);
+
+        let subtitle = document().create_element("h3").unwrap();
+        subtitle.set_text_content(locale.gettext("settings-language"));
+        node.append_child(&subtitle);
+
+        let current = Locale::select();
+        for &l in Locale::ALL.iter() {
+            let button = new_button(l.native_name());
+            if l == current {
+                button
+                    .set_attribute("class", "settings-button selected")
+                    .unwrap();
+            }
+            let requests_ref = requests.clone();
+            let _handle = button.add_event_listener::<event::ClickEvent, _>(move |_| {
+                requests_ref.set(Some(SettingsRequest::SelectLocale(l)));
+            });
+            node.append_child(&button);
+        }
+
+        pane.hide()?;
+
+        Ok(SettingsFrame {
+            pane,
+            table,
+            requests,
+            displayed: None,
+            phantom: PhantomData,
+        })
+    }
+    fn refresh(&mut self, bindings: &KeyBindings, locale: &TextDb) {
+        let displayed = Some((bindings.version(), bindings.capture));
+        if self.displayed == displayed {
+            return;
+        }
+        self.displayed = displayed;
+        self.table.remove_all_children();
+        for action in KeyAction::iter() {
+            let label = document().create_element("div").unwrap();
+            label.set_text_content(locale.gettext(key_action_key(action)));
+            self.table.append_child(&label);
+
+            let text = if bindings.capture == Some(action) {
+                locale.gettext("settings-press-key").to_owned()
+            } else {
+                key_label(bindings.key(action))
+            };
+            let button = new_button(&text);
+            let requests = self.requests.clone();
+            let _handle = button.add_event_listener::<event::ClickEvent, _>(move |_| {
+                requests.set(Some(SettingsRequest::Rebind(action)));
+            });
+            self.table.append_child(&button);
+        }
+    }
+}
+
+fn new_button(text: &str) -> Element {
+    let button = document().create_element("div").unwrap();
+    button.set_attribute("class", "settings-button").unwrap();
+    button.set_text_content(text);
+    button
+}
+
+impl<'a, 'b> Frame for SettingsFrame<'a, 'b> {
+    type Error = PadlError;
+    type State = Game<'a, 'b>;
+    type Graphics = QuicksilverWindow;
+    type Event = PadlEvent;
+    type Signal = Signal;
+    fn update(&mut self, state: &mut Self::State) -> Result<(), Self::Error> {
+        let mut bindings = state.world.write_resource::<KeyBindings>();
+        match self.requests.take() {
+            // The next key pressed is captured when the game handles the key event
+            Some(SettingsRequest::Rebind(action)) => bindings.capture = Some(action),
+            Some(SettingsRequest::Reset) => bindings.reset(),
+            Some(SettingsRequest::SelectLocale(locale)) => {
+                crate::net::url::reload_with_locale(locale.code())
+            }
+            None => {}
+        }
+        self.refresh(&bindings, &state.locale);
+        Ok(())
+    }
+    fn draw(
+        &mut self,
+        state: &mut Self::State,
+        window: &mut Self::Graphics,
+    ) -> Result<(), Self::Error> {
+        let ui_state = state.world.read_resource::<ViewState>();
+        let main_area = Rectangle::new(
+            (0, 0),
+            (
+                ui_state.menu_box_area.x(),
+                (window.project() * window.screen_size()).y,
+            ),
+        );
+        std::mem::drop(ui_state);
+        window.draw_ex(&main_area, Col(DARK_BLUE), Transform::IDENTITY, Z_TEXTURE);
+        Ok(())
+    }
+    fn enter(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        self.pane.show()?;
+        Ok(())
+    }
+    fn leave(&mut self, state: &mut Self::State) -> Result<(), Self::Error> {
+        // Do not capture keys once the view is left
+        state.world.write_resource::<KeyBindings>().capture = None;
+        self.pane.hide()?;
+        Ok(())
+    }
+}
@@ -1,19 +1,14 @@
 pub mod entity_trigger;
+pub mod quest_log;
 pub mod scene;
 
-use crate::game::{player_info::PlayerInfo, story::scene::SceneIndex, Game};
+use crate::game::{player_info::PlayerInfo, Game};
 use crate::gui::input::UiView;
 use crate::prelude::*;
 use crate::view::entry_view;
 use paddlers_shared_lib::story::story_state::StoryState;
 use scene::*;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum StoryAction {
-    OpenScene(SceneIndex, SlideIndex),
-    StoryProgress(StoryState),
-}
-
 impl Game<'_, '_> {
     pub fn set_story_state(&self, s: StoryState) {
         self.world.fetch_mut::<PlayerInfo>().set_story_state(s);
@@ -1,5 +1,4 @@
-use crate::game::story::scene::SceneIndex;
-use crate::game::story::StoryAction;
+use crate::game::story::scene::{SceneIndex, StoryAction};
 use crate::game::units::workers::Worker;
 use crate::gui::ui_state::UiState;
 use crate::logging::ErrorQueue;
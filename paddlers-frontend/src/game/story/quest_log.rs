@@ -0,0 +1,276 @@
+use crate::gui::utils::colors::LIGHT_BLUE;
+use crate::gui::z::*;
+use crate::i18n::{
+    ability_name_key, building_name_key, quest_description_key, quest_title_key, resource_name_key,
+};
+use crate::init::quicksilver_integration::Signal;
+use crate::net::game_master_api::RestApiState;
+use crate::net::graphql::query_types::{quests_query::QuestsQueryPlayerQuests, QuestsResponse};
+use crate::net::NetMsg;
+use crate::prelude::*;
+use crate::view::*;
+use core::marker::PhantomData;
+use paddlers_shared_lib::api::story::QuestCollect;
+use paddlers_shared_lib::story::quest::{QuestName, QuestObjective, QuestReward};
+use paddlers_shared_lib::story::scene::StoryAction;
+use quicksilver::prelude::{Col, Rectangle, Transform, Window};
+use specs::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use stdweb::web::*;
+
+/// Lists the quests of the player with their progress and lets the player collect the rewards.
+pub(crate) struct QuestFrame<'a, 'b> {
+    pane: panes::PaneHandle,
+    list: Element,
+    /// Quests of which the rewards have been requested but not yet confirmed by the game master
+    collecting: Rc<RefCell<Vec<QuestName>>>,
+    /// What the list currently shows, to rebuild it only when something has changed
+    displayed: Option<(Vec<QuestState>, Vec<QuestName>)>,
+    _phantom: PhantomData<(&'a (), &'b ())>,
+}
+
+/// Progress of a quest as displayed: name, completion and current and target of each objective
+type QuestState = (QuestName, bool, Vec<(i64, i64)>);
+
+impl<'a, 'b> QuestFrame<'a, 'b> {
+    pub fn new(area: Rectangle, resolution: ScreenResolution, locale: &TextDb) -> PadlResult<Self> {
+        let right_padding = resolution.leaves_border_w() * 0.75;
+        let pane = panes::new_pane(
+            area.x() as u32,
+            area.y() as u32,
+            (area.width() - right_padding) as u32,
+            area.height() as u32,
+            r#"<section class="quests"></section>"#,
+        )?;
+        pane.hide()?;
+        let node = pane.first_inner_node()?;
+
+        let title = document().create_element("h2").unwrap();
+        title.set_text_content(locale.gettext("quests-title"));
+        node.append_child(&title);
+
+        let list = document().create_element("div").unwrap();
+        list.set_attribute("class", "quest-list").unwrap();
+        node.append_child(&list);
+
+        Ok(QuestFrame {
+            pane,
+            list,
+            collecting: Default::default(),
+            displayed: None,
+            _phantom: Default::default(),
+        })
+    }
+    fn update_quests(
+        &mut self,
+        quests: &QuestsResponse,
+        game: &mut Game<'a, 'b>,
+    ) -> PadlResult<()> {
+        // Open follow-up scenes once the game master has confirmed the collection
+        let mut follow_ups = vec![];
+        for quest in quests {
+            let name: QuestName = (&quest.name).into();
+            if quest.completed {
+                let mut collecting = self.collecting.borrow_mut();
+                if let Some(i) = collecting.iter().position(|q| *q == name) {
+                    collecting.swap_remove(i);
+                    if let Some(scene) = name.definition().follow_up {
+                        follow_ups.push(StoryAction::OpenScene(scene, 0));
+                    }
+                }
+            }
+        }
+        if !follow_ups.is_empty() {
+            game.event_pool.send(GameEvent::StoryActions(follow_ups))?;
+        }
+
+        let state: Vec<QuestState> = quests
+            .iter()
+            .map(|q| {
+                let objectives = q.objectives.iter().map(|o| (o.current, o.target));
+                ((&q.name).into(), q.completed, objectives.collect())
+            })
+            .collect();
+        let displayed = Some((state, self.collecting.borrow().clone()));
+        if self.displayed == displayed {
+            return Ok(());
+        }
+        self.displayed = displayed;
+        while let Some(child) = self.list.first_child() {
+            self.list.remove_child(&child).unwrap();
+        }
+        for quest in quests {
+            let node = self.new_quest_node((&quest.name).into(), quest, &game.locale);
+            self.list.append_child(&node);
+        }
+        Ok(())
+    }
+    fn new_quest_node(
+        &self,
+        name: QuestName,
+        quest: &QuestsQueryPlayerQuests,
+        locale: &TextDb,
+    ) -> Element {
+        let quest_node = document().create_element("div").unwrap();
+        quest_node
+            .set_attribute(
+                "class",
+                if quest.completed {
+                    "letter quest-done"
+                } else {
+                    "letter"
+                },
+            )
+            .unwrap();
+
+        let header_node = document().create_element("p").unwrap();
+        header_node
+            .set_attribute("class", "message-header")
+            .unwrap();
+        header_node.set_text_content(locale.gettext(quest_title_key(name)));
+        quest_node.append_child(&header_node);
+
+        let text_node = document().create_element("p").unwrap();
+        text_node.set_text_content(locale.gettext(quest_description_key(name)));
+        quest_node.append_child(&text_node);
+
+        let definition = name.definition();
+        let objectives_node = document().create_element("ul").unwrap();
+        for (objective, progress) in definition.objectives.iter().zip(&quest.objectives) {
+            let current = progress.current.min(progress.target).to_string();
+            let target = progress.target.to_string();
+            let text = match objective {
+                QuestObjective::Build(bt, _) => locale.gettext_args(
+                    "quest-objective-build",
+                    &[
+                        ("building", locale.gettext(building_name_key(*bt))),
+                        ("current", &current),
+                        ("target", &target),
+                    ],
+                ),
+                QuestObjective::WelcomeVisitors(_) => locale.gettext_args(
+                    "quest-objective-visitors",
+                    &[("current", &current), ("target", &target)],
+                ),
+                QuestObjective::Karma(_) => locale.gettext_args(
+                    "quest-objective-karma",
+                    &[("current", &current), ("target", &target)],
+                ),
+            };
+            let item = document().create_element("li").unwrap();
+            if progress.current >= progress.target {
+                item.set_attribute("class", "objective-done").unwrap();
+            }
+            item.set_text_content(&text);
+            objectives_node.append_child(&item);
+        }
+        quest_node.append_child(&objectives_node);
+
+        let rewards_node = document().create_element("p").unwrap();
+        let rewards: Vec<String> = definition
+            .rewards
+            .iter()
+            .map(|reward| reward_text(reward, locale))
+            .collect();
+        rewards_node.set_text_content(
+            &locale.gettext_args("quest-rewards", &[("rewards", &rewards.join(", "))]),
+        );
+        quest_node.append_child(&rewards_node);
+
+        let fulfilled = quest.objectives.iter().all(|o| o.current >= o.target);
+        if !quest.completed && fulfilled && !self.collecting.borrow().contains(&name) {
+            let button_node = document().create_element("div").unwrap();
+            button_node.set_attribute("class", "letter-button").unwrap();
+            button_node.set_text_content(locale.gettext("quest-collect"));
+            let parent = quest_node.clone();
+            let button_ref = button_node.clone();
+            let collecting = self.collecting.clone();
+            let _handle = button_node.add_event_listener::<event::ClickEvent, _>(move |_| {
+                let msg = QuestCollect { quest: name };
+                let failed = collecting.clone();
+                let on_failure = move || {
+                    // Show the button again, with the refreshed quest list
+                    failed.borrow_mut().retain(|q| *q != name);
+                    crate::net::request_quests();
+                };
+                collecting.borrow_mut().push(name);
+                if let Err(e) = RestApiState::get().http_collect_quest(msg, on_failure) {
+                    println!("Failed to send API call {}", e);
+                    collecting.borrow_mut().retain(|q| *q != name);
+                    return;
+                }
+                let _node = parent.remove_child(&button_ref);
+            });
+            quest_node.append_child(&button_node);
+        }
+        quest_node
+    }
+}
+
+fn reward_text(reward: &QuestReward, locale: &TextDb) -> String {
+    match reward {
+        QuestReward::Resources(rt, n) => locale.gettext_args(
+            "quest-reward-resources",
+            &[
+                ("amount", &n.to_string()),
+                ("resource", locale.gettext(resource_name_key(*rt))),
+            ],
+        ),
+        QuestReward::Karma(n) => {
+            locale.gettext_args("quest-reward-karma", &[("amount", &n.to_string())])
+        }
+        QuestReward::Present(bt) => locale.gettext_args(
+            "quest-reward-present",
+            &[("building", locale.gettext(building_name_key(*bt)))],
+        ),
+        QuestReward::Ability(ability) => locale.gettext_args(
+            "quest-reward-ability",
+            &[("ability", locale.gettext(ability_name_key(*ability)))],
+        ),
+    }
+}
+
+impl<'a, 'b> Frame for QuestFrame<'a, 'b> {
+    type Error = PadlError;
+    type State = Game<'a, 'b>;
+    type Graphics = Window;
+    type Event = PadlEvent;
+    type Signal = Signal;
+    fn event(&mut self, state: &mut Self::State, event: &Self::Event) -> Result<(), Self::Error> {
+        match event {
+            PadlEvent::Network(NetMsg::Quests(quests)) => {
+                self.update_quests(quests, state)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    fn draw(
+        &mut self,
+        state: &mut Self::State,
+        window: &mut Self::Graphics,
+    ) -> Result<(), Self::Error> {
+        let ui_state = state.world.read_resource::<ViewState>();
+        let main_area = Rectangle::new(
+            (0, 0),
+            (
+                ui_state.menu_box_area.x(),
+                (window.project() * window.screen_size()).y,
+            ),
+        );
+        std::mem::drop(ui_state);
+        window.draw_ex(&main_area, Col(LIGHT_BLUE), Transform::IDENTITY, Z_TEXTURE);
+        Ok(())
+    }
+    fn enter(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        // Progress changes over time, e.g. with karma, so it is loaded whenever the list is opened
+        crate::net::request_quests();
+        self.pane.show()?;
+        Ok(())
+    }
+    fn leave(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        self.pane.hide()?;
+        Ok(())
+    }
+}
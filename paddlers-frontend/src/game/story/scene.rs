@@ -1,12 +1,13 @@
-use crate::game::story::StoryAction;
-use crate::gui::input::UiView;
+use crate::gui::input::{UiView, VisitorViewTab};
 use crate::gui::sprites::*;
 use crate::prelude::*;
-use paddlers_shared_lib::story::story_state::StoryState;
+use paddlers_shared_lib::story::scene::{SceneDefinition, SceneSprite, SceneView};
+pub use paddlers_shared_lib::story::scene::{SceneIndex, SlideIndex, StoryAction};
 
 /// A Scene consists of a set of slides and can be loaded in the Dialogue view.
 /// It starts at a specific slide and the player can click through the, as defined on the slides.
 /// Slides are referenced (within a scene) by their index.
+/// Scenes are defined in `scenes/*.json` and loaded from the game master when they are opened.
 pub struct Scene {
     slides: Vec<Slide>,
     active_slide: SlideIndex,
@@ -15,14 +16,14 @@ pub struct Scene {
 /// A Slide shows some text and optionally back/next and other buttons.
 /// At least one button should be visible, or players cannot do anything to progress the scene.
 pub struct Slide {
-    text_key: TextKey,
+    text_key: String,
     buttons: Vec<SlideButton>,
     sprite: SpriteIndex,
     back_button: bool,
     next_button: bool,
 }
 pub struct SlideButton {
-    pub text_key: TextKey,
+    pub text_key: String,
     pub action: SlideButtonAction,
 }
 #[derive(Default, Clone, Debug, PartialEq)]
@@ -32,8 +33,6 @@ pub struct SlideButtonAction {
     pub actions: Vec<StoryAction>,
 }
 
-pub type SlideIndex = usize;
-
 impl Scene {
     pub fn slide_text_key(&self) -> &str {
         &self.slides[self.active_slide].text_key
@@ -67,17 +66,42 @@ impl Scene {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum SceneIndex {
-    Entrance,
-}
-
-impl SceneIndex {
-    // Improvement: The scenes could be loaded from the server dynamically, to reduce the WASM binary size
-    pub fn load_scene(&self, slide: SlideIndex) -> Scene {
-        match self {
-            Self::Entrance => load_entry_scene(slide),
+impl Scene {
+    /// Fails if the scene has no slide with the given index
+    pub fn from_definition(
+        index: SceneIndex,
+        definition: &SceneDefinition,
+        active_slide: SlideIndex,
+    ) -> PadlResult<Self> {
+        if active_slide >= definition.slides.len() {
+            return PadlErrorCode::NoSuchSlide(index, active_slide).dev();
         }
+        let slides = definition
+            .slides
+            .iter()
+            .map(|slide| Slide {
+                text_key: slide.text_key.clone(),
+                buttons: slide
+                    .buttons
+                    .iter()
+                    .map(|button| SlideButton {
+                        text_key: button.text_key.clone(),
+                        action: SlideButtonAction {
+                            next_slide: button.next_slide,
+                            next_view: button.next_view.map(scene_view),
+                            actions: button.actions.clone(),
+                        },
+                    })
+                    .collect(),
+                sprite: scene_sprite(slide.sprite),
+                back_button: slide.back_button,
+                next_button: slide.next_button,
+            })
+            .collect();
+        Ok(Scene {
+            slides,
+            active_slide,
+        })
     }
 }
 
@@ -89,88 +113,26 @@ impl SlideButtonAction {
             actions: vec![],
         }
     }
-    fn with_action(mut self, a: StoryAction) -> Self {
-        self.actions.push(a);
-        self
-    }
-    fn with_view_change(mut self, v: UiView) -> Self {
-        self.next_view = Some(v);
-        self
-    }
 }
 
-fn load_entry_scene(active_slide: SlideIndex) -> Scene {
-    let mut slides = Vec::new();
-
-    // 0
-    slides.push(Slide {
-        text_key: "welcomescene-B10",
-        buttons: vec![],
-        sprite: SpriteIndex::Simple(SingleSprite::RogerLargeAstonished),
-        back_button: false,
-        next_button: true,
-    });
-    // 1
-    slides.push(Slide {
-        text_key: "welcomescene-B20",
-        sprite: SpriteIndex::Simple(SingleSprite::RogerLarge),
-        buttons: vec![],
-        back_button: true,
-        next_button: true,
-    });
-    // 2
-    slides.push(Slide {
-        text_key: "welcomescene-B30",
-        sprite: SpriteIndex::Simple(SingleSprite::RogerLarge),
-        buttons: vec![],
-        back_button: true,
-        next_button: true,
-    });
-    // 3
-    slides.push(Slide {
-        text_key: "welcomescene-B40",
-        sprite: SpriteIndex::Simple(SingleSprite::RogerLargeSad),
-        buttons: vec![],
-        back_button: true,
-        next_button: true,
-    });
-    let button = SlideButton {
-        text_key: "welcomescene-A60",
-        action: SlideButtonAction::to_slide(5)
-            .with_action(StoryAction::StoryProgress(StoryState::ServantAccepted)),
+fn scene_sprite(sprite: SceneSprite) -> SpriteIndex {
+    let single = match sprite {
+        SceneSprite::RogerLarge => SingleSprite::RogerLarge,
+        SceneSprite::RogerLargeAstonished => SingleSprite::RogerLargeAstonished,
+        SceneSprite::RogerLargeCelebrating => SingleSprite::RogerLargeCelebrating,
+        SceneSprite::RogerLargeObedient => SingleSprite::RogerLargeObedient,
+        SceneSprite::RogerLargeSad => SingleSprite::RogerLargeSad,
     };
-    // 4
-    slides.push(Slide {
-        text_key: "welcomescene-B50",
-        sprite: SpriteIndex::Simple(SingleSprite::RogerLargeObedient),
-        buttons: vec![button],
-        back_button: true,
-        next_button: false,
-    });
-    // 5
-    slides.push(Slide {
-        text_key: "welcomescene-B70",
-        sprite: SpriteIndex::Simple(SingleSprite::RogerLargeCelebrating),
-        buttons: vec![],
-        back_button: false,
-        next_button: true,
-    });
-
-    let button = SlideButton {
-        text_key: "welcomescene-A90",
-        action: SlideButtonAction::default().with_view_change(UiView::Town),
-    };
-    // 6
-    slides.push(Slide {
-        text_key: "welcomescene-B80",
-        sprite: SpriteIndex::Simple(SingleSprite::RogerLarge),
-        buttons: vec![button],
-        back_button: true,
-        next_button: false,
-    });
+    SpriteIndex::Simple(single)
+}
 
-    Scene {
-        slides,
-        active_slide,
+fn scene_view(view: SceneView) -> UiView {
+    match view {
+        SceneView::Town => UiView::Town,
+        SceneView::Map => UiView::Map,
+        SceneView::Leaderboard => UiView::Leaderboard,
+        SceneView::IncomingVisitors => UiView::Visitors(VisitorViewTab::IncomingAttacks),
+        SceneView::Letters => UiView::Visitors(VisitorViewTab::Letters),
+        SceneView::Messages => UiView::Visitors(VisitorViewTab::Messages),
     }
 }
@@ -5,18 +5,22 @@ pub mod town_defence;
 pub mod town_input;
 pub mod town_render;
 
+mod blueprint_frame;
 mod default_shop;
+mod task_queue_frame;
 mod temple_shop;
 mod town_context;
 mod town_frame;
+pub(crate) use blueprint_frame::*;
 pub use default_shop::*;
+pub(crate) use task_queue_frame::*;
 pub(crate) use temple_shop::*;
 pub(crate) use town_context::*;
 pub(crate) use town_frame::*;
 
 use crate::gui::{
     sprites::*,
-    z::{Z_TEXTURE, Z_TILE_SHADOW, Z_VISITOR},
+    z::{Z_BUILDINGS, Z_TEXTURE, Z_TILE_SHADOW, Z_VISITOR},
 };
 use crate::prelude::*;
 pub use paddlers_shared_lib::game_mechanics::town::TileIndex;
@@ -63,7 +67,9 @@ impl Town {
         self.state.forest_usage()
     }
     pub fn forest_size_free(&self) -> usize {
-        self.state.forest_size - self.state.forest_usage()
+        self.state
+            .forest_size
+            .saturating_sub(self.state.forest_usage())
     }
     pub fn ambience(&self) -> i64 {
         self.total_ambience
@@ -76,25 +82,16 @@ impl Town {
     pub fn grow_forest(&mut self, add_score: usize) {
         self.state.forest_size += add_score;
     }
-    /// Call this when a worker begins a task which has an effect on the Town's state
-    pub fn add_stateful_task(&mut self, task: TaskType) -> PadlResult<()> {
-        self.state
-            .register_task_begin(task)
-            .map_err(PadlError::from)
+    /// Call this when a worker begins a task which has an effect on the Town's state.
+    /// The game-master has already accepted the task, even if the forest has shrunk since.
+    pub fn add_stateful_task(&mut self, task: TaskType) {
+        self.state.register_ongoing_task(task);
     }
     /// Call this when a worker ends a task which has an effect on the Town's state
     pub fn remove_stateful_task(&mut self, task: TaskType) -> PadlResult<()> {
         self.state.register_task_end(task).map_err(PadlError::from)
     }
 
-    pub fn get_buildable_tile(&self, pos: impl Into<Vector>) -> Option<TileIndex> {
-        let (x, y) = self.resolution.tile(pos);
-        if self.is_buildable((x, y)) {
-            Some((x, y))
-        } else {
-            None
-        }
-    }
     fn tiles_in_rectified_circle(tile: TileIndex, radius: f32) -> Vec<TileIndex> {
         let r = radius.ceil() as usize;
         let xmin = tile.0.saturating_sub(r);
@@ -0,0 +1,191 @@
+use crate::game::{
+    buildings::Building, movement::Position, town::TileIndex, town_resources::TownResources, Game,
+};
+use crate::init::quicksilver_integration::Signal;
+use crate::net::game_master_api::RestApiState;
+use crate::net::state::current_village;
+use crate::prelude::*;
+use crate::view::Frame;
+use paddlers_shared_lib::api::shop::BlueprintPurchase;
+use paddlers_shared_lib::game_mechanics::town::blueprint::Blueprint;
+use quicksilver::prelude::{Rectangle, Window};
+use specs::prelude::*;
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use stdweb::unstable::TryInto;
+use stdweb::web::html_element::TextAreaElement;
+use stdweb::web::*;
+
+/// Lets the player export the layout of a town as blueprint string and build the missing parts of an imported blueprint.
+/// A previewed blueprint is drawn as ghost on top of the town.
+pub(crate) struct BlueprintFrame<'a, 'b> {
+    pane: panes::PaneHandle,
+    input: TextAreaElement,
+    build_button: Element,
+    requests: Rc<Cell<Option<BlueprintRequest>>>,
+    preview: Option<Blueprint>,
+    foreign: bool,
+    phantom: PhantomData<(&'a (), &'b ())>,
+}
+
+#[derive(Clone, Copy)]
+enum BlueprintRequest {
+    Export,
+    Preview,
+    Build,
+    Clear,
+}
+
+impl BlueprintFrame<'_, '_> {
+    pub fn new(area: &Rectangle, locale: &TextDb) -> PadlResult<Self> {
+        let pane = panes::new_styled_pane(
+            area.x() as u32,
+            area.y() as u32,
+            area.width() as u32,
+            area.height() as u32,
+            r#"<section class="blueprint"></section>"#,
+            &[""],
+            &[("color", "white")],
+        )?;
+        let node = pane.first_inner_node()?;
+
+        let text_area = document().create_element("textarea").unwrap();
+        text_area.set_attribute("rows", "1").unwrap();
+        text_area
+            .set_attribute("placeholder", locale.gettext("blueprint-placeholder"))
+            .unwrap();
+        node.append_child(&text_area);
+        let input: TextAreaElement = text_area.try_into().unwrap();
+
+        let requests = Rc::new(Cell::new(None));
+        let buttons = [
+            (BlueprintRequest::Export, "blueprint-export"),
+            (BlueprintRequest::Preview, "blueprint-preview"),
+            (BlueprintRequest::Build, "blueprint-build"),
+            (BlueprintRequest::Clear, "blueprint-clear"),
+        ];
+        let mut build_button = None;
+        for (request, key) in &buttons {
+            let button = document().create_element("div").unwrap();
+            button.set_attribute("class", "blueprint-button").unwrap();
+            button.set_text_content(locale.gettext(key));
+            let requests_ref = requests.clone();
+            let request = *request;
+            let _handle = button.add_event_listener::<event::ClickEvent, _>(move |_| {
+                requests_ref.set(Some(request));
+            });
+            node.append_child(&button);
+            if let BlueprintRequest::Build = request {
+                build_button = Some(button);
+            }
+        }
+
+        pane.hide()?;
+
+        Ok(BlueprintFrame {
+            pane,
+            input,
+            build_button: build_button.unwrap(),
+            requests,
+            preview: None,
+            foreign: false,
+            phantom: PhantomData,
+        })
+    }
+    fn apply_request(&mut self, request: BlueprintRequest, state: &mut Game) -> PadlResult<()> {
+        match request {
+            BlueprintRequest::Export => {
+                let blueprint = Blueprint::from_buildings(existing_buildings(state.town_world()));
+                self.input.set_value(&blueprint.to_string());
+            }
+            BlueprintRequest::Preview => {
+                self.preview = Some(self.parse_input()?);
+            }
+            BlueprintRequest::Build => {
+                let blueprint = self.parse_input()?;
+                let missing = blueprint.missing_parts(&existing_buildings(state.town_world()));
+                if !missing.is_empty() {
+                    let price = Blueprint::total_price(&missing);
+                    if !state
+                        .town_world()
+                        .fetch::<TownResources>()
+                        .can_afford(&price)
+                    {
+                        return PadlErrorCode::NotEnoughResources.usr();
+                    }
+                    RestApiState::get().http_buy_blueprint(BlueprintPurchase {
+                        village: current_village(),
+                        blueprint: blueprint.to_string(),
+                    })?;
+                }
+                self.preview = Some(blueprint);
+            }
+            BlueprintRequest::Clear => {
+                self.preview = None;
+                self.input.set_value("");
+            }
+        }
+        Ok(())
+    }
+    fn parse_input(&self) -> PadlResult<Blueprint> {
+        self.input
+            .value()
+            .parse()
+            .map_err(|_| PadlError::user_err(PadlErrorCode::InvalidBlueprint))
+    }
+    /// Blueprints can be exported from any town but only be built in the own town
+    fn update_build_button(&mut self, foreign: bool) {
+        if self.foreign != foreign {
+            let style = if foreign { "display: none;" } else { "" };
+            self.build_button.set_attribute("style", style).unwrap();
+            self.foreign = foreign;
+        }
+    }
+}
+
+impl<'a, 'b> Frame for BlueprintFrame<'a, 'b> {
+    type Error = PadlError;
+    type State = Game<'a, 'b>;
+    type Graphics = Window;
+    type Event = PadlEvent;
+    type Signal = Signal;
+    fn update(&mut self, state: &mut Self::State) -> Result<(), Self::Error> {
+        self.update_build_button(state.town_context.is_foreign());
+        if let Some(request) = self.requests.take() {
+            self.apply_request(request, state)?;
+        }
+        Ok(())
+    }
+    fn draw(
+        &mut self,
+        state: &mut Self::State,
+        window: &mut Self::Graphics,
+    ) -> Result<(), Self::Error> {
+        if let Some(blueprint) = &self.preview {
+            let missing = blueprint.missing_parts(&existing_buildings(state.town_world()));
+            let ul = state.world.fetch::<ScreenResolution>().unit_length();
+            let town = state.town_context.town();
+            town.render_blueprint(window, &mut state.sprites, &missing, ul)?;
+        }
+        Ok(())
+    }
+    fn enter(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        self.pane.show()?;
+        Ok(())
+    }
+    fn leave(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        self.pane.hide()?;
+        Ok(())
+    }
+}
+
+fn existing_buildings(world: &World) -> Vec<(BuildingType, TileIndex)> {
+    let resolution = *world.fetch::<ScreenResolution>();
+    let buildings = world.read_storage::<Building>();
+    let positions = world.read_storage::<Position>();
+    (&buildings, &positions)
+        .join()
+        .map(|(b, pos)| (b.bt, resolution.tile(pos.area.center())))
+        .collect()
+}
@@ -1,6 +1,8 @@
 use super::{Town, X, Y};
-use paddlers_shared_lib::game_mechanics::town::TileIndex;
+use paddlers_shared_lib::game_mechanics::town::{TileIndex, TownTileType, TOWN_LANE_Y};
+use paddlers_shared_lib::models::BuildingType;
 use pathfinding::prelude::{absdiff, astar};
+use std::collections::{HashSet, VecDeque};
 
 impl Town {
     pub fn shortest_path(&self, s: TileIndex, t: TileIndex) -> Option<(Vec<TileIndex>, u32)> {
@@ -27,6 +29,37 @@ impl Town {
         path.and_then(|p| p.0.last().cloned())
     }
 
+    /// Checks if a building placed on the tile would cut off parts of the town that can be reached now
+    pub fn building_blocks_path(&self, tile: TileIndex, bt: BuildingType) -> bool {
+        if TownTileType::BUILDING(bt).is_walkable() {
+            return false;
+        }
+        // The lane can always be walked on, therefore it is used as starting point
+        let start = (0, TOWN_LANE_Y);
+        let before = self.reachable_tiles(start, None);
+        let after = self.reachable_tiles(start, Some(tile));
+        if before.contains(&tile) {
+            after.len() + 1 < before.len()
+        } else {
+            after.len() < before.len()
+        }
+    }
+
+    fn reachable_tiles(&self, start: TileIndex, blocked: Option<TileIndex>) -> HashSet<TileIndex> {
+        let mut reached = HashSet::new();
+        let mut queue = VecDeque::new();
+        reached.insert(start);
+        queue.push_back(start);
+        while let Some(v) = queue.pop_front() {
+            for (next, _) in self.successors(v) {
+                if Some(next) != blocked && reached.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        reached
+    }
+
     fn successors(&self, index: TileIndex) -> Vec<(TileIndex, u32)> {
         let (x, y) = index;
         let mut nbrs = vec![];
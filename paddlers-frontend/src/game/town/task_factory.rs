@@ -1,5 +1,6 @@
 use super::*;
-use crate::game::components::{EntityContainer, Mana};
+use crate::game::components::{EntityContainer, Mana, NetObj};
+use crate::game::units::workers::WorkerTask;
 use crate::prelude::*;
 use paddlers_shared_lib::api::tasks::*;
 use paddlers_shared_lib::prelude::*;
@@ -8,6 +9,16 @@ use specs::prelude::*;
 /// Used to describe a new worker-task that has not been processed or checked yet
 pub type NewTaskDescriptor = (TaskType, Option<PadlId>);
 
+/// A job in the task queue of a worker, as shown in the queue editor.
+/// Walking from one job to the next is not part of the queue, it is added when the queue is submitted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QueuedJob {
+    pub job: NewTaskDescriptor,
+    pub destination: TileIndex,
+    /// Only known for jobs that have already been scheduled by the server
+    pub start_time: Option<Timestamp>,
+}
+
 impl Town {
     pub fn build_task_chain(
         &self,
@@ -23,6 +34,24 @@ impl Town {
             PadlErrorCode::PathBlocked.usr()
         }
     }
+    /// Chains the jobs of a queue, including the walks in between, starting from a given tile.
+    /// An empty queue lets the worker idle where it is.
+    pub fn build_queued_task_chain(
+        &self,
+        from: TileIndex,
+        jobs: &[QueuedJob],
+    ) -> PadlResult<Vec<RawTask>> {
+        if jobs.is_empty() {
+            return Ok(vec![RawTask::new(TaskType::Idle, from)]);
+        }
+        let mut tasks = vec![];
+        let mut current = from;
+        for queued in jobs {
+            tasks.extend(self.build_task_chain(current, queued.destination, &queued.job)?);
+            current = queued.destination;
+        }
+        Ok(tasks)
+    }
     pub fn check_task_constraints<'a>(
         &self,
         job: NewTaskDescriptor,
@@ -50,6 +79,9 @@ impl Town {
                     return PadlErrorCode::NotEnoughMana.usr();
                 }
             }
+            TaskType::PlantTree => {
+                self.check_placement(destination, BuildingType::Tree)?;
+            }
             TaskType::Defend => panic!("NIY"),
             TaskType::Idle | TaskType::CollectReward | TaskType::Walk => {}
         }
@@ -71,7 +103,7 @@ impl Town {
                 BuildingType::PresentA | BuildingType::PresentB => vec![TaskType::CollectReward],
                 _ => vec![],
             },
-            TileType::EMPTY => vec![TaskType::Idle],
+            TileType::EMPTY => vec![TaskType::Idle, TaskType::PlantTree],
             TileType::LANE => {
                 // TODO: Check for welcoming ability
                 vec![]
@@ -106,7 +138,7 @@ fn raw_job_execution_tasks(job: &NewTaskDescriptor, place: TileIndex) -> Vec<Raw
         TaskType::ChopTree | TaskType::GatherSticks | TaskType::Idle | TaskType::Walk => {
             // NOP
         }
-        TaskType::CollectReward | TaskType::WelcomeAbility => {
+        TaskType::CollectReward | TaskType::WelcomeAbility | TaskType::PlantTree => {
             tasks.push(RawTask::new(TaskType::Idle, place));
         }
         TaskType::Defend => {
@@ -117,8 +149,148 @@ fn raw_job_execution_tasks(job: &NewTaskDescriptor, place: TileIndex) -> Vec<Raw
     tasks
 }
 
+/// Reconstructs the jobs of a worker from the tasks scheduled by the server.
+/// Walks and the idle task added after some jobs (see `raw_job_execution_tasks`) are left out.
+pub fn queued_jobs<'a>(
+    tasks: impl IntoIterator<Item = &'a WorkerTask>,
+    net_ids: &ReadStorage<NetObj>,
+) -> Vec<QueuedJob> {
+    let mut jobs: Vec<QueuedJob> = vec![];
+    for task in tasks {
+        if task.task_type == TaskType::Walk {
+            continue;
+        }
+        if task.task_type == TaskType::Idle {
+            if let Some(previous) = jobs.last() {
+                let follow_up = match previous.job.0 {
+                    TaskType::CollectReward | TaskType::WelcomeAbility | TaskType::PlantTree => {
+                        true
+                    }
+                    _ => false,
+                };
+                if follow_up && previous.destination == task.position {
+                    continue;
+                }
+            }
+        }
+        let target = task.target.and_then(|e| net_ids.get(e)).map(|n| n.id);
+        jobs.push(QueuedJob {
+            job: (task.task_type, target),
+            destination: task.position,
+            start_time: Some(task.start_time),
+        });
+    }
+    jobs
+}
+
+/// Seconds a worker stays with a job in a routine, for jobs that would otherwise never end
+pub const ROUTINE_STEP_SECONDS: i64 = 60;
+
+/// Turns the jobs of a queue into the steps of a routine that the worker repeats.
+/// Jobs that cannot be repeated are left out, abilities are used on any visitor that has not been welcomed, yet.
+pub fn routine_steps(jobs: &[QueuedJob]) -> Vec<RoutineStep> {
+    jobs.iter()
+        .filter_map(|queued| {
+            let (duration, condition) = match queued.job.0 {
+                TaskType::Walk => (None, None),
+                TaskType::Idle | TaskType::GatherSticks | TaskType::ChopTree => {
+                    (Some(ROUTINE_STEP_SECONDS), None)
+                }
+                TaskType::WelcomeAbility => (None, Some(TaskCondition::UnwelcomedVisitor)),
+                TaskType::CollectReward | TaskType::PlantTree | TaskType::Defend => return None,
+            };
+            Some(RoutineStep {
+                task_type: queued.job.0,
+                x: queued.destination.0,
+                y: queued.destination.1,
+                duration,
+                condition,
+            })
+        })
+        .take(MAX_ROUTINE_STEPS)
+        .collect()
+}
+
 fn direction_vector(a: TileIndex, b: TileIndex) -> Vector {
     let a = Vector::new(a.0 as u32, a.1 as u32);
     let b = Vector::new(b.0 as u32, b.1 as u32);
     a - b
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(task_type: TaskType, position: TileIndex, s: i64) -> WorkerTask {
+        WorkerTask {
+            task_type,
+            position,
+            start_time: Timestamp::from_seconds(s),
+            target: None,
+        }
+    }
+
+    #[test]
+    fn routine_steps_repeat_what_can_be_repeated() {
+        let job = |task_type, destination| QueuedJob {
+            job: (task_type, None),
+            destination,
+            start_time: None,
+        };
+        let jobs = [
+            job(TaskType::GatherSticks, (1, 1)),
+            job(TaskType::PlantTree, (2, 2)),
+            QueuedJob {
+                job: (TaskType::WelcomeAbility, Some(7)),
+                ..job(TaskType::WelcomeAbility, (4, 2))
+            },
+        ];
+
+        let steps = routine_steps(&jobs);
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].task_type, TaskType::GatherSticks);
+        assert_eq!((steps[0].x, steps[0].y), (1, 1));
+        assert_eq!(steps[0].duration, Some(ROUTINE_STEP_SECONDS));
+        // The visitor of the queued job is gone by the time the step comes up again
+        assert_eq!(steps[1].task_type, TaskType::WelcomeAbility);
+        assert_eq!(steps[1].duration, None);
+        assert_eq!(steps[1].condition, Some(TaskCondition::UnwelcomedVisitor));
+    }
+
+    #[test]
+    fn queued_jobs_leave_out_walks_and_follow_up_idles() {
+        let mut world = World::new();
+        world.register::<NetObj>();
+        let hobo = world.create_entity().with(NetObj::hobo(7)).build();
+        let mut welcome = task(TaskType::WelcomeAbility, (5, 3), 4);
+        welcome.target = Some(hobo);
+        let tasks = vec![
+            task(TaskType::Walk, (2, 2), 0),
+            task(TaskType::PlantTree, (2, 2), 1),
+            task(TaskType::Idle, (2, 2), 2),
+            task(TaskType::Walk, (5, 3), 3),
+            welcome,
+            task(TaskType::Idle, (5, 3), 5),
+            task(TaskType::ChopTree, (6, 6), 6),
+            // Not a follow-up, chopping trees does not end with idling
+            task(TaskType::Idle, (6, 6), 7),
+        ];
+
+        let jobs = queued_jobs(&tasks, &world.read_storage());
+        let expected = [
+            ((TaskType::PlantTree, None), (2, 2), 1),
+            ((TaskType::WelcomeAbility, Some(7)), (5, 3), 4),
+            ((TaskType::ChopTree, None), (6, 6), 6),
+            ((TaskType::Idle, None), (6, 6), 7),
+        ];
+        let expected: Vec<QueuedJob> = expected
+            .iter()
+            .map(|(job, destination, s)| QueuedJob {
+                job: *job,
+                destination: *destination,
+                start_time: Some(Timestamp::from_seconds(*s)),
+            })
+            .collect();
+        assert_eq!(jobs, expected);
+    }
+}
@@ -0,0 +1,305 @@
+use crate::game::{
+    components::*,
+    town::task_factory::{queued_jobs, routine_steps, QueuedJob},
+    town::Town,
+    units::workers::{TaskQueueDraft, Worker},
+    Game,
+};
+use crate::gui::ui_state::UiState;
+use crate::i18n::task_name_key;
+use crate::init::quicksilver_integration::Signal;
+use crate::prelude::*;
+use crate::view::Frame;
+use quicksilver::prelude::{Rectangle, Window};
+use specs::prelude::*;
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use stdweb::web::*;
+
+/// Lists the upcoming jobs of the selected worker and lets the player rearrange them.
+/// Jobs are appended with a shift + right click in the town, the edited queue is sent to the server as a whole.
+/// The queue can also be sent as a routine, which the worker repeats until it gets new orders.
+pub(crate) struct TaskQueueFrame<'a, 'b> {
+    pane: panes::PaneHandle,
+    list: Element,
+    edit_buttons: Element,
+    repeat_button: Element,
+    requests: Rc<Cell<Option<QueueRequest>>>,
+    visible: bool,
+    displayed: Option<(Vec<QueuedJobView>, bool, bool)>,
+    phantom: PhantomData<(&'a (), &'b ())>,
+}
+
+#[derive(Clone, Copy)]
+enum QueueRequest {
+    MoveUp(usize),
+    MoveDown(usize),
+    Remove(usize),
+    Submit,
+    Reset,
+    Repeat,
+}
+
+#[derive(PartialEq)]
+struct QueuedJobView {
+    text: String,
+    time: String,
+}
+
+impl TaskQueueFrame<'_, '_> {
+    pub fn new(area: &Rectangle, locale: &TextDb) -> PadlResult<Self> {
+        let pane = panes::new_styled_pane(
+            area.x() as u32,
+            area.y() as u32,
+            area.width() as u32,
+            area.height() as u32,
+            r#"<section class="task-queue"></section>"#,
+            &[""],
+            &[],
+        )?;
+        let node = pane.first_inner_node()?;
+
+        let title = document().create_element("h3").unwrap();
+        title.set_text_content(locale.gettext("queue-title"));
+        node.append_child(&title);
+
+        let list = document().create_element("ol").unwrap();
+        node.append_child(&list);
+
+        let hint = document().create_element("p").unwrap();
+        hint.set_attribute("class", "task-queue-hint").unwrap();
+        hint.set_text_content(locale.gettext("queue-hint"));
+        node.append_child(&hint);
+
+        let requests = Rc::new(Cell::new(None));
+        let edit_buttons = document().create_element("div").unwrap();
+        for (request, key) in &[
+            (QueueRequest::Submit, "queue-submit"),
+            (QueueRequest::Reset, "queue-reset"),
+        ] {
+            edit_buttons.append_child(&queue_button(locale.gettext(key), *request, &requests));
+        }
+        node.append_child(&edit_buttons);
+        let repeat_button = queue_button(
+            locale.gettext("queue-repeat"),
+            QueueRequest::Repeat,
+            &requests,
+        );
+        node.append_child(&repeat_button);
+
+        pane.hide()?;
+
+        Ok(TaskQueueFrame {
+            pane,
+            list,
+            edit_buttons,
+            repeat_button,
+            requests,
+            visible: false,
+            displayed: None,
+            phantom: PhantomData,
+        })
+    }
+    /// Keeps the draft in sync with the selected worker and returns whether the queue should be shown
+    fn follow_selection(state: &mut Game) -> bool {
+        if state.town_context.is_foreign() {
+            return false;
+        }
+        let world = state.town_world();
+        let selected = world.fetch::<UiState>().selected_entity;
+        let workers = world.read_storage::<Worker>();
+        // Edits are kept while something else is selected, in case the player comes back to the same worker
+        match selected.and_then(|e| workers.get(e).map(|w| (e, w))) {
+            Some((e, worker)) => {
+                let net_ids = world.read_storage::<NetObj>();
+                let jobs = queued_jobs(&worker.tasks, &net_ids);
+                world.write_resource::<TaskQueueDraft>().follow(e, jobs);
+                true
+            }
+            None => false,
+        }
+    }
+    fn apply_request(&mut self, request: QueueRequest, state: &mut Game) -> PadlResult<()> {
+        let world = state.town_world();
+        let mut draft = world.write_resource::<TaskQueueDraft>();
+        match request {
+            QueueRequest::MoveUp(i) => draft.move_up(i),
+            QueueRequest::MoveDown(i) => draft.move_down(i),
+            QueueRequest::Remove(i) => draft.remove(i),
+            QueueRequest::Reset => draft.reset(),
+            QueueRequest::Submit => {
+                if let Some(e) = draft.worker() {
+                    submit(world, e, draft.jobs())?;
+                    draft.reset();
+                }
+            }
+            QueueRequest::Repeat => {
+                if let Some(e) = draft.worker() {
+                    repeat(world, e, draft.jobs())?;
+                    draft.reset();
+                }
+            }
+        }
+        Ok(())
+    }
+    fn display(
+        &mut self,
+        views: Vec<QueuedJobView>,
+        edited: bool,
+        repeatable: bool,
+        locale: &TextDb,
+    ) {
+        let next = Some((views, edited, repeatable));
+        if next == self.displayed {
+            return;
+        }
+        let (views, edited, repeatable) = next.unwrap();
+        self.list.set_text_content("");
+        if views.is_empty() {
+            let empty = document().create_element("li").unwrap();
+            empty.set_text_content(locale.gettext("queue-empty"));
+            self.list.append_child(&empty);
+        }
+        for (i, view) in views.iter().enumerate() {
+            let item = document().create_element("li").unwrap();
+            let text = document().create_element("span").unwrap();
+            text.set_text_content(&view.text);
+            item.append_child(&text);
+            let time = document().create_element("span").unwrap();
+            time.set_attribute("class", "task-queue-time").unwrap();
+            time.set_text_content(&view.time);
+            item.append_child(&time);
+            for (request, label) in &[
+                (QueueRequest::MoveUp(i), "▲"),
+                (QueueRequest::MoveDown(i), "▼"),
+                (QueueRequest::Remove(i), "✖"),
+            ] {
+                item.append_child(&queue_button(label, *request, &self.requests));
+            }
+            self.list.append_child(&item);
+        }
+        let style = if edited { "" } else { "display: none;" };
+        self.edit_buttons.set_attribute("style", style).unwrap();
+        let style = if repeatable { "" } else { "display: none;" };
+        self.repeat_button.set_attribute("style", style).unwrap();
+        self.displayed = Some((views, edited, repeatable));
+    }
+    fn set_visible(&mut self, visible: bool) -> PadlResult<()> {
+        if self.visible != visible {
+            if visible {
+                self.pane.show()?;
+            } else {
+                self.pane.hide()?;
+            }
+            self.visible = visible;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'b> Frame for TaskQueueFrame<'a, 'b> {
+    type Error = PadlError;
+    type State = Game<'a, 'b>;
+    type Graphics = Window;
+    type Event = PadlEvent;
+    type Signal = Signal;
+    fn update(&mut self, state: &mut Self::State) -> Result<(), Self::Error> {
+        let visible = Self::follow_selection(state);
+        self.set_visible(visible)?;
+        if let Some(request) = self.requests.take() {
+            self.apply_request(request, state)?;
+        }
+        if visible {
+            let draft = state.town_world().fetch::<TaskQueueDraft>();
+            let views = draft
+                .jobs()
+                .iter()
+                .map(|queued| job_view(queued, &state.locale))
+                .collect();
+            let edited = draft.is_edited();
+            let repeatable = !routine_steps(draft.jobs()).is_empty();
+            std::mem::drop(draft);
+            self.display(views, edited, repeatable, &state.locale);
+        }
+        Ok(())
+    }
+    fn enter(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        // The pane is shown in the next update, once it is known whether a worker is selected
+        self.displayed = None;
+        Ok(())
+    }
+    fn leave(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        self.set_visible(false)?;
+        Ok(())
+    }
+}
+
+fn submit(world: &World, e: Entity, jobs: &[QueuedJob]) -> PadlResult<()> {
+    let town = world.fetch::<Town>();
+    let mut workers = world.write_storage::<Worker>();
+    let mut containers = world.write_storage::<EntityContainer>();
+    let mana = world.read_storage::<Mana>();
+    let position = world.read_storage::<Position>();
+    let moving = world.read_storage::<Moving>();
+    let (from, movement) = (&position, &moving)
+        .join()
+        .get(e, &world.entities())
+        .ok_or(PadlError::dev_err(PadlErrorCode::MissingComponent(
+            "Position of worker",
+        )))?;
+    let start = town
+        .resolution
+        .next_tile_in_direction(from.area.pos, movement.momentum);
+    let worker = workers
+        .get_mut(e)
+        .ok_or(PadlError::dev_err(PadlErrorCode::MissingComponent(
+            "Worker",
+        )))?;
+    worker.submit_queue(e, start, jobs, &town, &mut containers, &mana)
+}
+
+fn repeat(world: &World, e: Entity, jobs: &[QueuedJob]) -> PadlResult<()> {
+    let workers = world.read_storage::<Worker>();
+    let worker = workers
+        .get(e)
+        .ok_or(PadlError::dev_err(PadlErrorCode::MissingComponent(
+            "Worker",
+        )))?;
+    worker.submit_routine(jobs)
+}
+
+fn job_view(queued: &QueuedJob, locale: &TextDb) -> QueuedJobView {
+    let (x, y) = queued.destination;
+    let text = locale.gettext_args(
+        "queue-job",
+        &[
+            ("task", locale.gettext(task_name_key(queued.job.0))),
+            ("x", &x.to_string()),
+            ("y", &y.to_string()),
+        ],
+    );
+    let time = match queued.start_time {
+        Some(t) => {
+            let seconds = ((t - utc_now()).micros() / 1_000_000).max(0);
+            locale.gettext_args("queue-starts-in", &[("seconds", &seconds.to_string())])
+        }
+        None => locale.gettext("queue-not-sent").to_owned(),
+    };
+    QueuedJobView { text, time }
+}
+
+fn queue_button(
+    label: &str,
+    request: QueueRequest,
+    requests: &Rc<Cell<Option<QueueRequest>>>,
+) -> Element {
+    let button = document().create_element("div").unwrap();
+    button.set_attribute("class", "task-queue-button").unwrap();
+    button.set_text_content(label);
+    let requests_ref = requests.clone();
+    button.add_event_listener::<event::ClickEvent, _>(move |_| {
+        requests_ref.set(Some(request));
+    });
+    button
+}
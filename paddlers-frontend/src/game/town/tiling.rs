@@ -1,5 +1,5 @@
-use super::{TileIndex, TileState, Town};
-use crate::resolution::ScreenResolution;
+use super::{TileIndex, TileState, TileType, Town};
+use crate::prelude::*;
 use quicksilver::prelude::*;
 
 impl ScreenResolution {
@@ -55,6 +55,33 @@ impl Town {
         }
         maybe_tile.unwrap().is_buildable()
     }
+    /// Checks if a new building can be placed on a tile, with a reason for the player if not
+    pub fn check_placement(&self, index: TileIndex, bt: BuildingType) -> PadlResult<()> {
+        match self.map.tile_type(index) {
+            None => PadlErrorCode::MapOverflow(index).dev(),
+            Some(TileType::LANE) => PadlErrorCode::CannotBuildOnLane.usr(),
+            Some(TileType::BUILDING(_)) => PadlErrorCode::TileOccupied.usr(),
+            Some(TileType::EMPTY) => {
+                if self.building_blocks_path(index, bt) {
+                    PadlErrorCode::BuildingBlocksPath.usr()
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+    /// All tiles on which a new building could be placed right now
+    pub fn buildable_tiles(&self) -> Vec<TileIndex> {
+        let mut tiles = vec![];
+        for x in 0..super::X {
+            for y in 0..super::Y {
+                if self.is_buildable((x, y)) {
+                    tiles.push((x, y));
+                }
+            }
+        }
+        tiles
+    }
     pub(super) fn is_walkable(&self, index: TileIndex) -> bool {
         let maybe_tile = self.map.tile_type(index);
         if maybe_tile.is_none() {
@@ -3,6 +3,8 @@ use crate::game::buildings::Building;
 use crate::game::fight::Aura;
 use crate::game::visits::attacks::Attack;
 use crate::net::graphql::attacks_query::{AttacksQueryVillageAttacksUnits, HoboAttributeType};
+use paddlers_shared_lib::game_mechanics::attributes::Attributes;
+use paddlers_shared_lib::game_mechanics::hobos::visitor_feathers;
 use paddlers_shared_lib::game_mechanics::town::*;
 use paddlers_shared_lib::graphql_types::*;
 use specs::prelude::*;
@@ -41,6 +43,14 @@ impl<'a> IAttackingHobo for AttackingHobo<'a> {
             .filter(|e| e.strength.is_some())
             .fold(0, |acc, e| acc + e.strength.unwrap() as i64) as i32
     }
+    fn color(&self) -> UnitColor {
+        self.unit
+            .hobo
+            .color
+            .as_ref()
+            .map(|c| c.into())
+            .unwrap_or(UnitColor::Yellow)
+    }
 }
 
 impl<'a, 'b> ITownLayoutMarker for Game<'a, 'b> {
@@ -68,3 +78,333 @@ impl<'a, 'b> IDefendingTown for Game<'a, 'b> {
         out
     }
 }
+
+/// Snapshot of a visitor, used to predict the outcome of a visit before it happens
+#[derive(Debug, Clone)]
+pub(crate) struct PlannedVisitor {
+    hp: u32,
+    speed: f32,
+    hurried: bool,
+    arrival: Timestamp,
+    released: Option<Timestamp>,
+    effects_strength: i32,
+    color: UnitColor,
+}
+
+impl From<&AttackingHobo<'_>> for PlannedVisitor {
+    fn from(unit: &AttackingHobo) -> Self {
+        PlannedVisitor {
+            hp: unit.max_hp(),
+            speed: unit.speed(),
+            hurried: unit.hurried(),
+            arrival: unit.arrival(),
+            released: unit.released(),
+            effects_strength: unit.effects_strength(),
+            color: unit.color(),
+        }
+    }
+}
+
+impl PlannedVisitor {
+    pub fn hp(&self) -> u32 {
+        self.hp
+    }
+    pub fn feathers(&self) -> i64 {
+        visitor_feathers(self.hp as i64, self.speed, self.hurried, self.color)
+    }
+}
+
+impl IAttackingHobo for PlannedVisitor {
+    fn max_hp(&self) -> u32 {
+        self.hp
+    }
+    fn speed(&self) -> f32 {
+        self.speed
+    }
+    fn hurried(&self) -> bool {
+        self.hurried
+    }
+    fn arrival(&self) -> Timestamp {
+        self.arrival
+    }
+    fn released(&self) -> Option<Timestamp> {
+        self.released
+    }
+    fn effects_strength(&self) -> i32 {
+        self.effects_strength
+    }
+    fn color(&self) -> UnitColor {
+        self.color
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VisitorOutcome {
+    Satisfied,
+    /// Unhurried visitors that are not satisfied wait in the town until they are welcomed
+    Waiting,
+    Unsatisfied,
+}
+
+/// Predicted result of a complete visit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct VisitForecast {
+    pub satisfied: usize,
+    pub waiting: usize,
+    pub unsatisfied: usize,
+    pub karma: i64,
+    pub feathers: i64,
+}
+
+#[derive(Clone)]
+struct PlannedAura {
+    id: u32,
+    tiles: Vec<TileIndex>,
+    effect: i32,
+    built: Timestamp,
+}
+
+/// Copy of the auras in a town, which can be extended with buildings that do not exist (yet).
+#[derive(Clone)]
+pub(crate) struct PlannedTown {
+    auras: Vec<PlannedAura>,
+}
+
+impl PlannedTown {
+    /// Takes the auras of all buildings currently in the town
+    pub fn from_world(world: &World) -> Self {
+        let auras = world.read_component::<Aura>();
+        let buildings = world.read_component::<Building>();
+        let entities = world.entities();
+        PlannedTown {
+            auras: (&auras, &entities, &buildings)
+                .join()
+                .map(|(aura, e, b)| PlannedAura {
+                    id: e.id(),
+                    tiles: aura.affected_tiles.clone(),
+                    effect: aura.effect as i32,
+                    built: b.built,
+                })
+                .collect(),
+        }
+    }
+    /// Adds a building that does not exist, as if it had always been there
+    pub fn add_hypothetical_building(&mut self, town: &Town, bt: BuildingType, tile: TileIndex) {
+        if let (Some(range), Some(ap)) = (bt.range(), bt.attack_power()) {
+            if bt.attacks_per_cycle().is_none() {
+                let aura = Aura::new(range, ap, tile, town);
+                self.auras.push(PlannedAura {
+                    id: std::u32::MAX,
+                    tiles: aura.affected_tiles,
+                    effect: ap as i32,
+                    built: Timestamp::from_us(0),
+                });
+            }
+        }
+    }
+    /// A copy of this town with an additional building that does not exist
+    pub fn with_hypothetical_building(
+        &self,
+        town: &Town,
+        bt: BuildingType,
+        tile: TileIndex,
+    ) -> Self {
+        let mut planned = self.clone();
+        planned.add_hypothetical_building(town, bt, tile);
+        planned
+    }
+    /// The free tile on which a new building satisfies the most visitors, if any improves the outcome
+    pub fn best_tile(
+        &self,
+        town: &Town,
+        visits: &[&[PlannedVisitor]],
+        bt: BuildingType,
+    ) -> Option<TileIndex> {
+        let total = |planned: &PlannedTown| {
+            visits
+                .iter()
+                .map(|visitors| planned.forecast(visitors).1)
+                .fold((0, 0), |acc, f| (acc.0 + f.karma, acc.1 + f.feathers))
+        };
+        let current = total(self);
+        town.buildable_tiles()
+            .into_iter()
+            .map(|tile| {
+                (
+                    total(&self.with_hypothetical_building(town, bt, tile)),
+                    tile,
+                )
+            })
+            .filter(|(score, _)| *score > current)
+            .max_by_key(|(score, _)| *score)
+            .map(|(_, tile)| tile)
+    }
+    pub fn outcome(&self, visitor: &PlannedVisitor) -> VisitorOutcome {
+        if self.satisfaction_time(visitor).is_some() {
+            return VisitorOutcome::Satisfied;
+        }
+        if visitor.hurried {
+            return VisitorOutcome::Unsatisfied;
+        }
+        match self.left_rest_place(visitor) {
+            Some(left) => {
+                // +1 for swimming out of sight
+                let distance = self.path_from_rest_place().len() + 1;
+                let gone = left + Timestamp::from_float_seconds(distance as f32 / visitor.speed);
+                if self.hp_left(visitor, gone) == 0 {
+                    VisitorOutcome::Satisfied
+                } else {
+                    VisitorOutcome::Unsatisfied
+                }
+            }
+            None => VisitorOutcome::Waiting,
+        }
+    }
+    pub fn forecast(&self, visitors: &[PlannedVisitor]) -> (Vec<VisitorOutcome>, VisitForecast) {
+        let mut forecast = VisitForecast::default();
+        let outcomes: Vec<_> = visitors.iter().map(|v| self.outcome(v)).collect();
+        for (visitor, outcome) in visitors.iter().zip(&outcomes) {
+            match outcome {
+                VisitorOutcome::Satisfied => {
+                    forecast.satisfied += 1;
+                    forecast.karma += 1;
+                    forecast.feathers += visitor.feathers();
+                }
+                VisitorOutcome::Waiting => forecast.waiting += 1,
+                VisitorOutcome::Unsatisfied => forecast.unsatisfied += 1,
+            }
+        }
+        (outcomes, forecast)
+    }
+}
+
+impl ITownLayoutMarker for PlannedTown {
+    const LAYOUT: TownLayout = TownLayout::Basic;
+}
+impl IDefendingTown for PlannedTown {
+    type AuraId = u32;
+    fn auras_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, i32)> {
+        self.auras
+            .iter()
+            .filter(|aura| aura.built <= time)
+            .filter(|aura| aura.tiles.contains(index))
+            .map(|aura| (aura.id, aura.effect))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolution::ScreenResolution;
+
+    fn visitor(hp: u32, hurried: bool, released: Option<Timestamp>) -> PlannedVisitor {
+        PlannedVisitor {
+            hp,
+            speed: 1.0,
+            hurried,
+            arrival: Timestamp::from_seconds(0),
+            released,
+            effects_strength: 0,
+            color: UnitColor::Yellow,
+        }
+    }
+    /// A town with a single aura on the lane
+    fn town_with_aura(effect: i32, built: Timestamp) -> PlannedTown {
+        PlannedTown {
+            auras: vec![PlannedAura {
+                id: 0,
+                tiles: vec![(7, TOWN_LANE_Y)],
+                effect,
+                built,
+            }],
+        }
+    }
+
+    #[test]
+    fn outcome_of_hurried_visitors() {
+        let town = town_with_aura(5, Timestamp::from_seconds(0));
+        assert_eq!(
+            town.outcome(&visitor(5, true, None)),
+            VisitorOutcome::Satisfied
+        );
+        assert_eq!(
+            town.outcome(&visitor(6, true, None)),
+            VisitorOutcome::Unsatisfied
+        );
+        // Buildings that only appear after the visitor swam by do not count
+        let late = town_with_aura(5, Timestamp::from_seconds(1_000));
+        assert_eq!(
+            late.outcome(&visitor(5, true, None)),
+            VisitorOutcome::Unsatisfied
+        );
+    }
+
+    #[test]
+    fn outcome_of_resting_visitors() {
+        let town = town_with_aura(5, Timestamp::from_seconds(0));
+        assert_eq!(
+            town.outcome(&visitor(5, false, None)),
+            VisitorOutcome::Satisfied
+        );
+        assert_eq!(
+            town.outcome(&visitor(6, false, None)),
+            VisitorOutcome::Waiting
+        );
+        let released = Some(Timestamp::from_seconds(100));
+        assert_eq!(
+            town.outcome(&visitor(6, false, released)),
+            VisitorOutcome::Unsatisfied
+        );
+    }
+
+    #[test]
+    fn forecast_sums_up_visitors() {
+        let town = town_with_aura(5, Timestamp::from_seconds(0));
+        let visitors = [
+            visitor(4, true, None),
+            visitor(8, false, None),
+            visitor(8, true, None),
+        ];
+        let (outcomes, forecast) = town.forecast(&visitors);
+        assert_eq!(
+            outcomes,
+            vec![
+                VisitorOutcome::Satisfied,
+                VisitorOutcome::Waiting,
+                VisitorOutcome::Unsatisfied
+            ]
+        );
+        assert_eq!(
+            forecast,
+            VisitForecast {
+                satisfied: 1,
+                waiting: 1,
+                unsatisfied: 1,
+                karma: 1,
+                feathers: visitors[0].feathers(),
+            }
+        );
+    }
+
+    #[test]
+    fn best_tile_must_improve_the_outcome() {
+        let town = Town::new(ScreenResolution::default());
+        let planned = PlannedTown { auras: vec![] };
+        let weak = [visitor(3, true, None)];
+        let tile = planned
+            .best_tile(&town, &[&weak], BuildingType::RedFlowers)
+            .expect("a tile next to the lane");
+        assert!(town.is_buildable(tile));
+        let (_, forecast) = planned
+            .with_hypothetical_building(&town, BuildingType::RedFlowers, tile)
+            .forecast(&weak);
+        assert_eq!(forecast.satisfied, 1);
+
+        let strong = [visitor(100, true, None)];
+        assert_eq!(
+            planned.best_tile(&town, &[&strong], BuildingType::RedFlowers),
+            None
+        );
+    }
+}
@@ -1,10 +1,16 @@
 use crate::game::{
-    components::*, fight::*, forestry::ForestrySystem, movement::MoveSystem,
-    story::entity_trigger::EntityTriggerSystem, town::Town, units::worker_system::WorkerSystem,
-    units::workers::Worker, Game,
+    components::*,
+    fight::*,
+    forestry::ForestrySystem,
+    movement::MoveSystem,
+    story::entity_trigger::EntityTriggerSystem,
+    town::Town,
+    units::worker_system::WorkerSystem,
+    units::workers::{TaskQueueDraft, Worker},
+    Game,
 };
 use crate::gui::{
-    input::{left_click::TownLeftClickSystem, MouseState},
+    input::{left_click::TownLeftClickSystem, pointer::PointerKind, KeyModifiers, MouseState},
     ui_state::*,
 };
 use crate::init::quicksilver_integration::Signal;
@@ -96,6 +102,7 @@ impl<'a, 'b> Frame for TownFrame<'a, 'b> {
         Ok(())
     }
     fn right_click(&mut self, state: &mut Self::State, pos: (i32, i32)) -> Result<(), Self::Error> {
+        let KeyModifiers { shift, ctrl } = *state.world.read_resource::<KeyModifiers>();
         let town_world = state.town_world();
         let view_state = town_world.fetch_mut::<ViewState>();
 
@@ -123,28 +130,40 @@ impl<'a, 'b> Frame for TownFrame<'a, 'b> {
         let net_ids = town_world.read_component::<NetObj>();
         let mana = town_world.read_component::<Mana>();
 
-        let maybe_top_hit = Town::clickable_lookup(&entities, mouse_pos, &position, &clickable);
+        let min_hit_target = town_world
+            .fetch::<PointerKind>()
+            .min_hit_target(town.resolution);
+        let maybe_top_hit =
+            Town::clickable_lookup(&entities, mouse_pos, &position, &clickable, min_hit_target);
 
         if let Some(e) = (*ui_state).selected_entity {
             if let Some(worker) = worker.get_mut(e) {
-                let maybe_job = worker.task_on_right_click(&mouse_pos, &town);
+                let maybe_job = worker.task_on_right_click(&mouse_pos, &town, ctrl);
                 if let Some((job, destination)) = maybe_job {
                     let target = maybe_top_hit.and_then(|e| net_ids.get(e)).map(|n| n.id);
-                    let (from, movement) = (&position, &moving).join().get(e, &entities).unwrap();
-                    let start = town
-                        .resolution
-                        .next_tile_in_direction(from.area.pos, movement.momentum);
                     let new_job = (job, target);
-                    worker.new_order(
-                        e,
-                        start,
-                        new_job,
-                        destination,
-                        &*town,
-                        &mut *errq,
-                        &mut containers,
-                        &mana,
-                    );
+                    let mut queue = town_world.write_resource::<TaskQueueDraft>();
+                    if shift {
+                        // Shift-click only plans the job, the queue editor submits it
+                        queue.append(e, new_job, destination);
+                    } else {
+                        let (from, movement) =
+                            (&position, &moving).join().get(e, &entities).unwrap();
+                        let start = town
+                            .resolution
+                            .next_tile_in_direction(from.area.pos, movement.momentum);
+                        worker.new_order(
+                            e,
+                            start,
+                            new_job,
+                            destination,
+                            &*town,
+                            &mut *errq,
+                            &mut containers,
+                            &mana,
+                        );
+                        queue.reset();
+                    }
                 }
             }
         }
@@ -163,7 +182,7 @@ impl<'a, 'b> TownFrame<'a, 'b> {
             .with(WorkerSystem::new(ep.clone()), "work", &[])
             .with(MoveSystem, "move", &["work"])
             .with(FightSystem::new(ep.clone()), "fight", &["move"])
-            .with(ForestrySystem, "forest", &[])
+            .with(ForestrySystem::default(), "forest", &[])
             .with(EntityTriggerSystem::new(ep), "ets", &[])
             .build();
 
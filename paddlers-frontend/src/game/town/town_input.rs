@@ -122,21 +122,28 @@ impl Town {
         errq: &mut WriteExpect<'a, ErrorQueue>,
         // TODO: Only temporary experiment
         signals: &mut WriteExpect<'a, crate::view::ExperimentalSignalChannel>,
+        min_hit_target: f32,
     ) -> Option<NewTaskDescriptor> {
-        let maybe_top_hit = Self::clickable_lookup(entities, mouse_pos, position, clickable);
+        let maybe_top_hit =
+            Self::clickable_lookup(entities, mouse_pos, position, clickable, min_hit_target);
         if let Some(grabbed) = ui_state.take_grabbed_item() {
             match grabbed {
                 Grabbable::NewBuilding(bt) => {
-                    if let Some(pos) = self.get_buildable_tile(mouse_pos) {
-                        RestApiState::get()
-                            .http_place_building(pos, bt, current_village())
-                            .unwrap_or_else(|e| errq.push(e));
-                        resources.spend(&bt.price());
-                        self.insert_new_building(&entities, &lazy, pos, bt);
-                        let signal = Signal::BuildingBuilt(bt);
-                        signals.push_back(signal);
-                    } else {
-                        ui_state.set_grabbed_item(grabbed);
+                    let pos = self.resolution.tile(mouse_pos);
+                    match self.check_placement(pos, bt) {
+                        Ok(()) => {
+                            RestApiState::get()
+                                .http_place_building(pos, bt, current_village())
+                                .unwrap_or_else(|e| errq.push(e));
+                            resources.spend(&bt.price());
+                            self.insert_new_building(&entities, &lazy, pos, bt);
+                            let signal = Signal::BuildingBuilt(bt);
+                            signals.push_back(signal);
+                        }
+                        Err(e) => {
+                            errq.push(e);
+                            ui_state.set_grabbed_item(grabbed);
+                        }
                     }
                 }
                 Grabbable::Ability(a) => {
@@ -161,25 +168,39 @@ impl Town {
         None
     }
 
-    /// Returns the top most entity clickable in the town view
+    /// Returns the top most entity clickable in the town view.
+    /// Entities smaller than `min_hit_target` can also be hit close to their actual area.
     pub fn clickable_lookup<'a>(
         entities: &Entities<'a>,
         mouse_pos: Vector,
         position: &ReadStorage<'a, Position>,
         clickable: &ReadStorage<'a, Clickable>,
+        min_hit_target: f32,
     ) -> Option<Entity> {
         let mut top_hit: Option<(i32, Entity)> = None;
+        let mut closest_hit: Option<(f32, Entity)> = None;
         for (e, pos, _) in (entities, position, clickable).join() {
             if mouse_pos.overlaps_rectangle(&pos.area) {
                 if top_hit.is_none() || top_hit.unwrap().0 < pos.z {
                     top_hit = Some((pos.z, e));
                 }
+            } else if mouse_pos.overlaps_rectangle(&hit_target(&pos.area, min_hit_target)) {
+                let d = mouse_pos.distance(pos.area.center());
+                if closest_hit.is_none() || closest_hit.unwrap().0 > d {
+                    closest_hit = Some((d, e));
+                }
             }
         }
-        top_hit.map(|tup| tup.1)
+        top_hit.or(closest_hit).map(|tup| tup.1)
     }
 }
 
+/// Enlarges an area around its center to be at least `min_size` wide and high
+fn hit_target(area: &Rectangle, min_size: f32) -> Rectangle {
+    let size = Vector::new(area.width().max(min_size), area.height().max(min_size));
+    Rectangle::new_sized(size).with_center(area.center())
+}
+
 pub fn check_condition(condition: &Condition, resources: &TownResources) -> PadlResult<()> {
     match condition {
         Condition::HasResources(price) => {
@@ -1,6 +1,10 @@
 use super::*;
 use quicksilver::graphics::{Drawable, Mesh};
 use quicksilver::prelude::*;
+use std::collections::HashMap;
+
+/// Aura damage at which the heatmap reaches its strongest color
+const HEATMAP_MAX_DAMAGE: i64 = 8;
 
 impl Town {
     pub fn render_background(
@@ -89,6 +93,90 @@ impl Town {
         Ok(())
     }
 
+    /// Draws planned buildings as transparent ghosts.
+    /// Tiles that are free are tinted blue, tiles blocked by something else are tinted red.
+    pub fn render_blueprint(
+        &self,
+        window: &mut Window,
+        sprites: &mut Sprites,
+        parts: &[(BuildingType, TileIndex)],
+        unit_length: f32,
+    ) -> Result<()> {
+        let d = unit_length;
+        let ghost_col = Color {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+            a: 0.5,
+        };
+        for (bt, (x, y)) in parts {
+            let area = Rectangle::new((d * *x as f32, d * *y as f32), (d, d));
+            let tile_col = if self.is_buildable((*x, *y)) {
+                Color {
+                    r: 0.5,
+                    g: 0.7,
+                    b: 1.0,
+                    a: 0.3,
+                }
+            } else {
+                Color {
+                    r: 1.0,
+                    g: 0.3,
+                    b: 0.3,
+                    a: 0.4,
+                }
+            };
+            window.draw_ex(&area, Col(tile_col), Transform::IDENTITY, Z_TILE_SHADOW);
+            let img = sprites.index(bt.sprite().default());
+            window.draw_ex(
+                &area,
+                Blended(&img, ghost_col),
+                Transform::IDENTITY,
+                Z_BUILDINGS + 1,
+            );
+        }
+        Ok(())
+    }
+
+    /// Colors lane tiles by the total damage of the auras reaching them
+    pub fn render_aura_heatmap(&self, window: &mut Window, damage: &HashMap<TileIndex, i64>) {
+        let d = self.resolution.unit_length();
+        for ((x, y), dmg) in damage {
+            if *dmg <= 0 {
+                continue;
+            }
+            let intensity = (*dmg as f32 / HEATMAP_MAX_DAMAGE as f32).min(1.0);
+            let col = Color {
+                r: 1.0,
+                g: 1.0 - 0.8 * intensity,
+                b: 0.2,
+                a: 0.3 + 0.3 * intensity,
+            };
+            let area = Rectangle::new((d * *x as f32, d * *y as f32), (d, d));
+            window.draw_ex(&area, Col(col), Transform::IDENTITY, Z_TILE_SHADOW);
+        }
+    }
+    /// Marks the tile on which a held building would be placed
+    pub fn render_placement_marker(&self, window: &mut Window, tile: TileIndex, valid: bool) {
+        let col = if valid {
+            Color {
+                r: 0.3,
+                g: 1.0,
+                b: 0.3,
+                a: 0.4,
+            }
+        } else {
+            Color {
+                r: 1.0,
+                g: 0.3,
+                b: 0.3,
+                a: 0.4,
+            }
+        };
+        let area = self.resolution.tile_area(tile);
+        window.draw_ex(&area, Col(col), Transform::IDENTITY, Z_TILE_SHADOW);
+    }
+
     pub fn shadow_rectified_circle(
         resolution: ScreenResolution,
         window: &mut Window,
@@ -2,7 +2,7 @@ mod visitor_maintenance;
 
 pub use visitor_maintenance::*;
 
-use crate::game::town::town_defence::AttackingHobo;
+use crate::game::town::town_defence::{AttackingHobo, PlannedVisitor};
 use crate::game::{
     components::NetObj,
     fight::Health,
@@ -121,14 +121,19 @@ impl AttacksQueryVillageAttacks {
             .attacker
             .as_ref()
             .map(|a| &a.display_name)
-            .map(|player| format!("From {}", player))
-            .unwrap_or("Anarchists".to_owned());
+            .map(|player| {
+                game.locale
+                    .gettext_args("visit-origin", &[("player", player)])
+            })
+            .unwrap_or(game.locale.gettext("visit-without-origin").to_owned());
         let size = self.units.len() as u32;
         let atk = Attack::new(birth_time, description, size);
 
         let mut out = vec![];
+        let mut visitors = vec![];
         for (i, unit) in self.units.into_iter().enumerate() {
             let unit_rep = AttackingHobo { unit, attack: &atk };
+            visitors.push(PlannedVisitor::from(&unit_rep));
             let effects = game.touched_auras(&unit_rep, now);
             let builder = unit_rep.create_entity(
                 game.town_context.home_world_mut().create_entity(),
@@ -141,6 +146,7 @@ impl AttacksQueryVillageAttacks {
             out.push(builder.build());
         }
 
+        let atk = atk.with_visitors(visitors);
         game.world.create_entity().with(atk).build();
 
         Ok(out)
@@ -174,10 +180,19 @@ impl<'a> AttackingHobo<'a> {
         let time_until_resting = self.time_until_resting();
 
         // Simulate all interactions with buildings for the visitor which happened in the past
-        let dmg = <Game<'_, '_> as IDefendingTown>::damage(&auras) + self.effects_strength();
+        let stats = self.color_stats();
+        let affecting = auras.get(stats.ignored_auras..).unwrap_or(&[]);
+        let dmg =
+            <Game<'_, '_> as IDefendingTown>::damage(affecting) + self.effective_effects_strength();
         let hp_left = (hp - dmg as i64).max(0);
-        let aura_ids = auras.into_iter().map(|a| a.0).collect();
-        let health = Health::new(hp, hp_left, aura_ids);
+        let auras_passed = auras.len();
+        let mut aura_ids: Vec<_> = auras.into_iter().map(|a| a.0).collect();
+        aura_ids.sort();
+        let health = Health::new(hp, hp_left, aura_ids).with_color_stats(
+            stats,
+            auras_passed,
+            self.effects_strength() as i64,
+        );
 
         // Adapt position for units that have been resting and were then released
         if let Some(released) = &self.unit.info.released {
@@ -1,6 +1,18 @@
 use crate::game::town::TownContext;
-use crate::game::{components::NetObj, movement::Position, Game};
-use crate::gui::{render::Renderable, sprites::*, utils::*, z::Z_UNITS};
+use crate::game::{
+    components::{NetObj, UiMenu},
+    game_event_manager::GameEvent,
+    movement::Position,
+    Game,
+};
+use crate::gui::{
+    gui_components::{ClickOutput, UiBox, UiElement},
+    input::Clickable,
+    render::Renderable,
+    sprites::*,
+    utils::*,
+    z::Z_UNITS,
+};
 use crate::net::graphql::query_types::{
     hobos_query::HobosQueryVillageHobosNest, HobosQueryResponse, HobosQueryUnitColor,
 };
@@ -67,12 +79,27 @@ fn new_sitting_hobo(
     let size = (ul, ul);
     let rend = RenderVariant::Img(SpriteSet::Simple(SingleSprite::SittingYellowDuck));
 
+    // Settled hobos can be turned into prophets or sent away
+    let mut menu = UiBox::new(2, 1, 0.0, 1.0);
+    menu.add(
+        UiElement::new(ClickOutput::Event(GameEvent::HttpOrdainHobo(HoboKey(id))))
+            .with_image(SpriteSet::Simple(SingleSprite::Prophet))
+            .with_background_color(LIGHT_BLUE),
+    );
+    menu.add(
+        UiElement::new(ClickOutput::Event(GameEvent::HttpEvictHobo(HoboKey(id))))
+            .with_image(SpriteSet::Simple(SingleSprite::DuckSteps))
+            .with_background_color(RED),
+    );
+
     let entity = world
         .create_entity()
         .with(NetObj::hobo(id))
         .with(Hobo)
         .with(Renderable::new(rend))
         .with(Position::new(pos, size, Z_UNITS))
+        .with(Clickable)
+        .with(UiMenu { ui: menu })
         .build();
     Ok(entity)
 }
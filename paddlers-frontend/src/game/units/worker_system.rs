@@ -126,6 +126,13 @@ impl<'a> System<'a> for WorkerSystem {
                             errq.push(e)
                         }
                     }
+                    TaskType::PlantTree => {
+                        mov.stand_still(task.start_time);
+                        anim.direction = Direction::Undirected;
+                        if let Err(e) = plant_tree(&mut town, task.position, &entities, &lazy) {
+                            errq.push(e)
+                        }
+                    }
                     _ => debug_assert!(false, "Unexpected task"),
                 }
             }
@@ -160,3 +167,16 @@ fn collect_reward(
     Ok(())
     // TODO: Level up
 }
+
+fn plant_tree(
+    town: &mut Town,
+    position: TileIndex,
+    entities: &Entities,
+    lazy: &LazyUpdate,
+) -> PadlResult<()> {
+    if town.building_type(position).is_ok() {
+        return PadlErrorCode::TileOccupied.dev();
+    }
+    town.insert_new_building(entities, lazy, position, BuildingType::Tree);
+    Ok(())
+}
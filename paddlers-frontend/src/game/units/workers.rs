@@ -1,7 +1,10 @@
 use crate::game::{
     components::*,
     movement::Position,
-    town::{task_factory::NewTaskDescriptor, TileIndex, Town},
+    town::{
+        task_factory::{routine_steps, NewTaskDescriptor, QueuedJob},
+        TileIndex, Town,
+    },
 };
 use crate::gui::render::Renderable;
 use crate::gui::z::*;
@@ -23,6 +26,15 @@ pub struct Worker {
     pub netid: i64,
 }
 
+/// Jobs planned for the selected worker in the task queue editor.
+/// The draft follows the tasks of the worker until the player edits it, then it is kept until submitted or reset.
+#[derive(Default, Debug)]
+pub struct TaskQueueDraft {
+    worker: Option<Entity>,
+    jobs: Vec<QueuedJob>,
+    edited: bool,
+}
+
 #[derive(Debug)]
 pub struct WorkerTask {
     pub task_type: TaskType,
@@ -73,6 +85,36 @@ impl Worker {
         }
     }
 
+    /// Worker is ordered to perform all jobs of a queue, one after another, replacing what it was doing so far.
+    pub fn submit_queue<'a>(
+        &mut self,
+        entity: Entity,
+        start: TileIndex,
+        jobs: &[QueuedJob],
+        town: &Town,
+        containers: &mut WriteStorage<'a, EntityContainer>,
+        mana: &ReadStorage<'a, Mana>,
+    ) -> PadlResult<()> {
+        let mana = mana.get(entity);
+        for queued in jobs {
+            town.check_task_constraints(queued.job, queued.destination, containers, mana)?;
+        }
+        let msg = TaskList {
+            worker_id: self.key(),
+            tasks: town.build_queued_task_chain(start, jobs)?,
+        };
+        RestApiState::get().http_overwrite_tasks(msg)
+    }
+
+    /// Lets the worker repeat the jobs, until it gets new orders
+    pub fn submit_routine(&self, jobs: &[QueuedJob]) -> PadlResult<()> {
+        let msg = RoutineDefinition {
+            worker_id: self.key(),
+            steps: routine_steps(jobs),
+        };
+        RestApiState::get().http_set_routine(msg)
+    }
+
     /// Create a list of tasks that walk a worker to a place and let's it perform a job there.
     /// The returned format can be understood by the backend interface.
     /// Returns an error if the job cannot be done by this worker at the desired position.
@@ -95,20 +137,22 @@ impl Worker {
         };
         Ok(msg)
     }
-    /// Finds the default-task that is performed on a right click in the town area
+    /// Finds the default-task that is performed on a right click in the town area.
+    /// With `alternative`, the second task available on the tile is picked, e.g. planting a tree instead of walking there.
     pub fn task_on_right_click<'a>(
         &mut self,
         click: &Vector,
         town: &Town,
+        alternative: bool,
     ) -> Option<(TaskType, TileIndex)> {
         let destination = town.resolution.tile(*click); // TODO: destination is not always where it has been clicked
-        let job = town
-            .available_tasks(destination)
-            .into_iter()
-            // .filter(
-            //     || TODO
-            // )
-            .next()?;
+        let mut jobs = town.available_tasks(destination).into_iter();
+        let default = jobs.next()?;
+        let job = if alternative {
+            jobs.next().unwrap_or(default)
+        } else {
+            default
+        };
         Some((job, destination))
     }
     fn go_idle(&mut self, idx: TileIndex) -> Result<TaskList, String> {
@@ -129,6 +173,68 @@ impl Worker {
     }
 }
 
+impl TaskQueueDraft {
+    /// Shows the queue of a worker, unless the player is currently editing the queue of that same worker
+    pub fn follow(&mut self, worker: Entity, jobs: Vec<QueuedJob>) {
+        if self.worker != Some(worker) || !self.edited {
+            self.worker = Some(worker);
+            self.jobs = jobs;
+            self.edited = false;
+        }
+    }
+    /// Discards all edits, the draft shows the queue known by the server again
+    pub fn reset(&mut self) {
+        self.edited = false;
+    }
+    pub fn worker(&self) -> Option<Entity> {
+        self.worker
+    }
+    pub fn jobs(&self) -> &[QueuedJob] {
+        &self.jobs
+    }
+    pub fn is_edited(&self) -> bool {
+        self.edited
+    }
+    /// Adds a job at the end of the queue of the given worker
+    pub fn append(&mut self, worker: Entity, job: NewTaskDescriptor, destination: TileIndex) {
+        if self.worker != Some(worker) {
+            self.worker = Some(worker);
+            self.jobs.clear();
+        }
+        self.jobs.push(QueuedJob {
+            job,
+            destination,
+            start_time: None,
+        });
+        self.edited = true;
+    }
+    pub fn move_up(&mut self, index: usize) {
+        if index > 0 && index < self.jobs.len() {
+            self.jobs.swap(index - 1, index);
+            self.edited_from(index - 1);
+        }
+    }
+    pub fn move_down(&mut self, index: usize) {
+        if index + 1 < self.jobs.len() {
+            self.jobs.swap(index, index + 1);
+            self.edited_from(index);
+        }
+    }
+    pub fn remove(&mut self, index: usize) {
+        if index < self.jobs.len() {
+            self.jobs.remove(index);
+            self.edited_from(index);
+        }
+    }
+    /// Jobs from the given index onwards will start at a different time than scheduled so far
+    fn edited_from(&mut self, index: usize) {
+        for queued in &mut self.jobs[index..] {
+            queued.start_time = None;
+        }
+        self.edited = true;
+    }
+}
+
 pub fn move_worker_into_building<'a>(
     containers: &mut WriteStorage<'a, EntityContainer>,
     ui_menus: &mut WriteStorage<'a, UiMenu>,
@@ -145,8 +251,7 @@ pub fn move_worker_into_building<'a>(
     c.add_entity_unchecked(worker_e, &renderable, &mut ui_menu);
     town.add_entity_to_building(&building_pos)
         .expect("Task has conflict");
-    town.add_stateful_task(c.task)
-        .expect("Task has conflict in town state");
+    town.add_stateful_task(c.task);
     lazy.remove::<Position>(worker_e);
 }
 
@@ -188,3 +293,80 @@ impl Worker {
         WorkerKey(self.netid)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduled(task_type: TaskType, x: usize, s: i64) -> QueuedJob {
+        QueuedJob {
+            job: (task_type, None),
+            destination: (x, 0),
+            start_time: Some(Timestamp::from_seconds(s)),
+        }
+    }
+
+    #[test]
+    fn draft_keeps_edits_until_reset() {
+        let mut world = World::new();
+        let worker = world.create_entity().build();
+        let mut draft = TaskQueueDraft::default();
+        draft.follow(worker, vec![scheduled(TaskType::ChopTree, 1, 0)]);
+        assert!(!draft.is_edited());
+
+        draft.append(worker, (TaskType::PlantTree, None), (2, 0));
+        assert!(draft.is_edited());
+        assert_eq!(draft.jobs().len(), 2);
+        assert_eq!(draft.jobs()[1].start_time, None);
+
+        // Updates from the server do not overwrite the edited queue
+        draft.follow(worker, vec![]);
+        assert_eq!(draft.jobs().len(), 2);
+
+        draft.reset();
+        draft.follow(worker, vec![]);
+        assert!(draft.jobs().is_empty());
+    }
+
+    #[test]
+    fn draft_switches_workers() {
+        let mut world = World::new();
+        let first = world.create_entity().build();
+        let second = world.create_entity().build();
+        let mut draft = TaskQueueDraft::default();
+        draft.append(first, (TaskType::PlantTree, None), (2, 0));
+
+        draft.follow(second, vec![scheduled(TaskType::ChopTree, 1, 0)]);
+        assert_eq!(draft.worker(), Some(second));
+        assert_eq!(draft.jobs(), &[scheduled(TaskType::ChopTree, 1, 0)]);
+
+        draft.append(first, (TaskType::Idle, None), (3, 0));
+        assert_eq!(draft.worker(), Some(first));
+        assert_eq!(draft.jobs().len(), 1);
+    }
+
+    #[test]
+    fn removing_a_job_reschedules_later_jobs() {
+        let mut world = World::new();
+        let worker = world.create_entity().build();
+        let mut draft = TaskQueueDraft::default();
+        draft.follow(
+            worker,
+            vec![
+                scheduled(TaskType::ChopTree, 1, 0),
+                scheduled(TaskType::GatherSticks, 2, 10),
+                scheduled(TaskType::Idle, 3, 20),
+            ],
+        );
+
+        draft.remove(1);
+        assert!(draft.is_edited());
+        assert_eq!(draft.jobs()[0], scheduled(TaskType::ChopTree, 1, 0));
+        assert_eq!(draft.jobs()[1].job, (TaskType::Idle, None));
+        assert_eq!(draft.jobs()[1].start_time, None);
+
+        // Out of range indices are ignored
+        draft.remove(5);
+        assert_eq!(draft.jobs().len(), 2);
+    }
+}
@@ -1,5 +1,9 @@
-//! View for incoming and outgoing visits as well as reports of passed visits
+//! View for incoming and outgoing visits as well as reports and replays of passed visits
+//! and messages from other players
 
 pub mod attacks;
+pub mod defence_planner;
+pub mod messages;
+pub mod replay;
 pub mod reports;
 pub mod visitor_menu;
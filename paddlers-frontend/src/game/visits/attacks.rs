@@ -1,5 +1,6 @@
 //! For incoming visits (attacks)
 
+use crate::game::town::town_defence::PlannedVisitor;
 use crate::gui::utils::colors::LIGHT_BLUE;
 use crate::gui::z::*;
 use crate::init::quicksilver_integration::Signal;
@@ -23,6 +24,7 @@ pub struct Attack {
     size: u32,
     description: String,
     dom_node: Option<TextNode>,
+    visitors: Vec<PlannedVisitor>,
 }
 
 impl Game<'_, '_> {
@@ -50,27 +52,30 @@ impl Attack {
             dom_node: None,
             description,
             size,
+            visitors: vec![],
         }
     }
-    fn arrival(&self) -> String {
-        let t = (self.arrival - utc_now()).seconds();
-        if t > 0 {
-            t.to_string() + "s"
-        } else {
-            "Arrived".to_owned()
-        }
+    pub fn with_visitors(mut self, visitors: Vec<PlannedVisitor>) -> Self {
+        self.visitors = visitors;
+        self
+    }
+    pub fn visitors(&self) -> &[PlannedVisitor] {
+        &self.visitors
     }
-    fn to_html(&self) -> String {
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+    fn to_html(&self, labels: &ArrivalLabels) -> String {
         format!(
             "<div>{}</div><div>{}</div><div>{}</div>",
             self.description,
             self.size,
-            self.arrival()
+            labels.text(self.arrival)
         )
     }
-    fn update_dom(&mut self) -> PadlResult<()> {
+    fn update_dom(&mut self, labels: &ArrivalLabels) -> PadlResult<()> {
         if self.dom_node.is_some() {
-            let text = self.arrival();
+            let text = labels.text(self.arrival);
             self.dom_node.as_mut().unwrap().update_owned(text);
             self.dom_node.as_mut().unwrap().draw();
             return Ok(());
@@ -85,18 +90,43 @@ impl Attack {
     }
 }
 
+/// Localized texts for the time until a visit arrives
+#[derive(Clone)]
+struct ArrivalLabels {
+    countdown: String,
+    arrived: String,
+}
+
+impl ArrivalLabels {
+    fn new(locale: &TextDb) -> Self {
+        ArrivalLabels {
+            countdown: locale.gettext("visit-arrival-countdown").to_owned(),
+            arrived: locale.gettext("visit-arrived").to_owned(),
+        }
+    }
+    fn text(&self, arrival: Timestamp) -> String {
+        let t = (arrival - utc_now()).seconds();
+        if t > 0 {
+            self.countdown.replace("{seconds}", &t.to_string())
+        } else {
+            self.arrived.clone()
+        }
+    }
+}
+
 pub(crate) struct VisitorFrame<'a, 'b> {
     incoming_attacks_table: HtmlElement,
     update_dispatcher: Dispatcher<'a, 'b>,
     pane: panes::PaneHandle,
+    labels: ArrivalLabels,
 }
 
 impl<'a, 'b> VisitorFrame<'a, 'b> {
-    pub fn new(x: f32, y: f32, w: f32, h: f32) -> PadlResult<Self> {
+    pub fn new(x: f32, y: f32, w: f32, h: f32, locale: &TextDb) -> PadlResult<Self> {
         let pane = new_pane(
             x as u32,
             y as u32,
-            (w / 2.0) as u32,
+            w as u32,
             h as u32,
             r#"<div class="attack-table"></div>"#,
         )
@@ -106,16 +136,25 @@ impl<'a, 'b> VisitorFrame<'a, 'b> {
             .try_into()
             .map_err(|_| PadlError::dev_err(PadlErrorCode::InvalidDom("No table in pane")))?;
 
+        let labels = ArrivalLabels::new(locale);
         let update_dispatcher = DispatcherBuilder::new()
-            .with(UpdateAttackViewSystem::new(), "update_atk", &[])
+            .with(
+                UpdateAttackViewSystem::new(labels.clone()),
+                "update_atk",
+                &[],
+            )
             .build();
 
         let mut attack = VisitorFrame {
             incoming_attacks_table: table,
             update_dispatcher,
             pane,
+            labels,
         };
-        attack.add_row("<h2>Incoming Visitors</h2>")?;
+        attack.add_row(&format!(
+            "<h2>{}</h2>",
+            locale.gettext("visits-incoming-title")
+        ))?;
         attack.pane.hide()?;
 
         Ok(attack)
@@ -144,11 +183,12 @@ impl<'a, 'b> Frame for VisitorFrame<'a, 'b> {
         let mut errq = state.world.write_resource::<ErrorQueue>();
         for a in (&mut attack).join() {
             if a.dom_node.is_none() {
-                let html = a.to_html();
+                let html = a.to_html(&self.labels);
                 match self.add_row(&html) {
                     Ok(node) => {
                         if let Some(arrival_node) = node.last_child() {
-                            let text_node = TextNode::new(arrival_node, a.arrival());
+                            let text_node =
+                                TextNode::new(arrival_node, self.labels.text(a.arrival));
                             a.dom_node = Some(text_node);
                         } else {
                             errq.push(PadlError::dev_err(PadlErrorCode::InvalidDom(
@@ -191,11 +231,13 @@ impl<'a, 'b> Frame for VisitorFrame<'a, 'b> {
 
 pub struct UpdateAttackViewSystem {
     last_update: Timestamp,
+    labels: ArrivalLabels,
 }
 impl UpdateAttackViewSystem {
-    pub fn new() -> Self {
+    fn new(labels: ArrivalLabels) -> Self {
         UpdateAttackViewSystem {
             last_update: utc_now(),
+            labels,
         }
     }
 }
@@ -211,7 +253,7 @@ impl<'a> System<'a> for UpdateAttackViewSystem {
         self.last_update = now;
         for a in (&mut attack).join() {
             if a.dom_node.is_some() {
-                a.update_dom().unwrap_or_else(|e| errq.push(e));
+                a.update_dom(&self.labels).unwrap_or_else(|e| errq.push(e));
             }
         }
     }
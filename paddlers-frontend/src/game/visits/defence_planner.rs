@@ -0,0 +1,354 @@
+//! Predicts the outcome of incoming visits against the current town.
+//!
+//! The prediction uses the same computations as the live fight in the town (see `IDefendingTown`),
+//! it assumes the town does not change until the visitors have left.
+//! Optionally, a building that does not exist yet is added to the town, to see how it would change the outcome.
+
+use crate::game::town::town_defence::{PlannedTown, PlannedVisitor, VisitForecast, VisitorOutcome};
+use crate::game::town::Town;
+use crate::game::visits::attacks::Attack;
+use crate::i18n::building_name_key;
+use crate::init::quicksilver_integration::Signal;
+use crate::prelude::*;
+use crate::view::Frame;
+use paddlers_shared_lib::game_mechanics::attributes::Attributes;
+use paddlers_shared_lib::game_mechanics::town::*;
+use quicksilver::prelude::{Rectangle, Window};
+use specs::prelude::*;
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use stdweb::unstable::TryInto;
+use stdweb::web::html_element::SelectElement;
+use stdweb::web::*;
+
+/// Minimal time between two updates of the prediction, in microseconds
+const PLANNER_UPDATE_INTERVAL: i64 = 1_000_000;
+
+pub(crate) struct DefencePlannerFrame<'a, 'b> {
+    pane: panes::PaneHandle,
+    results: Element,
+    building_select: SelectElement,
+    tile_select: SelectElement,
+    /// Building types that can be tried out, in the order of the select options (after the empty option)
+    building_options: Vec<BuildingType>,
+    /// Free tiles that can be chosen, in the order of the select options (after the option for the best tile)
+    tile_options: Vec<TileIndex>,
+    best_tile_label: String,
+    changed: Rc<Cell<bool>>,
+    last_update: Timestamp,
+    displayed: Vec<PlannedVisitView>,
+    phantom: PhantomData<(&'a (), &'b ())>,
+}
+
+/// Texts shown for one incoming visit
+#[derive(PartialEq)]
+struct PlannedVisitView {
+    title: String,
+    summary: String,
+    with_building: Option<String>,
+    visitors: Vec<(VisitorOutcome, String)>,
+}
+
+struct Hypothesis {
+    building: BuildingType,
+    tile: TileIndex,
+}
+
+impl DefencePlannerFrame<'_, '_> {
+    pub fn new(area: &Rectangle, locale: &TextDb) -> PadlResult<Self> {
+        let pane = panes::new_styled_pane(
+            area.x() as u32,
+            area.y() as u32,
+            area.width() as u32,
+            area.height() as u32,
+            r#"<section class="planner"></section>"#,
+            &[""],
+            &[],
+        )?;
+        let node = pane.first_inner_node()?;
+
+        let title = document().create_element("h2").unwrap();
+        title.set_text_content(locale.gettext("planner-title"));
+        node.append_child(&title);
+
+        let controls = document().create_element("div").unwrap();
+        controls.set_attribute("class", "planner-controls").unwrap();
+        node.append_child(&controls);
+        let label = document().create_element("span").unwrap();
+        label.set_text_content(locale.gettext("planner-hypothesis"));
+        controls.append_child(&label);
+
+        let building_options: Vec<BuildingType> = BuildingType::default_shop_buildings()
+            .cloned()
+            .filter(|bt| bt.attacks_per_cycle().is_none())
+            .filter(|bt| bt.attack_power().is_some() && bt.range().is_some())
+            .collect();
+        let mut labels = vec![locale.gettext("planner-no-building").to_owned()];
+        labels.extend(
+            building_options
+                .iter()
+                .map(|bt| locale.gettext(building_name_key(*bt)).to_owned()),
+        );
+        let building_select = new_select(&controls, &labels)?;
+
+        // The free tiles are filled in once the town is known
+        let best_tile_label = locale.gettext("planner-best-tile").to_owned();
+        let tile_select = new_select(&controls, &[best_tile_label.clone()])?;
+
+        let changed = Rc::new(Cell::new(true));
+        for select in &[&building_select, &tile_select] {
+            let changed_ref = changed.clone();
+            select.add_event_listener::<event::ChangeEvent, _>(move |_| {
+                changed_ref.set(true);
+            });
+        }
+
+        let results = document().create_element("div").unwrap();
+        results.set_attribute("class", "planner-results").unwrap();
+        node.append_child(&results);
+
+        pane.hide()?;
+
+        Ok(DefencePlannerFrame {
+            pane,
+            results,
+            building_select,
+            tile_select,
+            building_options,
+            tile_options: vec![],
+            best_tile_label,
+            changed,
+            last_update: Timestamp::from_us(0),
+            displayed: vec![],
+            phantom: PhantomData,
+        })
+    }
+    fn hypothesis(
+        &self,
+        town: &PlannedTown,
+        attacks: &[&Attack],
+        world: &World,
+    ) -> Option<Hypothesis> {
+        let building_index = self.building_select.selected_index()? as usize;
+        let building = *self.building_options.get(building_index.checked_sub(1)?)?;
+        let tile_index = self.tile_select.selected_index()? as usize;
+        let tile = match tile_index.checked_sub(1) {
+            Some(i) => *self.tile_options.get(i)?,
+            None => {
+                let visits: Vec<&[PlannedVisitor]> =
+                    attacks.iter().map(|atk| atk.visitors()).collect();
+                town.best_tile(&world.fetch::<Town>(), &visits, building)?
+            }
+        };
+        Some(Hypothesis { building, tile })
+    }
+    /// Offers only the tiles that are free at the moment, the selected tile is kept if it is still free
+    fn update_tile_options(&mut self, tiles: Vec<TileIndex>) {
+        if tiles == self.tile_options {
+            return;
+        }
+        let selected = self
+            .tile_select
+            .selected_index()
+            .and_then(|i| (i as usize).checked_sub(1))
+            .and_then(|i| self.tile_options.get(i).copied());
+        let mut labels = vec![self.best_tile_label.clone()];
+        labels.extend(tiles.iter().map(|(x, y)| format!("{}|{}", x, y)));
+        fill_select(&self.tile_select, &labels);
+        let index = selected
+            .and_then(|tile| tiles.iter().position(|t| *t == tile))
+            .map_or(0, |i| i + 1);
+        self.tile_select.set_selected_index(Some(index as u32));
+        self.tile_options = tiles;
+    }
+    fn plan(&self, state: &Game) -> Vec<PlannedVisitView> {
+        let now = utc_now();
+        let world = state.town_context.home_world();
+        let town = PlannedTown::from_world(world);
+        let attack_storage = state.world.read_storage::<Attack>();
+        let mut attacks: Vec<&Attack> = attack_storage
+            .join()
+            .filter(|atk| !atk.visitors().iter().all(|v| town.hobo_left_town(v, now)))
+            .collect();
+        attacks.sort_by(|a, b| a.arrival.partial_cmp(&b.arrival).unwrap());
+
+        let hypothesis = self.hypothesis(&town, &attacks, world);
+        let alternative = hypothesis
+            .as_ref()
+            .map(|h| town.with_hypothetical_building(&world.fetch::<Town>(), h.building, h.tile));
+
+        let locale = &state.locale;
+        attacks
+            .iter()
+            .map(|atk| {
+                let visitors = atk.visitors();
+                let (outcomes, forecast) = town.forecast(visitors);
+                let alternative = alternative.as_ref().map(|t| t.forecast(visitors));
+                let visitor_texts = visitors
+                    .iter()
+                    .zip(&outcomes)
+                    .enumerate()
+                    .map(|(i, (v, outcome))| {
+                        let hp = v.hp().to_string();
+                        let key = match outcome {
+                            VisitorOutcome::Satisfied => "planner-visitor-satisfied",
+                            VisitorOutcome::Waiting => "planner-visitor-waiting",
+                            VisitorOutcome::Unsatisfied => "planner-visitor-unsatisfied",
+                        };
+                        let mut text = locale.gettext_args(key, &[("hp", &hp)]);
+                        if let Some((new_outcomes, _)) = &alternative {
+                            if new_outcomes[i] != *outcome {
+                                text.push_str(locale.gettext("planner-visitor-improved"));
+                            }
+                        }
+                        (*outcome, text)
+                    })
+                    .collect();
+                PlannedVisitView {
+                    title: atk.description().to_owned(),
+                    summary: summary_text(
+                        locale,
+                        "planner-summary",
+                        &forecast,
+                        visitors.len(),
+                        &[],
+                    ),
+                    with_building: match (&alternative, &hypothesis) {
+                        (Some((_, forecast)), Some(h)) => {
+                            let (x, y) = h.tile;
+                            Some(summary_text(
+                                locale,
+                                "planner-summary-with-building",
+                                forecast,
+                                visitors.len(),
+                                &[
+                                    ("building", locale.gettext(building_name_key(h.building))),
+                                    ("x", &x.to_string()),
+                                    ("y", &y.to_string()),
+                                ],
+                            ))
+                        }
+                        _ => None,
+                    },
+                    visitors: visitor_texts,
+                }
+            })
+            .collect()
+    }
+    fn display(&mut self, views: Vec<PlannedVisitView>, locale: &TextDb) {
+        if views == self.displayed {
+            return;
+        }
+        self.results.set_text_content("");
+        if views.is_empty() {
+            let empty = document().create_element("p").unwrap();
+            empty.set_text_content(locale.gettext("planner-no-visits"));
+            self.results.append_child(&empty);
+        }
+        for view in &views {
+            let visit = document().create_element("div").unwrap();
+            visit.set_attribute("class", "planner-visit").unwrap();
+            let title = document().create_element("h3").unwrap();
+            title.set_text_content(&view.title);
+            visit.append_child(&title);
+            let summary = document().create_element("p").unwrap();
+            summary.set_text_content(&view.summary);
+            visit.append_child(&summary);
+            if let Some(text) = &view.with_building {
+                let p = document().create_element("p").unwrap();
+                p.set_attribute("class", "planner-hypothesis").unwrap();
+                p.set_text_content(text);
+                visit.append_child(&p);
+            }
+            let list = document().create_element("ul").unwrap();
+            for (outcome, text) in &view.visitors {
+                let item = document().create_element("li").unwrap();
+                let class = match outcome {
+                    VisitorOutcome::Satisfied => "satisfied",
+                    VisitorOutcome::Waiting => "waiting",
+                    VisitorOutcome::Unsatisfied => "unsatisfied",
+                };
+                item.set_attribute("class", class).unwrap();
+                item.set_text_content(text);
+                list.append_child(&item);
+            }
+            visit.append_child(&list);
+            self.results.append_child(&visit);
+        }
+        self.displayed = views;
+    }
+}
+
+impl<'a, 'b> Frame for DefencePlannerFrame<'a, 'b> {
+    type Error = PadlError;
+    type State = Game<'a, 'b>;
+    type Graphics = Window;
+    type Event = PadlEvent;
+    type Signal = Signal;
+    fn update(&mut self, state: &mut Self::State) -> Result<(), Self::Error> {
+        let now = real_now();
+        if self.changed.get() || (now - self.last_update).micros() >= PLANNER_UPDATE_INTERVAL {
+            self.changed.set(false);
+            self.last_update = now;
+            let tiles = state
+                .town_context
+                .home_world()
+                .fetch::<Town>()
+                .buildable_tiles();
+            self.update_tile_options(tiles);
+            let views = self.plan(state);
+            self.display(views, &state.locale);
+        }
+        Ok(())
+    }
+    fn enter(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        self.changed.set(true);
+        self.pane.show()?;
+        Ok(())
+    }
+    fn leave(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        self.pane.hide()?;
+        Ok(())
+    }
+}
+
+fn new_select(parent: &Element, labels: &[String]) -> PadlResult<SelectElement> {
+    let select = document().create_element("select").unwrap();
+    fill_select(&select, labels);
+    parent.append_child(&select);
+    select
+        .try_into()
+        .map_err(|_| PadlError::dev_err(PadlErrorCode::InvalidDom("Not a select element")))
+}
+
+/// Replaces all options of a select element
+fn fill_select<S: INode>(select: &S, labels: &[String]) {
+    select.set_text_content("");
+    for label in labels {
+        let option = document().create_element("option").unwrap();
+        option.set_text_content(label);
+        select.append_child(&option);
+    }
+}
+
+fn summary_text(
+    locale: &TextDb,
+    key: TextKey,
+    forecast: &VisitForecast,
+    total: usize,
+    extra: &[(&str, &str)],
+) -> String {
+    let satisfied = forecast.satisfied.to_string();
+    let total = total.to_string();
+    let karma = forecast.karma.to_string();
+    let feathers = forecast.feathers.to_string();
+    let mut args = vec![
+        ("satisfied", satisfied.as_str()),
+        ("total", total.as_str()),
+        ("karma", karma.as_str()),
+        ("feathers", feathers.as_str()),
+    ];
+    args.extend_from_slice(extra);
+    locale.gettext_args(key, &args)
+}
@@ -0,0 +1,235 @@
+use crate::gui::utils::colors::LIGHT_BLUE;
+use crate::gui::z::*;
+use crate::init::quicksilver_integration::Signal;
+use crate::net::game_master_api::RestApiState;
+use crate::net::graphql::query_types::MessagesResponse;
+use crate::net::NetMsg;
+use crate::prelude::*;
+use crate::view::*;
+use core::marker::PhantomData;
+use paddlers_shared_lib::api::messages::{MessageSend, MessagesRead, MAX_MESSAGE_LENGTH};
+use paddlers_shared_lib::prelude::{MessageKey, VillageKey};
+use quicksilver::prelude::{Col, Rectangle, Transform, Window};
+use specs::prelude::*;
+use std::cell::Cell;
+use std::rc::Rc;
+use stdweb::unstable::TryInto;
+use stdweb::web::html_element::TextAreaElement;
+use stdweb::web::*;
+
+/// Shows messages received from other players and allows writing new ones.
+pub(crate) struct MessageFrame<'a, 'b> {
+    pane: panes::PaneHandle,
+    list: Element,
+    compose: Element,
+    recipient_label: Element,
+    recipient: Rc<Cell<Option<VillageKey>>>,
+    _phantom: PhantomData<(&'a (), &'b ())>,
+}
+
+impl<'a, 'b> MessageFrame<'a, 'b> {
+    pub fn new(area: Rectangle, resolution: ScreenResolution, locale: &TextDb) -> PadlResult<Self> {
+        let right_padding = resolution.leaves_border_w() * 0.75;
+        let pane = panes::new_pane(
+            area.x() as u32,
+            area.y() as u32,
+            (area.width() - right_padding) as u32,
+            area.height() as u32,
+            r#"<section class="messages"></section>"#,
+        )?;
+        pane.hide()?;
+        let node = pane.first_inner_node()?;
+
+        let title = document().create_element("h2").unwrap();
+        title.set_text_content(locale.gettext("messages-title"));
+        node.append_child(&title);
+
+        let recipient = Rc::new(Cell::new(None));
+        let (compose, recipient_label) = Self::new_compose_node(recipient.clone(), locale);
+        node.append_child(&compose);
+
+        let list = document().create_element("div").unwrap();
+        list.set_attribute("class", "message-list").unwrap();
+        node.append_child(&list);
+
+        Ok(MessageFrame {
+            pane,
+            list,
+            compose,
+            recipient_label,
+            recipient,
+            _phantom: Default::default(),
+        })
+    }
+    fn new_compose_node(
+        recipient: Rc<Cell<Option<VillageKey>>>,
+        locale: &TextDb,
+    ) -> (Element, Element) {
+        let compose = document().create_element("div").unwrap();
+        compose.set_attribute("class", "letter compose").unwrap();
+        compose.set_attribute("hidden", "").unwrap();
+
+        let label = document().create_element("p").unwrap();
+        compose.append_child(&label);
+
+        let text_area = document().create_element("textarea").unwrap();
+        text_area
+            .set_attribute("maxlength", &MAX_MESSAGE_LENGTH.to_string())
+            .unwrap();
+        compose.append_child(&text_area);
+
+        let send_button = document().create_element("div").unwrap();
+        send_button.set_attribute("class", "letter-button").unwrap();
+        send_button.set_text_content(locale.gettext("message-send"));
+        compose.append_child(&send_button);
+
+        let cancel_button = document().create_element("div").unwrap();
+        cancel_button
+            .set_attribute("class", "letter-button")
+            .unwrap();
+        cancel_button.set_text_content(locale.gettext("message-cancel"));
+        compose.append_child(&cancel_button);
+
+        let area: TextAreaElement = text_area.try_into().unwrap();
+        let compose_ref = compose.clone();
+        let area_ref = area.clone();
+        let recipient_ref = recipient.clone();
+        let _handle = send_button.add_event_listener::<event::ClickEvent, _>(move |_| {
+            let body = area_ref.value();
+            if body.trim().is_empty() {
+                return;
+            }
+            if let Some(village) = recipient_ref.take() {
+                let msg = MessageSend {
+                    recipient: village,
+                    body,
+                };
+                if let Err(e) = RestApiState::get().http_send_message(msg) {
+                    println!("Failed to send API call {}", e);
+                }
+            }
+            area_ref.set_value("");
+            compose_ref.set_attribute("hidden", "").unwrap();
+        });
+        let compose_ref = compose.clone();
+        let _handle = cancel_button.add_event_listener::<event::ClickEvent, _>(move |_| {
+            recipient.set(None);
+            area.set_value("");
+            compose_ref.set_attribute("hidden", "").unwrap();
+        });
+        (compose, label)
+    }
+    fn open_compose(&mut self, village: VillageKey, player_name: &str, locale: &TextDb) {
+        self.recipient.set(Some(village));
+        self.recipient_label.set_text_content(
+            &locale.gettext_args("message-recipient", &[("player", player_name)]),
+        );
+        self.compose.remove_attribute("hidden");
+    }
+    fn add_messages(&mut self, messages: &MessagesResponse, locale: &TextDb) {
+        // Response is sorted with the newest message first, newest should end up on top
+        for msg in messages.iter().rev() {
+            let id = MessageKey(msg.id.parse().unwrap());
+            let sender = msg
+                .sender
+                .as_ref()
+                .map(|p| p.display_name.as_str())
+                .unwrap_or(locale.gettext("message-unknown-sender"));
+            let node = self.new_message_node(id, sender, &msg.sent(), &msg.body, msg.read, locale);
+            if let Some(first) = self.list.first_child() {
+                self.list.insert_before(&node, &first).unwrap();
+            } else {
+                self.list.append_child(&node);
+            }
+        }
+    }
+    fn new_message_node(
+        &self,
+        id: MessageKey,
+        sender: &str,
+        sent: &chrono::NaiveDateTime,
+        body: &str,
+        read: bool,
+        locale: &TextDb,
+    ) -> Element {
+        let letter_node = document().create_element("div").unwrap();
+        letter_node
+            .set_attribute("class", if read { "letter" } else { "letter unread" })
+            .unwrap();
+
+        let header_node = document().create_element("p").unwrap();
+        header_node
+            .set_attribute("class", "message-header")
+            .unwrap();
+        let header = format!("{} ({})", sender, sent.format("%d.%m.%Y %H:%M"));
+        header_node.set_text_content(&header);
+        letter_node.append_child(&header_node);
+
+        let text_node = document().create_element("p").unwrap();
+        text_node.set_text_content(body);
+        letter_node.append_child(&text_node);
+
+        if !read {
+            let button_node = document().create_element("div").unwrap();
+            button_node.set_attribute("class", "letter-button").unwrap();
+            button_node.set_text_content(locale.gettext("message-mark-read"));
+            let parent = letter_node.clone();
+            let button_ref = button_node.clone();
+            let _handle = button_node.add_event_listener::<event::ClickEvent, _>(move |_| {
+                let msg = MessagesRead { messages: vec![id] };
+                if let Err(e) = RestApiState::get().http_mark_messages_read(msg) {
+                    println!("Failed to send API call {}", e);
+                }
+                parent.set_attribute("class", "letter").unwrap();
+                let _node = parent.remove_child(&button_ref);
+            });
+            letter_node.append_child(&button_node);
+        }
+        letter_node
+    }
+}
+
+impl<'a, 'b> Frame for MessageFrame<'a, 'b> {
+    type Error = PadlError;
+    type State = Game<'a, 'b>;
+    type Graphics = Window;
+    type Event = PadlEvent;
+    type Signal = Signal;
+    fn event(&mut self, state: &mut Self::State, event: &Self::Event) -> Result<(), Self::Error> {
+        match event {
+            PadlEvent::Network(NetMsg::Messages(messages)) => {
+                self.add_messages(messages, &state.locale);
+            }
+            PadlEvent::Signal(Signal::ComposeMessage(village, player_name)) => {
+                self.open_compose(*village, player_name, &state.locale);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    fn draw(
+        &mut self,
+        state: &mut Self::State,
+        window: &mut Self::Graphics,
+    ) -> Result<(), Self::Error> {
+        let ui_state = state.world.read_resource::<ViewState>();
+        let main_area = Rectangle::new(
+            (0, 0),
+            (
+                ui_state.menu_box_area.x(),
+                (window.project() * window.screen_size()).y,
+            ),
+        );
+        std::mem::drop(ui_state);
+        window.draw_ex(&main_area, Col(LIGHT_BLUE), Transform::IDENTITY, Z_TEXTURE);
+        Ok(())
+    }
+    fn enter(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        self.pane.show()?;
+        Ok(())
+    }
+    fn leave(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        self.pane.hide()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,579 @@
+//! Replays of finished visits, reconstructed from the archive kept by the game master.
+//!
+//! The archive only stores what the visit started with: visitors, their effects and
+//! release times and the buildings of the town. Positions and satisfaction at any moment
+//! of the replay are computed with the shared defence logic, the same way the game master
+//! evaluated the visit.
+
+use crate::game::Game;
+use crate::gui::sprites::*;
+use crate::gui::ui_state::ClockTick;
+use crate::gui::utils::*;
+use crate::gui::z::*;
+use crate::init::quicksilver_integration::Signal;
+use crate::net::graphql::query_types::{
+    parse_timestamp, replays_query, ReplayBuildingResponse, ReplayHoboResponse,
+    ReplaysQueryVillageReplays,
+};
+use crate::net::NetMsg;
+use crate::prelude::*;
+use crate::view::Frame;
+use paddlers_shared_lib::game_mechanics::town::*;
+use paddlers_shared_lib::prelude::VisitReplayKey;
+use quicksilver::graphics::Mesh;
+use quicksilver::prelude::Window as QuicksilverWindow;
+use quicksilver::prelude::{Rectangle, Vector};
+use specs::WorldExt;
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use stdweb::web::*;
+
+const MAX_REPLAY_SPEED: u32 = 16;
+
+pub(crate) struct ReplayFrame<'a, 'b> {
+    pane: panes::PaneHandle,
+    clock: Element,
+    speed: Element,
+    /// Requests from DOM event listeners, applied on the next update
+    requests: Rc<Cell<Option<ReplayRequest>>>,
+    replays: Vec<Replay>,
+    playback: Option<Playback>,
+    background_cache: Option<Mesh>,
+    phantom: PhantomData<(&'a (), &'b ())>,
+}
+
+#[derive(Clone, Copy)]
+enum ReplayRequest {
+    TogglePause,
+    Restart,
+    Faster,
+    Slower,
+    Back,
+}
+
+struct Playback {
+    replay: VisitReplayKey,
+    /// Point in time of the visit that is currently shown
+    time: Timestamp,
+    /// Real time at which `time` was last advanced
+    last_update: Timestamp,
+    speed: u32,
+    paused: bool,
+}
+
+struct Replay {
+    id: VisitReplayKey,
+    arrival: Timestamp,
+    finished: Timestamp,
+    town: ReplayTown,
+    hobos: Vec<ReplayHobo>,
+}
+
+struct ReplayTown {
+    buildings: Vec<ReplayBuilding>,
+}
+
+struct ReplayBuilding {
+    building_type: BuildingType,
+    x: i32,
+    y: i32,
+    range: Option<f32>,
+    attack_power: Option<i32>,
+    attacks_per_cycle: Option<i32>,
+    creation: Timestamp,
+}
+
+struct ReplayHobo {
+    color: UnitColor,
+    hp: u32,
+    speed: f32,
+    hurried: bool,
+    arrival: Timestamp,
+    released: Option<Timestamp>,
+    /// Strength and start of all health effects
+    effects: Vec<(Timestamp, i32)>,
+}
+
+/// A visitor as it was at a specific point in time of the replay
+struct HoboAt<'a> {
+    hobo: &'a ReplayHobo,
+    time: Timestamp,
+}
+
+impl ReplayFrame<'_, '_> {
+    pub fn new(area: &Rectangle, locale: &TextDb) -> PadlResult<Self> {
+        let pane = panes::new_styled_pane(
+            area.x() as u32,
+            area.y() as u32,
+            area.width() as u32,
+            area.height() as u32,
+            r#"<section class="replay"></section>"#,
+            &[""],
+            &[("color", "white")],
+        )?;
+        let node = pane.first_inner_node()?;
+
+        let title = document().create_element("h2").unwrap();
+        title.set_text_content(locale.gettext("replay-title"));
+        node.append_child(&title);
+
+        let clock = document().create_element("p").unwrap();
+        node.append_child(&clock);
+        let speed = document().create_element("p").unwrap();
+        node.append_child(&speed);
+
+        let requests = Rc::new(Cell::new(None));
+        let buttons = [
+            (ReplayRequest::TogglePause, "replay-pause"),
+            (ReplayRequest::Slower, "replay-slower"),
+            (ReplayRequest::Faster, "replay-faster"),
+            (ReplayRequest::Restart, "replay-restart"),
+            (ReplayRequest::Back, "replay-back"),
+        ];
+        for (request, key) in &buttons {
+            let button = document().create_element("div").unwrap();
+            button.set_attribute("class", "replay-button").unwrap();
+            button.set_text_content(locale.gettext(key));
+            let requests_ref = requests.clone();
+            let request = *request;
+            let _handle = button.add_event_listener::<event::ClickEvent, _>(move |_| {
+                requests_ref.set(Some(request));
+            });
+            node.append_child(&button);
+        }
+
+        pane.hide()?;
+
+        Ok(ReplayFrame {
+            pane,
+            clock,
+            speed,
+            requests,
+            replays: vec![],
+            playback: None,
+            background_cache: None,
+            phantom: PhantomData,
+        })
+    }
+    fn replay(&self) -> Option<(&Replay, &Playback)> {
+        let playback = self.playback.as_ref()?;
+        self.replays
+            .iter()
+            .find(|r| r.id == playback.replay)
+            .map(|r| (r, playback))
+    }
+    fn apply_request(&mut self, request: ReplayRequest, state: &mut Game) {
+        if let ReplayRequest::Back = request {
+            state
+                .event_pool
+                .send(GameEvent::SwitchToView(UiView::Visitors(
+                    VisitorViewTab::Letters,
+                )))
+                .expect("Event pool send failed");
+            return;
+        }
+        let start = match self.replay() {
+            Some((replay, _)) => replay.arrival,
+            None => return,
+        };
+        let playback = self.playback.as_mut().unwrap();
+        match request {
+            ReplayRequest::TogglePause => playback.paused = !playback.paused,
+            ReplayRequest::Restart => playback.time = start,
+            ReplayRequest::Faster => playback.speed = (playback.speed * 2).min(MAX_REPLAY_SPEED),
+            ReplayRequest::Slower => playback.speed = (playback.speed / 2).max(1),
+            ReplayRequest::Back => unreachable!(),
+        }
+    }
+    fn advance(&mut self) {
+        let end = match self.replay() {
+            Some((replay, _)) => replay.end(),
+            None => return,
+        };
+        let playback = self.playback.as_mut().unwrap();
+        let now = real_now();
+        if !playback.paused {
+            let elapsed = (now - playback.last_update).micros() * playback.speed as i64;
+            let time = playback.time + Timestamp::from_us(elapsed);
+            playback.time = if time > end { end } else { time };
+        }
+        playback.last_update = now;
+    }
+    fn update_labels(&self, locale: &TextDb) {
+        if let Some((replay, playback)) = self.replay() {
+            let seconds = (playback.time - replay.arrival).seconds();
+            let total = (replay.end() - replay.arrival).seconds();
+            self.clock.set_text_content(&locale.gettext_args(
+                "replay-clock",
+                &[
+                    ("seconds", &seconds.to_string()),
+                    ("total", &total.to_string()),
+                ],
+            ));
+            self.speed.set_text_content(
+                &locale.gettext_args("replay-speed", &[("speed", &playback.speed.to_string())]),
+            );
+        }
+    }
+    fn draw_replay(
+        &self,
+        window: &mut QuicksilverWindow,
+        sprites: &mut Sprites,
+        ul: f32,
+    ) -> PadlResult<()> {
+        let (replay, playback) = match self.replay() {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+        let now = playback.time;
+        for b in &replay.town.buildings {
+            if now < b.creation {
+                continue;
+            }
+            let area = Rectangle::new((b.x as f32 * ul, b.y as f32 * ul), (ul, ul));
+            draw_static_image(
+                sprites,
+                window,
+                &area,
+                b.building_type.sprite().default(),
+                Z_BUILDINGS,
+                FitStrategy::Center,
+            )?;
+        }
+        for (rank, hobo) in replay.hobos.iter().enumerate() {
+            let unit = HoboAt { hobo, time: now };
+            if now < hobo.arrival || replay.town.hobo_left_town(&unit, now) {
+                continue;
+            }
+            let x = TOWN_X as f32 - replay.town.distance_walked(&unit, now);
+            let pos = Vector::new(x * ul, TOWN_LANE_Y as f32 * ul)
+                + Vector::new(0.3 * ul * rank as f32, (rank % 2) as f32 * 0.5 * ul);
+            let area = Rectangle::new(pos, (0.6 * ul, 0.4 * ul));
+            let happy = replay.town.hp_left(&unit, now) == 0;
+            draw_static_image(
+                sprites,
+                window,
+                &area,
+                SpriteIndex::Simple(hobo_sprite(hobo.color, happy)),
+                Z_VISITOR,
+                FitStrategy::Center,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'b> Frame for ReplayFrame<'a, 'b> {
+    type Error = PadlError;
+    type State = Game<'a, 'b>;
+    type Graphics = QuicksilverWindow;
+    type Event = PadlEvent;
+    type Signal = Signal;
+    fn event(&mut self, _state: &mut Self::State, event: &Self::Event) -> Result<(), Self::Error> {
+        match event {
+            PadlEvent::Network(NetMsg::Replays(data)) => {
+                self.replays = data.iter().map(Replay::from).collect();
+            }
+            PadlEvent::Signal(Signal::WatchReplay(key)) => {
+                if let Some(replay) = self.replays.iter().find(|r| r.id == *key) {
+                    self.playback = Some(Playback {
+                        replay: *key,
+                        time: replay.arrival,
+                        last_update: real_now(),
+                        speed: 1,
+                        paused: false,
+                    });
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    fn update(&mut self, state: &mut Self::State) -> Result<(), Self::Error> {
+        if let Some(request) = self.requests.take() {
+            self.apply_request(request, state);
+        }
+        self.advance();
+        self.update_labels(&state.locale);
+        Ok(())
+    }
+    fn draw(
+        &mut self,
+        state: &mut Self::State,
+        window: &mut Self::Graphics,
+    ) -> Result<(), Self::Error> {
+        let ul = state.world.fetch::<ScreenResolution>().unit_length();
+        let tick = state.world.read_resource::<ClockTick>().0;
+        let sprites = &mut state.sprites;
+        let town = state.town_context.town();
+        if self.background_cache.is_none() {
+            let mut mesh = Mesh::new();
+            town.render_background(&mut mesh, sprites, ul)?;
+            self.background_cache = Some(mesh);
+        }
+        window
+            .mesh()
+            .extend(self.background_cache.as_ref().unwrap());
+        town.render(window, sprites, tick, ul)?;
+        self.draw_replay(window, sprites, ul)?;
+        Ok(())
+    }
+    fn enter(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        if let Some(playback) = &mut self.playback {
+            playback.last_update = real_now();
+        }
+        self.pane.show()?;
+        Ok(())
+    }
+    fn leave(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        self.pane.hide()?;
+        Ok(())
+    }
+}
+
+impl Replay {
+    /// The moment the last visitor left, or the moment the game master finished the visit
+    fn end(&self) -> Timestamp {
+        self.hobos
+            .iter()
+            .map(|hobo| self.town.leave_time(hobo).unwrap_or(self.finished))
+            .fold(self.arrival, |end, t| if t > end { t } else { end })
+    }
+}
+
+impl From<&ReplaysQueryVillageReplays> for Replay {
+    fn from(data: &ReplaysQueryVillageReplays) -> Self {
+        let arrival = parse_timestamp(&data.arrival);
+        Replay {
+            id: VisitReplayKey(data.id.parse().unwrap()),
+            arrival,
+            finished: parse_timestamp(&data.finished),
+            town: ReplayTown {
+                buildings: data.buildings.iter().map(ReplayBuilding::from).collect(),
+            },
+            hobos: data
+                .hobos
+                .iter()
+                .map(|hobo| ReplayHobo::new(hobo, arrival))
+                .collect(),
+        }
+    }
+}
+
+impl From<&ReplayBuildingResponse> for ReplayBuilding {
+    fn from(data: &ReplayBuildingResponse) -> Self {
+        ReplayBuilding {
+            building_type: (&data.building_type).into(),
+            x: data.x as i32,
+            y: data.y as i32,
+            range: data.building_range.map(|r| r as f32),
+            attack_power: data.attack_power.map(|ap| ap as i32),
+            attacks_per_cycle: data.attacks_per_cycle.map(|n| n as i32),
+            creation: parse_timestamp(&data.creation),
+        }
+    }
+}
+
+impl ReplayHobo {
+    fn new(data: &ReplayHoboResponse, arrival: Timestamp) -> Self {
+        let effects = data
+            .effects
+            .iter()
+            .filter(|e| e.attribute == replays_query::HoboAttributeType::HEALTH)
+            .filter_map(|e| Some((parse_timestamp(&e.start_time), e.strength? as i32)))
+            .collect();
+        ReplayHobo {
+            color: data
+                .color
+                .as_ref()
+                .map(|c| c.into())
+                .unwrap_or(UnitColor::Yellow),
+            hp: data.hp as u32,
+            speed: data.speed as f32,
+            hurried: data.hurried,
+            arrival,
+            released: data.released.as_ref().map(parse_timestamp),
+            effects,
+        }
+    }
+}
+
+impl ReplayTown {
+    /// Number of tiles the visitor has swum since arriving in town
+    fn distance_walked(&self, unit: &HoboAt, now: Timestamp) -> f32 {
+        let speed = unit.speed();
+        if unit.hurried() {
+            return (now - unit.arrival()).seconds_float() * speed;
+        }
+        let to_rest_place = self.path_to_rest_place().len() as f32;
+        match self.left_rest_place(unit) {
+            Some(left) if left <= now => to_rest_place + (now - left).seconds_float() * speed,
+            _ => ((now - unit.arrival()).seconds_float() * speed).min(to_rest_place),
+        }
+    }
+    /// The moment the visitor swims out of sight, if it ever leaves
+    fn leave_time(&self, hobo: &ReplayHobo) -> Option<Timestamp> {
+        let unit = HoboAt {
+            hobo,
+            time: hobo.arrival,
+        };
+        let (start, tiles) = if hobo.hurried {
+            (hobo.arrival, self.path_straight_through().len())
+        } else {
+            (
+                self.left_rest_place(&unit)?,
+                self.path_from_rest_place().len(),
+            )
+        };
+        // +1 for swimming out of sight
+        Some(start + Timestamp::from_float_seconds((tiles + 1) as f32 / hobo.speed))
+    }
+}
+
+impl IAttackingHobo for HoboAt<'_> {
+    fn max_hp(&self) -> u32 {
+        self.hobo.hp
+    }
+    fn speed(&self) -> f32 {
+        self.hobo.speed
+    }
+    fn hurried(&self) -> bool {
+        self.hobo.hurried
+    }
+    fn arrival(&self) -> Timestamp {
+        self.hobo.arrival
+    }
+    fn released(&self) -> Option<Timestamp> {
+        self.hobo.released
+    }
+    fn effects_strength(&self) -> i32 {
+        self.hobo
+            .effects
+            .iter()
+            .filter(|(start, _)| *start <= self.time)
+            .map(|(_, strength)| strength)
+            .sum()
+    }
+    fn color(&self) -> UnitColor {
+        self.hobo.color
+    }
+}
+
+impl ITownLayoutMarker for ReplayTown {
+    const LAYOUT: TownLayout = TownLayout::Basic;
+}
+impl IDefendingTown for ReplayTown {
+    type AuraId = usize;
+    fn auras_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, i32)> {
+        let mut auras = vec![];
+        for (id, b) in self.buildings.iter().enumerate() {
+            if time < b.creation || b.attacks_per_cycle.is_some() {
+                continue;
+            }
+            if let (Some(range), Some(ap)) = (b.range, b.attack_power) {
+                let dx = b.x - index.0 as i32;
+                let dy = b.y - index.1 as i32;
+                if (dx * dx + dy * dy) as f32 <= range * range {
+                    auras.push((id, ap));
+                }
+            }
+        }
+        auras
+    }
+}
+
+fn hobo_sprite(color: UnitColor, happy: bool) -> SingleSprite {
+    match (color, happy) {
+        (UnitColor::Yellow, false) => SingleSprite::Duck,
+        (UnitColor::Yellow, true) => SingleSprite::DuckHappy,
+        (UnitColor::White, false) => SingleSprite::WhiteDuck,
+        (UnitColor::White, true) => SingleSprite::WhiteDuckHappy,
+        (UnitColor::Camo, false) => SingleSprite::CamoDuck,
+        (UnitColor::Camo, true) => SingleSprite::CamoDuckHappy,
+        (UnitColor::Prophet, _) => SingleSprite::Prophet,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(seconds: i64) -> Timestamp {
+        Timestamp::from_seconds(seconds)
+    }
+    fn building(x: i32, y: i32, creation: Timestamp) -> ReplayBuilding {
+        ReplayBuilding {
+            building_type: BuildingType::RedFlowers,
+            x,
+            y,
+            range: Some(1.0),
+            attack_power: Some(2),
+            attacks_per_cycle: None,
+            creation,
+        }
+    }
+    fn hobo(speed: f32, hurried: bool, released: Option<Timestamp>) -> ReplayHobo {
+        ReplayHobo {
+            color: UnitColor::Yellow,
+            hp: 10,
+            speed,
+            hurried,
+            arrival: s(0),
+            released,
+            effects: vec![],
+        }
+    }
+
+    #[test]
+    fn auras_in_range_at_the_time() {
+        let lane = TOWN_LANE_Y as i32;
+        let mut tower = building(8, lane - 1, s(0));
+        tower.attacks_per_cycle = Some(1);
+        let town = ReplayTown {
+            buildings: vec![
+                building(8, lane - 1, s(0)),
+                building(0, 0, s(0)),
+                building(8, lane + 1, s(10)),
+                tower,
+            ],
+        };
+        let tile = (8, TOWN_LANE_Y);
+        assert_eq!(town.auras_in_range(&tile, s(5)), vec![(0, 2)]);
+        assert_eq!(town.auras_in_range(&tile, s(10)), vec![(0, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn hurried_visitors_leave_after_swimming_through() {
+        let town = ReplayTown { buildings: vec![] };
+        let visitor = hobo(0.5, true, None);
+        let tiles = town.path_straight_through().len() as i64;
+        assert_eq!(town.leave_time(&visitor), Some(s((tiles + 1) * 2)));
+
+        let unit = HoboAt {
+            hobo: &visitor,
+            time: s(4),
+        };
+        assert_eq!(town.distance_walked(&unit, s(4)), 2.0);
+    }
+
+    #[test]
+    fn resting_visitors_wait_until_released() {
+        let town = ReplayTown { buildings: vec![] };
+        let to_rest_place = town.path_to_rest_place().len() as f32;
+        let waiting = hobo(1.0, false, None);
+        assert_eq!(town.leave_time(&waiting), None);
+
+        let released = hobo(1.0, false, Some(s(100)));
+        let tiles = town.path_from_rest_place().len() as i64;
+        assert_eq!(town.leave_time(&released), Some(s(100 + tiles + 1)));
+
+        let unit = HoboAt {
+            hobo: &released,
+            time: s(0),
+        };
+        assert_eq!(town.distance_walked(&unit, s(3)), 3.0);
+        assert_eq!(town.distance_walked(&unit, s(50)), to_rest_place);
+        assert_eq!(town.distance_walked(&unit, s(102)), to_rest_place + 2.0);
+    }
+}
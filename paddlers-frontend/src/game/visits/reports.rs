@@ -3,19 +3,26 @@ use crate::gui::utils::colors::LIGHT_BLUE;
 use crate::gui::z::*;
 use crate::init::quicksilver_integration::Signal;
 use crate::net::game_master_api::RestApiState;
+use crate::net::graphql::query_types::{parse_timestamp, ReplaysQueryVillageReplays};
 use crate::net::NetMsg;
 use crate::prelude::*;
 use crate::view::*;
 use core::marker::PhantomData;
 use paddlers_shared_lib::api::reports::ReportCollect;
-use paddlers_shared_lib::prelude::VisitReportKey;
+use paddlers_shared_lib::prelude::{VisitReplayKey, VisitReportKey};
 use quicksilver::prelude::{Col, Rectangle, Transform, Window};
 use specs::prelude::*;
+use std::cell::Cell;
+use std::rc::Rc;
 use stdweb::web::*;
 
 pub(crate) struct ReportFrame<'a, 'b> {
     pane: panes::PaneHandle,
     table: Node,
+    /// Archived visits, listed after the letters
+    replays: Element,
+    /// Replay selected in the DOM, opened on the next update
+    watch: Rc<Cell<Option<VisitReplayKey>>>,
     _phantom: PhantomData<(&'a (), &'b ())>,
 }
 
@@ -28,7 +35,7 @@ struct Report {
 }
 
 impl<'a, 'b> ReportFrame<'a, 'b> {
-    pub fn new(area: Rectangle, resolution: ScreenResolution) -> PadlResult<Self> {
+    pub fn new(area: Rectangle, resolution: ScreenResolution, locale: &TextDb) -> PadlResult<Self> {
         let right_padding = resolution.leaves_border_w() * 0.75;
         let pane = panes::new_pane(
             area.x() as u32,
@@ -41,21 +48,27 @@ impl<'a, 'b> ReportFrame<'a, 'b> {
         let node = pane.first_inner_node()?;
 
         let title = document().create_element("h2").unwrap();
-        title.set_text_content("Mailbox");
+        title.set_text_content(locale.gettext("mailbox-title"));
         node.append_child(&title);
 
+        let replays = document().create_element("div").unwrap();
+        replays.set_attribute("class", "replay-list").unwrap();
+        node.append_child(&replays);
+
         Ok(ReportFrame {
             pane,
             table: node,
+            replays,
+            watch: Rc::new(Cell::new(None)),
             _phantom: Default::default(),
         })
     }
-    fn add_report(&mut self, report: Report, sprites: &Sprites) {
+    fn add_report(&mut self, report: Report, sprites: &Sprites, locale: &TextDb) {
         let letter_node = document().create_element("div").unwrap();
         letter_node.set_attribute("class", "letter").unwrap();
 
         let text_node = document().create_element("p").unwrap();
-        text_node.set_text_content(self.letter_text(&report));
+        text_node.set_text_content(locale.gettext(self.letter_text(&report)));
         letter_node.append_child(&text_node);
 
         if report.karma > 0 {
@@ -85,12 +98,56 @@ impl<'a, 'b> ReportFrame<'a, 'b> {
 
         let button_node = document().create_element("div").unwrap();
         button_node.set_attribute("class", "letter-button").unwrap();
-        button_node.set_text_content("Collect");
+        button_node.set_text_content(locale.gettext("mailbox-collect"));
         self.add_listener(&button_node, report, letter_node.clone());
 
         letter_node.append_child(&button_node);
 
-        self.table.append_child(&letter_node);
+        self.table
+            .insert_before(&letter_node, &self.replays)
+            .expect("Replay list not found");
+    }
+    fn show_replays(&mut self, replays: &[ReplaysQueryVillageReplays], locale: &TextDb) {
+        self.replays.remove_all_children();
+        if replays.is_empty() {
+            return;
+        }
+        let title = document().create_element("h3").unwrap();
+        title.set_text_content(locale.gettext("mailbox-replays"));
+        self.replays.append_child(&title);
+
+        for replay in replays {
+            let node = document().create_element("div").unwrap();
+            node.set_attribute("class", "letter").unwrap();
+
+            let arrival = parse_timestamp(&replay.arrival);
+            let date = chrono::NaiveDateTime::from(arrival).format("%d.%m.%Y %H:%M");
+            let visitors = replay.hobos.len();
+            let text = locale.ngettext_args(
+                "mailbox-replay-text",
+                "mailbox-replay-text-plural",
+                visitors as u64,
+                &[
+                    ("date", &date.to_string()),
+                    ("visitors", &visitors.to_string()),
+                ],
+            );
+            let text_node = document().create_element("p").unwrap();
+            text_node.set_text_content(&text);
+            node.append_child(&text_node);
+
+            let button_node = document().create_element("div").unwrap();
+            button_node.set_attribute("class", "letter-button").unwrap();
+            button_node.set_text_content(locale.gettext("mailbox-watch-replay"));
+            let key = VisitReplayKey(replay.id.parse().unwrap());
+            let watch = self.watch.clone();
+            let _handle = button_node.add_event_listener::<event::ClickEvent, _>(move |_| {
+                watch.set(Some(key));
+            });
+            node.append_child(&button_node);
+
+            self.replays.append_child(&node);
+        }
     }
     fn add_listener(&self, button_node: &Element, report: Report, parent: Element) {
         let table_ref = self.table.clone();
@@ -118,13 +175,13 @@ impl<'a, 'b> ReportFrame<'a, 'b> {
         node.append_child(&img);
         node
     }
-    fn letter_text(&self, report: &Report) -> &'static str {
+    fn letter_text(&self, report: &Report) -> TextKey {
         match report.id.0 as usize % 5 {
-            0 => "Thank you, was a very enjoyable visit.",
-            1 => "Cheers!",
-            2 => "Thanks for showing me your town.",
-            3 => "See you again soon.",
-            4 => "A lovely place you have there.",
+            0 => "mailbox-letter-0",
+            1 => "mailbox-letter-1",
+            2 => "mailbox-letter-2",
+            3 => "mailbox-letter-3",
+            4 => "mailbox-letter-4",
             _ => unreachable!(),
         }
     }
@@ -149,13 +206,26 @@ impl<'a, 'b> Frame for ReportFrame<'a, 'b> {
                             sticks: r.sticks,
                         },
                         &state.sprites,
+                        &state.locale,
                     )
                 }
             }
+            PadlEvent::Network(NetMsg::Replays(data)) => {
+                self.show_replays(data, &state.locale);
+            }
             _ => {}
         }
         Ok(())
     }
+    fn update(&mut self, state: &mut Self::State) -> Result<(), Self::Error> {
+        if let Some(replay) = self.watch.take() {
+            state
+                .event_pool
+                .send(GameEvent::WatchReplay(replay))
+                .expect("Event pool send failed");
+        }
+        Ok(())
+    }
     fn draw(
         &mut self,
         state: &mut Self::State,
@@ -174,6 +244,7 @@ impl<'a, 'b> Frame for ReportFrame<'a, 'b> {
         Ok(())
     }
     fn enter(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        crate::net::request_replays();
         self.pane.show()?;
         Ok(())
     }
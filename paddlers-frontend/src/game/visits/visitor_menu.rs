@@ -15,7 +15,7 @@ pub(crate) struct VisitorMenuFrame<'a, 'b> {
 }
 
 impl<'a, 'b> VisitorMenuFrame<'a, 'b> {
-    pub fn new() -> Self {
+    pub fn new(locale: &TextDb) -> Self {
         let mut ui_box = UiBox::new(1, 5, 0.0, 10.0);
         let tabs = [
             (VisitorViewTab::Letters, SingleSprite::Letters),
@@ -29,6 +29,19 @@ impl<'a, 'b> VisitorMenuFrame<'a, 'b> {
                     .with_render_variant(rend),
             );
         }
+        let text_tabs = [
+            (VisitorViewTab::Messages, "messages-title"),
+            (VisitorViewTab::Quests, "quests-title"),
+        ];
+        for (view, key) in &text_tabs {
+            ui_box.add(
+                UiElement::new(GameEvent::SwitchToView(UiView::Visitors(*view)))
+                    .with_render_variant(RenderVariant::TextWithColBackground(
+                        locale.gettext(key).to_owned(),
+                        LIGHT_BLUE,
+                    )),
+            );
+        }
 
         VisitorMenuFrame {
             ui: ui_box,
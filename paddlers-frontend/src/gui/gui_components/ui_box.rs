@@ -182,6 +182,13 @@ impl UiBox {
         let i = (pos.y / dy) as usize * self.columns + (pos.x / dx) as usize;
         Some(i)
     }
+    /// Clicks the n-th element, as if the mouse had been over it
+    pub fn click_nth(&self, n: usize) -> PadlResult<Option<(ClickOutput, Option<Condition>)>> {
+        match self.elements.get(n) {
+            Some(el) => el.click(),
+            None => Ok(None),
+        }
+    }
     fn find_element_under_mouse(&self, mouse: impl Into<Vector>) -> Option<&UiElement> {
         self.element_index_under_mouse(mouse)
             .and_then(|i| self.elements.get(i))
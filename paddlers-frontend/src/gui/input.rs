@@ -1,6 +1,7 @@
 use crate::game::fight::*;
 use crate::game::movement::Position;
-use crate::gui::input::pointer::PointerManager;
+use crate::gui::input::key_bindings::{typing_in_text_field, KeyBindings};
+use crate::gui::input::pointer::{PointerKind, PointerManager};
 use crate::gui::ui_state::Now;
 use crate::net::game_master_api::RestApiState;
 use crate::net::state::current_village;
@@ -14,14 +15,23 @@ use specs::prelude::*;
 
 pub mod drag;
 pub mod hover;
+pub mod key_bindings;
 pub mod left_click;
 pub mod pointer;
+pub mod touch;
 pub use self::{hover::*, left_click::*};
 use crate::gui::ui_state::UiState;
 
 #[derive(Default, Clone, Copy)]
 pub struct MouseState(pub Vector, pub Option<MouseButton>);
 
+/// Modifier keys that are currently held down
+#[derive(Default, Clone, Copy)]
+pub struct KeyModifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum UiView {
     Visitors(VisitorViewTab),
@@ -29,11 +39,15 @@ pub enum UiView {
     Map,
     Town,
     Dialogue,
+    Settings,
+    Replay,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum VisitorViewTab {
     IncomingAttacks,
     Letters,
+    Messages,
+    Quests,
 }
 
 #[derive(Default, Debug, Component)]
@@ -58,12 +72,31 @@ impl crate::game::Game<'_, '_> {
         //     let mut t = self.world.write_resource::<TextBoard>();
         //     t.display_debug_message(format!("{:?}", event));
         // }
+        match event {
+            Event::Key(Key::LShift, state) | Event::Key(Key::RShift, state) => {
+                self.world.write_resource::<KeyModifiers>().shift = state.is_down();
+            }
+            Event::Key(Key::LControl, state) | Event::Key(Key::RControl, state) => {
+                self.world.write_resource::<KeyModifiers>().ctrl = state.is_down();
+            }
+            _ => {}
+        }
+        if let Event::Key(key, ButtonState::Pressed) = event {
+            // Keys pressed for the settings view or typed into text fields are no commands
+            if self.world.write_resource::<KeyBindings>().capture_key(*key)
+                || typing_in_text_field()
+            {
+                return Ok(());
+            }
+        }
         match event {
             Event::MouseMoved(pos) => {
+                pointer_manager.switch_kind(self, PointerKind::Mouse);
                 pointer_manager.move_pointer(&mut self.world, &pos);
                 pointer_manager.move_pointer(self.town_world_mut(), &pos);
             }
             Event::MouseButton(button, state) => {
+                pointer_manager.switch_kind(self, PointerKind::Mouse);
                 let now = self.world.read_resource::<Now>().0;
                 let pos = &window.mouse().pos();
                 pointer_manager.button_event(now, pos, *button, *state);
@@ -127,8 +160,12 @@ impl crate::game::Game<'_, '_> {
                     _ => {}
                 }
             }
-            Event::Key(key, state) if *key == Key::Tab && *state == ButtonState::Pressed => {
-                self.toggle_view();
+            Event::Key(key, ButtonState::Pressed) => {
+                let action = self.world.read_resource::<KeyBindings>().action(*key);
+                if let Some(action) = action {
+                    let r = self.key_action(action, pointer_manager);
+                    self.check(r);
+                }
             }
             _evt => {
                 // println!("Event: {:#?}", _evt)
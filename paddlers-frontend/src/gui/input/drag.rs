@@ -4,18 +4,25 @@ use crate::gui::ui_state::ViewState;
 use quicksilver::prelude::*;
 use specs::prelude::*;
 
-/// Consumes dragging movements and applies them to the game state.
+/// Consumes dragging and pinching movements and applies them to the game state.
 pub struct DragSystem;
 
 impl<'a> System<'a> for DragSystem {
     type SystemData = (
         Write<'a, Drag>,
+        Write<'a, Pinch>,
         Write<'a, GlobalMapSharedState>,
         ReadExpect<'a, ViewState>,
         ReadExpect<'a, UiView>,
     );
 
-    fn run(&mut self, (mut drag, mut map, ui_state, view): Self::SystemData) {
+    fn run(&mut self, (mut drag, mut pinch, mut map, ui_state, view): Self::SystemData) {
+        if let Some((center, factor)) = pinch.0.take() {
+            let in_menu_area = center.overlaps_rectangle(&(*ui_state).menu_box_area);
+            if *view == UiView::Map && !in_menu_area {
+                map.zoom(center, factor);
+            }
+        }
         if let Some((start, end)) = drag.0.take() {
             let in_menu_area = start.overlaps_rectangle(&(*ui_state).menu_box_area);
 
@@ -39,6 +46,12 @@ impl<'a> System<'a> for DragSystem {
                 (UiView::Dialogue, false) => {
                     // NOP
                 }
+                (UiView::Settings, false) => {
+                    // NOP
+                }
+                (UiView::Replay, false) => {
+                    // NOP
+                }
             }
         }
     }
@@ -65,3 +78,21 @@ impl Drag {
         self.0 = None;
     }
 }
+
+#[derive(Default, Clone, Copy)]
+/// Represents a pinch input waiting to be processed by the DragSystem.
+/// Consecutive pinches are summarized to one, centered where the latest took place.
+pub struct Pinch(Option<(Vector, f32)>);
+
+impl Pinch {
+    pub fn add(&mut self, center: Vector, factor: f32) {
+        let total = self.0.map_or(factor, |(_, old)| old * factor);
+        self.0 = Some((center, total));
+    }
+    pub fn is_some(&self) -> bool {
+        self.0.is_some()
+    }
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+}
@@ -33,6 +33,8 @@ impl<'a> System<'a> for HoverSystem {
             UiView::Visitors(_) => {}
             UiView::Leaderboard => {}
             UiView::Dialogue => {}
+            UiView::Settings => {}
+            UiView::Replay => {}
         }
     }
 }
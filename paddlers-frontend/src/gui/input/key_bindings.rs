@@ -0,0 +1,423 @@
+//! Maps keyboard keys to actions in the game.
+//!
+//! The player can change the bindings in the settings view.
+//! They are stored in the local storage of the browser.
+use super::{pointer::PointerManager, Grabbable, MouseState};
+use crate::game::{
+    components::{Position, UiMenu},
+    map::GlobalMapSharedState,
+    units::attackers::Visitor,
+    units::workers::Worker,
+};
+use crate::gui::gui_components::ClickOutput;
+use crate::gui::ui_state::UiState;
+use crate::prelude::*;
+use quicksilver::prelude::{Key, Vector};
+use specs::prelude::*;
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Key in the local storage of the browser to remember the bindings of the player
+const KEY_BINDINGS_STORAGE_KEY: &str = "paddlers-key-bindings";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+pub enum KeyAction {
+    ToggleView,
+    ShowTown,
+    ShowMap,
+    ShowVisitors,
+    ShowLeaderboard,
+    ShowSettings,
+    NextWorker,
+    Ability1,
+    Ability2,
+    Ability3,
+    Ability4,
+    OpenShop,
+    /// The map is an endless strip from left to right, it is scrolled rather than rotated
+    ScrollMapLeft,
+    ScrollMapRight,
+}
+
+/// Keys that can be bound to actions.
+/// Escape and Delete are reserved for cancelling and deleting.
+const BINDABLE_KEYS: &[Key] = &[
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::Key1,
+    Key::Key2,
+    Key::Key3,
+    Key::Key4,
+    Key::Key5,
+    Key::Key6,
+    Key::Key7,
+    Key::Key8,
+    Key::Key9,
+    Key::Key0,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+    Key::Left,
+    Key::Up,
+    Key::Right,
+    Key::Down,
+    Key::Space,
+    Key::Tab,
+    Key::Return,
+    Key::Back,
+    Key::Insert,
+    Key::Home,
+    Key::End,
+    Key::PageUp,
+    Key::PageDown,
+];
+
+/// Resource holding the current bindings
+#[derive(Clone, Debug)]
+pub struct KeyBindings {
+    bindings: HashMap<KeyAction, Key>,
+    /// Set while the settings view waits for the player to press the new key of an action
+    pub capture: Option<KeyAction>,
+    /// Incremented on every change, allows views to detect that they are outdated
+    version: u32,
+}
+
+impl KeyAction {
+    pub fn default_key(self) -> Key {
+        match self {
+            KeyAction::ToggleView => Key::Tab,
+            KeyAction::ShowTown => Key::H,
+            KeyAction::ShowMap => Key::M,
+            KeyAction::ShowVisitors => Key::V,
+            KeyAction::ShowLeaderboard => Key::L,
+            KeyAction::ShowSettings => Key::O,
+            KeyAction::NextWorker => Key::N,
+            KeyAction::Ability1 => Key::Q,
+            KeyAction::Ability2 => Key::W,
+            KeyAction::Ability3 => Key::E,
+            KeyAction::Ability4 => Key::R,
+            KeyAction::OpenShop => Key::B,
+            KeyAction::ScrollMapLeft => Key::Left,
+            KeyAction::ScrollMapRight => Key::Right,
+        }
+    }
+    /// Index into the abilities of the selected unit
+    pub fn ability_slot(self) -> Option<usize> {
+        match self {
+            KeyAction::Ability1 => Some(0),
+            KeyAction::Ability2 => Some(1),
+            KeyAction::Ability3 => Some(2),
+            KeyAction::Ability4 => Some(3),
+            _ => None,
+        }
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            bindings: KeyAction::iter().map(|a| (a, a.default_key())).collect(),
+            capture: None,
+            version: 0,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// The bindings stored in the browser, completed with defaults for actions that have no stored binding
+    pub fn load() -> Self {
+        let mut bindings = Self::default();
+        let stored = stdweb::web::window()
+            .local_storage()
+            .get(KEY_BINDINGS_STORAGE_KEY);
+        if let Some(stored) = stored {
+            bindings.apply_stored(&stored);
+        }
+        bindings
+    }
+    /// Stores actions and keys by their names, which stay valid when variants are added
+    fn store(&self) {
+        let stored: HashMap<String, String> = self
+            .bindings
+            .iter()
+            .map(|(action, key)| (format!("{:?}", action), format!("{:?}", key)))
+            .collect();
+        let result = serde_json::to_string(&stored)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                stdweb::web::window()
+                    .local_storage()
+                    .insert(KEY_BINDINGS_STORAGE_KEY, &json)
+                    .map_err(|e| format!("{:?}", e))
+            });
+        if let Err(e) = result {
+            println!("Could not store key bindings: {}", e);
+        }
+    }
+    fn apply_stored(&mut self, json: &str) {
+        match serde_json::from_str::<HashMap<String, String>>(json) {
+            Ok(stored) => {
+                for action in KeyAction::iter() {
+                    let key = stored
+                        .get(&format!("{:?}", action))
+                        .and_then(|name| parse_key(name));
+                    if let Some(key) = key {
+                        self.bind(action, key);
+                    }
+                }
+            }
+            Err(e) => println!("Ignoring invalid key bindings: {}", e),
+        }
+    }
+    pub fn action(&self, key: Key) -> Option<KeyAction> {
+        self.bindings
+            .iter()
+            .find(|(_, k)| **k == key)
+            .map(|(action, _)| *action)
+    }
+    pub fn key(&self, action: KeyAction) -> Key {
+        self.bindings[&action]
+    }
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+    /// Binds a key to an action.
+    /// An action that was bound to the same key before gets the previous key of the rebound action.
+    pub fn bind(&mut self, action: KeyAction, key: Key) {
+        let old_key = self.key(action);
+        if let Some(other) = self.action(key) {
+            self.bindings.insert(other, old_key);
+        }
+        self.bindings.insert(action, key);
+        self.version += 1;
+    }
+    /// Handles a key pressed while the settings view is waiting for a new binding.
+    /// Returns false if no binding was being captured.
+    pub fn capture_key(&mut self, key: Key) -> bool {
+        if let Some(action) = self.capture.take() {
+            if is_bindable(key) {
+                self.bind(action, key);
+                self.store();
+            }
+            self.version += 1;
+            true
+        } else {
+            false
+        }
+    }
+    pub fn reset(&mut self) {
+        *self = Self {
+            version: self.version + 1,
+            ..Self::default()
+        };
+        self.store();
+    }
+}
+
+fn is_bindable(key: Key) -> bool {
+    BINDABLE_KEYS.contains(&key)
+}
+
+fn parse_key(name: &str) -> Option<Key> {
+    BINDABLE_KEYS
+        .iter()
+        .copied()
+        .find(|k| format!("{:?}", k) == name)
+}
+
+/// Short name of a key to display to the player
+pub fn key_label(key: Key) -> String {
+    let name = format!("{:?}", key);
+    // Digits are called Key0 to Key9
+    if name.len() == 4 && name.starts_with("Key") {
+        name[3..].to_owned()
+    } else {
+        name
+    }
+}
+
+impl Game<'_, '_> {
+    pub(crate) fn key_action(
+        &mut self,
+        action: KeyAction,
+        pointer_manager: &mut PointerManager,
+    ) -> PadlResult<()> {
+        let view = *self.world.fetch::<UiView>();
+        // Dialogues are left through the story only
+        if view == UiView::Dialogue {
+            return Ok(());
+        }
+        match action {
+            KeyAction::ToggleView => self.toggle_view(),
+            KeyAction::ShowTown => self.switch_view(UiView::Town),
+            KeyAction::ShowMap => self.switch_view(UiView::Map),
+            KeyAction::ShowVisitors => self.switch_view(UiView::Visitors(VisitorViewTab::Letters)),
+            KeyAction::ShowLeaderboard => self.switch_view(UiView::Leaderboard),
+            KeyAction::ShowSettings => self.switch_view(UiView::Settings),
+            KeyAction::NextWorker if view == UiView::Town => self.select_next_worker(),
+            KeyAction::OpenShop => {
+                self.switch_view(UiView::Town);
+                // The shop is shown in the menu while nothing is selected
+                self.town_world().write_resource::<UiState>().leave_view();
+            }
+            KeyAction::ScrollMapLeft if view == UiView::Map => {
+                let mut map = self.world.write_resource::<GlobalMapSharedState>();
+                map.drag(Vector::new(1.0, 0.0));
+            }
+            KeyAction::ScrollMapRight if view == UiView::Map => {
+                let mut map = self.world.write_resource::<GlobalMapSharedState>();
+                map.drag(Vector::new(-1.0, 0.0));
+            }
+            _ => {
+                if let (Some(slot), UiView::Town) = (action.ability_slot(), view) {
+                    self.grab_ability(slot, pointer_manager)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Selects the worker following the currently selected one, or the first if none is selected
+    fn select_next_worker(&mut self) {
+        let world = self.town_world();
+        let workers: Vec<Entity> = (&world.entities(), &world.read_storage::<Worker>())
+            .join()
+            .map(|(e, _)| e)
+            .collect();
+        let mut ui_state = world.write_resource::<UiState>();
+        let current = ui_state
+            .selected_entity
+            .and_then(|selected| workers.iter().position(|e| *e == selected));
+        ui_state.selected_entity = match current {
+            Some(i) => workers.get((i + 1) % workers.len()).copied(),
+            None => workers.first().copied(),
+        };
+    }
+    /// Grabs an ability of the selected unit, as if it had been clicked in the menu.
+    /// If the pointer is on a visitor, the ability is used on it right away.
+    /// With a visitor selected instead of a worker, the first worker with an ability in this slot
+    /// is selected and uses it on the selected visitor, unless the pointer is on another visitor.
+    fn grab_ability(
+        &mut self,
+        slot: usize,
+        pointer_manager: &mut PointerManager,
+    ) -> PadlResult<()> {
+        let world = self.town_world();
+        let mut ui_state = world.write_resource::<UiState>();
+        let visitors = world.read_storage::<Visitor>();
+        let menus = world.read_storage::<UiMenu>();
+        let selected_visitor = ui_state
+            .selected_entity
+            .filter(|e| visitors.get(*e).is_some());
+        let casters: Vec<Entity> = match (selected_visitor, ui_state.selected_entity) {
+            (Some(_), _) => (&world.entities(), &world.read_storage::<Worker>())
+                .join()
+                .map(|(e, _)| e)
+                .collect(),
+            (None, Some(e)) => vec![e],
+            (None, None) => return Ok(()),
+        };
+        let mut grabbed = None;
+        for caster in casters {
+            if let Some(menu) = menus.get(caster) {
+                if let Some((ClickOutput::Ability(a), _)) = menu.ui.click_nth(slot)? {
+                    grabbed = Some((caster, a));
+                    break;
+                }
+            }
+        }
+        let (caster, ability) = match grabbed {
+            Some(grabbed) => grabbed,
+            None => return Ok(()),
+        };
+        ui_state.selected_entity = Some(caster);
+        ui_state.set_grabbed_item(Grabbable::Ability(ability));
+
+        let hovered_visitor = ui_state
+            .hovered_entity
+            .filter(|e| visitors.get(*e).is_some());
+        let click = if hovered_visitor.is_some() {
+            let MouseState(pos, _) = *world.read_resource::<MouseState>();
+            Some(pos)
+        } else {
+            selected_visitor.and_then(|v| {
+                world
+                    .read_storage::<Position>()
+                    .get(v)
+                    .map(|p| p.area.center())
+            })
+        };
+        if let Some(pos) = click {
+            pointer_manager.queue_primary_click(&pos);
+        }
+        Ok(())
+    }
+}
+
+/// True if the player is currently typing into a text field of the page, which should not trigger key bindings
+pub fn typing_in_text_field() -> bool {
+    use stdweb::unstable::TryInto;
+    js!(
+        var e = document.activeElement;
+        return !!e && (e.tagName == "INPUT" || e.tagName == "TEXTAREA");
+    )
+    .try_into()
+    .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebinding_swaps_conflicting_keys() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(KeyAction::ShowMap, Key::H);
+        assert_eq!(bindings.key(KeyAction::ShowMap), Key::H);
+        assert_eq!(bindings.key(KeyAction::ShowTown), Key::M);
+        assert_eq!(bindings.action(Key::H), Some(KeyAction::ShowMap));
+    }
+
+    #[test]
+    fn stored_bindings_override_defaults() {
+        let mut bindings = KeyBindings::default();
+        bindings.apply_stored(r#"{"ShowMap":"F2","NextWorker":"Escape","Unknown":"A"}"#);
+        assert_eq!(bindings.key(KeyAction::ShowMap), Key::F2);
+        // Reserved and unknown keys are ignored
+        assert_eq!(bindings.key(KeyAction::NextWorker), Key::N);
+        assert_eq!(key_label(Key::Key7), "7");
+    }
+}
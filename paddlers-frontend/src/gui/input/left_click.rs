@@ -1,4 +1,4 @@
-use super::{Clickable, MouseState};
+use super::{pointer::PointerKind, Clickable, MouseState};
 use crate::game::{
     components::*,
     movement::*,
@@ -25,6 +25,7 @@ impl<'a> System<'a> for TownLeftClickSystem {
     type SystemData = (
         Entities<'a>,
         Read<'a, MouseState>,
+        Read<'a, PointerKind>,
         WriteExpect<'a, UiState>,
         WriteExpect<'a, TownResources>,
         WriteExpect<'a, Town>,
@@ -46,6 +47,7 @@ impl<'a> System<'a> for TownLeftClickSystem {
         (
             entities,
             mouse_state,
+            pointer_kind,
             mut ui_state,
             mut resources,
             mut town,
@@ -75,6 +77,7 @@ impl<'a> System<'a> for TownLeftClickSystem {
             &mut resources,
             &mut errq,
             &mut signals,
+            pointer_kind.min_hit_target(town.resolution),
         );
         if let Some(job) = maybe_job {
             let active_entity = active_entity.expect("Ability requires unit");
@@ -1,6 +1,8 @@
 //! Processes and routes mouse-like input.
 //! Triggers the corresponding mouse-click systems when necessary.
-use super::{drag::*, HoverSystem, MouseState};
+//! Touch gestures are mapped to the same input handling as the mouse.
+use super::{drag::*, touch::*, HoverSystem, MouseState};
+use crate::gui::ui_state::{Now, UiState};
 use crate::prelude::*;
 use crate::Framer;
 use quicksilver::prelude::*;
@@ -16,6 +18,16 @@ pub struct PointerManager<'a, 'b> {
     buffered_click: Option<(Vector, PointerButton)>,
     dragging: bool,
     pointer_down: Option<(Vector, Timestamp)>,
+    kind: PointerKind,
+    touch_queue: TouchQueue,
+    touch_tracker: TouchTracker,
+}
+
+/// The kind of device that produced the latest pointer input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerKind {
+    Mouse,
+    Touch,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -36,6 +48,7 @@ impl PointerManager<'_, '_> {
         hover_dispatcher.setup(&mut world);
 
         world.insert(Drag::default());
+        world.insert(Pinch::default());
         let mut drag_dispatcher = DispatcherBuilder::new()
             .with(DragSystem, "drag", &[])
             .build();
@@ -47,6 +60,9 @@ impl PointerManager<'_, '_> {
             buffered_click: None,
             dragging: false,
             pointer_down: None,
+            kind: PointerKind::Mouse,
+            touch_queue: TouchQueue::register(),
+            touch_tracker: TouchTracker::new(),
         }
     }
 
@@ -66,12 +82,92 @@ impl PointerManager<'_, '_> {
         }
         self.buffered_click = None;
 
-        if game.world.read_resource::<Drag>().is_some() {
+        // Without a right mouse button, holding a finger still opens what a right click would
+        if self.kind == PointerKind::Touch && !self.dragging {
+            if let Some((pos, t)) = self.pointer_down {
+                let now = game.world.read_resource::<Now>().0;
+                if now - t >= Timestamp::from_us(LONG_CLICK_DELAY) {
+                    self.pointer_down = None;
+                    self.queue_click(&pos, PointerButton::Secondary);
+                }
+            }
+        }
+
+        if game.world.read_resource::<Drag>().is_some()
+            || game.world.read_resource::<Pinch>().is_some()
+        {
             self.drag_dispatcher.dispatch(&mut game.world);
             game.world.write_resource::<Drag>().clear();
+            game.world.write_resource::<Pinch>().clear();
         }
     }
 
+    /// Processes all touch events received since the last call
+    pub(crate) fn run_touch(&mut self, game: &mut crate::game::Game, window: &Window) {
+        let offset = window.screen_offset();
+        let project = window.project();
+        for snapshot in self.touch_queue.drain() {
+            let snapshot = TouchSnapshot {
+                points: snapshot
+                    .points
+                    .into_iter()
+                    .map(|p| project * (p - offset))
+                    .collect(),
+                ..snapshot
+            };
+            if let Some(gesture) = self.touch_tracker.next(&snapshot) {
+                self.switch_kind(game, PointerKind::Touch);
+                self.gesture(game, gesture);
+            }
+        }
+    }
+
+    fn gesture(&mut self, game: &mut crate::game::Game, gesture: Gesture) {
+        let now = game.world.read_resource::<Now>().0;
+        match gesture {
+            Gesture::PointerDown(pos) => {
+                // There is no hovering with fingers, the pointer jumps to where the screen is touched
+                self.move_pointer(&mut game.world, &pos);
+                self.move_pointer(game.town_world_mut(), &pos);
+                self.button_event(now, &pos, MouseButton::Left, ButtonState::Pressed);
+            }
+            Gesture::PointerMoved(pos) => {
+                self.move_pointer(&mut game.world, &pos);
+                self.move_pointer(game.town_world_mut(), &pos);
+            }
+            Gesture::PointerUp(pos) => {
+                // Dragging a grabbed item and lifting the finger drops it
+                let grabbing = game
+                    .town_world()
+                    .read_resource::<UiState>()
+                    .grabbed_item()
+                    .is_some();
+                if self.dragging && grabbing {
+                    self.queue_click(&pos, PointerButton::Primary);
+                }
+                self.button_event(now, &pos, MouseButton::Left, ButtonState::Released);
+            }
+            Gesture::Pinch { center, pan, zoom } => {
+                game.world
+                    .write_resource::<Drag>()
+                    .add(center - pan, center);
+                game.world.write_resource::<Pinch>().add(center, zoom);
+            }
+            Gesture::Cancel => {
+                self.dragging = false;
+                self.pointer_down = None;
+            }
+        }
+    }
+
+    /// Makes the kind of the latest input device available to systems.
+    /// Inserted every time because the active town world may have been swapped since the last input.
+    pub(crate) fn switch_kind(&mut self, game: &mut crate::game::Game, kind: PointerKind) {
+        self.kind = kind;
+        game.world.insert(kind);
+        game.town_world_mut().insert(kind);
+    }
+
     pub fn move_pointer(&mut self, mut world: &mut World, position: &Vector) {
         Self::update(world, position, None);
         self.hover_dispatcher.dispatch(&mut world);
@@ -126,6 +222,11 @@ impl PointerManager<'_, '_> {
         world.insert(MouseState(*position, key));
     }
 
+    /// Clicks at a position with the primary button during the next update
+    pub(crate) fn queue_primary_click(&mut self, position: &Vector) {
+        self.queue_click(position, PointerButton::Primary);
+    }
+
     // Current implementation only queues a single click and drops what doesn't fit
     fn queue_click(&mut self, position: &Vector, button: PointerButton) {
         if self.buffered_click.is_some() {
@@ -135,3 +236,19 @@ impl PointerManager<'_, '_> {
         self.buffered_click = Some((*position, button));
     }
 }
+
+impl Default for PointerKind {
+    fn default() -> Self {
+        PointerKind::Mouse
+    }
+}
+
+impl PointerKind {
+    /// Minimal side length of an area that can be clicked reliably
+    pub fn min_hit_target(&self, resolution: ScreenResolution) -> f32 {
+        match self {
+            PointerKind::Mouse => 0.0,
+            PointerKind::Touch => resolution.touch_target_size(),
+        }
+    }
+}
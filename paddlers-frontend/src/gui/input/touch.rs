@@ -0,0 +1,163 @@
+//! Reads touch input from the browser and translates it to gestures.
+//!
+//! Quicksilver does not forward touch events, hence the listeners are registered directly on the canvas.
+//! The events are buffered and turned into gestures once per update by the `PointerManager`.
+use quicksilver::prelude::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use stdweb::traits::*;
+use stdweb::unstable::TryFrom;
+use stdweb::web::event::{TouchCancel, TouchEnd, TouchMove, TouchStart};
+use stdweb::web::html_element::CanvasElement;
+
+/// Positions of all fingers currently on the screen, in browser pixel coordinates
+#[derive(Debug, Clone)]
+pub struct TouchSnapshot {
+    pub points: Vec<Vector>,
+    /// The browser aborted the touch, for example because the page lost focus
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// A single finger touched the screen, like pressing the left mouse button
+    PointerDown(Vector),
+    PointerMoved(Vector),
+    /// The single finger has been lifted at the given position
+    PointerUp(Vector),
+    /// Two fingers moved. Panning is the movement of their center, zooming the change of their distance.
+    Pinch {
+        center: Vector,
+        pan: Vector,
+        zoom: f32,
+    },
+    /// Anything started by a single finger should be aborted
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TouchState {
+    Idle,
+    Single(Vector),
+    Multi(Vector, Vector),
+    /// After a multi-touch gesture, remaining fingers are ignored until all have been lifted
+    Released,
+}
+
+/// Keeps track of the fingers on the screen and detects gestures
+pub struct TouchTracker {
+    state: TouchState,
+}
+
+impl TouchTracker {
+    pub fn new() -> Self {
+        TouchTracker {
+            state: TouchState::Idle,
+        }
+    }
+    pub fn next(&mut self, snapshot: &TouchSnapshot) -> Option<Gesture> {
+        let points = &snapshot.points;
+        let (state, gesture) = match (self.state, points.len()) {
+            (TouchState::Single(_), _) if snapshot.cancelled => {
+                (TouchState::Idle, Some(Gesture::Cancel))
+            }
+            (_, 0) => match self.state {
+                TouchState::Single(last) => (TouchState::Idle, Some(Gesture::PointerUp(last))),
+                _ => (TouchState::Idle, None),
+            },
+            (TouchState::Idle, 1) => (
+                TouchState::Single(points[0]),
+                Some(Gesture::PointerDown(points[0])),
+            ),
+            (TouchState::Single(_), 1) => (
+                TouchState::Single(points[0]),
+                Some(Gesture::PointerMoved(points[0])),
+            ),
+            (TouchState::Multi(..), 1) | (TouchState::Released, 1) => (TouchState::Released, None),
+            (TouchState::Single(_), _) => (
+                TouchState::Multi(points[0], points[1]),
+                Some(Gesture::Cancel),
+            ),
+            (TouchState::Multi(a, b), _) => {
+                let (c, d) = (points[0], points[1]);
+                let before = a.distance(b);
+                let zoom = if before > 0.0 {
+                    c.distance(d) / before
+                } else {
+                    1.0
+                };
+                let center = (c + d) * 0.5;
+                let pan = center - (a + b) * 0.5;
+                (
+                    TouchState::Multi(c, d),
+                    Some(Gesture::Pinch { center, pan, zoom }),
+                )
+            }
+            (TouchState::Idle, _) | (TouchState::Released, _) => {
+                (TouchState::Multi(points[0], points[1]), None)
+            }
+        };
+        self.state = state;
+        gesture
+    }
+}
+
+/// Touch events received from the browser, waiting to be processed
+#[derive(Clone, Default)]
+pub struct TouchQueue(Rc<RefCell<VecDeque<TouchSnapshot>>>);
+
+impl TouchQueue {
+    /// Starts listening to touch events on the game canvas
+    pub fn register() -> Self {
+        let queue = TouchQueue::default();
+        let canvas = stdweb::web::document()
+            .get_element_by_id("game-root")
+            .and_then(|root| {
+                root.child_nodes()
+                    .iter()
+                    .find_map(|node| CanvasElement::try_from(node).ok())
+            });
+        if let Some(canvas) = canvas {
+            let q = queue.clone();
+            canvas.add_event_listener(move |e: TouchStart| {
+                e.prevent_default();
+                q.push(snapshot(&e.touches(), false));
+            });
+            let q = queue.clone();
+            canvas.add_event_listener(move |e: TouchMove| {
+                e.prevent_default();
+                q.push(snapshot(&e.touches(), false));
+            });
+            let q = queue.clone();
+            canvas.add_event_listener(move |e: TouchEnd| {
+                e.prevent_default();
+                q.push(snapshot(&e.touches(), false));
+            });
+            let q = queue.clone();
+            canvas.add_event_listener(move |e: TouchCancel| {
+                q.push(snapshot(&e.touches(), true));
+            });
+        } else {
+            println!("No canvas found, touch input is disabled");
+        }
+        queue
+    }
+    pub fn drain(&self) -> Vec<TouchSnapshot> {
+        self.0.borrow_mut().drain(..).collect()
+    }
+    fn push(&self, snapshot: TouchSnapshot) {
+        self.0.borrow_mut().push_back(snapshot);
+    }
+}
+
+fn snapshot(touches: &[stdweb::web::Touch], cancelled: bool) -> TouchSnapshot {
+    let mut touches: Vec<_> = touches.iter().collect();
+    // Keep the order of fingers stable between events
+    touches.sort_by_key(|t| t.identifier());
+    let points = touches
+        .iter()
+        .map(|t| Vector::new(t.client_x() as f32, t.client_y() as f32))
+        .collect();
+    TouchSnapshot { points, cancelled }
+}
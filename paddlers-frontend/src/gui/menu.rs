@@ -1,10 +1,12 @@
 mod map_menu;
 mod menu_background;
+mod player_menu;
 mod town_menu;
 
 use crate::gui::sprites::Sprites;
 pub(crate) use map_menu::MapMenuFrame;
 pub(crate) use menu_background::MenuBackgroundFrame;
+pub(crate) use player_menu::PlayerMenuFrame;
 pub(crate) use town_menu::TownMenuFrame;
 
 use crate::game::{
@@ -45,6 +47,14 @@ impl ScreenResolution {
             ScreenResolution::High => 80.0,
         }
     }
+    /// Height of the blueprint controls at the bottom of the town menu
+    pub fn blueprint_h(&self) -> f32 {
+        match self {
+            ScreenResolution::Low => 50.0,
+            ScreenResolution::Mid => 80.0,
+            ScreenResolution::High => 150.0,
+        }
+    }
     pub fn leaves_border_h(&self) -> f32 {
         match self {
             ScreenResolution::Low => 15.0,
@@ -185,8 +195,9 @@ impl Game<'_, '_> {
         table.push(forest_details(
             self.town().forest_size(),
             self.town().forest_usage(),
+            &self.locale,
         ));
-        table.push(total_aura_details(self.town().ambience()));
+        table.push(total_aura_details(self.town().ambience(), &self.locale));
         let shop = &mut self.town_context.world().write_resource::<DefaultShop>();
         Self::draw_shop_prices(window, &mut area, &mut shop.ui, res_comp)?;
 
@@ -266,6 +277,7 @@ pub fn draw_entity_img(
 
 pub fn draw_map_entity_details_table(
     world: &World,
+    locale: &TextDb,
     sprites: &mut Sprites,
     window: &mut Window,
     e: Entity,
@@ -276,7 +288,7 @@ pub fn draw_map_entity_details_table(
     {
         let villages = world.read_storage::<VillageMetaInfo>();
         if let Some(v) = villages.get(e) {
-            for row in v.village_details().into_iter() {
+            for row in v.village_details(locale).into_iter() {
                 table.push(row);
             }
         }
@@ -301,6 +313,7 @@ pub fn draw_map_entity_details_table(
 }
 pub fn draw_town_entity_details_table(
     world: &World,
+    locale: &TextDb,
     sprites: &mut Sprites,
     window: &mut Window,
     e: Entity,
@@ -313,7 +326,7 @@ pub fn draw_town_entity_details_table(
 
     let health = world.read_storage::<Health>();
     if let Some(health) = health.get(e) {
-        table.push(health_details(health));
+        table.push(health_details(health, locale));
     }
 
     let lvls = world.read_storage::<Level>();
@@ -337,10 +350,12 @@ pub fn draw_town_entity_details_table(
 
     let mut container = world.write_storage::<EntityContainer>();
     if let Some(c) = container.get_mut(e) {
-        table.push(TableRow::Text(format!(
-            "{}/{} occupied",
-            c.count(),
-            c.capacity
+        table.push(TableRow::Text(locale.gettext_args(
+            "building-occupation",
+            &[
+                ("count", &c.count().to_string()),
+                ("capacity", &c.capacity.to_string()),
+            ],
         )));
     }
 
@@ -349,14 +364,16 @@ pub fn draw_town_entity_details_table(
     if let Some(b) = buildings.get(e) {
         if b.bt == BuildingType::Temple && ui_menu.get(e).is_some() {
             let player_info = world.read_resource::<PlayerInfo>();
-            table.extend(temple_details(&player_info));
+            table.extend(temple_details(&player_info, locale));
         }
     }
     let effects = world.read_storage::<StatusEffects>();
     if let Some(ef) = effects.get(e) {
         let list = ef.menu_table_infos();
         if list.len() > 0 {
-            TableRow::Text("Status effects".to_owned());
+            table.push(TableRow::Text(
+                locale.gettext("status-effects-title").to_owned(),
+            ));
             table.extend(list);
         }
     }
@@ -384,26 +401,38 @@ fn aura_details(aura: &Aura) -> TableRow {
     let text = format!("+{}", aura.effect);
     TableRow::TextWithImage(text, SpriteIndex::Simple(SingleSprite::Ambience))
 }
-fn health_details(health: &Health) -> TableRow {
-    let health_text = format!("Well-being {}/{}", health.max_hp - health.hp, health.max_hp);
+fn health_details<'a>(health: &Health, locale: &TextDb) -> TableRow<'a> {
+    let health_text = locale.gettext_args(
+        "visitor-well-being",
+        &[
+            ("current", &(health.max_hp - health.hp).to_string()),
+            ("max", &health.max_hp.to_string()),
+        ],
+    );
     TableRow::TextWithImage(health_text, SpriteIndex::Simple(SingleSprite::Heart))
 }
 fn tree_details(forest: &ForestComponent) -> TableRow {
     let text = format!("+{}", forest.score);
     TableRow::TextWithImage(text, SpriteIndex::Simple(SingleSprite::Tree))
 }
-fn forest_details<'a>(forest_size: usize, forest_usage: usize) -> TableRow<'a> {
-    let text = format!("{} (using {})", forest_size, forest_usage);
+fn forest_details<'a>(forest_size: usize, forest_usage: usize, locale: &TextDb) -> TableRow<'a> {
+    let text = locale.gettext_args(
+        "forest-size",
+        &[
+            ("size", &forest_size.to_string()),
+            ("used", &forest_usage.to_string()),
+        ],
+    );
     TableRow::TextWithImage(text, SpriteIndex::Simple(SingleSprite::Tree))
 }
-fn total_aura_details<'a>(aura_size: i64) -> TableRow<'a> {
-    let text = format!("Ambience: {}", aura_size);
+fn total_aura_details<'a>(aura_size: i64, locale: &TextDb) -> TableRow<'a> {
+    let text = locale.gettext_args("town-ambience", &[("ambience", &aura_size.to_string())]);
     TableRow::TextWithImage(text, SpriteIndex::Simple(SingleSprite::Ambience))
 }
-fn temple_details<'a>(player: &PlayerInfo) -> Vec<TableRow<'a>> {
+fn temple_details<'a>(player: &PlayerInfo, locale: &TextDb) -> Vec<TableRow<'a>> {
     let karma = player.karma();
     let row1 = TableRow::TextWithImage(
-        format!("{} Karma", karma),
+        locale.gettext_args("player-karma", &[("karma", &karma.to_string())]),
         SpriteIndex::Simple(SingleSprite::Karma),
     );
     let prophets = player.prophets_available();
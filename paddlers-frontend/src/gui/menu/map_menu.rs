@@ -53,6 +53,7 @@ impl<'a, 'b> Frame for MapMenuFrame<'a, 'b> {
             draw_entity_img(world, sprites, window, e, &img_area)?;
             draw_map_entity_details_table(
                 world,
+                &state.locale,
                 sprites,
                 window,
                 e,
@@ -0,0 +1,83 @@
+use crate::gui::{gui_components::*, input::UiView, ui_state::Now, utils::*};
+use crate::init::quicksilver_integration::Signal;
+use crate::prelude::*;
+use crate::view::*;
+use core::marker::PhantomData;
+use quicksilver::prelude::Window;
+use specs::WorldExt;
+
+/// Menu of the views that are about the player rather than the village
+pub(crate) struct PlayerMenuFrame<'a, 'b> {
+    ui: UiBox,
+    text_provider: TableTextProvider,
+    _phantom: PhantomData<(&'a (), &'b ())>,
+}
+
+impl<'a, 'b> PlayerMenuFrame<'a, 'b> {
+    pub fn new(locale: &TextDb) -> Self {
+        let mut ui_box = UiBox::new(1, 5, 0.0, 10.0);
+        let text_tabs = [
+            (UiView::Leaderboard, "leaderboard-title"),
+            (UiView::Settings, "settings-title"),
+        ];
+        for (view, key) in &text_tabs {
+            ui_box.add(
+                UiElement::new(GameEvent::SwitchToView(*view)).with_render_variant(
+                    RenderVariant::TextWithColBackground(
+                        locale.gettext(key).to_owned(),
+                        LIGHT_BLUE,
+                    ),
+                ),
+            );
+        }
+
+        PlayerMenuFrame {
+            ui: ui_box,
+            text_provider: TableTextProvider::new(),
+            _phantom: Default::default(),
+        }
+    }
+}
+
+impl<'a, 'b> Frame for PlayerMenuFrame<'a, 'b> {
+    type Error = PadlError;
+    type State = Game<'a, 'b>;
+    type Graphics = Window;
+    type Event = PadlEvent;
+    type Signal = Signal;
+    fn draw(
+        &mut self,
+        state: &mut Self::State,
+        window: &mut Self::Graphics,
+    ) -> Result<(), Self::Error> {
+        self.text_provider.reset();
+        let inner_area = state.inner_menu_area();
+        let (sprites, now) = (&mut state.sprites, state.world.read_resource::<Now>().0);
+        self.ui
+            .draw(window, sprites, &mut self.text_provider, now, &inner_area)?;
+        self.text_provider.finish_draw();
+        Ok(())
+    }
+    fn left_click(
+        &mut self,
+        state: &mut Self::State,
+        pos: (i32, i32),
+        _signals: &mut ExperimentalSignalChannel,
+    ) -> Result<(), Self::Error> {
+        let result = match self.ui.click(pos.into())? {
+            Some((ClickOutput::Event(event), _)) => Ok(Some(event)),
+            _ => Ok(None),
+        };
+        if let Some(event) = state.check(result).flatten() {
+            state
+                .event_pool
+                .send(event)
+                .expect("Event pool send failed");
+        }
+        Ok(())
+    }
+    fn leave(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        self.text_provider.hide();
+        Ok(())
+    }
+}
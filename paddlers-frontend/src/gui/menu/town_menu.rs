@@ -40,6 +40,10 @@ impl<'a, 'b> Frame for TownMenuFrame<'a, 'b> {
         let mut area = state.inner_menu_area();
         let resolution = *world.read_resource::<ScreenResolution>();
         let resources_height = resolution.resources_h();
+        // The bottom of the menu is left to the blueprint controls
+        let (remainder, _blueprint_area) =
+            area.cut_horizontal(area.height() - resolution.blueprint_h());
+        area = remainder;
         let foreign = state.town_context.is_foreign();
         let now = world.fetch::<Now>().0;
         let selected_entity = world.fetch::<UiState>().selected_entity;
@@ -68,6 +72,7 @@ impl<'a, 'b> Frame for TownMenuFrame<'a, 'b> {
             )?;
             draw_town_entity_details_table(
                 world,
+                &state.locale,
                 &mut state.sprites,
                 window,
                 selected_entity,
@@ -174,7 +179,7 @@ impl<'a, 'b> Frame for TownMenuFrame<'a, 'b> {
     }
 }
 impl TownMenuFrame<'_, '_> {
-    pub fn new<'a, 'b>(ep: EventPool) -> PadlResult<Self> {
+    pub fn new<'a, 'b>(ep: EventPool, locale: &TextDb) -> PadlResult<Self> {
         let left_click_dispatcher = DispatcherBuilder::new()
             .with(TownMenuLeftClickSystem::new(ep), "", &[])
             .build();
@@ -182,7 +187,7 @@ impl TownMenuFrame<'_, '_> {
         let mut foreign_town_menu = UiBox::new(1, 1, 1.0, 1.0);
         foreign_town_menu.add(
             UiElement::new(ClickOutput::Event(GameEvent::LoadHomeVillage))
-                .with_text("Go Home".to_owned())
+                .with_text(locale.gettext("town-go-home").to_owned())
                 .with_background_color(LIGHT_BLUE),
         );
 
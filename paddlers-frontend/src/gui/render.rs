@@ -1,7 +1,7 @@
 use crate::game::{
-    fight::{Health, Range},
+    fight::{Aura, Health, Range},
     movement::Position,
-    town::Town,
+    town::{TileIndex, Town},
     Game,
 };
 use crate::gui::ui_state::ClockTick;
@@ -10,10 +10,13 @@ use crate::gui::{
 };
 use crate::logging::text_to_user::TextBoard;
 use crate::prelude::*;
+use paddlers_shared_lib::game_mechanics::attributes::Attributes;
+use paddlers_shared_lib::game_mechanics::town::{TOWN_X, TOWN_Y};
 use quicksilver::graphics::Color;
 use quicksilver::input::MouseCursor;
 use quicksilver::prelude::*;
 use specs::prelude::*;
+use std::collections::HashMap;
 
 #[derive(Component, Debug)]
 #[storage(VecStorage)]
@@ -165,14 +168,17 @@ pub fn render_grabbed_item(
     let center = mouse - (ul / 2.0, ul / 2.0).into();
     let max_area = Rectangle::new(center, (ul, ul));
     match item {
-        Grabbable::NewBuilding(building_type) => draw_static_image(
-            sprites,
-            window,
-            &max_area,
-            building_type.sprite().default(),
-            Z_GRABBED_ITEM,
-            FitStrategy::TopLeft,
-        )?,
+        Grabbable::NewBuilding(building_type) => {
+            render_placement_preview(world, window, *building_type, mouse);
+            draw_static_image(
+                sprites,
+                window,
+                &max_area,
+                building_type.sprite().default(),
+                Z_GRABBED_ITEM,
+                FitStrategy::TopLeft,
+            )?
+        }
         Grabbable::Ability(ability) => draw_static_image(
             sprites,
             window,
@@ -185,6 +191,33 @@ pub fn render_grabbed_item(
     Ok(())
 }
 
+/// Shows where a held building can be placed and how the aura damage on the lane would look like with it
+fn render_placement_preview(world: &World, window: &mut Window, bt: BuildingType, mouse: Vector) {
+    let town = world.fetch::<Town>();
+    let aura_store = world.read_storage::<Aura>();
+    let mut damage: HashMap<TileIndex, i64> = HashMap::new();
+    for aura in aura_store.join() {
+        for tile in &aura.affected_tiles {
+            *damage.entry(*tile).or_insert(0) += aura.effect;
+        }
+    }
+    let tile = town.resolution.tile(mouse);
+    if tile.0 < TOWN_X && tile.1 < TOWN_Y {
+        let valid = town.check_placement(tile, bt).is_ok();
+        if valid {
+            if let (Some(range), Some(ap), None) =
+                (bt.range(), bt.attack_power(), bt.attacks_per_cycle())
+            {
+                for lane_tile in town.lane_in_range(tile, range) {
+                    *damage.entry(lane_tile).or_insert(0) += ap;
+                }
+            }
+        }
+        town.render_placement_marker(window, tile, valid);
+    }
+    town.render_aura_heatmap(window, &damage);
+}
+
 fn render_health(
     health: &Health,
     sprites: &mut Sprites,
@@ -219,6 +219,7 @@ impl WithSprite for BuildingType {
             BuildingType::Temple => SpriteSet::Simple(SingleSprite::Temple),
             BuildingType::SingleNest => SpriteSet::Simple(SingleSprite::SingleNest),
             BuildingType::TripleNest => SpriteSet::Simple(SingleSprite::TripleNest),
+            BuildingType::Shack => SpriteSet::Simple(SingleSprite::Shack),
         }
     }
 }
@@ -1,5 +1,6 @@
+use super::input::touch::*;
 use super::utils::*;
-use quicksilver::geom::Rectangle;
+use quicksilver::geom::{Rectangle, Vector};
 
 #[test]
 fn shrink_rectangle_to_center() {
@@ -111,3 +112,46 @@ fn cut_vertical() {
     assert_eq!(b, Rectangle::new((100, 100), (75, 100)));
     assert_eq!(c, Rectangle::new((175, 100), (25, 100)));
 }
+
+fn touches(points: &[(f32, f32)]) -> TouchSnapshot {
+    TouchSnapshot {
+        points: points.iter().map(|p| (*p).into()).collect(),
+        cancelled: false,
+    }
+}
+
+#[test]
+fn single_touch_gestures() {
+    let mut tracker = TouchTracker::new();
+
+    let down = tracker.next(&touches(&[(10.0, 10.0)]));
+    let moved = tracker.next(&touches(&[(20.0, 10.0)]));
+    let up = tracker.next(&touches(&[]));
+
+    assert_eq!(down, Some(Gesture::PointerDown(Vector::new(10, 10))));
+    assert_eq!(moved, Some(Gesture::PointerMoved(Vector::new(20, 10))));
+    assert_eq!(up, Some(Gesture::PointerUp(Vector::new(20, 10))));
+}
+
+#[test]
+fn pinch_gesture() {
+    let mut tracker = TouchTracker::new();
+
+    tracker.next(&touches(&[(10.0, 10.0)]));
+    let second_finger = tracker.next(&touches(&[(10.0, 10.0), (30.0, 10.0)]));
+    let pinch = tracker.next(&touches(&[(0.0, 20.0), (40.0, 20.0)]));
+    let one_lifted = tracker.next(&touches(&[(0.0, 20.0)]));
+    let all_lifted = tracker.next(&touches(&[]));
+
+    assert_eq!(second_finger, Some(Gesture::Cancel));
+    assert_eq!(
+        pinch,
+        Some(Gesture::Pinch {
+            center: Vector::new(20, 20),
+            pan: Vector::new(0, 10),
+            zoom: 2.0,
+        })
+    );
+    assert_eq!(one_lifted, None);
+    assert_eq!(all_lifted, None);
+}
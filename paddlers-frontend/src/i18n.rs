@@ -1,2 +1,205 @@
-pub type TextDb = gettext::Catalog;
+//! Localization of all texts shown to the player.
+//!
+//! Texts are looked up by key in a gettext catalog, which is compiled from `texts/<locale>.po`.
+//! Parameters in translated texts are written as `{name}` and filled in with `gettext_args`.
+
+#[cfg(test)]
+mod catalog_test;
+
+use crate::gui::input::key_bindings::KeyAction;
+use crate::prelude::*;
+use paddlers_shared_lib::story::quest::QuestName;
+use stdweb::unstable::TryInto;
+
 pub type TextKey = &'static str;
+
+/// Key in the local storage of the browser to remember the locale selected by the player
+const LOCALE_STORAGE_KEY: &str = "paddlers-locale";
+
+pub struct TextDb {
+    catalog: gettext::Catalog,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Locale {
+    En,
+    De,
+}
+
+impl TextDb {
+    pub fn parse<R: std::io::Read>(reader: R) -> Result<Self, gettext::Error> {
+        Ok(TextDb {
+            catalog: gettext::Catalog::parse(reader)?,
+        })
+    }
+    pub fn gettext<'a>(&'a self, key: &'a str) -> &'a str {
+        self.catalog.gettext(key)
+    }
+    /// Picks the plural form of the text for `n`
+    pub fn ngettext<'a>(&'a self, key: &'a str, plural_key: &'a str, n: u64) -> &'a str {
+        self.catalog.ngettext(key, plural_key, n)
+    }
+    pub fn gettext_args(&self, key: &str, args: &[(&str, &str)]) -> String {
+        fill_in(self.gettext(key), args)
+    }
+    pub fn ngettext_args(
+        &self,
+        key: &str,
+        plural_key: &str,
+        n: u64,
+        args: &[(&str, &str)],
+    ) -> String {
+        fill_in(self.ngettext(key, plural_key, n), args)
+    }
+}
+
+fn fill_in(text: &str, args: &[(&str, &str)]) -> String {
+    let mut out = text.to_owned();
+    for (name, value) in args {
+        out = out.replace(&format!("{{{}}}", name), value);
+    }
+    out
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::De];
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+        }
+    }
+    /// Name of the language in the language itself, which is why it is not translated
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::De => "Deutsch",
+        }
+    }
+    /// Accepts language tags like `de` or `de-CH`
+    pub fn from_code(code: &str) -> Option<Self> {
+        let language = code.split(|c| c == '-' || c == '_').next()?;
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|l| l.code().eq_ignore_ascii_case(language))
+    }
+    /// The locale set by the player, or the preferred language of the browser if there is none.
+    ///
+    /// The player can override the locale with the URL parameter `lang`, which is then remembered.
+    pub fn select() -> Self {
+        if let Some(locale) = crate::net::url::read_locale_param().and_then(|s| Self::from_code(&s))
+        {
+            locale.store();
+            return locale;
+        }
+        Self::stored()
+            .or_else(Self::from_browser)
+            .unwrap_or(Locale::En)
+    }
+    fn stored() -> Option<Self> {
+        stdweb::web::window()
+            .local_storage()
+            .get(LOCALE_STORAGE_KEY)
+            .and_then(|s| Self::from_code(&s))
+    }
+    fn store(&self) {
+        if let Err(e) = stdweb::web::window()
+            .local_storage()
+            .insert(LOCALE_STORAGE_KEY, self.code())
+        {
+            println!("Could not store locale: {:?}", e);
+        }
+    }
+    fn from_browser() -> Option<Self> {
+        let languages: Vec<String> = js!(
+            return navigator.languages || [navigator.language];
+        )
+        .try_into()
+        .ok()?;
+        languages.iter().find_map(|l| Self::from_code(l))
+    }
+}
+
+/* Keys for texts that depend on game data */
+
+pub fn building_name_key(bt: BuildingType) -> TextKey {
+    match bt {
+        BuildingType::BlueFlowers => "building-blue-flowers",
+        BuildingType::RedFlowers => "building-red-flowers",
+        BuildingType::Tree => "building-tree",
+        BuildingType::BundlingStation => "building-bundling-station",
+        BuildingType::SawMill => "building-saw-mill",
+        BuildingType::PresentA => "building-red-present",
+        BuildingType::PresentB => "building-orange-present",
+        BuildingType::Temple => "building-temple",
+        BuildingType::SingleNest => "building-single-nest",
+        BuildingType::TripleNest => "building-triple-nest",
+        BuildingType::Shack => "building-shack",
+    }
+}
+
+pub fn task_name_key(task: TaskType) -> TextKey {
+    match task {
+        TaskType::Idle => "task-idle",
+        TaskType::Walk => "task-walk",
+        TaskType::Defend => "task-defend",
+        TaskType::GatherSticks => "task-gather-sticks",
+        TaskType::ChopTree => "task-chop-tree",
+        TaskType::WelcomeAbility => "task-welcome-ability",
+        TaskType::CollectReward => "task-collect-reward",
+        TaskType::PlantTree => "task-plant-tree",
+    }
+}
+
+pub fn ability_name_key(ability: AbilityType) -> TextKey {
+    match ability {
+        AbilityType::Work => "ability-work",
+        AbilityType::Welcome => "ability-welcome",
+    }
+}
+
+pub fn resource_name_key(rt: ResourceType) -> TextKey {
+    match rt {
+        ResourceType::Sticks => "resource-sticks",
+        ResourceType::Logs => "resource-logs",
+        ResourceType::Feathers => "resource-feathers",
+    }
+}
+
+pub fn quest_title_key(quest: QuestName) -> TextKey {
+    match quest {
+        QuestName::PlantTrees => "quest-plant-trees",
+        QuestName::WelcomeVisitors => "quest-welcome-visitors",
+        QuestName::BuildSawMill => "quest-build-saw-mill",
+        QuestName::GatherKarma => "quest-gather-karma",
+    }
+}
+
+pub fn quest_description_key(quest: QuestName) -> TextKey {
+    match quest {
+        QuestName::PlantTrees => "quest-plant-trees-description",
+        QuestName::WelcomeVisitors => "quest-welcome-visitors-description",
+        QuestName::BuildSawMill => "quest-build-saw-mill-description",
+        QuestName::GatherKarma => "quest-gather-karma-description",
+    }
+}
+
+pub fn key_action_key(action: KeyAction) -> TextKey {
+    match action {
+        KeyAction::ToggleView => "key-action-toggle-view",
+        KeyAction::ShowTown => "key-action-show-town",
+        KeyAction::ShowMap => "key-action-show-map",
+        KeyAction::ShowVisitors => "key-action-show-visitors",
+        KeyAction::ShowLeaderboard => "key-action-show-leaderboard",
+        KeyAction::ShowSettings => "key-action-show-settings",
+        KeyAction::NextWorker => "key-action-next-worker",
+        KeyAction::Ability1 => "key-action-ability-1",
+        KeyAction::Ability2 => "key-action-ability-2",
+        KeyAction::Ability3 => "key-action-ability-3",
+        KeyAction::Ability4 => "key-action-ability-4",
+        KeyAction::OpenShop => "key-action-open-shop",
+        KeyAction::ScrollMapLeft => "key-action-scroll-map-left",
+        KeyAction::ScrollMapRight => "key-action-scroll-map-right",
+    }
+}
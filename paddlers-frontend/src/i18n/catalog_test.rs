@@ -0,0 +1,225 @@
+use super::*;
+use crate::gui::input::key_bindings::KeyAction;
+use paddlers_shared_lib::story::quest::QuestName;
+use std::collections::BTreeSet;
+use std::path::Path;
+use strum::IntoEnumIterator;
+
+const PO_FILES: [(&str, &str); 2] = [
+    ("en", include_str!("../../../texts/en.po")),
+    ("de", include_str!("../../../texts/de.po")),
+];
+const MO_FILES: [(&str, &[u8]); 2] = [
+    ("en", include_bytes!("../../static/locale/en.mo")),
+    ("de", include_bytes!("../../static/locale/de.mo")),
+];
+
+#[test]
+fn catalogs_define_the_same_keys() {
+    let (_, reference) = PO_FILES[0];
+    let expected = msgids(reference);
+    for (code, po) in PO_FILES.iter().skip(1) {
+        assert_eq!(
+            expected,
+            msgids(po),
+            "keys of {}.po differ from en.po",
+            code
+        );
+    }
+}
+
+#[test]
+fn all_used_keys_are_translated() {
+    let mut keys = BTreeSet::new();
+    collect_keys(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+        &mut keys,
+    );
+    collect_scene_keys(&mut keys);
+    for bt in BuildingType::iter() {
+        keys.insert(building_name_key(bt).to_owned());
+    }
+    for task in TaskType::iter() {
+        keys.insert(task_name_key(task).to_owned());
+    }
+    for ability in AbilityType::iter() {
+        keys.insert(ability_name_key(ability).to_owned());
+    }
+    for rt in ResourceType::iter() {
+        keys.insert(resource_name_key(rt).to_owned());
+    }
+    for quest in QuestName::iter() {
+        keys.insert(quest_title_key(quest).to_owned());
+        keys.insert(quest_description_key(quest).to_owned());
+    }
+    for action in KeyAction::iter() {
+        keys.insert(key_action_key(action).to_owned());
+    }
+    assert!(!keys.is_empty());
+
+    for (code, mo) in MO_FILES.iter() {
+        let db = TextDb::parse(*mo).expect("parsing catalog");
+        for key in &keys {
+            assert_ne!(
+                db.gettext(key),
+                key.as_str(),
+                "{} missing in {}.po (or the .mo file is outdated)",
+                key,
+                code
+            );
+        }
+    }
+}
+
+#[test]
+fn locale_from_language_tag() {
+    assert_eq!(Some(Locale::De), Locale::from_code("de-CH"));
+    assert_eq!(Some(Locale::En), Locale::from_code("EN_us"));
+    assert_eq!(None, Locale::from_code("fr"));
+}
+
+#[test]
+fn fill_in_named_arguments() {
+    assert_eq!(
+        "Village <3:-1>",
+        fill_in("Village <{x}:{y}>", &[("x", "3"), ("y", "-1")])
+    );
+}
+
+fn msgids(po: &str) -> BTreeSet<&str> {
+    po.lines()
+        .filter(|l| l.starts_with("msgid \""))
+        .map(|l| l["msgid \"".len()..].trim_end_matches('"'))
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+/// Functions that take a text key, they are looked for with an opening parenthesis
+const LOOKUP_FUNCTIONS: [&str; 3] = ["gettext(", "gettext_args(", "summary_text("];
+
+/// Finds all keys written as literals, passed to lookup functions or listed in key tables.
+/// Keys must never be assembled at runtime, otherwise they cannot be checked here.
+fn collect_keys(dir: &Path, keys: &mut BTreeSet<String>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_keys(&path, keys);
+        } else if path.ends_with("catalog_test.rs") {
+            // Mentions the lookup functions without calling them
+            continue;
+        } else if path.extension().map_or(false, |ext| ext == "rs") {
+            let src = std::fs::read_to_string(&path).unwrap();
+            for function in LOOKUP_FUNCTIONS.iter() {
+                for (i, _) in src.match_indices(function) {
+                    let args = call_args(&src[i + function.len()..]);
+                    for arg in &args {
+                        assert!(
+                            !arg.trim_start_matches('&').starts_with("format!"),
+                            "Text key built at runtime in {}: {}",
+                            path.display(),
+                            arg
+                        );
+                    }
+                    // The plural key of `ngettext` follows the key and is not a msgid itself
+                    if let Some(key) = args.into_iter().find_map(string_literal) {
+                        keys.insert(key.to_owned());
+                    }
+                }
+            }
+            // Keys in tables like `[(Request::Back, "replay-back")]` and in match arms
+            for line in src.lines().map(str::trim) {
+                let literal = line
+                    .rfind(", \"")
+                    .filter(|_| line.ends_with("\"),"))
+                    .map(|i| &line[i + 2..line.len() - 2])
+                    .or_else(|| {
+                        line.find("=> \"")
+                            .filter(|_| line.ends_with("\","))
+                            .map(|i| &line[i + 3..line.len() - 1])
+                    });
+                if let Some(key) = literal.and_then(string_literal) {
+                    if is_key(key) {
+                        keys.insert(key.to_owned());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Top-level arguments of a call, starting after the opening parenthesis
+fn call_args(src: &str) -> Vec<&str> {
+    let mut args = vec![];
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut start = 0;
+    let mut prev = ' ';
+    for (i, c) in src.char_indices() {
+        match c {
+            '"' if prev != '\\' => in_string = !in_string,
+            _ if in_string => {}
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' if depth == 0 => {
+                args.push(src[start..i].trim());
+                break;
+            }
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(src[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+    args
+}
+
+fn string_literal(s: &str) -> Option<&str> {
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        Some(&s[1..s.len() - 1])
+    } else {
+        None
+    }
+}
+
+/// Keys are lower case words joined by dashes, like `replay-back`
+fn is_key(s: &str) -> bool {
+    s.contains('-')
+        && s.chars()
+            .all(|c| c == '-' || c.is_ascii_lowercase() || c.is_ascii_digit())
+}
+
+/// Keys of the texts in the story scenes, which are loaded from the game master
+fn collect_scene_keys(keys: &mut BTreeSet<String>) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../scenes");
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().map_or(false, |ext| ext == "json") {
+            let scene: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            collect_text_keys(&scene, keys);
+        }
+    }
+}
+
+fn collect_text_keys(value: &serde_json::Value, keys: &mut BTreeSet<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (name, v) in map {
+                match v {
+                    serde_json::Value::String(key) if name == "text_key" => {
+                        keys.insert(key.clone());
+                    }
+                    _ => collect_text_keys(v, keys),
+                }
+            }
+        }
+        serde_json::Value::Array(list) => {
+            for v in list {
+                collect_text_keys(v, keys);
+            }
+        }
+        _ => {}
+    }
+}
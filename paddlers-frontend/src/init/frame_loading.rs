@@ -1,12 +1,17 @@
 use crate::game::dialogue::DialogueFrame;
 use crate::game::leaderboard::LeaderboardFrame;
 use crate::game::map::MapFrame;
-use crate::game::town::TownFrame;
+use crate::game::settings::SettingsFrame;
+use crate::game::story::quest_log::QuestFrame;
+use crate::game::town::{BlueprintFrame, TaskQueueFrame, TownFrame};
 use crate::game::visits::{
-    attacks::VisitorFrame, reports::ReportFrame, visitor_menu::VisitorMenuFrame,
+    attacks::VisitorFrame, defence_planner::DefencePlannerFrame, messages::MessageFrame,
+    replay::ReplayFrame, reports::ReportFrame, visitor_menu::VisitorMenuFrame,
 };
 use crate::game::Game;
-use crate::gui::menu::{MapMenuFrame, MenuBackgroundFrame, TownMenuFrame};
+use crate::gui::menu::{
+    menu_box_inner_split, MapMenuFrame, MenuBackgroundFrame, PlayerMenuFrame, TownMenuFrame,
+};
 use crate::prelude::*;
 use crate::Framer;
 use quicksilver::prelude::*;
@@ -15,7 +20,7 @@ use specs::WorldExt;
 pub(crate) fn load_viewer(game: &mut Game<'static, 'static>, ep: EventPool) -> Framer {
     let view = game.entry_view();
     let mut viewer = Framer::new(view);
-    let resolution = *game.world.read_resource();
+    let resolution: ScreenResolution = *game.world.read_resource();
 
     /* Town */
 
@@ -27,7 +32,7 @@ pub(crate) fn load_viewer(game: &mut Game<'static, 'static>, ep: EventPool) -> F
         (0, 0), // TODO
     );
 
-    let menu = TownMenuFrame::new(ep.clone()).expect("Town menu loading");
+    let menu = TownMenuFrame::new(ep.clone(), &game.locale).expect("Town menu loading");
     viewer.add_frame(
         Box::new(menu),
         &[UiView::Town],
@@ -35,6 +40,39 @@ pub(crate) fn load_viewer(game: &mut Game<'static, 'static>, ep: EventPool) -> F
         (0, 0), // TODO
     );
 
+    // Blueprint controls are placed at the bottom of the menu, the town menu leaves that space free.
+    // The queue of a selected worker is shown right above, below the worker details.
+    let (w, _h) = resolution.main_area();
+    let (menu_w, menu_h) = resolution.menu_area();
+    let menu_area = Rectangle::new((w, 0.0), (menu_w, menu_h));
+    let (_buttons, inner_menu_area) = menu_box_inner_split(menu_area, resolution);
+    let blueprint_h = resolution.blueprint_h();
+    let queue_h = inner_menu_area.height() / 3.0;
+    let (upper_menu_area, blueprint_area) =
+        inner_menu_area.cut_horizontal(inner_menu_area.height() - blueprint_h);
+    let frame = BlueprintFrame::new(&blueprint_area, &game.locale).expect("Blueprint loading");
+    viewer.add_frame(
+        Box::new(frame),
+        &[UiView::Town],
+        (0, 0), // TODO
+        (0, 0), // TODO
+    );
+
+    let queue_area = Rectangle::new(
+        (
+            upper_menu_area.x(),
+            upper_menu_area.y() + upper_menu_area.height() - queue_h,
+        ),
+        (upper_menu_area.width(), queue_h),
+    );
+    let frame = TaskQueueFrame::new(&queue_area, &game.locale).expect("Task queue loading");
+    viewer.add_frame(
+        Box::new(frame),
+        &[UiView::Town],
+        (0, 0), // TODO
+        (0, 0), // TODO
+    );
+
     /* Menu background and buttons */
     // Somehow, the town rendering gets messed up if TownFrame is added after this frame...
     let menu = MenuBackgroundFrame::new();
@@ -43,9 +81,13 @@ pub(crate) fn load_viewer(game: &mut Game<'static, 'static>, ep: EventPool) -> F
         &[
             UiView::Town,
             UiView::Leaderboard,
+            UiView::Settings,
+            UiView::Replay,
             UiView::Map,
             UiView::Visitors(VisitorViewTab::IncomingAttacks),
             UiView::Visitors(VisitorViewTab::Letters),
+            UiView::Visitors(VisitorViewTab::Messages),
+            UiView::Visitors(VisitorViewTab::Quests),
         ],
         (0, 0), // TODO
         (0, 0), // TODO
@@ -71,28 +113,40 @@ pub(crate) fn load_viewer(game: &mut Game<'static, 'static>, ep: EventPool) -> F
 
     /* Visitors */
 
-    let menu = VisitorMenuFrame::new();
+    let menu = VisitorMenuFrame::new(&game.locale);
     viewer.add_frame(
         Box::new(menu),
         &[
             UiView::Visitors(VisitorViewTab::IncomingAttacks),
             UiView::Visitors(VisitorViewTab::Letters),
+            UiView::Visitors(VisitorViewTab::Messages),
+            UiView::Visitors(VisitorViewTab::Quests),
         ],
         (0, 0), // TODO
         (0, 0), // TODO
     );
 
+    // The table of incoming visits uses the left half, the planner goes on the right
     let (w, h) = game.world.fetch::<ScreenResolution>().main_area();
-    let menu = VisitorFrame::new(0.0, 0.0, w, h).expect("Attacks loading");
+    let menu = VisitorFrame::new(0.0, 0.0, w / 2.0, h, &game.locale).expect("Attacks loading");
     viewer.add_frame(
         Box::new(menu),
         &[UiView::Visitors(VisitorViewTab::IncomingAttacks)],
         (0, 0),
-        (w as i32, h as i32),
+        ((w / 2.0) as i32, h as i32),
+    );
+
+    let planner_area = Rectangle::new((w / 2.0, 0.0), (w / 2.0, h));
+    let frame = DefencePlannerFrame::new(&planner_area, &game.locale).expect("Planner loading");
+    viewer.add_frame(
+        Box::new(frame),
+        &[UiView::Visitors(VisitorViewTab::IncomingAttacks)],
+        (0, 0), // TODO
+        (0, 0), // TODO
     );
 
     let rect = Rectangle::new((0.0, 0.0), (w, h));
-    let frame = ReportFrame::new(rect, resolution).expect("Report frame loading");
+    let frame = ReportFrame::new(rect, resolution, &game.locale).expect("Report frame loading");
     viewer.add_frame(
         Box::new(frame),
         &[UiView::Visitors(VisitorViewTab::Letters)],
@@ -100,9 +154,36 @@ pub(crate) fn load_viewer(game: &mut Game<'static, 'static>, ep: EventPool) -> F
         (0, 0), // TODO
     );
 
+    let frame = MessageFrame::new(rect, resolution, &game.locale).expect("Message frame loading");
+    viewer.add_frame(
+        Box::new(frame),
+        &[UiView::Visitors(VisitorViewTab::Messages)],
+        (0, 0), // TODO
+        (0, 0), // TODO
+    );
+
+    let frame = QuestFrame::new(rect, resolution, &game.locale).expect("Quest frame loading");
+    viewer.add_frame(
+        Box::new(frame),
+        &[UiView::Visitors(VisitorViewTab::Quests)],
+        (0, 0), // TODO
+        (0, 0), // TODO
+    );
+
+    let (menu_w, menu_h) = resolution.menu_area();
+    let menu_area = Rectangle::new((w, 0.0), (menu_w, menu_h));
+    let (_buttons, inner_menu_area) = menu_box_inner_split(menu_area, resolution);
+    let frame = ReplayFrame::new(&inner_menu_area, &game.locale).expect("Replay loading");
+    viewer.add_frame(
+        Box::new(frame),
+        &[UiView::Replay],
+        (0, 0), // TODO
+        (0, 0), // TODO
+    );
+
     /* Leaderboard */
 
-    let menu = LeaderboardFrame::new(&rect).expect("Leaderboard loading");
+    let menu = LeaderboardFrame::new(&rect, &game.locale).expect("Leaderboard loading");
     viewer.add_frame(
         Box::new(menu),
         &[UiView::Leaderboard],
@@ -110,6 +191,24 @@ pub(crate) fn load_viewer(game: &mut Game<'static, 'static>, ep: EventPool) -> F
         (0, 0), // TODO
     );
 
+    /* Settings */
+
+    let menu = SettingsFrame::new(&rect, &game.locale).expect("Settings loading");
+    viewer.add_frame(
+        Box::new(menu),
+        &[UiView::Settings],
+        (0, 0), // TODO
+        (0, 0), // TODO
+    );
+
+    let menu = PlayerMenuFrame::new(&game.locale);
+    viewer.add_frame(
+        Box::new(menu),
+        &[UiView::Leaderboard, UiView::Settings],
+        (0, 0), // TODO
+        (0, 0), // TODO
+    );
+
     /* Dialogue box */
 
     let (w1, _h1) = game.world.fetch::<ScreenResolution>().menu_area();
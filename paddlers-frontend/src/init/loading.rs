@@ -13,6 +13,7 @@ use crate::gui::sprites::{
     Sprites,
 };
 use crate::gui::utils::*;
+use crate::i18n::Locale;
 use crate::init::quicksilver_integration::GameState;
 use crate::init::quicksilver_integration::QuicksilverState;
 use crate::logging::{error::PadlError, text_to_user::TextBoard, AsyncErr, ErrorQueue};
@@ -240,9 +241,9 @@ pub fn start_loading_animations(images: &Vec<Image>) -> Vec<(Asset<AnimatedObjec
 }
 
 fn start_loading_locale() -> Asset<TextDb> {
+    let path = format!("locale/{}.mo", Locale::select().code());
     Asset::new(
-        // TODO
-        quicksilver::load_file("locale/en.mo")
+        quicksilver::load_file(path)
             .map(|data| TextDb::parse(data.as_slice()).expect("could not parse the catalog")),
     )
 }
@@ -13,6 +13,7 @@ use crate::logging::{text_to_user::TextBoard, ErrorQueue};
 use crate::net::game_master_api::RestApiState;
 use crate::prelude::*;
 use crate::view::FrameSignal;
+use paddlers_shared_lib::prelude::{VillageKey, VisitReplayKey};
 use paddlers_shared_lib::story::story_state::StoryState;
 
 use crate::game::story::scene::SceneIndex;
@@ -55,11 +56,13 @@ pub(crate) enum PadlEvent {
 #[derive(Clone, Debug)]
 /// Signals are a way to broadcast events for event listeners across views.
 pub enum Signal {
-    ResourcesUpdated,              // Notification
-    PlayerInfoUpdated,             // Notification
-    BuildingBuilt(BuildingType),   // Signal
-    Scene(SceneIndex, SlideIndex), // Signal(?)
-    NewStoryState(StoryState),     // Notification
+    ResourcesUpdated,                   // Notification
+    PlayerInfoUpdated,                  // Notification
+    BuildingBuilt(BuildingType),        // Signal
+    Scene(SceneIndex, SlideIndex),      // Signal(?)
+    NewStoryState(StoryState),          // Notification
+    ComposeMessage(VillageKey, String), // Signal
+    WatchReplay(VisitReplayKey),        // Signal
 }
 impl FrameSignal<PadlEvent> for Signal {
     // Improvement: This should be synced with a specification document (to be designed)
@@ -88,7 +91,7 @@ impl State for QuicksilverState {
                 state.queue_error(err);
                 RestApiState::get().poll_queue(&state.base.async_err);
                 let q = &mut state.base.errq;
-                q.pull_async(&mut state.base.err_recv, &mut state.base.tb);
+                q.pull_async(&mut state.base.err_recv, &mut state.base.tb, None);
                 self.try_finalize();
                 Ok(())
             }
@@ -136,6 +139,7 @@ impl GameState {
         self.game.total_updates += 1;
         window.set_max_updates(1); // 1 update per frame is enough
         self.game.update_time_reference();
+        self.pointer_manager.run_touch(&mut self.game, window);
         self.pointer_manager.run(&mut self.game, &mut self.viewer);
         {
             let now = self.game.world.read_resource::<Now>().0;
@@ -146,8 +150,9 @@ impl GameState {
         {
             let mut q = self.game.world.write_resource::<ErrorQueue>();
             let mut t = self.game.world.write_resource::<TextBoard>();
-            q.pull_async(&mut self.game.async_err_receiver, &mut t);
-            q.run(&mut t);
+            let locale = Some(&self.game.locale);
+            q.pull_async(&mut self.game.async_err_receiver, &mut t, locale);
+            q.run(&mut t, locale);
         }
 
         let res = self.update_net();
@@ -176,7 +181,7 @@ impl GameState {
 
         {
             let mut rest = RestApiState::get();
-            let err = self.game.stats.track_frame(&mut *rest, utc_now());
+            let err = self.game.stats.track_frame(&mut *rest, real_now());
             self.game.check(err);
         }
 
@@ -7,10 +7,17 @@ use specs::prelude::*;
 
 use crate::game::town::DefaultShop;
 use crate::game::{
-    components::*, player_info::PlayerInfo, town::Town, town_resources::TownResources,
-    units::hobos::Hobo, units::workers::Worker, visits::attacks::Attack,
+    components::*,
+    player_info::PlayerInfo,
+    town::Town,
+    town_resources::TownResources,
+    units::hobos::Hobo,
+    units::workers::{TaskQueueDraft, Worker},
+    visits::attacks::Attack,
+};
+use crate::gui::input::{
+    drag::Drag, key_bindings::KeyBindings, pointer::PointerKind, KeyModifiers,
 };
-use crate::gui::input::drag::Drag;
 use crate::gui::ui_state::*;
 use crate::logging::{text_to_user::TextBoard, AsyncErr, ErrorQueue};
 
@@ -28,7 +35,10 @@ pub(super) fn insert_global_resources(
     world.insert(ViewState::new());
     world.insert(async_err);
     world.insert(errq);
+    world.insert(KeyBindings::load());
+    world.insert(KeyModifiers::default());
     world.insert(player_info);
+    world.insert(PointerKind::default());
     world.insert(resolution);
     world.insert(tb);
     let view = entry_view(player_info.story_state());
@@ -60,6 +70,8 @@ pub fn insert_town_resources(
     world.insert(Drag::default());
     world.insert(ErrorQueue::new_endpoint());
     world.insert(Now(utc_now()));
+    world.insert(PointerKind::default());
+    world.insert(TaskQueueDraft::default());
     world.insert(TownResources::default());
     world.insert(UiState::new());
     world.insert(ViewState::new());
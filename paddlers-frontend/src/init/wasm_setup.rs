@@ -25,7 +25,12 @@ pub fn setup_wasm() {
 }
 
 use crate::stdweb::unstable::TryInto;
+/// Current game time, as synchronized with the game master
 pub fn utc_now() -> crate::Timestamp {
+    crate::net::clock::server_now()
+}
+/// Time of the local system, for anything that does not relate to the game state (animations, measurements)
+pub fn real_now() -> crate::Timestamp {
     let millis: f64 = js!(
         var date = new Date();
         return date.getTime();
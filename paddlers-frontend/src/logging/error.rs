@@ -1,4 +1,6 @@
+use crate::game::story::scene::{SceneIndex, SlideIndex};
 use crate::game::town::{TileIndex, TileType};
+use crate::i18n::building_name_key;
 use crate::net::ajax::AjaxError;
 use crate::prelude::*;
 use crate::stdweb::unstable::TryInto;
@@ -65,6 +67,10 @@ pub enum PadlErrorCode {
     NotReadyYet,
     PathBlocked,
     NoNetwork,
+    InvalidBlueprint,
+    CannotBuildOnLane,
+    TileOccupied,
+    BuildingBlocksPath,
     // Dev only
     DevMsg(&'static str),
     MapOverflow(TileIndex),
@@ -89,7 +95,9 @@ pub enum PadlErrorCode {
     NoDataFromBrowser(&'static str),
     BrowserError(String),
     DialogueEmpty,
+    NoSuchSlide(SceneIndex, SlideIndex),
     UserNotInDB,
+    PlayerBanned,
     AuthorizationRequired,
     DataForInactiveTownReceived(&'static str),
 }
@@ -112,6 +120,12 @@ impl fmt::Display for PadlErrorCode {
             PadlErrorCode::NotEnoughUnits => write!(f, "Require more units."),
             PadlErrorCode::PathBlocked => write!(f, "The path is blocked."),
             PadlErrorCode::NoNetwork => write!(f, "Connection to server dropped."),
+            PadlErrorCode::InvalidBlueprint => write!(f, "This is not a valid blueprint."),
+            PadlErrorCode::CannotBuildOnLane => write!(f, "Nothing can be built on the water."),
+            PadlErrorCode::TileOccupied => write!(f, "There is already something here."),
+            PadlErrorCode::BuildingBlocksPath => {
+                write!(f, "This would block the way for your workers.")
+            }
             // Dev
             PadlErrorCode::DevMsg(msg) => write!(f, "Dev Error Msg: {}", msg),
             PadlErrorCode::MapOverflow(i) => write!(f, "Index is outside the map: {:?}", i),
@@ -162,9 +176,13 @@ impl fmt::Display for PadlErrorCode {
             }
             PadlErrorCode::BrowserError(s) => write!(f, "Unexpected browser error: {}", s),
             PadlErrorCode::DialogueEmpty => write!(f, "No scene loaded in dialogue"),
+            PadlErrorCode::NoSuchSlide(scene, slide) => {
+                write!(f, "Scene {} has no slide {}", scene, slide)
+            }
             PadlErrorCode::UserNotInDB => {
                 write!(f, "The user logged in is not present in the game database.")
             }
+            PadlErrorCode::PlayerBanned => write!(f, "This account has been banned."),
             PadlErrorCode::AuthorizationRequired => {
                 write!(f, "The requested resource permits authorized access only.")
             }
@@ -175,6 +193,51 @@ impl fmt::Display for PadlErrorCode {
     }
 }
 
+impl PadlErrorCode {
+    /// Text shown to the player, technical errors are not translated
+    pub fn user_text(&self, locale: &TextDb) -> String {
+        match self {
+            PadlErrorCode::BuildingFull(Some(b)) => locale.gettext_args(
+                "error-building-full",
+                &[("building", locale.gettext(building_name_key(*b)))],
+            ),
+            PadlErrorCode::BuildingFull(None) => {
+                locale.gettext("error-any-building-full").to_owned()
+            }
+            PadlErrorCode::ForestTooSmall(amount) => locale.ngettext_args(
+                "error-forest-too-small",
+                "error-forest-too-small-plural",
+                *amount as u64,
+                &[("amount", &amount.to_string())],
+            ),
+            PadlErrorCode::NotReadyYet => locale.gettext("error-not-ready-yet").to_owned(),
+            PadlErrorCode::NotEnoughResources => {
+                locale.gettext("error-not-enough-resources").to_owned()
+            }
+            PadlErrorCode::NotEnoughSupply => locale.gettext("error-not-enough-supply").to_owned(),
+            PadlErrorCode::NotEnoughMana => locale.gettext("error-not-enough-mana").to_owned(),
+            PadlErrorCode::NotEnoughKarma => locale.gettext("error-not-enough-karma").to_owned(),
+            PadlErrorCode::NotEnoughUnits => locale.gettext("error-not-enough-units").to_owned(),
+            PadlErrorCode::PathBlocked => locale.gettext("error-path-blocked").to_owned(),
+            PadlErrorCode::NoNetwork => locale.gettext("error-no-network").to_owned(),
+            PadlErrorCode::InvalidBlueprint => locale.gettext("error-invalid-blueprint").to_owned(),
+            PadlErrorCode::CannotBuildOnLane => {
+                locale.gettext("error-cannot-build-on-lane").to_owned()
+            }
+            PadlErrorCode::TileOccupied => locale.gettext("error-tile-occupied").to_owned(),
+            PadlErrorCode::BuildingBlocksPath => {
+                locale.gettext("error-building-blocks-path").to_owned()
+            }
+            PadlErrorCode::UserNotInDB => locale.gettext("error-user-not-in-db").to_owned(),
+            PadlErrorCode::PlayerBanned => locale.gettext("error-player-banned").to_owned(),
+            PadlErrorCode::AuthorizationRequired => {
+                locale.gettext("error-authorization-required").to_owned()
+            }
+            _ => self.to_string(),
+        }
+    }
+}
+
 impl From<stdweb::private::ConversionError> for PadlError {
     fn from(error: stdweb::private::ConversionError) -> Self {
         PadlError::dev_err(PadlErrorCode::StdWebConversion(error))
@@ -1,6 +1,7 @@
 pub mod error;
 pub mod statistics;
 pub mod text_to_user;
+use crate::i18n::TextDb;
 use error::*;
 use std::cell::{RefCell, RefMut};
 use std::collections::VecDeque;
@@ -37,20 +38,26 @@ impl ErrorQueue {
     pub fn push(&mut self, e: PadlError) {
         self.queue().push_front(e)
     }
-    pub fn run(&mut self, tb: &mut TextBoard) {
+    /// Displays queued errors, translated if the locale has been loaded already
+    pub fn run(&mut self, tb: &mut TextBoard, locale: Option<&TextDb>) {
         while let Some(e) = self.queue().pop_front() {
-            self.route_err(e, tb);
+            self.route_err(e, tb, locale);
         }
     }
-    pub fn pull_async(&self, chan: &mut Receiver<PadlError>, tb: &mut TextBoard) {
+    pub fn pull_async(
+        &self,
+        chan: &mut Receiver<PadlError>,
+        tb: &mut TextBoard,
+        locale: Option<&TextDb>,
+    ) {
         while let Ok(e) = chan.try_recv() {
-            self.route_err(e, tb);
+            self.route_err(e, tb, locale);
         }
     }
     fn queue(&self) -> RefMut<VecDeque<PadlError>> {
         RefMut::map(self.0.borrow_mut(), |singleton| &mut singleton.queue)
     }
-    fn route_err(&self, e: PadlError, tb: &mut TextBoard) {
+    fn route_err(&self, e: PadlError, tb: &mut TextBoard, locale: Option<&TextDb>) {
         let err = match e.channel {
             ErrorChannel::Technical => {
                 println!("Error: {}", e);
@@ -60,7 +67,10 @@ impl ErrorQueue {
                 let err = Ok(());
                 err
             }
-            ErrorChannel::UserFacing => tb.display_error_message(format!("{}", e)),
+            ErrorChannel::UserFacing => match locale {
+                Some(locale) => tb.display_error_message(e.err.user_text(locale)),
+                None => tb.display_error_message(format!("{}", e)),
+            },
         };
         if let Err(err) = err {
             println!("Failed to display error. Reason of failure: {}", err);
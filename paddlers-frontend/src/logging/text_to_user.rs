@@ -35,7 +35,7 @@ impl TextBoard {
         self.display_message(msg, BLUE, 3_000_000)
     }
     fn display_message(&mut self, msg: String, col: Color, time_us: i64) -> PadlResult<()> {
-        let show_until = utc_now() + Timestamp::from_us(time_us);
+        let show_until = real_now() + Timestamp::from_us(time_us);
         let float = Self::new_float(msg, col)?;
         self.messages.push(TextMessage { float, show_until });
         Ok(())
@@ -53,7 +53,7 @@ impl TextBoard {
         Ok(())
     }
     fn remove_old_messages(&mut self) {
-        let now = utc_now();
+        let now = real_now();
         self.messages.retain(|msg| msg.show_until > now);
     }
     fn new_float(s: String, col: Color) -> PadlResult<FloatingText> {
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use paddlers_shared_lib::game_clock::GAME_TIME_HEADER;
 use paddlers_shared_lib::prelude::PadlApiError;
 use stdweb::unstable::{TryFrom, TryInto};
 use stdweb::PromiseFuture;
@@ -8,11 +9,20 @@ pub fn send(
     uri: &str,
     request_body: &str,
 ) -> PadlResult<PromiseFuture<String, AjaxError>> {
+    let sent = real_now();
+    let on_game_time = stdweb::Once(move |game_time: Option<f64>| {
+        if let Some(t) = game_time {
+            super::clock::record_server_time(Timestamp::from_us(t as i64), real_now() - sent);
+        }
+    });
     let promise: Result<PromiseFuture<String, AjaxError>, _> =
     js! (
+        var on_game_time = @{on_game_time};
         return new Promise(function (resolve, reject) {
             var xhr = new XMLHttpRequest();
             xhr.onload = function() {
+                var game_time = xhr.getResponseHeader(@{GAME_TIME_HEADER});
+                on_game_time(game_time === null ? null : Number(game_time));
                 var response = xhr.response;
                 try {
                     response = JSON.parse(response)
@@ -34,7 +44,10 @@ pub fn send(
             xhr.open(@{method}, @{uri});
             xhr.setRequestHeader("Content-Type", "application/json;charset=UTF-8");
             xhr.setRequestHeader("Authorization", window.keycloak.token);
-            xhr.onerror = reject;
+            xhr.onerror = function(e) {
+                on_game_time(null);
+                reject(e);
+            };
             xhr.send(@{request_body});
         });
     ).try_into();
@@ -97,6 +110,7 @@ fn padl_error_from_js_array(val: stdweb::Value) -> (Option<PadlErrorCode>, Optio
         .and_then(PadlApiError::try_from_num)
         .map(|api_err| match api_err {
             PadlApiError::PlayerNotCreated => PadlErrorCode::UserNotInDB,
+            PadlApiError::PlayerBanned => PadlErrorCode::PlayerBanned,
         });
     (error_code, error_message)
 }
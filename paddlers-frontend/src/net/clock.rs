@@ -0,0 +1,138 @@
+//! Keeps the local game clock in sync with the game master.
+//!
+//! The local system clock can be off by any amount, and the game clock of the server
+//! does not necessarily run at real time. Every response of the game master carries its
+//! current game time in a header. From these samples, a smoothed estimate of the server's
+//! game time is maintained, which is what `utc_now()` returns.
+
+use crate::prelude::*;
+use paddlers_shared_lib::game_clock::{ClockSync, GameClock};
+use std::cell::Cell;
+
+/// Weight of a new sample compared to the current estimate
+const SMOOTHING: f64 = 0.2;
+/// Deviations above this are not smoothed but taken as a jump of the server clock
+const MAX_SMOOTHED_DEVIATION_US: i64 = 5_000_000;
+
+#[derive(Clone, Copy)]
+struct ServerClock {
+    clock: GameClock,
+    synced: bool,
+}
+
+thread_local!(
+    static SERVER_CLOCK: Cell<ServerClock> = Cell::new(ServerClock {
+        clock: GameClock::real_time(),
+        synced: false,
+    })
+);
+
+/// Current game time of the game master, as estimated locally
+pub fn server_now() -> Timestamp {
+    SERVER_CLOCK.with(|c| c.get().clock.game_time(real_now()))
+}
+
+/// Adds a sample of the server's game time, received in a response that took `round_trip` to arrive.
+pub fn record_server_time(game_time: Timestamp, round_trip: Timestamp) {
+    SERVER_CLOCK.with(|c| c.set(c.get().with_sample(game_time, round_trip, real_now())));
+}
+
+/// Takes over the mode of the server clock, e.g. when the server runs at a scaled speed
+pub fn record_clock_snapshot(snapshot: &ClockSync) {
+    SERVER_CLOCK.with(|c| c.set(c.get().with_snapshot(snapshot, real_now())));
+}
+
+impl ServerClock {
+    fn with_sample(mut self, game_time: Timestamp, round_trip: Timestamp, now: Timestamp) -> Self {
+        // Assume the server answered halfway through the round trip
+        let half_trip =
+            Timestamp::from_us((round_trip.micros() as f64 / 2.0 * self.clock.speed()) as i64);
+        let deviation = (game_time + half_trip - self.clock.game_time(now)).micros();
+        let correction = if !self.synced || deviation.abs() > MAX_SMOOTHED_DEVIATION_US {
+            deviation
+        } else {
+            (deviation as f64 * SMOOTHING) as i64
+        };
+        self.clock.advance(Timestamp::from_us(correction));
+        self.synced = true;
+        self
+    }
+    fn with_snapshot(mut self, snapshot: &ClockSync, now: Timestamp) -> Self {
+        if !self.synced || self.clock.mode() != snapshot.mode {
+            let game_time_us = if self.synced {
+                self.clock.game_time(now).micros()
+            } else {
+                snapshot.game_time_us
+            };
+            let continued = ClockSync {
+                game_time_us,
+                mode: snapshot.mode,
+            };
+            self.clock.sync(&continued, now);
+            self.synced = true;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paddlers_shared_lib::game_clock::ClockMode;
+
+    fn unsynced() -> ServerClock {
+        ServerClock {
+            clock: GameClock::real_time(),
+            synced: false,
+        }
+    }
+    fn s(seconds: i64) -> Timestamp {
+        Timestamp::from_seconds(seconds)
+    }
+
+    #[test]
+    fn first_sample_is_taken_over() {
+        let clock = unsynced().with_sample(s(1_000), s(2), s(50));
+        assert_eq!(clock.clock.game_time(s(50)), s(1_001));
+    }
+
+    #[test]
+    fn small_deviations_are_smoothed() {
+        let clock = unsynced().with_sample(s(1_000), s(0), s(50));
+        let clock = clock.with_sample(s(1_002), s(0), s(50));
+        assert_eq!(
+            clock.clock.game_time(s(50)),
+            s(1_000) + Timestamp::from_millis(400)
+        );
+    }
+
+    #[test]
+    fn large_deviations_are_jumps() {
+        let clock = unsynced().with_sample(s(1_000), s(0), s(50));
+        let clock = clock.with_sample(s(1_600), s(0), s(50));
+        assert_eq!(clock.clock.game_time(s(50)), s(1_600));
+    }
+
+    #[test]
+    fn snapshot_sets_mode_and_keeps_time() {
+        let clock = unsynced().with_sample(s(1_000), s(0), s(50));
+        let snapshot = ClockSync {
+            game_time_us: s(5_000).micros(),
+            mode: ClockMode::Scaled(10.0),
+        };
+        let clock = clock.with_snapshot(&snapshot, s(50));
+        assert_eq!(clock.clock.mode(), ClockMode::Scaled(10.0));
+        // The time from samples is more accurate than the snapshot, which is only used for the mode
+        assert_eq!(clock.clock.game_time(s(52)), s(1_020));
+    }
+
+    #[test]
+    fn snapshot_initializes_unsynced_clock() {
+        let snapshot = ClockSync {
+            game_time_us: s(5_000).micros(),
+            mode: ClockMode::Manual,
+        };
+        let clock = unsynced().with_snapshot(&snapshot, s(50));
+        assert_eq!(clock.clock.game_time(s(80)), s(5_000));
+    }
+}
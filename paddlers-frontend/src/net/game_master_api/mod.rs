@@ -4,10 +4,17 @@ use super::{
 use crate::logging::AsyncErr;
 use crate::prelude::*;
 use futures_util::future::FutureExt;
+use paddlers_shared_lib::api::hobos::{HoboEviction, HoboOrdination};
+use paddlers_shared_lib::api::messages::{MessageSend, MessagesRead};
 use paddlers_shared_lib::api::reports::ReportCollect;
-use paddlers_shared_lib::api::story::StoryStateTransition;
+use paddlers_shared_lib::api::story::{QuestCollect, StoryStateTransition};
 use paddlers_shared_lib::api::{
-    attacks::*, keys::*, shop::*, statistics::*, tasks::TaskList, PlayerInitData,
+    attacks::*,
+    keys::*,
+    shop::*,
+    statistics::*,
+    tasks::{RoutineDefinition, TaskList},
+    PlayerInitData,
 };
 use specs::prelude::*;
 use std::collections::VecDeque;
@@ -16,10 +23,14 @@ use stdweb::PromiseFuture;
 
 static SENT_PLAYER_CREATION: AtomicBool = AtomicBool::new(false);
 
+/// Called when a request to the game master fails
+type FailureHandler = Box<dyn FnOnce()>;
+
 pub struct RestApiState {
     pub queue: VecDeque<(
         stdweb::PromiseFuture<std::string::String, AjaxError>,
         Option<NetUpdateRequest>,
+        Option<FailureHandler>,
     )>,
     err_chan: Mutex<Sender<PadlError>>,
 }
@@ -85,6 +96,18 @@ impl RestApiState {
         Ok(())
     }
 
+    pub fn http_buy_blueprint(&mut self, msg: BlueprintPurchase) -> PadlResult<()> {
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/shop/blueprint", game_master_url()?),
+            request_string,
+        );
+        // Buildings and resources both change, the frontend does not predict the outcome
+        self.push_promise(promise, Some(NetUpdateRequest::CompleteReload));
+        Ok(())
+    }
+
     pub fn http_buy_prophet(&mut self, msg: ProphetPurchase) -> PadlResult<()> {
         let request_string = &serde_json::to_string(&msg).unwrap();
         let promise = ajax::send(
@@ -98,6 +121,41 @@ impl RestApiState {
         Ok(())
     }
 
+    pub fn http_hire_worker(&mut self, msg: WorkerPurchase) -> PadlResult<()> {
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/shop/unit/worker", game_master_url()?),
+            request_string,
+        );
+        // The new worker and the spent resources both have to be loaded
+        self.push_promise(promise, Some(NetUpdateRequest::CompleteReload));
+        Ok(())
+    }
+
+    pub fn http_evict_hobo(&mut self, msg: HoboEviction) -> PadlResult<()> {
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/hobo/evict", game_master_url()?),
+            request_string,
+        );
+        self.push_promise(promise, Some(NetUpdateRequest::CompleteReload));
+        Ok(())
+    }
+
+    pub fn http_ordain_hobo(&mut self, msg: HoboOrdination) -> PadlResult<()> {
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/hobo/ordain", game_master_url()?),
+            request_string,
+        );
+        // The new prophet and the produced feathers both have to be loaded
+        self.push_promise(promise, Some(NetUpdateRequest::CompleteReload));
+        Ok(())
+    }
+
     pub fn http_overwrite_tasks(&mut self, msg: TaskList) -> PadlResult<()> {
         let request_string = &serde_json::to_string(&msg).unwrap();
         let promise = ajax::send(
@@ -110,6 +168,19 @@ impl RestApiState {
         Ok(())
     }
 
+    pub fn http_set_routine(&mut self, msg: RoutineDefinition) -> PadlResult<()> {
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/worker/routine", game_master_url()?),
+            request_string,
+        );
+        // The first step begins right away
+        let afterwards = NetUpdateRequest::WorkerTasks(msg.worker_id.num());
+        self.push_promise(promise, Some(afterwards));
+        Ok(())
+    }
+
     pub fn http_send_statistics(&mut self, msg: FrontendRuntimeStatistics) -> PadlResult<()> {
         let request_string = &serde_json::to_string(&msg).unwrap();
         let promise = ajax::send(
@@ -124,7 +195,10 @@ impl RestApiState {
     pub fn http_create_player(&mut self) -> PadlResult<()> {
         if !SENT_PLAYER_CREATION.load(std::sync::atomic::Ordering::Relaxed) {
             let display_name = read_jwt_preferred_username().unwrap_or("Unnamed Player".to_owned());
-            let msg = PlayerInitData { display_name };
+            let msg = PlayerInitData {
+                display_name,
+                referral_code: read_referral_code(),
+            };
             let request_string = &serde_json::to_string(&msg).unwrap();
             let promise = ajax::send(
                 "POST",
@@ -148,36 +222,64 @@ impl RestApiState {
         Ok(())
     }
 
-    pub fn http_notify_visitor_satisfied(&mut self, msg: HoboKey) -> PadlResult<()> {
+    pub fn http_update_story_state(&mut self, msg: StoryStateTransition) -> PadlResult<()> {
         let request_string = &serde_json::to_string(&msg).unwrap();
         let promise = ajax::send(
             "POST",
-            &format!(
-                "{}/attacks/notifications/visitor_satisfied",
-                game_master_url()?
-            ),
+            &format!("{}/story/transition", game_master_url()?),
             request_string,
         );
         self.push_promise(promise, None);
         Ok(())
     }
 
-    pub fn http_update_story_state(&mut self, msg: StoryStateTransition) -> PadlResult<()> {
+    pub fn http_collect_reward(&mut self, msg: ReportCollect) -> PadlResult<()> {
         let request_string = &serde_json::to_string(&msg).unwrap();
         let promise = ajax::send(
             "POST",
-            &format!("{}/story/transition", game_master_url()?),
+            &format!("{}/report/collect", game_master_url()?),
             request_string,
         );
         self.push_promise(promise, None);
         Ok(())
     }
 
-    pub fn http_collect_reward(&mut self, msg: ReportCollect) -> PadlResult<()> {
+    pub fn http_collect_quest(
+        &mut self,
+        msg: QuestCollect,
+        on_failure: impl FnOnce() + 'static,
+    ) -> PadlResult<()> {
         let request_string = &serde_json::to_string(&msg).unwrap();
         let promise = ajax::send(
             "POST",
-            &format!("{}/report/collect", game_master_url()?),
+            &format!("{}/quest/collect", game_master_url()?),
+            request_string,
+        );
+        // Rewards may include buildings, resources and karma
+        self.push_promise_with_failure_handler(
+            promise,
+            Some(NetUpdateRequest::CompleteReload),
+            Some(Box::new(on_failure)),
+        );
+        Ok(())
+    }
+
+    pub fn http_send_message(&mut self, msg: MessageSend) -> PadlResult<()> {
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/message/send", game_master_url()?),
+            request_string,
+        );
+        self.push_promise(promise, None);
+        Ok(())
+    }
+
+    pub fn http_mark_messages_read(&mut self, msg: MessagesRead) -> PadlResult<()> {
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/message/read", game_master_url()?),
             request_string,
         );
         self.push_promise(promise, None);
@@ -188,19 +290,31 @@ impl RestApiState {
         &mut self,
         maybe_promise: PadlResult<PromiseFuture<String, AjaxError>>,
         afterwards: Option<NetUpdateRequest>,
+    ) {
+        self.push_promise_with_failure_handler(maybe_promise, afterwards, None)
+    }
+    fn push_promise_with_failure_handler(
+        &mut self,
+        maybe_promise: PadlResult<PromiseFuture<String, AjaxError>>,
+        afterwards: Option<NetUpdateRequest>,
+        on_failure: Option<FailureHandler>,
     ) {
         match maybe_promise {
-            Ok(promise) => self.queue.push_back((promise, afterwards)),
-            Err(e) => self
-                .err_chan
-                .lock()
-                .expect("Lock on err mpsc")
-                .send(e)
-                .expect("Sending error over mpsc failed"),
+            Ok(promise) => self.queue.push_back((promise, afterwards, on_failure)),
+            Err(e) => {
+                self.err_chan
+                    .lock()
+                    .expect("Lock on err mpsc")
+                    .send(e)
+                    .expect("Sending error over mpsc failed");
+                if let Some(handler) = on_failure {
+                    handler();
+                }
+            }
         }
     }
     pub fn poll_queue(&mut self, error: &AsyncErr) {
-        while let Some((promise, afterwards)) = self.queue.pop_front() {
+        while let Some((promise, afterwards, on_failure)) = self.queue.pop_front() {
             let error_chan = error.clone_sender();
             stdweb::spawn_local(promise.map(move |r| {
                 if r.is_err() {
@@ -210,6 +324,9 @@ impl RestApiState {
                     error_chan
                         .send(err.unwrap_err())
                         .expect("sending over mpsc");
+                    if let Some(handler) = on_failure {
+                        handler();
+                    }
                 } else {
                     if let Some(req) = afterwards {
                         match req {
@@ -3,6 +3,7 @@ use crate::prelude::*;
 use futures::Future;
 use futures_util::future::FutureExt;
 use graphql_client::GraphQLQuery;
+use paddlers_shared_lib::leaderboard::LeaderboardCategory;
 use paddlers_shared_lib::prelude::*;
 
 pub(super) fn http_read_incoming_attacks(
@@ -144,8 +145,14 @@ pub(super) fn http_read_player_info(
 }
 
 pub(super) fn http_read_leaderboard(
+    category: LeaderboardCategory,
+    offset: i64,
 ) -> PadlResult<impl Future<Output = PadlResult<LeaderboardResponse>>> {
-    let request_body = LeaderboardQuery::build_query(leaderboard_query::Variables { offset: 0 });
+    let request_body = LeaderboardQuery::build_query(leaderboard_query::Variables {
+        category: category.into(),
+        offset,
+        limit: None,
+    });
     let request_string = &serde_json::to_string(&request_body)?;
     let promise = ajax::send("POST", &graphql_url()?, request_string)?;
     Ok(promise.map(|x| {
@@ -156,7 +163,6 @@ pub(super) fn http_read_leaderboard(
                 .ok_or(PadlError::dev_err(PadlErrorCode::InvalidGraphQLData(
                     "leaderboard",
                 )))?;
-        let response = response.scoreboard;
         Ok(response)
     }))
 }
@@ -182,3 +188,57 @@ pub(super) fn http_read_reports(
         Ok(response)
     }))
 }
+
+pub(super) fn http_read_replays(
+    village_id: VillageKey,
+) -> PadlResult<impl Future<Output = PadlResult<ReplaysResponse>>> {
+    let request_body = ReplaysQuery::build_query(replays_query::Variables {
+        village_id: village_id.num(),
+    });
+    let request_string = &serde_json::to_string(&request_body)?;
+    let promise = ajax::send("POST", &graphql_url()?, request_string)?;
+    Ok(promise.map(|x| {
+        let raw_response: ReplaysRawResponse = serde_json::from_str(&x?)?;
+        let response =
+            raw_response
+                .data
+                .ok_or(PadlError::dev_err(PadlErrorCode::InvalidGraphQLData(
+                    "replays",
+                )))?;
+        Ok(response.village.replays)
+    }))
+}
+
+pub(super) fn http_read_messages(
+    min_message_id: Option<i64>,
+) -> PadlResult<impl Future<Output = PadlResult<MessagesResponse>>> {
+    let request_body = MessagesQuery::build_query(messages_query::Variables { min_message_id });
+    let request_string = &serde_json::to_string(&request_body)?;
+    let promise = ajax::send("POST", &graphql_url()?, request_string)?;
+    Ok(promise.map(|x| {
+        let raw_response: MessagesRawResponse = serde_json::from_str(&x?)?;
+        let response =
+            raw_response
+                .data
+                .ok_or(PadlError::dev_err(PadlErrorCode::InvalidGraphQLData(
+                    "messages",
+                )))?;
+        Ok(response.player.messages)
+    }))
+}
+
+pub(super) fn http_read_quests() -> PadlResult<impl Future<Output = PadlResult<QuestsResponse>>> {
+    let request_body = QuestsQuery::build_query(quests_query::Variables);
+    let request_string = &serde_json::to_string(&request_body)?;
+    let promise = ajax::send("POST", &graphql_url()?, request_string)?;
+    Ok(promise.map(|x| {
+        let raw_response: QuestsRawResponse = serde_json::from_str(&x?)?;
+        let response =
+            raw_response
+                .data
+                .ok_or(PadlError::dev_err(PadlErrorCode::InvalidGraphQLData(
+                    "quests",
+                )))?;
+        Ok(response.player.quests)
+    }))
+}
@@ -10,12 +10,14 @@ use futures::future::TryFuture;
 use futures::Future;
 use futures_util::future::FutureExt;
 use futures_util::try_future::TryFutureExt;
+use paddlers_shared_lib::leaderboard::LeaderboardCategory;
 use paddlers_shared_lib::prelude::VillageKey;
 use std::sync::atomic::{AtomicI64, Ordering};
 
 pub struct GraphQlState {
     next_attack_id: AtomicI64,
     next_report_id: AtomicI64,
+    next_message_id: AtomicI64,
 }
 
 impl GraphQlState {
@@ -23,6 +25,7 @@ impl GraphQlState {
         GraphQlState {
             next_attack_id: AtomicI64::new(0),
             next_report_id: AtomicI64::new(0),
+            next_message_id: AtomicI64::new(0),
         }
     }
 
@@ -69,6 +72,14 @@ impl GraphQlState {
             .map(|response| Ok(NetMsg::Buildings(response?)))
         })
     }
+    pub(super) fn forest_query(&self) -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
+        current_village_async().map(|fut| {
+            fut.and_then(move |village: VillageKey| {
+                http_read_buildings(village).expect("Query building error")
+            })
+            .map(|response| Ok(NetMsg::Forest(response?)))
+        })
+    }
     pub(super) fn foreign_buildings_query(
         &self,
         vid: VillageKey,
@@ -142,15 +153,28 @@ impl GraphQlState {
         Ok(fp.map(move |response| Ok(NetMsg::Player(response?.into()))))
     }
 
-    pub fn leaderboard_query(&self) -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
-        let fp = http_read_leaderboard()?;
+    pub fn quests_query() -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
+        let fp = http_read_quests()?;
+        Ok(fp.map(move |response| Ok(NetMsg::Quests(response?))))
+    }
+
+    pub fn leaderboard_query(
+        &self,
+        category: LeaderboardCategory,
+        offset: usize,
+    ) -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
+        let fp = http_read_leaderboard(category, offset as i64)?;
         Ok(fp.map(move |response| {
+            let response = response?;
             Ok(NetMsg::Leaderboard(
-                1,
-                response?
+                category,
+                offset + 1,
+                response
+                    .leaderboard
                     .into_iter()
-                    .map(|player| (player.display_name, player.karma))
+                    .map(|entry| (entry.player.display_name, parse_big_int(&entry.score)))
                     .collect(),
+                response.my_rank.map(|rank| rank as usize),
             ))
         }))
     }
@@ -176,6 +200,29 @@ impl GraphQlState {
             })
         })
     }
+    pub(super) fn replays_query(&self) -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
+        current_village_async().map(|fut| {
+            fut.and_then(move |village: VillageKey| {
+                http_read_replays(village).expect("Query building error")
+            })
+            .map(|response| Ok(NetMsg::Replays(response?)))
+        })
+    }
+    pub(super) fn messages_query(
+        &'static self,
+    ) -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
+        let fp = http_read_messages(Some(self.next_message_id.load(Ordering::Relaxed)))?;
+        Ok(fp.map(move |response| {
+            let messages: MessagesResponse = response?;
+            let max_id = messages
+                .iter()
+                .map(|m| m.id.parse().unwrap())
+                .fold(0, i64::max);
+            let next = self.next_message_id.load(Ordering::Relaxed).max(max_id + 1);
+            self.next_message_id.store(next, Ordering::Relaxed);
+            Ok(NetMsg::Messages(messages))
+        }))
+    }
 }
 
 pub fn own_villages_query() -> PadlResult<impl TryFuture<Ok = Vec<VillageKey>, Error = PadlError>> {
@@ -7,6 +7,7 @@ use specs::prelude::*;
 
 pub use serde::Deserialize;
 type GqlTimestamp = String;
+type GqlBigInt = String;
 
 pub fn parse_timestamp(s: &String) -> Timestamp {
     timestamp(s).into()
@@ -14,6 +15,9 @@ pub fn parse_timestamp(s: &String) -> Timestamp {
 fn timestamp(s: &String) -> graphql_types::GqlTimestamp {
     graphql_types::GqlTimestamp::from_string(s).unwrap()
 }
+pub fn parse_big_int(s: &String) -> i64 {
+    graphql_types::GqlBigInt::from_string(s).unwrap().0
+}
 
 #[derive(GraphQLQuery)]
 #[graphql(
@@ -186,6 +190,7 @@ impl Into<TaskType> for &WorkerTaskType {
             WorkerTaskType::DEFEND => TaskType::Defend,
             WorkerTaskType::WELCOME_ABILITY => TaskType::WelcomeAbility,
             WorkerTaskType::COLLECT_REWARD => TaskType::CollectReward,
+            WorkerTaskType::PLANT_TREE => TaskType::PlantTree,
             WorkerTaskType::Other(_) => panic!("Unexpected task type"),
         }
     }
@@ -205,6 +210,7 @@ impl Into<TaskType> for &VillageUnitsTaskType {
             VillageUnitsTaskType::DEFEND => TaskType::Defend,
             VillageUnitsTaskType::WELCOME_ABILITY => TaskType::WelcomeAbility,
             VillageUnitsTaskType::COLLECT_REWARD => TaskType::CollectReward,
+            VillageUnitsTaskType::PLANT_TREE => TaskType::PlantTree,
             VillageUnitsTaskType::Other(_) => panic!("Unexpected task type"),
         }
     }
@@ -234,7 +240,20 @@ impl Into<UnitColor> for &attacks_query::UnitColor {
 )]
 pub struct LeaderboardQuery;
 pub type LeaderboardRawResponse = Response<leaderboard_query::ResponseData>;
-pub type LeaderboardResponse = Vec<leaderboard_query::LeaderboardQueryScoreboard>;
+pub type LeaderboardResponse = leaderboard_query::ResponseData;
+
+use paddlers_shared_lib::leaderboard::LeaderboardCategory;
+impl From<LeaderboardCategory> for leaderboard_query::LeaderboardCategory {
+    fn from(category: LeaderboardCategory) -> Self {
+        match category {
+            LeaderboardCategory::Karma => Self::KARMA,
+            LeaderboardCategory::SeasonKarma => Self::SEASON_KARMA,
+            LeaderboardCategory::Villages => Self::VILLAGES,
+            LeaderboardCategory::VisitorsWelcomed => Self::VISITORS_WELCOMED,
+            LeaderboardCategory::Prophets => Self::PROPHETS,
+        }
+    }
+}
 
 use paddlers_shared_lib::story::story_state::StoryState;
 impl Into<StoryState> for &PlayerStoryState {
@@ -270,3 +289,82 @@ impl Into<StoryState> for PlayerStoryState {
 pub struct ReportsQuery;
 pub type ReportsRawResponse = Response<reports_query::ResponseData>;
 pub type ReportsResponse = reports_query::ResponseData;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "api/schema.json",
+    query_path = "api/queries/replays_query.graphql"
+)]
+pub struct ReplaysQuery;
+pub type ReplaysRawResponse = Response<replays_query::ResponseData>;
+pub type ReplaysResponse = Vec<ReplaysQueryVillageReplays>;
+pub type ReplaysQueryVillageReplays = replays_query::ReplaysQueryVillageReplays;
+pub type ReplayHoboResponse = replays_query::ReplaysQueryVillageReplaysHobos;
+pub type ReplayBuildingResponse = replays_query::ReplaysQueryVillageReplaysBuildings;
+
+impl Into<UnitColor> for &replays_query::UnitColor {
+    fn into(self) -> UnitColor {
+        match self {
+            replays_query::UnitColor::YELLOW => UnitColor::Yellow,
+            replays_query::UnitColor::WHITE => UnitColor::White,
+            replays_query::UnitColor::CAMO => UnitColor::Camo,
+            replays_query::UnitColor::PROPHET => UnitColor::Prophet,
+            replays_query::UnitColor::Other(_) => panic!("Unexpected unit color"),
+        }
+    }
+}
+impl Into<BuildingType> for &replays_query::BuildingType {
+    fn into(self) -> BuildingType {
+        match self {
+            replays_query::BuildingType::RED_FLOWERS => BuildingType::RedFlowers,
+            replays_query::BuildingType::BLUE_FLOWERS => BuildingType::BlueFlowers,
+            replays_query::BuildingType::TREE => BuildingType::Tree,
+            replays_query::BuildingType::BUNDLING_STATION => BuildingType::BundlingStation,
+            replays_query::BuildingType::SAW_MILL => BuildingType::SawMill,
+            replays_query::BuildingType::PRESENT_A => BuildingType::PresentA,
+            replays_query::BuildingType::PRESENT_B => BuildingType::PresentB,
+            replays_query::BuildingType::TEMPLE => BuildingType::Temple,
+            replays_query::BuildingType::SINGLE_NEST => BuildingType::SingleNest,
+            replays_query::BuildingType::TRIPLE_NEST => BuildingType::TripleNest,
+            replays_query::BuildingType::SHACK => BuildingType::Shack,
+            replays_query::BuildingType::Other(_) => panic!("Unexpected BuildingType"),
+        }
+    }
+}
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "api/schema.json",
+    query_path = "api/queries/messages_query.graphql"
+)]
+pub struct MessagesQuery;
+pub type MessagesRawResponse = Response<messages_query::ResponseData>;
+pub type MessagesResponse = Vec<messages_query::MessagesQueryPlayerMessages>;
+
+impl messages_query::MessagesQueryPlayerMessages {
+    pub fn sent(&self) -> chrono::NaiveDateTime {
+        timestamp(&self.sent).to_chrono()
+    }
+}
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "api/schema.json",
+    query_path = "api/queries/quests_query.graphql"
+)]
+pub struct QuestsQuery;
+pub type QuestsRawResponse = Response<quests_query::ResponseData>;
+pub type QuestsResponse = Vec<quests_query::QuestsQueryPlayerQuests>;
+
+use paddlers_shared_lib::story::quest::QuestName;
+impl Into<QuestName> for &quests_query::QuestName {
+    fn into(self) -> QuestName {
+        match self {
+            quests_query::QuestName::PLANT_TREES => QuestName::PlantTrees,
+            quests_query::QuestName::WELCOME_VISITORS => QuestName::WelcomeVisitors,
+            quests_query::QuestName::BUILD_SAW_MILL => QuestName::BuildSawMill,
+            quests_query::QuestName::GATHER_KARMA => QuestName::GatherKarma,
+            quests_query::QuestName::Other(_) => panic!("Unexpected quest name"),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod ajax;
 pub mod authentication;
+pub mod clock;
 pub mod game_master_api;
 pub mod graphql;
 pub mod state;
@@ -8,13 +9,17 @@ pub mod url;
 use crate::game::player_info::PlayerInfo;
 use game_master_api::RestApiState;
 use graphql::{query_types::*, GraphQlState};
+use paddlers_shared_lib::game_clock::ClockSync;
+use paddlers_shared_lib::leaderboard::LeaderboardCategory;
 use paddlers_shared_lib::prelude::VillageKey;
+use paddlers_shared_lib::story::scene::{SceneDefinition, SceneIndex, SlideIndex};
 use std::sync::Arc;
 
 use stdweb::spawn_local;
 
 use futures::future::TryFutureExt;
 use futures::Future;
+use futures_util::future::FutureExt;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     mpsc::Sender,
@@ -27,10 +32,23 @@ pub enum NetMsg {
     Attacks(AttacksResponse),
     Buildings(BuildingsResponse),
     Error(PadlError),
+    /// Buildings of the home village, of which only the trees are updated
+    Forest(BuildingsResponse),
     Hobos(HobosQueryResponse, VillageKey),
-    Leaderboard(usize, Vec<(String, i64)>),
+    /// Category, rank of first entry, entries as (name, score), rank of the player
+    Leaderboard(
+        LeaderboardCategory,
+        usize,
+        Vec<(String, i64)>,
+        Option<usize>,
+    ),
     Map(MapResponse, i32, i32),
+    Messages(MessagesResponse),
     Player(PlayerInfo),
+    Quests(QuestsResponse),
+    Replays(ReplaysResponse),
+    /// Scene definition and the slide at which it should be opened
+    Scene(SceneIndex, SlideIndex, SceneDefinition),
     VillageInfo(VolatileVillageInfoResponse),
     UpdateWorkerTasks(WorkerTasksResponse),
     Workers(WorkerResponse, VillageKey),
@@ -88,6 +106,7 @@ pub fn start_network_thread() {
         while STATIC_NET_STATE.chan.is_none() {
             // NOP
         }
+        STATIC_NET_STATE.sync_clock();
         STATIC_NET_STATE.work();
     }
 }
@@ -100,9 +119,15 @@ pub fn request_client_state() {
             STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.buildings_query());
             STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.workers_query());
             STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.hobos_query());
-            STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.leaderboard_query());
+            STATIC_NET_STATE.spawn(
+                STATIC_NET_STATE
+                    .gql_state
+                    .leaderboard_query(LeaderboardCategory::Karma, 0),
+            );
             STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.attacks_query());
             STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.resource_query());
+            // Quest progress depends on buildings and resources, which may have changed
+            STATIC_NET_STATE.spawn(GraphQlState::quests_query());
             request_player_update();
         } else {
             stdweb::web::set_timeout(request_client_state, 10);
@@ -116,11 +141,63 @@ pub fn request_map_read(min: i32, max: i32) {
         }
     }
 }
+/// Loads a page of a leaderboard, starting after `offset` entries
+pub fn request_leaderboard(category: LeaderboardCategory, offset: usize) {
+    unsafe {
+        if STATIC_NET_STATE.logged_in.load(Ordering::Relaxed) {
+            STATIC_NET_STATE.spawn(
+                STATIC_NET_STATE
+                    .gql_state
+                    .leaderboard_query(category, offset),
+            );
+        }
+    }
+}
+/// Loads the definition of a story scene from the game master
+pub fn request_scene(scene: SceneIndex, slide: SlideIndex) {
+    unsafe {
+        STATIC_NET_STATE.spawn(scene_query(scene, slide));
+    }
+}
+fn scene_query(
+    scene: SceneIndex,
+    slide: SlideIndex,
+) -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
+    let url = format!("{}/story/scene/{}", url::game_master_url()?, scene);
+    let promise = ajax::send("GET", &url, "")?;
+    Ok(promise.map(move |response| {
+        let body = response.map_err(PadlError::from)?;
+        let definition: SceneDefinition = serde_json::from_str(&body)?;
+        Ok(NetMsg::Scene(scene, slide, definition))
+    }))
+}
+/// Loads the quests of the player with their progress
+pub fn request_quests() {
+    unsafe {
+        if STATIC_NET_STATE.logged_in.load(Ordering::Relaxed) {
+            STATIC_NET_STATE.spawn(GraphQlState::quests_query());
+        }
+    }
+}
+/// Loads the archived visits of the current village
+pub fn request_replays() {
+    unsafe {
+        if STATIC_NET_STATE.logged_in.load(Ordering::Relaxed) {
+            STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.replays_query());
+        }
+    }
+}
 pub fn request_worker_tasks_update(unit_id: i64) {
     unsafe {
         STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.worker_tasks_query(unit_id));
     }
 }
+/// Trees are chopped by the game-master, which is not visible to the frontend otherwise
+pub fn request_forest_update() {
+    unsafe {
+        STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.forest_query());
+    }
+}
 pub fn request_resource_update() {
     unsafe {
         STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.resource_query());
@@ -152,12 +229,37 @@ impl NetState {
         if self.game_ready.load(Ordering::Relaxed) {
             self.spawn(self.gql_state.attacks_query());
             self.spawn(self.gql_state.reports_query());
+            self.spawn(self.gql_state.messages_query());
             self.spawn(self.gql_state.resource_query());
             self.spawn(GraphQlState::player_info_query());
         }
         self.register_networking();
     }
 
+    /// Lets the local game clock follow the mode of the game master's clock, once at startup.
+    /// The time itself is synchronized with every response from the game master.
+    fn sync_clock(&'static self) {
+        let request =
+            url::game_master_url().and_then(|url| ajax::send("GET", &format!("{}/clock", url), ""));
+        match request {
+            Ok(promise) => {
+                let sender = self.get_channel();
+                spawn_local(promise.map(move |response| {
+                    let snapshot: PadlResult<ClockSync> = response
+                        .map_err(PadlError::from)
+                        .and_then(|body| Ok(serde_json::from_str(&body)?));
+                    match snapshot {
+                        Ok(snapshot) => clock::record_clock_snapshot(&snapshot),
+                        Err(e) => sender
+                            .send(NetMsg::Error(e))
+                            .expect("Transferring data to game"),
+                    }
+                }));
+            }
+            Err(e) => self.net_msg_to_game_thread(NetMsg::Error(e)),
+        }
+    }
+
     fn get_channel(&self) -> Sender<NetMsg> {
         match self.chan.as_ref().unwrap().lock() {
             Ok(chan) => chan.clone(),
@@ -200,10 +302,15 @@ impl std::fmt::Debug for NetMsg {
             Self::Attacks(_) => write!(f, "NetMsg: Attacks"),
             Self::Buildings(_) => write!(f, "NetMsg: Buildings"),
             Self::Error(_) => write!(f, "NetMsg: Error"),
+            Self::Forest(_) => write!(f, "NetMsg: Forest"),
             Self::Hobos(_, _) => write!(f, "NetMsg: Hobos"),
-            Self::Leaderboard(_, _) => write!(f, "NetMsg: Leaderboard"),
+            Self::Leaderboard(..) => write!(f, "NetMsg: Leaderboard"),
             Self::Map(_, _, _) => write!(f, "NetMsg: Map"),
+            Self::Messages(_) => write!(f, "NetMsg: Messages"),
             Self::Player(_) => write!(f, "NetMsg: Player"),
+            Self::Quests(_) => write!(f, "NetMsg: Quests"),
+            Self::Replays(_) => write!(f, "NetMsg: Replays"),
+            Self::Scene(..) => write!(f, "NetMsg: Scene"),
             Self::VillageInfo(_) => write!(f, "NetMsg: VillageInfo"),
             Self::UpdateWorkerTasks(_) => write!(f, "NetMsg: UpdateWorkerTasks"),
             Self::Workers(_, _) => write!(f, "NetMsg: Workers"),
@@ -26,6 +26,25 @@ pub fn read_current_village_id() -> PadlResult<VillageKey> {
     })
 }
 
+/// Referral code of the player that invited the current player, if the URL contains one
+pub fn read_referral_code() -> Option<String> {
+    query_param("ref").ok()
+}
+
+/// Locale explicitly selected in the URL, e.g. `?lang=de`
+pub fn read_locale_param() -> Option<String> {
+    query_param("lang").ok()
+}
+
+/// Loads the game again with the given locale, the page cannot switch its catalog while running
+pub fn reload_with_locale(code: &str) {
+    js! { @(no_return)
+        var url = new URL(window.location.href);
+        url.searchParams.set("lang", @{code});
+        window.location.href = url.toString();
+    }
+}
+
 fn hostname() -> PadlResult<String> {
     stdweb::web::window()
         .location()
@@ -5,7 +5,7 @@ pub(crate) use crate::gui::ui_state::ViewState;
 pub(crate) use crate::gui::utils::{JmrRectangle, JmrVector};
 pub(crate) use crate::i18n::{TextDb, TextKey};
 pub(crate) use crate::init::quicksilver_integration::PadlEvent;
-pub(crate) use crate::init::wasm_setup::{utc_now, PadlINode};
+pub(crate) use crate::init::wasm_setup::{real_now, utc_now, PadlINode};
 pub(crate) use crate::logging::error::{PadlError, PadlErrorCode, PadlResult};
 pub(crate) use crate::resolution::ScreenResolution;
 pub(crate) use crate::view::text_pool::TextPool;
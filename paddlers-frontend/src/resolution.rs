@@ -34,6 +34,14 @@ impl ScreenResolution {
     pub fn menu_width(&self) -> f32 {
         self.unit_length() * ((16.0 * TOWN_Y as f32) - (9.0 * TOWN_X as f32)) / 9.0
     }
+    /// Smallest side length of an area that can be hit reliably with a finger
+    pub fn touch_target_size(&self) -> f32 {
+        match self {
+            ScreenResolution::Low => 24.0,
+            ScreenResolution::Mid => 48.0,
+            ScreenResolution::High => 72.0,
+        }
+    }
 }
 
 impl Default for ScreenResolution {
@@ -26,6 +26,8 @@ impl Game<'_, '_> {
             UiView::Town => UiView::Visitors(VisitorViewTab::Letters),
             UiView::Visitors(_) => UiView::Leaderboard,
             UiView::Leaderboard => UiView::Map,
+            UiView::Settings => UiView::Map,
+            UiView::Replay => UiView::Visitors(VisitorViewTab::Letters),
             UiView::Dialogue => return,
         };
 
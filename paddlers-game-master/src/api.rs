@@ -1,14 +1,17 @@
-mod attacks;
+pub(crate) mod admin;
+pub(crate) mod messages;
 mod reports;
-mod shop;
+pub(crate) mod shop;
 mod story;
 
-pub(crate) use attacks::visitor_satisfied_notification;
+pub(crate) use messages::{mark_messages_read, send_message};
 pub(crate) use reports::collect_report_rewards;
-pub(crate) use story::story_transition;
+pub(crate) use story::{collect_quest, story_scene, story_transition};
 
 use crate::authentication::Authentication;
 use crate::game_master::attack_funnel::PlannedAttack;
+use crate::game_master::attack_spawn::AttackSpawner;
+use crate::game_master::nests;
 use crate::setup::initialize_new_player_account;
 use crate::StringErr;
 use actix_web::error::BlockingError;
@@ -17,17 +20,26 @@ use futures::future::join_all;
 use futures::Future;
 use paddlers_shared_lib::api::{
     attacks::AttackDescriptor,
+    hobos::{HoboEviction, HoboOrdination},
     keys::{VillageKey, WorkerKey},
-    shop::{BuildingDeletion, BuildingPurchase, ProphetPurchase},
-    tasks::TaskList,
+    shop::{
+        BlueprintPurchase, BuildingDeletion, BuildingPurchase, ProphetPurchase, WorkerPurchase,
+    },
+    tasks::{RoutineDefinition, TaskList},
     PlayerInitData,
 };
+use paddlers_shared_lib::game_mechanics::town::blueprint::Blueprint;
 use paddlers_shared_lib::sql::GameDB;
 
 pub fn index() -> impl Responder {
     HttpResponse::Ok().body("Game Master OK")
 }
 
+/// Current game time, for clients to synchronize their clocks with
+pub fn game_clock() -> impl Responder {
+    HttpResponse::Ok().json(paddlers_shared_lib::game_clock::server_clock_snapshot())
+}
+
 pub(crate) fn purchase_prophet(
     pool: web::Data<crate::db::Pool>,
     actors: web::Data<crate::ActorAddresses>,
@@ -55,6 +67,66 @@ pub(crate) fn purchase_prophet(
     )
 }
 
+pub(crate) fn purchase_worker(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<WorkerPurchase>,
+    auth: Authentication,
+) -> impl Responder {
+    let db: crate::db::DB = pool.get_ref().into();
+
+    if let Err(err) = check_owns_village(&db, &auth, body.village) {
+        return err;
+    }
+    // Resources must not be spent unless the worker is inserted completely
+    let color = AttackSpawner::gen_color(&mut rand::thread_rng());
+    crate::db::store::GameStore::transaction(&db, || shop::try_buy_worker(&db, body.village, color))
+        .map_or_else(
+            |e| HttpResponse::BadRequest().body(e),
+            |_| HttpResponse::Ok().into(),
+        )
+}
+
+pub(crate) fn evict_hobo(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<HoboEviction>,
+    auth: Authentication,
+) -> impl Responder {
+    let db: crate::db::DB = pool.get_ref().into();
+
+    let hobo = match db.hobo(body.hobo) {
+        Some(hobo) => hobo,
+        None => return HttpResponse::BadRequest().body("No such hobo"),
+    };
+    if let Err(err) = check_owns_village(&db, &auth, VillageKey(hobo.home)) {
+        return err;
+    }
+    nests::evict_hobo(&db, &hobo).map_or_else(
+        |e| HttpResponse::BadRequest().body(e),
+        |_| HttpResponse::Ok().into(),
+    )
+}
+
+pub(crate) fn ordain_hobo(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<HoboOrdination>,
+    mut auth: Authentication,
+) -> impl Responder {
+    let db: crate::db::DB = pool.get_ref().into();
+
+    let hobo = match db.hobo(body.hobo) {
+        Some(hobo) => hobo,
+        None => return HttpResponse::BadRequest().body("No such hobo"),
+    };
+    if let Err(err) = check_owns_village(&db, &auth, VillageKey(hobo.home)) {
+        return err;
+    }
+    let player = auth.player_object(&db).expect("no player");
+    db.try_ordain_hobo(&hobo, player).map_or_else(
+        |e| HttpResponse::BadRequest().body(e),
+        |_| HttpResponse::Ok().into(),
+    )
+}
+
 pub(crate) fn purchase_building(
     pool: web::Data<crate::db::Pool>,
     body: web::Json<BuildingPurchase>,
@@ -77,7 +149,7 @@ pub(crate) fn purchase_building(
             .into();
     }
 
-    db.try_buy_building(building, (body.x, body.y), body.village)
+    shop::try_buy_building(&db, building, (body.x, body.y), body.village)
         .and_then(|_| {
             let player = auth.player_key(&db)?;
             db.building_insertion_triggers(building, player, addr)
@@ -85,6 +157,37 @@ pub(crate) fn purchase_building(
         .map_or_else(|e| HttpResponse::from(&e), |_| HttpResponse::Ok().into())
 }
 
+pub(crate) fn purchase_blueprint(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<BlueprintPurchase>,
+    mut auth: Authentication,
+    addr: web::Data<crate::ActorAddresses>,
+) -> impl Responder {
+    let db: crate::db::DB = pool.get_ref().into();
+
+    if let Err(err) = check_owns_village(&db, &auth, body.village) {
+        return err;
+    }
+    let blueprint = match body.blueprint.parse::<Blueprint>() {
+        Ok(blueprint) => blueprint,
+        Err(msg) => return HttpResponse::BadRequest().body(msg),
+    };
+    let player = auth.player_object(&db).expect("no player");
+
+    // The triggers may fail, in which case the buildings must not remain either
+    crate::db::store::GameStore::transaction(&db, || {
+        let built = shop::try_buy_blueprint(&db, &blueprint, body.village, &player)?;
+        for building in built {
+            db.building_insertion_triggers(building, player.key(), addr.clone())?;
+        }
+        Ok(())
+    })
+    .map_or_else(
+        |e| HttpResponse::BadRequest().body(e),
+        |_| HttpResponse::Ok().into(),
+    )
+}
+
 pub fn delete_building(
     pool: web::Data<crate::db::Pool>,
     body: web::Json<BuildingDeletion>,
@@ -100,6 +203,9 @@ pub fn delete_building(
         db.find_building_by_coordinates(body.x as i32, body.y as i32, body.village)
     {
         if building.building_type.can_be_deleted() {
+            if building.building_type.nest_capacity() > 0 {
+                nests::empty_nest(&db, &building);
+            }
             db.delete_building(&building);
             HttpResponse::Ok().into()
         } else {
@@ -142,6 +248,36 @@ pub(super) fn overwrite_tasks(
     HttpResponse::Ok().into()
 }
 
+pub(super) fn set_routine(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<RoutineDefinition>,
+    addr: web::Data<crate::ActorAddresses>,
+    auth: Authentication,
+) -> impl Responder {
+    let db: crate::db::DB = pool.get_ref().into();
+    if let Err(err) = check_owns_worker(&db, &auth, body.worker_id) {
+        return err;
+    }
+
+    match crate::worker_actions::validate_routine(&db, &body.0) {
+        Ok((routine, steps)) => {
+            if let Err(e) = crate::worker_actions::start_worker_routine(
+                &db,
+                &addr.town_worker,
+                &routine,
+                &steps,
+            ) {
+                return HttpResponse::InternalServerError().body(e.to_string());
+            }
+        }
+        Err(e) => {
+            println!("Routine creation failed. {} \n Body: {:?}", e, body.0);
+            return HttpResponse::BadRequest().body(e);
+        }
+    }
+    HttpResponse::Ok().into()
+}
+
 /// Must be called by an identified user (via JWT) before using any other Game-Master or GQL services
 pub(super) fn new_player(
     pool: web::Data<crate::db::Pool>,
@@ -0,0 +1,299 @@
+//! Game master API for operators
+//!
+//! All requests in here require the admin role in the JWT of the caller.
+
+use crate::authentication::Authentication;
+use crate::db::DB;
+use crate::game_master::attack_funnel::PlannedAttack;
+use actix_web::{web, HttpResponse, Responder};
+use paddlers_shared_lib::api::admin::*;
+use paddlers_shared_lib::api::check_display_name;
+use paddlers_shared_lib::api::tasks::{RawTask, TaskList};
+use paddlers_shared_lib::game_clock;
+use paddlers_shared_lib::game_mechanics::hobos::hobo_speed;
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::strum::IntoEnumIterator;
+
+pub(crate) fn inspect_player(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<InspectPlayer>,
+    auth: Authentication,
+) -> impl Responder {
+    if let Err(err) = check_admin(&auth) {
+        return err;
+    }
+    let db: crate::db::DB = pool.get_ref().into();
+    match db.select_player(&body.player) {
+        Some(player) => HttpResponse::Ok().json(db.player_report(&player)),
+        None => HttpResponse::NotFound().body("No such player"),
+    }
+}
+
+pub(crate) fn inspect_village(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<InspectVillage>,
+    auth: Authentication,
+) -> impl Responder {
+    if let Err(err) = check_admin(&auth) {
+        return err;
+    }
+    let db: crate::db::DB = pool.get_ref().into();
+    match db.village(body.village) {
+        Some(village) => HttpResponse::Ok().json(db.village_report(&village)),
+        None => HttpResponse::NotFound().body("No such village"),
+    }
+}
+
+pub(crate) fn grant_resources(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<GrantResources>,
+    auth: Authentication,
+) -> impl Responder {
+    if let Err(err) = check_admin(&auth) {
+        return err;
+    }
+    let db: crate::db::DB = pool.get_ref().into();
+    if db.village(body.village).is_none() {
+        return HttpResponse::NotFound().body("No such village");
+    }
+    for (res, n) in &body.resources {
+        if let Err(e) = db.add_resource(*res, body.village, *n) {
+            return HttpResponse::InternalServerError().body(e.to_string());
+        }
+    }
+    println!("Admin granted {:?} to {:?}", body.resources, body.village);
+    HttpResponse::Ok().into()
+}
+
+pub(crate) fn grant_karma(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<GrantKarma>,
+    auth: Authentication,
+) -> impl Responder {
+    if let Err(err) = check_admin(&auth) {
+        return err;
+    }
+    let db: crate::db::DB = pool.get_ref().into();
+    db.add_karma(body.player, body.karma)
+        .map_or_else(db_error, |_| HttpResponse::Ok().into())
+}
+
+pub(crate) fn force_story_state(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<ForceStoryState>,
+    auth: Authentication,
+    addr: web::Data<crate::ActorAddresses>,
+) -> impl Responder {
+    if let Err(err) = check_admin(&auth) {
+        return err;
+    }
+    let db: crate::db::DB = pool.get_ref().into();
+    if db.player(body.player).is_none() {
+        return HttpResponse::NotFound().body("No such player");
+    }
+    db.update_story_state(body.player, body.story_state, addr)
+        .map_or_else(
+            |e| HttpResponse::InternalServerError().body(e),
+            |_| HttpResponse::Ok().into(),
+        )
+}
+
+pub(crate) fn spawn_attack(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<SpawnAttack>,
+    auth: Authentication,
+    addr: web::Data<crate::ActorAddresses>,
+) -> impl Responder {
+    if let Err(err) = check_admin(&auth) {
+        return err;
+    }
+    let db: crate::db::DB = pool.get_ref().into();
+    let village = match db.village(body.village) {
+        Some(village) => village,
+        None => return HttpResponse::NotFound().body("No such village"),
+    };
+    if body.visitors.is_empty() || body.visitors.iter().any(|v| v.hp <= 0) {
+        return HttpResponse::BadRequest().body("Visitors need positive HP");
+    }
+    let hobos = body
+        .visitors
+        .iter()
+        .map(|v| {
+            db.insert_hobo(&NewHobo {
+                color: Some(v.color),
+                hp: v.hp,
+                speed: hobo_speed(v.hurried),
+                home: village.key().num(),
+                hurried: v.hurried,
+                nest: None,
+            })
+        })
+        .collect();
+    let pa = PlannedAttack {
+        origin_village: None,
+        destination_village: village,
+        hobos,
+    };
+    match addr.attack_funnel.try_send(pa) {
+        Ok(()) => HttpResponse::Ok().into(),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// Cancels all planned tasks of a worker, leaving it idle where it currently is
+pub(crate) fn flush_tasks(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<FlushTasks>,
+    auth: Authentication,
+    addr: web::Data<crate::ActorAddresses>,
+) -> impl Responder {
+    if let Err(err) = check_admin(&auth) {
+        return err;
+    }
+    let db: crate::db::DB = pool.get_ref().into();
+    let worker_id = body.worker;
+    // Find out where the current task can be interrupted, then idle on that tile
+    let interrupted = TaskList {
+        worker_id,
+        tasks: vec![],
+    };
+    let (x, y) = match crate::worker_actions::validate_task_list(&db, &interrupted) {
+        Ok(validated) => {
+            let current = &validated.update_tasks[0];
+            (current.x as usize, current.y as usize)
+        }
+        Err(e) => return HttpResponse::BadRequest().body(e.to_string()),
+    };
+    let idle = TaskList {
+        worker_id,
+        tasks: vec![RawTask {
+            task_type: TaskType::Idle,
+            x,
+            y,
+            target: None,
+        }],
+    };
+    match crate::worker_actions::validate_task_list(&db, &idle) {
+        Ok(validated) => {
+            for upd in validated.update_tasks {
+                db.update_task(&upd);
+            }
+            crate::worker_actions::replace_worker_tasks(
+                &db,
+                &addr.town_worker,
+                worker_id,
+                &validated.new_tasks,
+                validated.village_id,
+            );
+            HttpResponse::Ok().into()
+        }
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+pub(crate) fn ban_player(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<BanPlayer>,
+    auth: Authentication,
+) -> impl Responder {
+    if let Err(err) = check_admin(&auth) {
+        return err;
+    }
+    let db: crate::db::DB = pool.get_ref().into();
+    println!("Admin sets ban of {:?} to {}", body.player, body.banned);
+    db.set_player_banned(body.player, body.banned)
+        .map_or_else(db_error, |_| HttpResponse::Ok().into())
+}
+
+pub(crate) fn rename_player(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<RenamePlayer>,
+    auth: Authentication,
+) -> impl Responder {
+    if let Err(err) = check_admin(&auth) {
+        return err;
+    }
+    let display_name = match check_display_name(&body.display_name) {
+        Ok(name) => name,
+        Err(msg) => return HttpResponse::BadRequest().body(msg),
+    };
+    let db: crate::db::DB = pool.get_ref().into();
+    db.rename_player(body.player, display_name)
+        .map_or_else(db_error, |_| HttpResponse::Ok().into())
+}
+
+/// Jumps forward in game time.
+/// Other services pick up the advance from the database on their next request.
+pub(crate) fn advance_clock(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<AdvanceClock>,
+    auth: Authentication,
+) -> impl Responder {
+    if let Err(err) = check_admin(&auth) {
+        return err;
+    }
+    if body.seconds <= 0 {
+        return HttpResponse::BadRequest().body("The clock can only move forward");
+    }
+    let db: crate::db::DB = pool.get_ref().into();
+    db.advance_shared_clock(chrono::Duration::seconds(body.seconds));
+    game_clock::sync_server_clock(&db);
+    println!("Admin advanced the game clock by {}s", body.seconds);
+    HttpResponse::Ok().json(game_clock::server_clock_snapshot())
+}
+
+fn check_admin(auth: &Authentication) -> Result<(), HttpResponse> {
+    if auth.user.is_admin() {
+        Ok(())
+    } else {
+        Err(HttpResponse::Forbidden().body("Admin role required"))
+    }
+}
+
+fn db_error(e: diesel::result::Error) -> HttpResponse {
+    match e {
+        diesel::result::Error::NotFound => HttpResponse::NotFound().body("Not found"),
+        e => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+impl DB {
+    fn select_player(&self, selector: &PlayerSelector) -> Option<Player> {
+        match selector {
+            PlayerSelector::Id(key) => self.player(*key),
+            PlayerSelector::Name(name) => self.player_by_name(name),
+        }
+    }
+    fn player_report(&self, player: &Player) -> PlayerReport {
+        PlayerReport {
+            id: player.key(),
+            display_name: player.display_name.clone(),
+            karma: player.karma,
+            story_state: player.story_state,
+            last_active: player.last_active,
+            banned: player.banned,
+            villages: self
+                .player_villages(player.key())
+                .iter()
+                .map(|v| self.village_report(v))
+                .collect(),
+        }
+    }
+    fn village_report(&self, village: &Village) -> VillageReport {
+        let vid = village.key();
+        VillageReport {
+            id: vid,
+            owner: village.player_id.map(PlayerKey),
+            x: village.x,
+            y: village.y,
+            faith: village.faith,
+            resources: ResourceType::iter()
+                .filter_map(|res| self.maybe_resource(res, vid).map(|n| (res, n)))
+                .collect(),
+            workers: self.workers(vid).iter().map(|w| w.key()).collect(),
+            hobos: self.hobos(vid).len(),
+            buildings: self.buildings(vid).len(),
+            ongoing_attacks: self.attacks(vid, None).len(),
+        }
+    }
+}
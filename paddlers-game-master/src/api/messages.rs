@@ -0,0 +1,104 @@
+//! Game master API for messages between players
+
+use crate::authentication::Authentication;
+use crate::db::store::GameStore;
+use crate::StringErr;
+use actix_web::{web, HttpResponse, Responder};
+use paddlers_shared_lib::api::messages::*;
+use paddlers_shared_lib::prelude::*;
+
+/// Maximum number of messages a player can send within `MESSAGE_RATE_WINDOW_S`
+const MESSAGE_RATE_LIMIT: i64 = 5;
+const MESSAGE_RATE_WINDOW_S: i64 = 60;
+
+pub(crate) fn send_message(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<MessageSend>,
+    mut auth: Authentication,
+) -> impl Responder {
+    let db: crate::db::DB = pool.get_ref().into();
+    let sender = match auth.player_key(&db) {
+        Ok(key) => key,
+        Err(msg) => return HttpResponse::BadRequest().body(msg),
+    };
+    if message_rate_limit_reached(&db, sender) {
+        return HttpResponse::TooManyRequests().body("Too many messages, try again later");
+    }
+    match try_send_message(&db, sender, body.0) {
+        Ok(()) => HttpResponse::Ok().into(),
+        Err(msg) => HttpResponse::BadRequest().body(msg),
+    }
+}
+
+pub(crate) fn mark_messages_read(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<MessagesRead>,
+    mut auth: Authentication,
+) -> impl Responder {
+    let db: crate::db::DB = pool.get_ref().into();
+    let player = match auth.player_key(&db) {
+        Ok(key) => key,
+        Err(msg) => return HttpResponse::BadRequest().body(msg),
+    };
+    match try_mark_messages_read(&db, player, &body.0.messages) {
+        Ok(()) => HttpResponse::Ok().into(),
+        Err(msg) => HttpResponse::Forbidden().body(msg),
+    }
+}
+
+pub(crate) fn try_send_message<S: GameStore>(
+    db: &S,
+    sender: PlayerKey,
+    msg: MessageSend,
+) -> StringErr {
+    check_message_body(&msg.body)?;
+    let receiver = match db.player_by_village(msg.recipient) {
+        Some(player) => player.key(),
+        None => return Err("Village has no owner".to_owned()),
+    };
+    if receiver == sender {
+        return Err("Cannot send messages to yourself".to_owned());
+    }
+    db.insert_message(&NewPlayerMessage {
+        sender_id: Some(sender.num()),
+        receiver_id: receiver.num(),
+        body: msg.body,
+        sent: db.now(),
+    });
+    Ok(())
+}
+
+/// Marks all given messages as read, or none of them if any is not addressed to the player
+pub(crate) fn try_mark_messages_read<S: GameStore>(
+    db: &S,
+    player: PlayerKey,
+    messages: &[MessageKey],
+) -> StringErr {
+    db.transaction(|| {
+        for mid in messages {
+            match db.message(*mid) {
+                Some(msg) if msg.receiver() == player => db.mark_message_read(*mid),
+                _ => return Err("No such message".to_owned()),
+            }
+        }
+        Ok(())
+    })
+}
+
+pub(crate) fn message_rate_limit_reached<S: GameStore>(db: &S, sender: PlayerKey) -> bool {
+    let since = db.now() - chrono::Duration::seconds(MESSAGE_RATE_WINDOW_S);
+    db.messages_sent_since(sender, since) >= MESSAGE_RATE_LIMIT
+}
+
+fn check_message_body(body: &str) -> Result<(), String> {
+    if body.trim().is_empty() {
+        Err("Empty message".to_owned())
+    } else if body.chars().count() > MAX_MESSAGE_LENGTH {
+        Err(format!(
+            "Message too long, at most {} characters allowed",
+            MAX_MESSAGE_LENGTH
+        ))
+    } else {
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 mod buildings;
 mod prophets;
+mod workers;
 
 pub use buildings::*;
 pub use prophets::*;
+pub use workers::*;
@@ -1,22 +1,57 @@
 use crate::buildings::BuildingFactory;
+use crate::db::store::GameStore;
 use crate::db::DB;
+use crate::resource_system::try_spend;
 use crate::StringErr;
+use paddlers_shared_lib::game_mechanics::town::blueprint::Blueprint;
 use paddlers_shared_lib::story::story_state::StoryState;
 use paddlers_shared_lib::{api::shop::*, game_mechanics::attributes::Attributes, prelude::*};
 
-impl DB {
-    pub fn try_buy_building(
-        &self,
-        typ: BuildingType,
-        pos: (usize, usize),
-        village: VillageKey,
-    ) -> StringErr {
-        self.building_has_space(typ, pos, village)
-            .map(|_| self.try_spend(&typ.price(), village))
-            .map(|_| {
-                self.insert_building(&BuildingFactory::new(typ, pos, village));
-            })
+pub(crate) fn try_buy_building<S: GameStore>(
+    db: &S,
+    typ: BuildingType,
+    pos: (usize, usize),
+    village: VillageKey,
+) -> StringErr {
+    building_has_space(db, typ, pos, village)?;
+    try_spend(db, &typ.price(), village)?;
+    let mut building = BuildingFactory::new(typ, pos, village);
+    building.creation = db.now();
+    db.insert_building(&building);
+    Ok(())
+}
+
+/// Builds all missing parts of a blueprint, or nothing at all if any of them cannot be built.
+/// Returns the types of the buildings that have been inserted.
+pub(crate) fn try_buy_blueprint<S: GameStore>(
+    db: &S,
+    blueprint: &Blueprint,
+    village: VillageKey,
+    player: &Player,
+) -> Result<Vec<BuildingType>, String> {
+    let existing: Vec<_> = db
+        .buildings(village)
+        .into_iter()
+        .map(|b| (b.building_type, (b.x as usize, b.y as usize)))
+        .collect();
+    let missing = blueprint.missing_parts(&existing);
+    for (typ, pos) in &missing {
+        if !typ.player_can_build(player.karma, player.story_state) {
+            return Err(format!("Player not allowed to build {:?}", typ));
+        }
+        building_has_space(db, *typ, *pos, village)?;
     }
+    try_spend(db, &Blueprint::total_price(&missing), village)?;
+    let now = db.now();
+    for (typ, pos) in &missing {
+        let mut building = BuildingFactory::new(*typ, *pos, village);
+        building.creation = now;
+        db.insert_building(&building);
+    }
+    Ok(missing.into_iter().map(|(typ, _)| typ).collect())
+}
+
+impl DB {
     /// Check for events to be executed upon inserting new buildings
     pub fn building_insertion_triggers(
         &self,
@@ -36,66 +71,67 @@ impl DB {
         Ok(())
     }
 
-    fn building_has_space(
+    pub fn player_allowed_to_build(
         &self,
         typ: BuildingType,
-        pos: (usize, usize),
-        village: VillageKey,
-    ) -> StringErr {
-        // Check conflict with existing building
+        _vid: VillageKey,
+        player: &Player,
+    ) -> bool {
+        typ.player_can_build(player.karma, player.story_state)
+    }
+}
+
+pub(crate) fn building_has_space<S: GameStore>(
+    db: &S,
+    typ: BuildingType,
+    pos: (usize, usize),
+    village: VillageKey,
+) -> StringErr {
+    // Check conflict with existing building
+    let (w, h) = typ.size();
+    debug_assert_eq!(w, 1, "Not implemented yet");
+    debug_assert_eq!(h, 1, "Not implemented yet");
+    let (x0, y0) = (pos.0 as usize, pos.1 as usize);
+    // let(x1,y1) = (x0+w, y0+h);
+    for other in db.buildings(village) {
+        let typ: BuildingType = other.building_type;
         let (w, h) = typ.size();
         debug_assert_eq!(w, 1, "Not implemented yet");
         debug_assert_eq!(h, 1, "Not implemented yet");
-        let (x0, y0) = (pos.0 as usize, pos.1 as usize);
-        // let(x1,y1) = (x0+w, y0+h);
-        for other in self.buildings(village) {
-            let typ: BuildingType = other.building_type;
-            let (w, h) = typ.size();
-            debug_assert_eq!(w, 1, "Not implemented yet");
-            debug_assert_eq!(h, 1, "Not implemented yet");
-            let (x, y) = (other.x as usize, other.y as usize);
-            if x == x0 && y == y0 {
-                return Err("Space occupied".to_owned());
-            }
+        let (x, y) = (other.x as usize, other.y as usize);
+        if x == x0 && y == y0 {
+            return Err("Space occupied".to_owned());
         }
+    }
 
-        // Check conflict with map
-        // Note: Cleaner handling of map shape might be necessary in the future
-        if y0 == 6 {
-            return Err("Cannot build here".to_owned());
-        }
+    // Check conflict with map
+    // Note: Cleaner handling of map shape might be necessary in the future
+    if y0 == 6 {
+        return Err("Cannot build here".to_owned());
+    }
 
-        // Check conflict with stationary units
-        let workers = self.workers(village);
-        let (x0, y0) = (pos.0 as i32, pos.1 as i32);
-        for w in workers {
-            if w.x == x0 && w.y == y0 {
-                return Err("Unit blocks space".to_owned());
-            }
+    // Check conflict with stationary units
+    let workers = db.workers(village);
+    let (x0, y0) = (pos.0 as i32, pos.1 as i32);
+    for w in workers {
+        if w.x == x0 && w.y == y0 {
+            return Err("Unit blocks space".to_owned());
         }
-        // Check conflict with walking units
-        let workers = self.workers_with_job(village, &[TaskType::Walk]);
-        for w in workers {
-            let mut worker_x = w.x;
-            let mut worker_y = w.y;
-            for task in self.worker_tasks(w.key()) {
-                if is_between(x0, worker_x, task.x) || is_between(y0, worker_y, task.y) {
-                    return Err("Walking unit blocks space".to_owned());
-                }
-                worker_x = task.x;
-                worker_y = task.y;
+    }
+    // Check conflict with walking units
+    let workers = db.workers_with_job(village, &[TaskType::Walk]);
+    for w in workers {
+        let mut worker_x = w.x;
+        let mut worker_y = w.y;
+        for task in db.worker_tasks(w.key()) {
+            if is_between(x0, worker_x, task.x) || is_between(y0, worker_y, task.y) {
+                return Err("Walking unit blocks space".to_owned());
             }
+            worker_x = task.x;
+            worker_y = task.y;
         }
-        Ok(())
-    }
-    pub fn player_allowed_to_build(
-        &self,
-        typ: BuildingType,
-        _vid: VillageKey,
-        player: &Player,
-    ) -> bool {
-        typ.player_can_build(player.karma, player.story_state)
     }
+    Ok(())
 }
 
 fn is_between(x: i32, a: i32, b: i32) -> bool {
@@ -1,4 +1,6 @@
 use crate::db::{DeferredDbStatement, DB};
+use crate::game_master::nests::ordain_hobo;
+use crate::resource_system::try_spend;
 use crate::{ActorAddresses, StringErr};
 use paddlers_shared_lib::{api::shop::*, game_mechanics::prophets::*, prelude::*};
 
@@ -22,7 +24,7 @@ impl DB {
         p: &Player,
     ) -> StringErr {
         self.check_prophet_conditions(p)
-            .and_then(|cost| self.try_spend(&cost, village))
+            .and_then(|cost| try_spend(self, &cost, village))
             .and_then(|()| {
                 addrs
                     .db_actor
@@ -31,6 +33,12 @@ impl DB {
             })
     }
 
+    /// Turns a settled hobo into a prophet, for the same price as a new one
+    pub fn try_ordain_hobo(&self, hobo: &Hobo, p: &Player) -> StringErr {
+        let cost = self.check_prophet_conditions(p)?;
+        ordain_hobo(self, hobo, &cost)
+    }
+
     pub fn add_prophet(&self, v: VillageKey) {
         let prophet = NewHobo {
             hp: 10,
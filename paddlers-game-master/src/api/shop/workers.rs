@@ -0,0 +1,69 @@
+use crate::db::store::GameStore;
+use crate::quests::teach_ability;
+use crate::resource_system::try_spend;
+use paddlers_shared_lib::game_mechanics::town::TOWN_LANE_Y;
+use paddlers_shared_lib::game_mechanics::worker::*;
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::story::quest::QuestName;
+
+/// Hires a basic worker if there is space in the village's housing and enough resources.
+/// The new worker appears on the lane in front of its shack.
+pub(crate) fn try_buy_worker<S: GameStore>(
+    db: &S,
+    village: VillageKey,
+    color: UnitColor,
+) -> Result<Worker, String> {
+    let hired = db
+        .workers(village)
+        .into_iter()
+        .filter(|w| w.unit_type == UnitType::Basic)
+        .count();
+    // Workers fill up housing in the order the buildings are listed
+    let mut occupied = 0;
+    let home = db
+        .buildings(village)
+        .into_iter()
+        .find(|b| {
+            occupied += b.building_type.housing_capacity();
+            occupied > hired
+        })
+        .ok_or("No free housing for another worker")?;
+    try_spend(db, &basic_worker_cost(), village)?;
+
+    let (x, y) = (home.x, TOWN_LANE_Y as i32);
+    let worker = db.insert_worker(&NewWorker {
+        unit_type: UnitType::Basic,
+        x,
+        y,
+        color: Some(color),
+        speed: basic_worker_speed() * worker_color_stats(Some(color)).speed_factor,
+        home: village.num(),
+        mana: None,
+        level: 1,
+        exp: 0,
+    });
+    db.insert_tasks(&[NewTask {
+        worker_id: worker.id,
+        task_type: TaskType::Idle,
+        x,
+        y,
+        start_time: Some(db.now()),
+        target_hobo_id: None,
+    }]);
+    db.insert_ability(&NewAbility {
+        worker_id: worker.id,
+        ability_type: AbilityType::Work,
+    });
+    db.insert_worker_flag(WorkerFlag {
+        worker_id: worker.id,
+        flag_type: WorkerFlagType::Work,
+        last_update: db.now(),
+    });
+    // Abilities learned from quests are known by all workers of the player
+    if let Some(player) = db.player_by_village(village) {
+        for ability in QuestName::learned_abilities(&db.completed_quests(player.key())) {
+            teach_ability(db, &worker, ability);
+        }
+    }
+    Ok(db.worker_priv(worker.key()).unwrap_or(worker))
+}
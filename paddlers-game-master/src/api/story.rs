@@ -4,10 +4,13 @@ use crate::authentication::Authentication;
 use crate::db::NewHoboMessage;
 use crate::db::DB;
 use crate::game_master::attack_funnel::PlannedAttack;
+use crate::scenes::SceneCatalog;
 use actix::prelude::*;
 use actix_web::{web, HttpResponse, Responder};
-use paddlers_shared_lib::api::story::StoryStateTransition;
+use paddlers_shared_lib::api::story::{QuestCollect, StoryStateTransition};
+use paddlers_shared_lib::game_mechanics::hobos::UNHURRIED_HOBO_SPEED;
 use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::story::scene::SceneIndex;
 use paddlers_shared_lib::story::story_state::StoryState;
 
 pub(crate) fn story_transition(
@@ -28,6 +31,37 @@ pub(crate) fn story_transition(
     }
 }
 
+/// Completes a quest of the player and grants the rewards, once its objectives are fulfilled
+pub(crate) fn collect_quest(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<QuestCollect>,
+    mut auth: Authentication,
+) -> impl Responder {
+    let db: crate::db::DB = pool.get_ref().into();
+    if let Some(player) = auth.player_object(&db) {
+        crate::quests::collect_quest(&db, player, body.quest).map_or_else(
+            |e| HttpResponse::BadRequest().body(e),
+            |_| HttpResponse::Ok().into(),
+        )
+    } else {
+        HttpResponse::BadRequest().body(format!(
+            "No such player in the database: {}",
+            auth.user.uuid
+        ))
+    }
+}
+
+/// Definition of a story scene, as it is displayed in the frontend
+pub(crate) fn story_scene(
+    scene: web::Path<SceneIndex>,
+    scenes: web::Data<SceneCatalog>,
+) -> impl Responder {
+    match scenes.get(*scene) {
+        Some(definition) => HttpResponse::Ok().json(definition),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
 impl DB {
     fn try_execute_story_transition(
         &self,
@@ -78,7 +112,7 @@ impl DB {
                 let hobo = NewHobo {
                     color: Some(UnitColor::Yellow),
                     hp: 1,
-                    speed: 0.25,
+                    speed: UNHURRIED_HOBO_SPEED,
                     home: village.key().num(),
                     hurried: false,
                     nest: None,
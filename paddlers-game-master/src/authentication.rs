@@ -22,11 +22,15 @@ impl FromRequest for Authentication {
         match req.headers().get(actix_web::http::header::AUTHORIZATION) {
             Some(auth_header) => match auth_header.to_str() {
                 Ok(token) => match PadlUser::from_token(token, &config) {
-                    Ok(user) => Ok(Authentication {
-                        user,
-                        _private: (),
-                        cached_player: None,
-                    }),
+                    Ok(user) => {
+                        let mut auth = Authentication {
+                            user,
+                            _private: (),
+                            cached_player: None,
+                        };
+                        auth.record_activity(req);
+                        Ok(auth)
+                    }
                     Err(e) => Err(ErrorUnauthorized(e))?,
                 },
                 Err(_e) => Err(ErrorBadRequest("Unable to parse token"))?,
@@ -37,9 +41,19 @@ impl FromRequest for Authentication {
 }
 
 impl Authentication {
+    /// Any authenticated request counts as activity of the player, not only GraphQL queries
+    fn record_activity(&mut self, req: &HttpRequest) {
+        if let Some(pool) = req.app_data::<crate::db::Pool>() {
+            let db: DB = (&*pool).into();
+            if let Some(player) = self.player_object(&db) {
+                db.record_activity(player);
+            }
+        }
+    }
+    /// The player of the authenticated user, banned players are treated as not existing
     pub(crate) fn player_object(&mut self, db: &DB) -> Option<&Player> {
         if self.cached_player.is_none() {
-            self.cached_player = db.player_by_uuid(self.user.uuid);
+            self.cached_player = db.player_by_uuid(self.user.uuid).filter(|p| !p.banned);
         }
         self.cached_player.as_ref()
     }
@@ -0,0 +1,211 @@
+//! Command line tool for operators of a Paddlers server.
+//!
+//! Sends requests to the admin API of the game master.
+//! The JWT of a user with the admin role has to be provided in `PADDLERS_ADMIN_TOKEN`.
+
+use actix_web::client::Client;
+use actix_web::http::header;
+use futures::Future;
+use paddlers_shared_lib::api::admin::*;
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::strum::IntoEnumIterator;
+use serde::Serialize;
+
+const USAGE: &str = "Usage: paddlers-admin <command> [arguments]
+
+Commands:
+    player <id|name>                       Show a player and its villages
+    village <id>                           Show a village
+    grant-resources <village> <res>=<n>... Add resources to a village, e.g. Feathers=100
+    grant-karma <player> <n>               Add karma to a player
+    story <player> <state>                 Set the story state of a player
+    attack <village> <hp>:<color>[:hurried]...
+                                           Send visitors to a village, e.g. 5:Yellow 3:Camo:hurried
+    flush-tasks <worker>                   Cancel all tasks of a worker
+    ban <player>                           Ban a player
+    unban <player>                         Lift the ban of a player
+    rename <player> <name>                 Change the display name of a player
+    advance-clock <seconds>                Jump forward in game time
+
+Environment:
+    PADDLERS_ADMIN_TOKEN   JWT of a user with the admin role (required)
+    GAME_MASTER_URL        defaults to http://localhost:8088";
+
+fn main() {
+    dotenv::dotenv().ok();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(msg) = run(&args) {
+        eprintln!("{}", msg);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let command = args.get(0).ok_or(USAGE)?;
+    let params = &args[1..];
+    match command.as_str() {
+        "player" => {
+            let arg = param(params, 0)?;
+            let player = match arg.parse() {
+                Ok(id) => PlayerSelector::Id(PlayerKey(id)),
+                Err(_) => PlayerSelector::Name(arg.to_owned()),
+            };
+            post("inspect/player", &InspectPlayer { player })
+        }
+        "village" => post(
+            "inspect/village",
+            &InspectVillage {
+                village: VillageKey(number(params, 0)?),
+            },
+        ),
+        "grant-resources" => {
+            let resources = params
+                .iter()
+                .skip(1)
+                .map(|arg| {
+                    let mut split = arg.splitn(2, '=');
+                    let res = parse_enum(split.next().unwrap_or(""))?;
+                    let n = split
+                        .next()
+                        .and_then(|n| n.parse().ok())
+                        .ok_or(format!("Invalid resource amount: {}", arg))?;
+                    Ok((res, n))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            post(
+                "grant/resources",
+                &GrantResources {
+                    village: VillageKey(number(params, 0)?),
+                    resources,
+                },
+            )
+        }
+        "grant-karma" => post(
+            "grant/karma",
+            &GrantKarma {
+                player: PlayerKey(number(params, 0)?),
+                karma: number(params, 1)?,
+            },
+        ),
+        "story" => post(
+            "story",
+            &ForceStoryState {
+                player: PlayerKey(number(params, 0)?),
+                story_state: parse_enum(param(params, 1)?)?,
+            },
+        ),
+        "attack" => {
+            let visitors = params
+                .iter()
+                .skip(1)
+                .map(|arg| parse_visitor(arg))
+                .collect::<Result<Vec<_>, String>>()?;
+            post(
+                "attack",
+                &SpawnAttack {
+                    village: VillageKey(number(params, 0)?),
+                    visitors,
+                },
+            )
+        }
+        "flush-tasks" => post(
+            "worker/flush",
+            &FlushTasks {
+                worker: WorkerKey(number(params, 0)?),
+            },
+        ),
+        "ban" | "unban" => post(
+            "player/ban",
+            &BanPlayer {
+                player: PlayerKey(number(params, 0)?),
+                banned: command == "ban",
+            },
+        ),
+        "rename" => post(
+            "player/rename",
+            &RenamePlayer {
+                player: PlayerKey(number(params, 0)?),
+                display_name: params[1..].join(" "),
+            },
+        ),
+        "advance-clock" => post(
+            "clock/advance",
+            &AdvanceClock {
+                seconds: number(params, 0)?,
+            },
+        ),
+        _ => Err(USAGE.to_owned()),
+    }
+}
+
+/// Sends the request and prints the response body
+fn post<T: Serialize>(path: &str, body: &T) -> Result<(), String> {
+    let token = std::env::var("PADDLERS_ADMIN_TOKEN")
+        .map_err(|_| "PADDLERS_ADMIN_TOKEN must be set".to_owned())?;
+    let base_url =
+        std::env::var("GAME_MASTER_URL").unwrap_or_else(|_| "http://localhost:8088".to_owned());
+    let url = format!("{}/admin/{}", base_url.trim_end_matches('/'), path);
+
+    let request = Client::default()
+        .post(url)
+        .header(header::AUTHORIZATION, token)
+        .send_json(body)
+        .map_err(|e| format!("Request failed: {}", e))
+        .and_then(|mut response| {
+            let status = response.status();
+            response
+                .body()
+                .map_err(|e| format!("Reading response failed: {}", e))
+                .map(move |body| (status, body))
+        });
+    let (status, body) = actix::System::new("paddlers-admin").block_on(request)?;
+    let body = String::from_utf8_lossy(&body);
+    if status.is_success() {
+        if !body.is_empty() {
+            println!("{}", body);
+        }
+        Ok(())
+    } else {
+        Err(format!("{}: {}", status, body))
+    }
+}
+
+fn param(params: &[String], i: usize) -> Result<&str, String> {
+    params
+        .get(i)
+        .map(String::as_str)
+        .ok_or(format!("Missing argument\n\n{}", USAGE))
+}
+
+fn number(params: &[String], i: usize) -> Result<i64, String> {
+    let arg = param(params, i)?;
+    arg.parse().map_err(|_| format!("Not a number: {}", arg))
+}
+
+/// Looks up an enum variant by its name, ignoring case
+fn parse_enum<T: IntoEnumIterator + std::fmt::Display>(s: &str) -> Result<T, String> {
+    T::iter()
+        .find(|variant| variant.to_string().eq_ignore_ascii_case(s))
+        .ok_or_else(|| {
+            let options: Vec<String> = T::iter().map(|v| v.to_string()).collect();
+            format!(
+                "Unknown value {}, expected one of {}",
+                s,
+                options.join(", ")
+            )
+        })
+}
+
+fn parse_visitor(arg: &str) -> Result<AttackingVisitor, String> {
+    let parts: Vec<&str> = arg.split(':').collect();
+    match parts.as_slice() {
+        [hp, color] | [hp, color, "hurried"] => Ok(AttackingVisitor {
+            hp: hp
+                .parse()
+                .map_err(|_| format!("Invalid visitor HP: {}", arg))?,
+            color: parse_enum(color)?,
+            hurried: parts.len() == 3,
+        }),
+        _ => Err(format!("Invalid visitor: {}", arg)),
+    }
+}
@@ -1,10 +1,12 @@
-use paddlers_shared_lib::{game_mechanics::attributes::*, models::*, prelude::VillageKey};
+use paddlers_shared_lib::{
+    game_clock, game_mechanics::attributes::*, models::*, prelude::VillageKey,
+};
 
 pub struct BuildingFactory;
 
 impl BuildingFactory {
     pub fn new(typ: BuildingType, pos: (usize, usize), village: VillageKey) -> NewBuilding {
-        let now = chrono::Utc::now().naive_utc();
+        let now = game_clock::now();
         NewBuilding {
             x: pos.0 as i32,
             y: pos.1 as i32,
@@ -2,6 +2,8 @@ mod messages;
 pub use messages::*;
 
 use crate::db::*;
+use crate::game_master::attack_funnel::insert_scheduled_attack;
+use crate::game_master::town_defence::collect_report_rewards;
 use actix::prelude::*;
 
 /// This actor executes DB requests which can be done concurrent to
@@ -18,16 +20,7 @@ impl Handler<DeferredDbStatement> for DbActor {
                 self.db().add_prophet(village);
             }
             DeferredDbStatement::NewAttack(planned_atk) => {
-                let attack = self.db().insert_attack(&planned_atk.attack);
-                for hobo in planned_atk.hobos.iter() {
-                    let atu = AttackToHobo {
-                        attack_id: attack.id,
-                        hobo_id: hobo.num(),
-                        satisfied: None,
-                        released: None,
-                    };
-                    self.db().insert_attack_to_hobo(&atu);
-                }
+                insert_scheduled_attack(&self.db(), &planned_atk);
             }
         }
     }
@@ -48,20 +41,7 @@ impl Handler<CollectReportRewardsMessage> for DbActor {
         msg: CollectReportRewardsMessage,
         _ctx: &mut SyncContext<Self>,
     ) -> Self::Result {
-        let report = msg.0;
-        let village = report.village();
-        let db = self.db();
-        for (resource_type, n) in db.rewards(report.key()) {
-            if let Err(e) = db.add_resource(resource_type, village, n) {
-                eprintln!("Reward collection failed: {}", e);
-            }
-        }
-        if let Some(player) = db.player_by_village(village) {
-            if let Err(e) = db.add_karma(player.key(), report.karma) {
-                eprintln!("Karma reward collection failed: {}", e);
-            }
-        }
-        db.delete_visit_report(&report);
+        collect_report_rewards(&self.db(), &msg.0);
     }
 }
 
@@ -1,7 +1,10 @@
 use super::*;
 use diesel::prelude::*;
+use paddlers_shared_lib::game_clock;
+use paddlers_shared_lib::leaderboard::season_of;
 use paddlers_shared_lib::models::dsl;
 use paddlers_shared_lib::schema::*;
+use paddlers_shared_lib::story::quest::QuestName;
 use paddlers_shared_lib::story::story_state::StoryState;
 
 impl DB {
@@ -17,9 +20,23 @@ impl DB {
             .get_result(self.dbconn())
     }
 
+    pub fn set_player_banned(&self, p: PlayerKey, banned: bool) -> QueryResult<Player> {
+        let target = players::table.find(p.num());
+        diesel::update(target)
+            .set(players::banned.eq(banned))
+            .get_result(self.dbconn())
+    }
+    pub fn rename_player(&self, p: PlayerKey, display_name: &str) -> QueryResult<Player> {
+        let target = players::table.find(p.num());
+        diesel::update(target)
+            .set(players::display_name.eq(display_name))
+            .get_result(self.dbconn())
+    }
+    /// Deletes the visitors of an attack, except those that have settled in a nest
     pub fn delete_attack_hobos(&self, atk: AttackKey) {
         // Performance: This is a lot of sequential queries, could be reduced to one
-        for hobo in self.attack_hobos(atk) {
+        let hobos = self.attack_hobos(atk).into_iter();
+        for hobo in hobos.filter(|h| h.nest.is_none()) {
             let result = diesel::delete(&hobo).execute(self.dbconn());
             if result.is_err() {
                 println!("Couldn't delete hobo {:?}", hobo);
@@ -40,6 +57,32 @@ impl DB {
             .get_result(self.dbconn())
             .expect("Inserting hobo")
     }
+    pub fn update_hobo(&self, hobo: &Hobo) {
+        // Columns are listed explicitly, since `set(hobo)` would skip fields that are None
+        diesel::update(hobo)
+            .set((
+                hobos::home.eq(hobo.home),
+                hobos::color.eq(hobo.color),
+                hobos::speed.eq(hobo.speed),
+                hobos::hp.eq(hobo.hp),
+                hobos::hurried.eq(hobo.hurried),
+                hobos::nest.eq(hobo.nest),
+                hobos::last_production.eq(hobo.last_production),
+            ))
+            .execute(self.dbconn())
+            .expect("Updating hobo");
+    }
+    pub fn delete_hobo(&self, hobo: &Hobo) {
+        let result = diesel::delete(hobo).execute(self.dbconn());
+        if result.is_err() {
+            println!("Couldn't delete hobo {:?}", hobo);
+        }
+    }
+    pub fn delete_effects_on_hobo(&self, hobo: HoboKey) {
+        diesel::delete(effects::table.filter(effects::hobo_id.eq(hobo.num())))
+            .execute(self.dbconn())
+            .expect("Deleting effects");
+    }
     pub fn insert_worker(&self, u: &NewWorker) -> Worker {
         diesel::insert_into(workers::dsl::workers)
             .values(u)
@@ -88,11 +131,36 @@ impl DB {
             .get_result(self.dbconn())
     }
     pub fn add_karma(&self, p: PlayerKey, plus: i64) -> QueryResult<Player> {
+        self.add_season_stats(p, plus, 0)?;
         let target = players::table.find(p.num());
         diesel::update(target)
             .set(players::karma.eq(players::karma + plus))
             .get_result(self.dbconn())
     }
+    /// Adds to the scores of the current leaderboard season
+    pub fn add_season_stats(
+        &self,
+        p: PlayerKey,
+        karma: i64,
+        visitors_welcomed: i64,
+    ) -> QueryResult<usize> {
+        let stats = PlayerSeasonStats {
+            player_id: p.num(),
+            season: season_of(game_clock::now()),
+            karma,
+            visitors_welcomed,
+        };
+        diesel::insert_into(player_season_stats::table)
+            .values(&stats)
+            .on_conflict((player_season_stats::player_id, player_season_stats::season))
+            .do_update()
+            .set((
+                player_season_stats::karma.eq(player_season_stats::karma + karma),
+                player_season_stats::visitors_welcomed
+                    .eq(player_season_stats::visitors_welcomed + visitors_welcomed),
+            ))
+            .execute(self.dbconn())
+    }
     pub fn insert_building(&self, new_building: &NewBuilding) -> Building {
         diesel::insert_into(buildings::dsl::buildings)
             .values(new_building)
@@ -104,6 +172,27 @@ impl DB {
             .execute(self.dbconn())
             .expect("Deleting building");
     }
+    pub fn update_building_creation(&self, building: &Building, creation: chrono::NaiveDateTime) {
+        diesel::update(buildings::table.filter(buildings::id.eq(building.id)))
+            .set(buildings::creation.eq(creation))
+            .execute(self.dbconn())
+            .expect("Updating building");
+    }
+    /// Removes the owner and everything inside a village, leaving an empty town.
+    /// Visits to the village are called off.
+    pub fn clear_village(&self, village: VillageKey) -> QueryResult<()> {
+        let vid = village.num();
+        diesel::delete(workers::table.filter(workers::home.eq(vid))).execute(self.dbconn())?;
+        diesel::delete(hobos::table.filter(hobos::home.eq(vid))).execute(self.dbconn())?;
+        diesel::delete(buildings::table.filter(buildings::village_id.eq(vid)))
+            .execute(self.dbconn())?;
+        diesel::delete(attacks::table.filter(attacks::destination_village_id.eq(vid)))
+            .execute(self.dbconn())?;
+        diesel::update(villages::table.find(vid))
+            .set(villages::player_id.eq(None::<i64>))
+            .execute(self.dbconn())?;
+        Ok(())
+    }
     pub fn insert_task(&self, task: &NewTask) -> Task {
         diesel::insert_into(tasks::dsl::tasks)
             .values(task)
@@ -130,16 +219,75 @@ impl DB {
     }
     pub fn flush_task_queue(&self, worker_id: WorkerKey) {
         diesel::delete(tasks::table.filter(tasks::worker_id.eq(worker_id.num())))
-            .filter(tasks::start_time.gt(diesel::dsl::now.at_time_zone("UTC")))
+            .filter(tasks::start_time.gt(game_clock::now()))
             .execute(self.dbconn())
             .expect("Deleting task");
     }
+    /// Replaces the routine of a worker, if it has one
+    pub fn insert_worker_routine(
+        &self,
+        routine: &WorkerRoutine,
+        steps: &[RoutineTask],
+    ) -> QueryResult<()> {
+        self.dbconn().transaction(|| {
+            diesel::delete(worker_routines::table.find(routine.worker_id))
+                .execute(self.dbconn())?;
+            diesel::insert_into(worker_routines::table)
+                .values(routine)
+                .execute(self.dbconn())?;
+            diesel::insert_into(routine_tasks::table)
+                .values(steps)
+                .execute(self.dbconn())?;
+            Ok(())
+        })
+    }
+    pub fn update_worker_routine(&self, routine: &WorkerRoutine) {
+        diesel::update(routine)
+            .set(routine)
+            .execute(self.dbconn())
+            .expect("Updating worker routine");
+    }
+    pub fn delete_worker_routine(&self, worker_id: WorkerKey) {
+        diesel::delete(worker_routines::table.find(worker_id.num()))
+            .execute(self.dbconn())
+            .expect("Deleting worker routine");
+    }
     pub fn insert_streams(&self, streams: &[NewStream]) -> Vec<Stream> {
         diesel::insert_into(streams::dsl::streams)
             .values(streams)
             .get_results(self.dbconn())
             .expect("Inserting streams")
     }
+    /// Advances the game clock of all services
+    pub fn advance_shared_clock(&self, by: chrono::Duration) {
+        let by_us = by.num_microseconds().expect("Clock advance out of range");
+        diesel::update(shared_clock::table)
+            .set(shared_clock::advanced_us.eq(shared_clock::advanced_us + by_us))
+            .execute(self.dbconn())
+            .expect("Advancing clock");
+    }
+    /// Marks a map chunk as generated.
+    /// Returns false if the chunk has been claimed before.
+    pub fn claim_map_chunk(&self, chunk: i32) -> bool {
+        diesel::insert_into(map_chunks::table)
+            .values((
+                map_chunks::id.eq(chunk),
+                map_chunks::generated.eq(game_clock::now()),
+            ))
+            .on_conflict_do_nothing()
+            .execute(self.dbconn())
+            .expect("Inserting map chunk")
+            == 1
+    }
+    /// Fails if another village is at the same position
+    pub fn insert_village(&self, village: &NewVillage) -> QueryResult<Village> {
+        // A savepoint keeps a surrounding transaction usable after a failure
+        self.dbconn().transaction(|| {
+            diesel::insert_into(villages::dsl::villages)
+                .values(village)
+                .get_result(self.dbconn())
+        })
+    }
     pub fn insert_villages(&self, villages: &[NewVillage]) -> Vec<Village> {
         diesel::insert_into(villages::dsl::villages)
             .values(villages)
@@ -152,6 +300,18 @@ impl DB {
             .get_result(self.dbconn())
             .expect("Inserting ability")
     }
+    /// Fails if the quest has been completed before
+    pub fn insert_completed_quest(&self, p: PlayerKey, quest: QuestName) -> QueryResult<()> {
+        let completed = NewCompletedQuest {
+            player_id: p.num(),
+            quest,
+            completed: game_clock::now(),
+        };
+        diesel::insert_into(completed_quests::table)
+            .values(&completed)
+            .execute(self.dbconn())
+            .map(|_| ())
+    }
     pub fn insert_effect(&self, e: &NewEffect) -> Effect {
         diesel::insert_into(effects::dsl::effects)
             .values(e)
@@ -161,7 +321,7 @@ impl DB {
     pub fn update_ability_used_timestamp(&self, worker: WorkerKey, at: AbilityType) {
         let target = abilities::table.find((at, worker.num()));
         diesel::update(target)
-            .set(abilities::last_used.eq(diesel::dsl::now.at_time_zone("UTC").nullable()))
+            .set(abilities::last_used.eq(Some(game_clock::now())))
             .execute(self.dbconn())
             .expect("Updating ability timestamp");
     }
@@ -174,7 +334,7 @@ impl DB {
     pub fn update_worker_flag_timestamp_now(&self, w: WorkerKey, f: WorkerFlagType) {
         let target = worker_flags::table.find((w.num(), f));
         diesel::update(target)
-            .set(worker_flags::last_update.eq(diesel::dsl::now.at_time_zone("UTC")))
+            .set(worker_flags::last_update.eq(game_clock::now()))
             .execute(self.dbconn())
             .expect("Updating flag timestamp to now");
     }
@@ -214,6 +374,42 @@ impl DB {
             println!("Couldn't delete {:?}", obj);
         }
     }
+    pub fn insert_visit_replay(&self, vr: &NewVisitReplay) -> VisitReplay {
+        diesel::insert_into(visit_replays::dsl::visit_replays)
+            .values(vr)
+            .get_result(self.dbconn())
+            .expect("Inserting visit replay")
+    }
+    pub fn insert_replay_hobo(&self, hobo: &NewReplayHobo) -> ReplayHobo {
+        diesel::insert_into(replay_hobos::dsl::replay_hobos)
+            .values(hobo)
+            .get_result(self.dbconn())
+            .expect("Inserting replay hobo")
+    }
+    pub fn insert_replay_effects(&self, effects: Vec<NewReplayEffect>) {
+        if effects.is_empty() {
+            return;
+        }
+        diesel::insert_into(replay_effects::dsl::replay_effects)
+            .values(effects)
+            .execute(self.dbconn())
+            .expect("Inserting replay effects");
+    }
+    pub fn insert_replay_buildings(&self, buildings: Vec<NewReplayBuilding>) {
+        if buildings.is_empty() {
+            return;
+        }
+        diesel::insert_into(replay_buildings::dsl::replay_buildings)
+            .values(buildings)
+            .execute(self.dbconn())
+            .expect("Inserting replay buildings");
+    }
+    pub fn delete_visit_replay(&self, obj: &VisitReplay) {
+        let result = diesel::delete(obj).execute(self.dbconn());
+        if result.is_err() {
+            println!("Couldn't delete {:?}", obj);
+        }
+    }
     pub fn set_satisfied(&self, hid: HoboKey, aid: AttackKey, satisfied: bool) {
         let target = attacks_to_hobos::table.find((aid.num(), hid.num()));
         diesel::update(target)
@@ -224,8 +420,20 @@ impl DB {
     pub fn release_resting_visitor(&self, hid: HoboKey, aid: AttackKey) {
         let target = attacks_to_hobos::table.find((aid.num(), hid.num()));
         diesel::update(target)
-            .set(attacks_to_hobos::released.eq(diesel::dsl::now.at_time_zone("UTC").nullable()))
+            .set(attacks_to_hobos::released.eq(Some(game_clock::now())))
             .execute(self.dbconn())
             .expect("setting released");
     }
+    pub fn insert_message(&self, msg: &NewPlayerMessage) -> PlayerMessage {
+        diesel::insert_into(messages::dsl::messages)
+            .values(msg)
+            .get_result(self.dbconn())
+            .expect("Inserting message")
+    }
+    pub fn mark_message_read(&self, id: MessageKey) {
+        diesel::update(messages::table.find(id.num()))
+            .set(messages::read.eq(true))
+            .execute(self.dbconn())
+            .expect("Marking message read");
+    }
 }
@@ -0,0 +1,422 @@
+//! Storage abstraction for the game logic.
+//!
+//! Game logic that is written against `GameStore` instead of `DB` can also run on
+//! other implementations, like the in-memory store used for offline simulations.
+//! Only the queries and updates required by that logic are part of the trait.
+//!
+//! Note: The trait is deliberately not re-exported from `crate::db`, since its method names
+//! clash with `GameDB` for code that uses a `DB` directly.
+
+use super::DB;
+use chrono::NaiveDateTime;
+use diesel::{Connection, QueryResult};
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::story::quest::{QuestName, QuestProgress};
+
+pub(crate) trait GameStore {
+    /// Current time as seen by the game logic
+    fn now(&self) -> NaiveDateTime;
+    /// Runs `f` such that either all or none of its changes are stored
+    fn transaction<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce() -> Result<T, String>;
+
+    // Players and villages
+    fn player_by_village(&self, vid: VillageKey) -> Option<Player>;
+    fn village(&self, village: VillageKey) -> Option<Village>;
+    fn player_villages(&self, p: PlayerKey) -> Vec<Village>;
+    fn all_player_villages(&self) -> Vec<Village>;
+    fn add_karma(&self, p: PlayerKey, plus: i64) -> QueryResult<()>;
+    fn add_season_stats(&self, p: PlayerKey, karma: i64, visitors_welcomed: i64)
+        -> QueryResult<()>;
+
+    // Resources
+    fn resource(&self, r: ResourceType, v: VillageKey) -> i64;
+    fn add_resource(&self, rt: ResourceType, vk: VillageKey, plus: i64) -> QueryResult<()>;
+
+    // Buildings
+    fn buildings(&self, village: VillageKey) -> Vec<Building>;
+    fn find_building_by_coordinates(&self, x: i32, y: i32, village: VillageKey)
+        -> Option<Building>;
+    fn insert_building(&self, new_building: &NewBuilding) -> Building;
+    fn delete_building(&self, building: &Building);
+    fn update_building_creation(&self, building: &Building, creation: NaiveDateTime);
+
+    // Workers and their tasks
+    fn worker_priv(&self, worker_id: WorkerKey) -> Option<Worker>;
+    fn workers(&self, village: VillageKey) -> Vec<Worker>;
+    fn insert_worker(&self, u: &NewWorker) -> Worker;
+    fn workers_with_job(&self, village: VillageKey, jobs: &[TaskType]) -> Vec<Worker>;
+    fn count_workers_at_pos_doing_job(
+        &self,
+        village: VillageKey,
+        x: i32,
+        y: i32,
+        job: TaskType,
+    ) -> usize;
+    fn update_worker(&self, u: &Worker);
+    fn insert_worker_flag(&self, wf: WorkerFlag);
+    fn update_worker_flag_timestamp_now(&self, w: WorkerKey, f: WorkerFlagType);
+    fn update_worker_flag_timestamp(&self, w: WorkerKey, f: WorkerFlagType, ts: NaiveDateTime);
+    fn worker_flags(&self, worker: WorkerKey) -> Vec<WorkerFlag>;
+    fn add_worker_mana(&self, w: WorkerKey, plus: i32, max: i32);
+    fn worker_ability(&self, worker_id: WorkerKey, ability_type: AbilityType) -> Option<Ability>;
+    fn insert_ability(&self, a: &NewAbility);
+    fn update_ability_used_timestamp(&self, worker: WorkerKey, at: AbilityType);
+    fn task(&self, task_id: TaskKey) -> Option<Task>;
+    fn worker_tasks(&self, worker_id: WorkerKey) -> Vec<Task>;
+    fn past_worker_tasks(&self, worker_id: WorkerKey) -> Vec<Task>;
+    fn current_task(&self, worker_id: WorkerKey) -> Option<Task>;
+    fn earliest_future_task(&self, worker_id: WorkerKey) -> Option<Task>;
+    fn current_and_next_task(&self, worker_id: WorkerKey) -> (Option<Task>, Option<Task>);
+    fn insert_tasks(&self, tasks: &[NewTask]) -> Vec<Task>;
+    fn update_task(&self, t: &Task);
+    fn delete_task(&self, task: &Task);
+    fn flush_task_queue(&self, worker_id: WorkerKey);
+    fn worker_routine(&self, worker_id: WorkerKey) -> Option<WorkerRoutine>;
+    fn routine_tasks(&self, worker_id: WorkerKey) -> Vec<RoutineTask>;
+    fn insert_worker_routine(
+        &self,
+        routine: &WorkerRoutine,
+        steps: &[RoutineTask],
+    ) -> QueryResult<()>;
+    fn update_worker_routine(&self, routine: &WorkerRoutine);
+    fn delete_worker_routine(&self, worker_id: WorkerKey);
+
+    // Visitors and attacks
+    fn hobo(&self, hobo_id: HoboKey) -> Option<Hobo>;
+    fn hobos(&self, village: VillageKey) -> Vec<Hobo>;
+    fn insert_hobo(&self, u: &NewHobo) -> Hobo;
+    fn update_hobo(&self, hobo: &Hobo);
+    fn delete_hobo(&self, hobo: &Hobo);
+    fn effects_on_hobo(&self, hobo: HoboKey) -> Vec<Effect>;
+    fn delete_effects_on_hobo(&self, hobo: HoboKey);
+    fn insert_effect(&self, e: &NewEffect) -> Effect;
+    fn attacks(&self, village: VillageKey, min_id: Option<i64>) -> Vec<Attack>;
+    fn attack_hobos(&self, atk: AttackKey) -> Vec<Hobo>;
+    fn attack_hobos_with_attack_info(&self, atk: &Attack) -> Vec<(Hobo, AttackToHobo)>;
+    fn attack_hobos_active_with_attack_info(&self, atk: &Attack) -> Vec<(Hobo, AttackToHobo)>;
+    fn attack_hobos_satisfied(&self, atk: &Attack) -> Vec<Hobo>;
+    fn attack_done(&self, atk: &Attack) -> bool {
+        self.attack_hobos_active_with_attack_info(atk).is_empty()
+    }
+    fn resting_visitors(&self, village_id: VillageKey) -> Vec<(Hobo, AttackKey)>;
+    fn hobo_attack_info(&self, hid: HoboKey) -> Vec<(Attack, AttackToHobo)>;
+    fn insert_attack(&self, new_attack: &NewAttack) -> Attack;
+    fn insert_attack_to_hobo(&self, atu: &AttackToHobo);
+    fn set_satisfied(&self, hid: HoboKey, aid: AttackKey, satisfied: bool);
+    fn release_resting_visitor(&self, hid: HoboKey, aid: AttackKey);
+    /// Deletes the visitors of an attack, except those that have settled in a nest
+    fn delete_attack_hobos(&self, atk: AttackKey);
+    fn delete_attack(&self, atk: &Attack);
+
+    // Visit reports
+    fn reports(&self, v: VillageKey, min_id: Option<i64>) -> Vec<VisitReport>;
+    fn rewards(&self, vr: VisitReportKey) -> Vec<(ResourceType, i64)>;
+    fn insert_visit_report(&self, vr: NewVisitReport) -> VisitReport;
+    fn insert_visit_report_rewards(&self, rewards: Vec<NewReward>);
+    fn delete_visit_report(&self, obj: &VisitReport);
+
+    // Visit replays
+    fn visit_replays(&self, v: VillageKey) -> Vec<VisitReplay>;
+    fn insert_visit_replay(&self, vr: &NewVisitReplay) -> VisitReplay;
+    fn insert_replay_hobo(&self, hobo: &NewReplayHobo) -> ReplayHobo;
+    fn insert_replay_effects(&self, effects: Vec<NewReplayEffect>);
+    fn insert_replay_buildings(&self, buildings: Vec<NewReplayBuilding>);
+    fn delete_visit_replay(&self, obj: &VisitReplay);
+
+    // Quests
+    fn completed_quests(&self, p: PlayerKey) -> Vec<QuestName>;
+    fn quest_progress(&self, player: &Player) -> QuestProgress;
+    fn insert_completed_quest(&self, p: PlayerKey, quest: QuestName) -> QueryResult<()>;
+
+    // Messages
+    fn message(&self, id: MessageKey) -> Option<PlayerMessage>;
+    fn messages_sent_since(&self, sender: PlayerKey, since: NaiveDateTime) -> i64;
+    fn insert_message(&self, msg: &NewPlayerMessage) -> PlayerMessage;
+    fn mark_message_read(&self, id: MessageKey);
+}
+
+impl GameStore for DB {
+    fn now(&self) -> NaiveDateTime {
+        paddlers_shared_lib::game_clock::now()
+    }
+    fn transaction<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce() -> Result<T, String>,
+    {
+        // The error of `f` is kept aside, diesel only needs to know that it should roll back
+        let mut err = None;
+        self.dbconn()
+            .transaction(|| {
+                f().map_err(|e| {
+                    err = Some(e);
+                    diesel::result::Error::RollbackTransaction
+                })
+            })
+            .map_err(|e| err.take().unwrap_or_else(|| e.to_string()))
+    }
+    fn player_by_village(&self, vid: VillageKey) -> Option<Player> {
+        GameDB::player_by_village(self, vid)
+    }
+    fn village(&self, village: VillageKey) -> Option<Village> {
+        GameDB::village(self, village)
+    }
+    fn player_villages(&self, p: PlayerKey) -> Vec<Village> {
+        GameDB::player_villages(self, p)
+    }
+    fn all_player_villages(&self) -> Vec<Village> {
+        GameDB::all_player_villages(self)
+    }
+    fn add_karma(&self, p: PlayerKey, plus: i64) -> QueryResult<()> {
+        DB::add_karma(self, p, plus).map(|_| ())
+    }
+    fn add_season_stats(
+        &self,
+        p: PlayerKey,
+        karma: i64,
+        visitors_welcomed: i64,
+    ) -> QueryResult<()> {
+        DB::add_season_stats(self, p, karma, visitors_welcomed).map(|_| ())
+    }
+    fn resource(&self, r: ResourceType, v: VillageKey) -> i64 {
+        GameDB::resource(self, r, v)
+    }
+    fn add_resource(&self, rt: ResourceType, vk: VillageKey, plus: i64) -> QueryResult<()> {
+        DB::add_resource(self, rt, vk, plus).map(|_| ())
+    }
+    fn buildings(&self, village: VillageKey) -> Vec<Building> {
+        GameDB::buildings(self, village)
+    }
+    fn find_building_by_coordinates(
+        &self,
+        x: i32,
+        y: i32,
+        village: VillageKey,
+    ) -> Option<Building> {
+        GameDB::find_building_by_coordinates(self, x, y, village)
+    }
+    fn insert_building(&self, new_building: &NewBuilding) -> Building {
+        DB::insert_building(self, new_building)
+    }
+    fn delete_building(&self, building: &Building) {
+        DB::delete_building(self, building)
+    }
+    fn update_building_creation(&self, building: &Building, creation: NaiveDateTime) {
+        DB::update_building_creation(self, building, creation)
+    }
+    fn worker_priv(&self, worker_id: WorkerKey) -> Option<Worker> {
+        GameDB::worker_priv(self, worker_id)
+    }
+    fn workers(&self, village: VillageKey) -> Vec<Worker> {
+        GameDB::workers(self, village)
+    }
+    fn insert_worker(&self, u: &NewWorker) -> Worker {
+        DB::insert_worker(self, u)
+    }
+    fn workers_with_job(&self, village: VillageKey, jobs: &[TaskType]) -> Vec<Worker> {
+        GameDB::workers_with_job(self, village, jobs)
+    }
+    fn count_workers_at_pos_doing_job(
+        &self,
+        village: VillageKey,
+        x: i32,
+        y: i32,
+        job: TaskType,
+    ) -> usize {
+        GameDB::count_workers_at_pos_doing_job(self, village, x, y, job)
+    }
+    fn update_worker(&self, u: &Worker) {
+        DB::update_worker(self, u)
+    }
+    fn insert_worker_flag(&self, wf: WorkerFlag) {
+        DB::insert_worker_flag(self, wf)
+    }
+    fn update_worker_flag_timestamp_now(&self, w: WorkerKey, f: WorkerFlagType) {
+        DB::update_worker_flag_timestamp_now(self, w, f)
+    }
+    fn update_worker_flag_timestamp(&self, w: WorkerKey, f: WorkerFlagType, ts: NaiveDateTime) {
+        DB::update_worker_flag_timestamp(self, w, f, ts)
+    }
+    fn worker_flags(&self, worker: WorkerKey) -> Vec<WorkerFlag> {
+        DB::worker_flags(self, worker)
+    }
+    fn add_worker_mana(&self, w: WorkerKey, plus: i32, max: i32) {
+        DB::add_worker_mana(self, w, plus, max)
+    }
+    fn worker_ability(&self, worker_id: WorkerKey, ability_type: AbilityType) -> Option<Ability> {
+        GameDB::worker_ability(self, worker_id, ability_type)
+    }
+    fn insert_ability(&self, a: &NewAbility) {
+        DB::insert_ability(self, a);
+    }
+    fn update_ability_used_timestamp(&self, worker: WorkerKey, at: AbilityType) {
+        DB::update_ability_used_timestamp(self, worker, at)
+    }
+    fn task(&self, task_id: TaskKey) -> Option<Task> {
+        GameDB::task(self, task_id)
+    }
+    fn worker_tasks(&self, worker_id: WorkerKey) -> Vec<Task> {
+        GameDB::worker_tasks(self, worker_id)
+    }
+    fn past_worker_tasks(&self, worker_id: WorkerKey) -> Vec<Task> {
+        GameDB::past_worker_tasks(self, worker_id)
+    }
+    fn current_task(&self, worker_id: WorkerKey) -> Option<Task> {
+        GameDB::current_task(self, worker_id)
+    }
+    fn earliest_future_task(&self, worker_id: WorkerKey) -> Option<Task> {
+        GameDB::earliest_future_task(self, worker_id)
+    }
+    fn current_and_next_task(&self, worker_id: WorkerKey) -> (Option<Task>, Option<Task>) {
+        GameDB::current_and_next_task(self, worker_id)
+    }
+    fn insert_tasks(&self, tasks: &[NewTask]) -> Vec<Task> {
+        DB::insert_tasks(self, tasks)
+    }
+    fn update_task(&self, t: &Task) {
+        DB::update_task(self, t)
+    }
+    fn delete_task(&self, task: &Task) {
+        DB::delete_task(self, task)
+    }
+    fn flush_task_queue(&self, worker_id: WorkerKey) {
+        DB::flush_task_queue(self, worker_id)
+    }
+    fn worker_routine(&self, worker_id: WorkerKey) -> Option<WorkerRoutine> {
+        GameDB::worker_routine(self, worker_id)
+    }
+    fn routine_tasks(&self, worker_id: WorkerKey) -> Vec<RoutineTask> {
+        GameDB::routine_tasks(self, worker_id)
+    }
+    fn insert_worker_routine(
+        &self,
+        routine: &WorkerRoutine,
+        steps: &[RoutineTask],
+    ) -> QueryResult<()> {
+        DB::insert_worker_routine(self, routine, steps)
+    }
+    fn update_worker_routine(&self, routine: &WorkerRoutine) {
+        DB::update_worker_routine(self, routine)
+    }
+    fn delete_worker_routine(&self, worker_id: WorkerKey) {
+        DB::delete_worker_routine(self, worker_id)
+    }
+    fn hobo(&self, hobo_id: HoboKey) -> Option<Hobo> {
+        GameDB::hobo(self, hobo_id)
+    }
+    fn hobos(&self, village: VillageKey) -> Vec<Hobo> {
+        GameDB::hobos(self, village)
+    }
+    fn insert_hobo(&self, u: &NewHobo) -> Hobo {
+        DB::insert_hobo(self, u)
+    }
+    fn update_hobo(&self, hobo: &Hobo) {
+        DB::update_hobo(self, hobo)
+    }
+    fn delete_hobo(&self, hobo: &Hobo) {
+        DB::delete_hobo(self, hobo)
+    }
+    fn effects_on_hobo(&self, hobo: HoboKey) -> Vec<Effect> {
+        GameDB::effects_on_hobo(self, hobo)
+    }
+    fn delete_effects_on_hobo(&self, hobo: HoboKey) {
+        DB::delete_effects_on_hobo(self, hobo)
+    }
+    fn insert_effect(&self, e: &NewEffect) -> Effect {
+        DB::insert_effect(self, e)
+    }
+    fn attacks(&self, village: VillageKey, min_id: Option<i64>) -> Vec<Attack> {
+        GameDB::attacks(self, village, min_id)
+    }
+    fn attack_hobos(&self, atk: AttackKey) -> Vec<Hobo> {
+        GameDB::attack_hobos(self, atk)
+    }
+    fn attack_hobos_with_attack_info(&self, atk: &Attack) -> Vec<(Hobo, AttackToHobo)> {
+        GameDB::attack_hobos_with_attack_info(self, atk)
+    }
+    fn attack_hobos_active_with_attack_info(&self, atk: &Attack) -> Vec<(Hobo, AttackToHobo)> {
+        GameDB::attack_hobos_active_with_attack_info(self, atk)
+    }
+    fn attack_hobos_satisfied(&self, atk: &Attack) -> Vec<Hobo> {
+        GameDB::attack_hobos_satisfied(self, atk)
+    }
+    fn resting_visitors(&self, village_id: VillageKey) -> Vec<(Hobo, AttackKey)> {
+        GameDB::resting_visitors(self, village_id)
+    }
+    fn hobo_attack_info(&self, hid: HoboKey) -> Vec<(Attack, AttackToHobo)> {
+        GameDB::hobo_attack_info(self, hid)
+    }
+    fn insert_attack(&self, new_attack: &NewAttack) -> Attack {
+        DB::insert_attack(self, new_attack)
+    }
+    fn insert_attack_to_hobo(&self, atu: &AttackToHobo) {
+        DB::insert_attack_to_hobo(self, atu)
+    }
+    fn set_satisfied(&self, hid: HoboKey, aid: AttackKey, satisfied: bool) {
+        DB::set_satisfied(self, hid, aid, satisfied)
+    }
+    fn release_resting_visitor(&self, hid: HoboKey, aid: AttackKey) {
+        DB::release_resting_visitor(self, hid, aid)
+    }
+    fn delete_attack_hobos(&self, atk: AttackKey) {
+        DB::delete_attack_hobos(self, atk)
+    }
+    fn delete_attack(&self, atk: &Attack) {
+        DB::delete_attack(self, atk)
+    }
+    fn reports(&self, v: VillageKey, min_id: Option<i64>) -> Vec<VisitReport> {
+        GameDB::reports(self, v, min_id)
+    }
+    fn rewards(&self, vr: VisitReportKey) -> Vec<(ResourceType, i64)> {
+        GameDB::rewards(self, vr)
+    }
+    fn insert_visit_report(&self, vr: NewVisitReport) -> VisitReport {
+        DB::insert_visit_report(self, vr)
+    }
+    fn insert_visit_report_rewards(&self, rewards: Vec<NewReward>) {
+        DB::insert_visit_report_rewards(self, rewards)
+    }
+    fn delete_visit_report(&self, obj: &VisitReport) {
+        DB::delete_visit_report(self, obj)
+    }
+    fn visit_replays(&self, v: VillageKey) -> Vec<VisitReplay> {
+        GameDB::visit_replays(self, v)
+    }
+    fn insert_visit_replay(&self, vr: &NewVisitReplay) -> VisitReplay {
+        DB::insert_visit_replay(self, vr)
+    }
+    fn insert_replay_hobo(&self, hobo: &NewReplayHobo) -> ReplayHobo {
+        DB::insert_replay_hobo(self, hobo)
+    }
+    fn insert_replay_effects(&self, effects: Vec<NewReplayEffect>) {
+        DB::insert_replay_effects(self, effects)
+    }
+    fn insert_replay_buildings(&self, buildings: Vec<NewReplayBuilding>) {
+        DB::insert_replay_buildings(self, buildings)
+    }
+    fn delete_visit_replay(&self, obj: &VisitReplay) {
+        DB::delete_visit_replay(self, obj)
+    }
+    fn completed_quests(&self, p: PlayerKey) -> Vec<QuestName> {
+        GameDB::completed_quests(self, p)
+    }
+    fn quest_progress(&self, player: &Player) -> QuestProgress {
+        GameDB::quest_progress(self, player)
+    }
+    fn insert_completed_quest(&self, p: PlayerKey, quest: QuestName) -> QueryResult<()> {
+        DB::insert_completed_quest(self, p, quest)
+    }
+    fn message(&self, id: MessageKey) -> Option<PlayerMessage> {
+        GameDB::message(self, id)
+    }
+    fn messages_sent_since(&self, sender: PlayerKey, since: NaiveDateTime) -> i64 {
+        GameDB::messages_sent_since(self, sender, since)
+    }
+    fn insert_message(&self, msg: &NewPlayerMessage) -> PlayerMessage {
+        DB::insert_message(self, msg)
+    }
+    fn mark_message_read(&self, id: MessageKey) {
+        DB::mark_message_read(self, id)
+    }
+}
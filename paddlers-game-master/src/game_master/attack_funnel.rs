@@ -7,12 +7,14 @@
 //!     - No hobo is involved in more than one attack at the time
 //!     - The maximum number of resting hobos is not surpassed
 
+use crate::db::store::GameStore;
 use crate::db::*;
 use crate::game_master::event::Event;
-use crate::game_master::town_defence::AttackingHobo;
+use crate::game_master::town_defence::{predict_satisfaction, AttackingHobo};
 use crate::game_master::town_worker::{TownWorker, TownWorkerEventMsg};
+use crate::town_view::TownView;
 use actix::prelude::*;
-use chrono::{offset::TimeZone, NaiveDateTime, Utc};
+use chrono::{offset::TimeZone, DateTime, NaiveDateTime, Utc};
 use paddlers_shared_lib::game_mechanics::{map::map_distance, town::defence::IAttackingHobo};
 use paddlers_shared_lib::prelude::*;
 use std::ops::Add;
@@ -48,73 +50,138 @@ impl Handler<PlannedAttack> for AttackFunnel {
     type Result = ();
 
     fn handle(&mut self, msg: PlannedAttack, _ctx: &mut Context<Self>) -> Self::Result {
-        let db = self.db();
-        let vid = msg.destination_village.key();
-
-        // TODO (Correctness): Somehow efficiently check that hobos are not attacking already
-        let unit_count = msg.hobos.len();
-        let unhurried = msg
-            .hobos
-            .iter()
-            .cloned()
-            .filter(|h| !h.hurried)
-            .collect::<Vec<_>>();
-        let hobos = msg.hobos.into_iter().map(|h| h.key()).collect();
-
-        let min_secs = 15;
-        let travel_time = if let Some(v0) = msg.origin_village {
-            let v1 = msg.destination_village;
-            let distance = map_distance((v0.x, v0.y), (v1.x, v1.y));
-            let seconds = 20.0 * distance;
-            chrono::Duration::seconds(min_secs + seconds as i64)
-        } else {
-            chrono::Duration::seconds(min_secs)
-        };
-        let now = chrono::Utc::now().naive_utc();
-        let earliest_arrival = now.add(travel_time);
-        let arrival = Self::next_timeslot(&db, vid, unit_count, earliest_arrival);
-        let attack = NewAttack {
-            departure: now,
-            arrival: arrival,
-            origin_village_id: msg.origin_village.map(|k| k.id),
-            destination_village_id: msg.destination_village.id,
-        };
+        let (scheduled, events) = schedule_attack(&self.db(), msg);
 
         // Put new attack in DB
-        let pa = ScheduledAttack { attack, hobos };
         self.db_actor
-            .try_send(DeferredDbStatement::NewAttack(pa))
+            .try_send(DeferredDbStatement::NewAttack(scheduled))
             .expect("Sending attack failed");
 
-        // Validate the resting queue the attack arrives, unless there is no unhurried hobo
-        if unhurried.len() > 0 {
-            let delayed_event = Event::CheckRestingVisitors {
-                village_id: msg.destination_village.key(),
-            };
-            let time = arrival;
+        for (event, time) in events {
             self.town_worker
-                .try_send(TownWorkerEventMsg(
-                    delayed_event,
-                    Utc.from_utc_datetime(&time),
-                ))
+                .try_send(TownWorkerEventMsg(event, time))
                 .expect("Sending event failed");
         }
-        // For all unhurried hobos, the hp should be checked when they reach the resting place
-        for hobo in unhurried {
+    }
+}
+
+/// Decides when a planned attack arrives and which events have to be triggered for it.
+pub(crate) fn schedule_attack<S: GameStore>(
+    db: &S,
+    msg: PlannedAttack,
+) -> (ScheduledAttack, Vec<(Event, DateTime<Utc>)>) {
+    let vid = msg.destination_village.key();
+
+    // TODO (Correctness): Somehow efficiently check that hobos are not attacking already
+    let unit_count = msg.hobos.len();
+    let unhurried = msg
+        .hobos
+        .iter()
+        .cloned()
+        .filter(|h| !h.hurried)
+        .collect::<Vec<_>>();
+
+    let min_secs = 15;
+    let travel_time = if let Some(v0) = msg.origin_village {
+        let v1 = msg.destination_village;
+        let distance = map_distance((v0.x, v0.y), (v1.x, v1.y));
+        let seconds = 20.0 * distance;
+        chrono::Duration::seconds(min_secs + seconds as i64)
+    } else {
+        chrono::Duration::seconds(min_secs)
+    };
+    let now = db.now();
+    let earliest_arrival = now.add(travel_time);
+    let arrival = next_timeslot(db, vid, unit_count, earliest_arrival);
+    let attack = NewAttack {
+        departure: now,
+        arrival: arrival,
+        origin_village_id: msg.origin_village.map(|k| k.id),
+        destination_village_id: msg.destination_village.id,
+    };
+
+    let mut events = vec![];
+    // Validate the resting queue the attack arrives, unless there is no unhurried hobo
+    if unhurried.len() > 0 {
+        let delayed_event = Event::CheckRestingVisitors { village_id: vid };
+        events.push((delayed_event, Utc.from_utc_datetime(&arrival)));
+    }
+    // For all unhurried hobos, the hp should be checked when they reach the resting place
+    for hobo in unhurried {
+        let delayed_event = Event::CheckVisitorHp {
+            hobo_id: hobo.key(),
+        };
+        let swim_time: chrono::Duration = AttackingHobo::s_time_until_resting(hobo.speed).into();
+        let event_time = arrival + swim_time;
+        events.push((delayed_event, Utc.from_utc_datetime(&event_time)));
+    }
+    // Hobos satisfied on their way into the town are checked at the predicted moment
+    let town = TownView::load_village(db, vid);
+    for hobo in &msg.hobos {
+        let effects = db.effects_on_hobo(hobo.key());
+        if let Some(event_time) = predict_satisfaction(&town, hobo, &effects, arrival) {
             let delayed_event = Event::CheckVisitorHp {
                 hobo_id: hobo.key(),
             };
-            let swim_time: chrono::Duration =
-                AttackingHobo::s_time_until_resting(hobo.speed).into();
-            let event_time = arrival + swim_time;
-            self.town_worker
-                .try_send(TownWorkerEventMsg(
-                    delayed_event,
-                    Utc.from_utc_datetime(&event_time),
-                ))
-                .expect("Sending event failed");
+            events.push((delayed_event, Utc.from_utc_datetime(&event_time)));
         }
     }
+    let hobos = msg.hobos.into_iter().map(|h| h.key()).collect();
+    (ScheduledAttack { attack, hobos }, events)
+}
+
+pub(crate) fn insert_scheduled_attack<S: GameStore>(db: &S, scheduled: &ScheduledAttack) {
+    let attack = db.insert_attack(&scheduled.attack);
+    for hobo in scheduled.hobos.iter() {
+        let atu = AttackToHobo {
+            attack_id: attack.id,
+            hobo_id: hobo.num(),
+            satisfied: None,
+            released: None,
+        };
+        db.insert_attack_to_hobo(&atu);
+    }
+}
+
+fn next_timeslot<S: GameStore>(
+    db: &S,
+    vid: VillageKey,
+    unit_count: usize,
+    mut earliest: NaiveDateTime,
+) -> NaiveDateTime {
+    // TODO (Optimization): These are potentially many DB queries
+
+    // Query returns attacks sorted by arrival date
+    let already_running_attacks = db.attacks(vid, None);
+    let duration = attack_duration(unit_count);
+    let mut i = 0;
+    let len = already_running_attacks.len();
+    while i < len {
+        let atk = &already_running_attacks[i];
+        let n = db.attack_hobos(atk.key()).len();
+        let d = attack_duration(n);
+        if atk.arrival + d <= earliest {
+            // No conflict with i, i is earlier than new attack
+            i += 1;
+        } else if atk.arrival < earliest + duration {
+            // Conflict with i, need to delay new attack to be after i
+            i += 1;
+            earliest = atk.arrival + d;
+        } else {
+            // No overlap and i is entirely afterwards
+            //  => thanks to sorted input we can stop here
+            break;
+        }
+    }
+    earliest
+}
+
+pub(crate) fn attack_duration(units: usize) -> chrono::Duration {
+    // Assumptions:
+    //  A) ~0.2 speed <=> 5s per tile
+    //  B) Two units parallel to each other
+    let seconds = (units + 1) * 5 / 2;
+    chrono::Duration::seconds(seconds as i64)
 }
 
 impl AttackFunnel {
@@ -128,43 +195,4 @@ impl AttackFunnel {
     fn db(&self) -> DB {
         (&self.dbpool).into()
     }
-    fn next_timeslot(
-        db: &DB,
-        vid: VillageKey,
-        unit_count: usize,
-        mut earliest: NaiveDateTime,
-    ) -> NaiveDateTime {
-        // TODO (Optimization): These are potentially many DB queries
-
-        // Query returns attacks sorted by arrival date
-        let already_running_attacks = db.attacks(vid, None);
-        let duration = Self::attack_duration(unit_count);
-        let mut i = 0;
-        let len = already_running_attacks.len();
-        while i < len {
-            let atk = &already_running_attacks[i];
-            let n = db.attack_hobos(atk.key()).len();
-            let d = Self::attack_duration(n);
-            if atk.arrival + d <= earliest {
-                // No conflict with i, i is earlier than new attack
-                i += 1;
-            } else if atk.arrival < earliest + duration {
-                // Conflict with i, need to delay new attack to be after i
-                i += 1;
-                earliest = atk.arrival + d;
-            } else {
-                // No overlap and i is entirely afterwards
-                //  => thanks to sorted input we can stop here
-                break;
-            }
-        }
-        earliest
-    }
-    fn attack_duration(units: usize) -> chrono::Duration {
-        // Assumptions:
-        //  A) ~0.2 speed <=> 5s per tile
-        //  B) Two units parallel to each other
-        let seconds = (units + 1) * 5 / 2;
-        chrono::Duration::seconds(seconds as i64)
-    }
 }
@@ -4,7 +4,9 @@ use crate::db::*;
 use crate::game_master::attack_funnel::{AttackFunnel, PlannedAttack};
 use actix::prelude::*;
 use futures::future::join_all;
-use paddlers_shared_lib::game_mechanics::hobos::HoboLevel;
+use paddlers_shared_lib::game_mechanics::hobos::{
+    HoboLevel, HURRIED_HOBO_SPEED, UNHURRIED_HOBO_SPEED,
+};
 use paddlers_shared_lib::prelude::*;
 use rand::Rng;
 
@@ -70,7 +72,7 @@ impl AttackSpawner {
                 let hobo = NewHobo {
                     color: Some(Self::gen_color(&mut rng)),
                     hp: rng.gen_range(min_hp, max_hp),
-                    speed: 0.0625,
+                    speed: HURRIED_HOBO_SPEED,
                     home: village.num(), // TODO: anarchists home
                     hurried: true,
                     nest: None,
@@ -84,7 +86,7 @@ impl AttackSpawner {
             let hobo = NewHobo {
                 color: Some(Self::gen_color(&mut rng)),
                 hp: level.unhurried_anarchist_hp(),
-                speed: 0.25,
+                speed: UNHURRIED_HOBO_SPEED,
                 home: village.num(), // TODO: anarchists home
                 hurried: false,
                 nest: None,
@@ -112,7 +114,8 @@ impl AttackSpawner {
         );
     }
 
-    fn gen_color<R>(rng: &mut R) -> UnitColor
+    /// Hired workers come in the same colors as visitors, with the same odds
+    pub(crate) fn gen_color<R>(rng: &mut R) -> UnitColor
     where
         R: Rng,
     {
@@ -1,16 +1,30 @@
+use crate::db::store::GameStore;
 use crate::db::*;
+use crate::game_master::forestry::{chop_forest, remove_dead_trees};
+use crate::game_master::nests::collect_nest_production;
 use actix::prelude::*;
+use chrono::NaiveDateTime;
+use paddlers_shared_lib::activity::ActivityLevel;
+use paddlers_shared_lib::game_clock;
 use paddlers_shared_lib::game_mechanics::worker::*;
 use paddlers_shared_lib::prelude::*;
 
-/// Actor for calculating gathered regular events on workers (resource collection, mana regeneration)
+/// Actor for calculating gathered regular events on workers (resource collection, mana regeneration), on settled hobos and on the forest
 pub struct EconomyWorker {
     dbpool: Pool,
+    tick: u32,
 }
 
+/// Villages of inactive players are only updated every n-th tick.
+/// Nothing is lost by that, the next update catches up on the time in between.
+const INACTIVE_TICK_DIVISOR: u32 = 12;
+
 impl EconomyWorker {
     pub fn new(dbpool: Pool) -> Self {
-        EconomyWorker { dbpool: dbpool }
+        EconomyWorker {
+            dbpool: dbpool,
+            tick: 0,
+        }
     }
     fn db(&self) -> DB {
         (&self.dbpool).into()
@@ -18,55 +32,72 @@ impl EconomyWorker {
     fn work(&mut self, ctx: &mut Context<Self>) {
         let db = &self.db();
 
-        for village in db.all_player_villages() {
+        self.tick = self.tick.wrapping_add(1);
+        let update_inactive = self.tick % INACTIVE_TICK_DIVISOR == 0;
+
+        for (village, last_active) in db.player_villages_with_activity() {
             let village_id = village.key();
-            let workers = db.workers(village_id);
-            let now = chrono::Utc::now().naive_utc();
-            for w in workers {
-                for flag in db.worker_flags(w.key()) {
-                    match flag.flag_type {
-                        WorkerFlagType::ManaRegeneration => {
-                            let mana_regen = hero_mana_regeneration_per_hour();
-                            let interval_ms = 3_600_000 / mana_regen as i64;
-                            let new_mana =
-                                (now - flag.last_update).num_milliseconds() / interval_ms;
-                            if new_mana > 0 {
-                                let new_time = flag.last_update
-                                    + chrono::Duration::milliseconds(interval_ms * new_mana);
-                                db.update_worker_flag_timestamp(
-                                    w.key(),
-                                    WorkerFlagType::ManaRegeneration,
-                                    new_time,
-                                );
-                                db.add_worker_mana(w.key(), new_mana as i32, hero_max_mana());
-                            }
-                        }
-                        WorkerFlagType::Work => {
-                            let task = db.current_task(w.key()).expect("Must have a job");
-                            if let Some((res, rate)) =
-                                hero_resource_collection_per_hour(task.task_type)
-                            {
-                                let interval_ms = 3_600_000 / rate as i64;
-                                let n = (now - flag.last_update).num_milliseconds() / interval_ms;
-                                if n > 0 {
-                                    let new_time = flag.last_update
-                                        + chrono::Duration::milliseconds(interval_ms * n);
-                                    db.update_worker_flag_timestamp(
-                                        w.key(),
-                                        WorkerFlagType::Work,
-                                        new_time,
-                                    );
-                                    db.add_resource(res, village_id, n)
-                                        .expect("Adding resources");
+            let now = game_clock::now();
+            if !update_inactive && ActivityLevel::of(last_active, now) != ActivityLevel::Active {
+                continue;
+            }
+            collect_nest_production(db, village_id);
+            remove_dead_trees(db, village_id);
+            update_workers(db, village_id, now);
+        }
+
+        ctx.run_later(std::time::Duration::from_millis(5000), Self::work);
+    }
+}
+
+/// Regenerates the mana of the workers in a village and collects the resources they gathered until `now`
+pub(crate) fn update_workers<S: GameStore>(db: &S, village_id: VillageKey, now: NaiveDateTime) {
+    for w in db.workers(village_id) {
+        for flag in db.worker_flags(w.key()) {
+            match flag.flag_type {
+                WorkerFlagType::ManaRegeneration => {
+                    let mana_regen = hero_mana_regeneration_per_hour();
+                    let interval_ms = 3_600_000 / mana_regen as i64;
+                    let new_mana = (now - flag.last_update).num_milliseconds() / interval_ms;
+                    if new_mana > 0 {
+                        let new_time = flag.last_update
+                            + chrono::Duration::milliseconds(interval_ms * new_mana);
+                        db.update_worker_flag_timestamp(
+                            w.key(),
+                            WorkerFlagType::ManaRegeneration,
+                            new_time,
+                        );
+                        db.add_worker_mana(w.key(), new_mana as i32, hero_max_mana());
+                    }
+                }
+                WorkerFlagType::Work => {
+                    let task = db.current_task(w.key()).expect("Must have a job");
+                    if let Some((res, rate)) = hero_resource_collection_per_hour(task.task_type) {
+                        let rate = rate * worker_color_stats(w.color).collection_factor;
+                        let interval_ms = 3_600_000 / rate as i64;
+                        let n = (now - flag.last_update).num_milliseconds() / interval_ms;
+                        if n > 0 {
+                            let new_time =
+                                flag.last_update + chrono::Duration::milliseconds(interval_ms * n);
+                            db.update_worker_flag_timestamp(
+                                w.key(),
+                                WorkerFlagType::Work,
+                                new_time,
+                            );
+                            // Logs are taken from the trees, as long as there are any
+                            let n = match task.task_type {
+                                TaskType::ChopTree => {
+                                    chop_forest(db, village_id, n as usize) as i64
                                 }
-                            }
+                                _ => n,
+                            };
+                            db.add_resource(res, village_id, n)
+                                .expect("Adding resources");
                         }
                     }
                 }
             }
         }
-
-        ctx.run_later(std::time::Duration::from_millis(5000), Self::work);
     }
 }
 
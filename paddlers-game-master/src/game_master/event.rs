@@ -1,5 +1,6 @@
-use crate::db::*;
-use crate::worker_actions::finish_task;
+use super::town_defence::maybe_evaluate_attack;
+use crate::db::store::GameStore;
+use crate::worker_actions::{continue_routine, finish_task};
 use chrono::prelude::*;
 use paddlers_shared_lib::game_mechanics::town::MAX_VISITOR_QUEUE;
 use paddlers_shared_lib::prelude::*;
@@ -9,22 +10,23 @@ use paddlers_shared_lib::prelude::*;
 /// These events can be queued up in the `EventQueue`
 pub enum Event {
     WorkerTask { task_id: TaskKey },
+    WorkerRoutine { worker_id: WorkerKey },
     CheckRestingVisitors { village_id: VillageKey },
     CheckVisitorHp { hobo_id: HoboKey },
 }
 
 impl Event {
-    pub(super) fn run(&self, db: &DB) -> Option<(Event, DateTime<Utc>)> {
+    /// Performs the action of the event and returns the events that follow from it
+    pub(crate) fn run<S: GameStore>(&self, db: &S) -> Vec<(Event, DateTime<Utc>)> {
         match self {
-            Self::WorkerTask { task_id } => {
-                let res = finish_task(db, *task_id, None, None);
-                if let Err(e) = res {
+            Self::WorkerTask { task_id } => match finish_task(db, *task_id, None, None) {
+                Ok(next) => next.into_iter().collect(),
+                Err(e) => {
                     println!("Task execution failed: {}", e);
-                    None
-                } else {
-                    res.unwrap()
+                    vec![]
                 }
-            }
+            },
+            Self::WorkerRoutine { worker_id } => continue_routine(db, *worker_id),
             Self::CheckRestingVisitors { village_id } => {
                 // Release all visitors that are queued beyond the limit
                 let visitors = db.resting_visitors(*village_id);
@@ -33,20 +35,20 @@ impl Event {
                         db.release_resting_visitor(hobo.key(), *attack_id)
                     }
                 }
-                None
+                vec![]
             }
             Self::CheckVisitorHp { hobo_id } => {
-                let now = chrono::Utc::now().naive_utc();
+                let now = db.now();
                 for (atk, _info) in db.hobo_attack_info(*hobo_id) {
                     // Performance: Checking the entire attack is a bit of an overkill (was the easiest to implement without code duplication)
-                    db.maybe_evaluate_attack(&atk, now);
+                    maybe_evaluate_attack(db, &atk, now);
                 }
-                None
+                vec![]
             }
         }
     }
-    pub(crate) fn load_next_worker_task(
-        db: &DB,
+    pub(crate) fn load_next_worker_task<S: GameStore>(
+        db: &S,
         worker_id: WorkerKey,
     ) -> Option<(Self, DateTime<Utc>)> {
         let (current, next) = db.current_and_next_task(worker_id);
@@ -60,4 +62,15 @@ impl Event {
             )
         })
     }
+    pub(crate) fn load_worker_routine<S: GameStore>(
+        db: &S,
+        worker_id: WorkerKey,
+    ) -> Option<(Self, DateTime<Utc>)> {
+        db.worker_routine(worker_id).map(|routine| {
+            (
+                Event::WorkerRoutine { worker_id },
+                Utc.from_utc_datetime(&routine.next_step_time),
+            )
+        })
+    }
 }
@@ -27,11 +27,11 @@ impl EventQueue {
             event: event,
         });
     }
-    /// Returns the next event in the queue if it is due
-    pub fn poll_event(&mut self) -> Option<Event> {
+    /// Returns the next event in the queue if it is due at the given time
+    pub fn poll_event(&mut self, now: DateTime<Utc>) -> Option<Event> {
         let next = self.queue.peek();
         if let Some(evt) = next {
-            if evt.time <= chrono::Utc::now() {
+            if evt.time <= now {
                 return self.queue.pop().map(|te| te.event);
             }
         }
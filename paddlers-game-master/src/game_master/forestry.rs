@@ -0,0 +1,45 @@
+//! Trees of a village change over time, not only by growing.
+//!
+//! Chopping takes size away from the trees, until they are gone completely.
+//! Trees that have reached the end of their lifespan die and are removed.
+
+use crate::db::store::GameStore;
+use paddlers_shared_lib::game_mechanics::forestry::Forest;
+use paddlers_shared_lib::prelude::*;
+
+/// Chops down up to `amount` of the forest size of a village and returns how much could be chopped
+pub(crate) fn chop_forest<S: GameStore>(db: &S, village: VillageKey, amount: usize) -> usize {
+    let (trees, mut forest) = load_forest(db, village);
+    let (chopped, changes) = forest.chop(amount);
+    for (i, new_age) in changes {
+        match new_age {
+            // A chopped tree is as big as if it had been planted later
+            Some(age) => db.update_building_creation(&trees[i], db.now() - age),
+            None => db.delete_building(&trees[i]),
+        }
+    }
+    chopped
+}
+
+/// Removes all trees of the village that have died of old age
+pub(crate) fn remove_dead_trees<S: GameStore>(db: &S, village: VillageKey) {
+    let (trees, forest) = load_forest(db, village);
+    for i in forest.dead_trees() {
+        db.delete_building(&trees[i]);
+    }
+}
+
+/// Trees of the village, which are referenced in the forest by their index
+fn load_forest<S: GameStore>(db: &S, village: VillageKey) -> (Vec<Building>, Forest<usize>) {
+    let now = db.now();
+    let trees: Vec<Building> = db
+        .buildings(village)
+        .into_iter()
+        .filter(|b| b.building_type == BuildingType::Tree)
+        .collect();
+    let mut forest = Forest::new();
+    for (i, tree) in trees.iter().enumerate() {
+        forest.add_tree(i, now - tree.creation);
+    }
+    (trees, forest)
+}
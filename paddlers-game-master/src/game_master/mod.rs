@@ -2,16 +2,19 @@ pub(super) mod attack_funnel;
 pub(super) mod attack_spawn;
 pub(super) mod economy_worker;
 pub(super) mod event;
-mod event_queue;
-mod town_defence;
+pub(super) mod event_queue;
+pub(super) mod forestry;
+pub(super) mod nests;
+pub(super) mod town_defence;
 pub(super) mod town_worker;
 
 use crate::db::*;
 use crate::game_master::attack_spawn::{AttackSpawner, SendAnarchistAttack};
 use actix::prelude::*;
 use chrono::NaiveDateTime;
+use paddlers_shared_lib::activity::ActivityLevel;
+use paddlers_shared_lib::game_clock;
 use paddlers_shared_lib::game_mechanics::hobos::HoboLevel;
-use paddlers_shared_lib::game_mechanics::town::TOWN_X;
 use paddlers_shared_lib::prelude::Player;
 use paddlers_shared_lib::prelude::VillageKey;
 use paddlers_shared_lib::sql::GameDB;
@@ -22,6 +25,7 @@ use std::time::Duration;
 
 pub struct GameMaster {
     last_attack: NaiveDateTime,
+    last_abandonment_check: NaiveDateTime,
     dbpool: Pool,
     attacker_addr: Addr<AttackSpawner>,
 }
@@ -29,6 +33,7 @@ impl GameMaster {
     pub fn new(dbpool: Pool, attacker_addr: &Addr<AttackSpawner>) -> Self {
         GameMaster {
             last_attack: NaiveDateTime::from_timestamp(0, 0),
+            last_abandonment_check: NaiveDateTime::from_timestamp(0, 0),
             dbpool: dbpool,
             attacker_addr: attacker_addr.clone(),
         }
@@ -53,14 +58,18 @@ impl GameMaster {
         // println!("Cycle");
 
         let db: DB = (&self.dbpool).into();
-        check_attacks(&db);
+        town_defence::evaluate_finished_attacks(&db);
 
-        let now = chrono::Utc::now().naive_utc();
+        let now = game_clock::now();
         if now - self.last_attack >= chrono::Duration::seconds(40) {
             self.last_attack = now;
             let mut rng = rand::thread_rng();
             let random_number = rng.next_u64() as usize;
-            for village in db.all_player_villages() {
+            for (village, last_active) in db.player_villages_with_activity() {
+                // Inactive players are left alone
+                if ActivityLevel::of(last_active, now) != ActivityLevel::Active {
+                    continue;
+                }
                 let vid = village.key();
                 let ongoing_attacks = db.attacks_count(vid, None);
 
@@ -79,6 +88,11 @@ impl GameMaster {
             }
         }
 
+        if now - self.last_abandonment_check >= chrono::Duration::hours(1) {
+            self.last_abandonment_check = now;
+            abandon_villages_of_inactive_players(&db, now);
+        }
+
         ctx.run_later(Duration::from_secs(1), Self::game_cycle);
     }
 }
@@ -105,14 +119,12 @@ fn repetitive_attack_strength(player: &Player) -> Option<HoboLevel> {
     }
 }
 
-// TODO: Efficiently check only required attacks
-fn check_attacks(db: &DB) {
-    for village in db.all_player_villages() {
-        let attacks = db.attacks(village.key(), None);
-        let now = chrono::Utc::now().naive_utc();
-        for atk in attacks.iter() {
-            if atk.arrival + chrono::Duration::seconds(2 * TOWN_X as i64) < now {
-                db.maybe_evaluate_attack(atk, now);
+fn abandon_villages_of_inactive_players(db: &DB, now: NaiveDateTime) {
+    for (village, last_active) in db.player_villages_with_activity() {
+        if ActivityLevel::of(last_active, now) == ActivityLevel::Abandoned {
+            match db.abandon_village(village.key()) {
+                Ok(()) => println!("Village {} has been abandoned", village.id),
+                Err(e) => eprintln!("Abandoning village {} failed: {}", village.id, e),
             }
         }
     }
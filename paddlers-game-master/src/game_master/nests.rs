@@ -0,0 +1,110 @@
+//! Nests are buildings in which hobos can settle down.
+//!
+//! Visitors that have been satisfied are invited to stay, as long as a nest of the visited village has space left.
+//! Settled hobos produce feathers for the village, until they are turned into prophets or sent away.
+
+use crate::db::store::GameStore;
+use crate::resource_system::try_spend;
+use crate::StringErr;
+use chrono::Duration;
+use paddlers_shared_lib::api::shop::Price;
+use paddlers_shared_lib::game_mechanics::hobos::settled_hobo_feathers_per_hour;
+use paddlers_shared_lib::prelude::*;
+
+/// Lets the satisfied visitors of a finished visit settle in the free nests of the visited village
+pub(crate) fn settle_visitors<S: GameStore>(db: &S, atk: &Attack) {
+    let village = atk.destination();
+    let now = db.now();
+    let mut free = free_nests(db, village).into_iter();
+    for mut hobo in db.attack_hobos_satisfied(atk) {
+        if hobo.color == Some(UnitColor::Prophet) {
+            continue;
+        }
+        let nest = match free.next() {
+            Some(nest) => nest,
+            None => break,
+        };
+        hobo.home = village.num();
+        hobo.nest = Some(nest);
+        hobo.hurried = false;
+        hobo.last_production = Some(now);
+        // Effects from the visit would otherwise follow the hobo on its next journey
+        db.delete_effects_on_hobo(hobo.key());
+        db.update_hobo(&hobo);
+    }
+}
+
+/// Adds the feathers that settled hobos have produced since the last collection
+pub(crate) fn collect_nest_production<S: GameStore>(db: &S, village: VillageKey) {
+    let now = db.now();
+    for mut hobo in db.hobos(village) {
+        let last = match (hobo.nest, hobo.last_production) {
+            (Some(_), Some(last)) => last,
+            _ => continue,
+        };
+        let rate = settled_hobo_feathers_per_hour(hobo.color.unwrap_or(UnitColor::Yellow));
+        let interval_ms = 3_600_000 / rate as i64;
+        let n = (now - last).num_milliseconds() / interval_ms;
+        if n > 0 {
+            hobo.last_production = Some(last + Duration::milliseconds(interval_ms * n));
+            db.update_hobo(&hobo);
+            if let Err(e) = db.add_resource(ResourceType::Feathers, village, n) {
+                eprintln!("Adding feathers from nest failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Sends a settled hobo away for good, which frees its place in the nest
+pub(crate) fn evict_hobo<S: GameStore>(db: &S, hobo: &Hobo) -> StringErr {
+    if hobo.nest.is_none() {
+        return Err("Hobo does not live in a nest".to_owned());
+    }
+    collect_nest_production(db, VillageKey(hobo.home));
+    db.delete_hobo(hobo);
+    Ok(())
+}
+
+/// Turns a settled hobo into a prophet of its village, which frees its place in the nest.
+/// The village pays the price of the prophet, nothing changes if it cannot afford it.
+pub(crate) fn ordain_hobo<S: GameStore>(db: &S, hobo: &Hobo, cost: &Price) -> StringErr {
+    if hobo.nest.is_none() {
+        return Err("Hobo does not live in a nest".to_owned());
+    }
+    let village = VillageKey(hobo.home);
+    db.transaction(|| {
+        collect_nest_production(db, village);
+        try_spend(db, cost, village)?;
+        let mut prophet = hobo.clone();
+        prophet.color = Some(UnitColor::Prophet);
+        prophet.hurried = true;
+        prophet.nest = None;
+        prophet.last_production = None;
+        db.update_hobo(&prophet);
+        Ok(())
+    })
+}
+
+/// Sends away all hobos living in the nest, which has to be done before the nest is deleted
+pub(crate) fn empty_nest<S: GameStore>(db: &S, nest: &Building) {
+    let village = VillageKey(nest.village_id);
+    collect_nest_production(db, village);
+    for hobo in db.hobos(village) {
+        if hobo.nest == Some(nest.id) {
+            db.delete_hobo(&hobo);
+        }
+    }
+}
+
+/// One entry per free place, nests with multiple free places appear repeatedly
+fn free_nests<S: GameStore>(db: &S, village: VillageKey) -> Vec<i64> {
+    let residents = db.hobos(village);
+    let mut free = vec![];
+    for b in db.buildings(village) {
+        let taken = residents.iter().filter(|h| h.nest == Some(b.id)).count();
+        for _ in taken..b.building_type.nest_capacity() {
+            free.push(b.id);
+        }
+    }
+    free
+}
@@ -2,9 +2,10 @@
 //!
 //! A fight report is generated as soon as all visitors have left or have been satisfied.
 //! Usually, the satisfaction of each visitor is only computed when time is up for an attack to be finished.
-//! But there are two exceptions.
-//!     1) When a player has an open browser window, the frontend can detect that a visitor is satisfied and then notify the server
+//! But there are some exceptions.
+//!     1) When an attack is planned, the moment each visitor will be satisfied is predicted and a check is scheduled for it
 //!     2) Units that wait in the town need to be checked regularly
+//!     3) When an ability is used on a visitor, it is checked right away
 //!
 //! Effects that must be taken into consideration:
 //!     * Defensive towers (flowers etc) which are only available by computing proximity
@@ -12,112 +13,230 @@
 //!
 //! When a unit is defeated or leaves otherwise, it still has to stick around in the database until all units of the group are done.
 //! This can be marked in the db using the status on each HoboToAttack.
+//!
+//! Before a finished visit is deleted, it is archived as a replay together with the buildings of the town.
+//! Only the latest few replays of each village are kept.
 
-use crate::db::DB;
+use crate::db::store::GameStore;
+use crate::game_master::nests::settle_visitors;
 use crate::town_view::TownView;
 use chrono::NaiveDateTime;
+use paddlers_shared_lib::game_mechanics::hobos::visitor_feathers;
 use paddlers_shared_lib::game_mechanics::town::*;
 use paddlers_shared_lib::prelude::*;
 
+/// Number of archived visits kept per village, older replays are deleted
+pub(crate) const MAX_REPLAYS_PER_VILLAGE: usize = 10;
+
 pub(crate) struct AttackingHobo<'a> {
     hobo: &'a Hobo,
-    attack_to_hobo: &'a AttackToHobo,
     effects: &'a [Effect],
-    attack: &'a Attack,
+    arrival: NaiveDateTime,
+    released: Option<NaiveDateTime>,
 }
 
-impl DB {
-    /// Checks if all visitors have already left (or been satisfied).
-    /// If so, the visit is evaluated and a report with rewards is generated.
-    pub fn maybe_evaluate_attack(&self, atk: &Attack, now: NaiveDateTime) {
-        let now: Timestamp = now.into();
-        let village = atk.destination();
-        let active_units = self.attack_hobos_active_with_attack_info(atk);
-        let town = TownView::load_village(&self, village);
-
-        for (hobo, info) in &active_units {
-            let effects = self.effects_on_hobo(hobo.key());
-            let unit = AttackingHobo {
-                hobo: hobo,
-                attack_to_hobo: info,
-                effects: &effects,
-                attack: atk,
-            };
-            if town.hp_left(&unit, now) == 0 {
-                self.set_satisfied(hobo.key(), atk.key(), true);
-                if !hobo.hurried && info.released.is_none() {
-                    self.release_resting_visitor(hobo.key(), atk.key());
-                }
-            } else if town.hobo_left_town(&unit, now) {
-                self.set_satisfied(hobo.key(), atk.key(), false);
+/// Evaluates all attacks that should be over by now, also if no event has been triggered for them.
+pub(crate) fn evaluate_finished_attacks<S: GameStore>(db: &S) {
+    let now = db.now();
+    for village in db.all_player_villages() {
+        for atk in db.attacks(village.key(), None).iter() {
+            if atk.arrival + chrono::Duration::seconds(2 * TOWN_X as i64) < now {
+                maybe_evaluate_attack(db, atk, now);
             }
         }
+    }
+}
 
-        // Check if all are satisfied or have left otherwise, then finish visit
-        if self.attack_done(atk) {
-            self.generate_report(atk);
-            if atk.origin_village_id.is_none() {
-                self.delete_attack_hobos(atk.key());
+/// Checks if all visitors have already left (or been satisfied).
+/// If so, the visit is evaluated and a report with rewards is generated.
+pub(crate) fn maybe_evaluate_attack<S: GameStore>(db: &S, atk: &Attack, now: NaiveDateTime) {
+    let now: Timestamp = now.into();
+    let village = atk.destination();
+    let active_units = db.attack_hobos_active_with_attack_info(atk);
+    let town = TownView::load_village(db, village);
+
+    for (hobo, info) in &active_units {
+        let effects = db.effects_on_hobo(hobo.key());
+        let unit = AttackingHobo {
+            hobo: hobo,
+            effects: &effects,
+            arrival: atk.arrival,
+            released: info.released,
+        };
+        if town.hp_left(&unit, now) == 0 {
+            db.set_satisfied(hobo.key(), atk.key(), true);
+            if !hobo.hurried && info.released.is_none() {
+                db.release_resting_visitor(hobo.key(), atk.key());
             }
-            self.delete_attack(atk);
+        } else if town.hobo_left_town(&unit, now) {
+            db.set_satisfied(hobo.key(), atk.key(), false);
         }
     }
 
-    fn generate_report(&self, atk: &Attack) {
-        let mut report = NewVisitReport {
-            village_id: atk.destination_village_id,
-            karma: 0,
-        };
-
-        let happy_hobos = self.attack_hobos_satisfied(atk);
-        report.karma = happy_hobos.len() as i64;
+    // Check if all are satisfied or have left otherwise, then finish visit
+    if db.attack_done(atk) {
+        generate_report(db, atk);
+        archive_visit(db, atk);
+        if atk.origin_village_id.is_none() {
+            settle_visitors(db, atk);
+            db.delete_attack_hobos(atk.key());
+        }
+        db.delete_attack(atk);
+    }
+}
 
-        use std::ops::Add;
-        let feathers = happy_hobos.iter().map(reward_feathers).fold(0, i64::add);
-        let sticks = happy_hobos.iter().map(reward_sticks).fold(0, i64::add);
-        let logs = happy_hobos.iter().map(reward_logs).fold(0, i64::add);
+/// Predicts when a visitor arriving at the given time will be satisfied, based on the current state of the town.
+pub(crate) fn predict_satisfaction(
+    town: &TownView,
+    hobo: &Hobo,
+    effects: &[Effect],
+    arrival: NaiveDateTime,
+) -> Option<NaiveDateTime> {
+    let unit = AttackingHobo {
+        hobo,
+        effects,
+        arrival,
+        released: None,
+    };
+    town.satisfaction_time(&unit).map(NaiveDateTime::from)
+}
 
-        if report.karma + feathers + sticks + logs == 0 {
-            return;
+/// Adds the rewards and karma of a report to the village and its owner, then deletes the report.
+pub(crate) fn collect_report_rewards<S: GameStore>(db: &S, report: &VisitReport) {
+    let village = report.village();
+    for (resource_type, n) in db.rewards(report.key()) {
+        if let Err(e) = db.add_resource(resource_type, village, n) {
+            eprintln!("Reward collection failed: {}", e);
+        }
+    }
+    if let Some(player) = db.player_by_village(village) {
+        if let Err(e) = db.add_karma(player.key(), report.karma) {
+            eprintln!("Karma reward collection failed: {}", e);
         }
+    }
+    db.delete_visit_report(report);
+}
 
-        let vr = self.insert_visit_report(report);
+fn generate_report<S: GameStore>(db: &S, atk: &Attack) {
+    let mut report = NewVisitReport {
+        village_id: atk.destination_village_id,
+        karma: 0,
+        reported: db.now(),
+    };
 
-        let mut rewards = vec![];
-        if feathers > 0 {
-            rewards.push(NewReward {
-                visit_report_id: vr.id,
-                resource_type: ResourceType::Feathers,
-                amount: feathers,
-            });
+    let happy_hobos = db.attack_hobos_satisfied(atk);
+    report.karma = happy_hobos.len() as i64;
+
+    if !happy_hobos.is_empty() {
+        if let Some(owner) = db.player_by_village(atk.destination()) {
+            let welcomed = happy_hobos.len() as i64;
+            if let Err(e) = db.add_season_stats(owner.key(), 0, welcomed) {
+                eprintln!("Failed to count welcomed visitors: {}", e);
+            }
         }
-        if sticks > 0 {
-            rewards.push(NewReward {
-                visit_report_id: vr.id,
-                resource_type: ResourceType::Sticks,
-                amount: sticks,
+    }
+
+    use std::ops::Add;
+    let feathers = happy_hobos.iter().map(reward_feathers).fold(0, i64::add);
+    let sticks = happy_hobos.iter().map(reward_sticks).fold(0, i64::add);
+    let logs = happy_hobos.iter().map(reward_logs).fold(0, i64::add);
+
+    if report.karma + feathers + sticks + logs == 0 {
+        return;
+    }
+
+    let vr = db.insert_visit_report(report);
+
+    let mut rewards = vec![];
+    if feathers > 0 {
+        rewards.push(NewReward {
+            visit_report_id: vr.id,
+            resource_type: ResourceType::Feathers,
+            amount: feathers,
+        });
+    }
+    if sticks > 0 {
+        rewards.push(NewReward {
+            visit_report_id: vr.id,
+            resource_type: ResourceType::Sticks,
+            amount: sticks,
+        });
+    }
+    if logs > 0 {
+        rewards.push(NewReward {
+            visit_report_id: vr.id,
+            resource_type: ResourceType::Logs,
+            amount: logs,
+        });
+    }
+    db.insert_visit_report_rewards(rewards);
+}
+
+/// Stores a copy of the visitors and the town, to let the player watch the visit again
+fn archive_visit<S: GameStore>(db: &S, atk: &Attack) {
+    // A replay is stored completely or not at all
+    let result = db.transaction(|| {
+        let village = atk.destination();
+        let replay = db.insert_visit_replay(&NewVisitReplay {
+            village_id: village.num(),
+            arrival: atk.arrival,
+            finished: db.now(),
+        });
+        for (hobo, info) in db.attack_hobos_with_attack_info(atk) {
+            let replay_hobo = db.insert_replay_hobo(&NewReplayHobo {
+                replay_id: replay.id,
+                color: hobo.color,
+                speed: hobo.speed,
+                hp: hobo.hp,
+                hurried: hobo.hurried,
+                released: info.released,
+                satisfied: info.satisfied.unwrap_or(false),
             });
+            let effects = db
+                .effects_on_hobo(hobo.key())
+                .into_iter()
+                .map(|e| NewReplayEffect {
+                    replay_hobo_id: replay_hobo.id,
+                    attribute: e.attribute,
+                    strength: e.strength,
+                    start_time: e.start_time,
+                })
+                .collect();
+            db.insert_replay_effects(effects);
         }
-        if logs > 0 {
-            rewards.push(NewReward {
-                visit_report_id: vr.id,
-                resource_type: ResourceType::Logs,
-                amount: logs,
-            });
+        let buildings = db
+            .buildings(village)
+            .into_iter()
+            .map(|b| NewReplayBuilding {
+                replay_id: replay.id,
+                x: b.x,
+                y: b.y,
+                building_type: b.building_type,
+                building_range: b.building_range,
+                attack_power: b.attack_power,
+                attacks_per_cycle: b.attacks_per_cycle,
+                creation: b.creation,
+            })
+            .collect();
+        db.insert_replay_buildings(buildings);
+
+        for old in db
+            .visit_replays(village)
+            .iter()
+            .skip(MAX_REPLAYS_PER_VILLAGE)
+        {
+            db.delete_visit_replay(old);
         }
-        self.insert_visit_report_rewards(rewards);
+        Ok(())
+    });
+    if let Err(e) = result {
+        eprintln!("Archiving visit failed: {}", e);
     }
 }
 
-/// TODO [0.1.5]
 fn reward_feathers(unit: &Hobo) -> i64 {
-    let f = if unit.hurried {
-        (1.0 + unit.hp as f32 * unit.speed / 4.0).log2().floor()
-    } else {
-        (1.0 + unit.hp as f32 / 16.0).log2().ceil()
-    };
-    // println!("Unit {:?} gives {} feathers", unit, f);
-    f as i64
+    let color = unit.color.unwrap_or(UnitColor::Yellow);
+    visitor_feathers(unit.hp, unit.speed, unit.hurried, color)
 }
 
 /// TODO [0.1.5]
@@ -149,10 +268,10 @@ impl<'a> IAttackingHobo for AttackingHobo<'a> {
         self.hobo.hurried
     }
     fn arrival(&self) -> Timestamp {
-        self.attack.arrival.into()
+        self.arrival.into()
     }
     fn released(&self) -> Option<Timestamp> {
-        self.attack_to_hobo.released.map(|t| t.into())
+        self.released.map(|t| t.into())
     }
     fn effects_strength(&self) -> i32 {
         self.effects
@@ -161,6 +280,9 @@ impl<'a> IAttackingHobo for AttackingHobo<'a> {
             .filter(|e| e.strength.is_some())
             .fold(0, |acc, e| acc + e.strength.unwrap() as i64) as i32
     }
+    fn color(&self) -> UnitColor {
+        self.hobo.color.unwrap_or(UnitColor::Yellow)
+    }
 }
 
 impl ITownLayoutMarker for TownView {
@@ -3,6 +3,7 @@ use super::event_queue::*;
 use crate::db::*;
 use actix::prelude::*;
 use chrono::prelude::*;
+use paddlers_shared_lib::game_clock;
 use paddlers_shared_lib::prelude::*;
 
 /// Actor for moving around workers inside the town
@@ -23,9 +24,11 @@ impl TownWorker {
         (&self.dbpool).into()
     }
     fn work(&mut self, ctx: &mut Context<Self>) {
-        while let Some(event) = self.event_queue.poll_event() {
-            let res = event.run(&self.db());
-            if let Some((next_event, time)) = res {
+        while let Some(event) = self
+            .event_queue
+            .poll_event(Utc.from_utc_datetime(&game_clock::now()))
+        {
+            for (next_event, time) in event.run(&self.db()) {
                 self.event_queue.add_event(next_event, time);
             }
         }
@@ -38,6 +41,9 @@ impl TownWorker {
                 if let Some((event, time)) = Event::load_next_worker_task(&db, unit.key()) {
                     self.event_queue.add_event(event, time);
                 }
+                if let Some((event, time)) = Event::load_worker_routine(&db, unit.key()) {
+                    self.event_queue.add_event(event, time);
+                }
             }
         }
         self
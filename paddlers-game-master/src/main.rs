@@ -7,32 +7,44 @@ mod authentication;
 mod buildings;
 mod db;
 mod game_master;
+mod quests;
 mod resource_system;
+mod scenes;
 mod setup;
+#[cfg(test)]
+mod simulation;
 mod statistics;
 mod town_view;
 mod worker_actions;
 
 use actix::prelude::*;
 use actix_cors::Cors;
+use actix_web::dev::Service;
 use actix_web::{http::header, web, App, HttpServer};
 use db::*;
+use futures::Future;
 use game_master::{
     attack_funnel::AttackFunnel, attack_spawn::AttackSpawner, economy_worker::EconomyWorker,
     town_worker::TownWorker, GameMaster,
 };
+use paddlers_shared_lib::api::admin;
+use paddlers_shared_lib::api::messages::{MessageSend, MessagesRead};
 use paddlers_shared_lib::api::reports::ReportCollect;
-use paddlers_shared_lib::api::story::StoryStateTransition;
-use paddlers_shared_lib::prelude::HoboKey;
+use paddlers_shared_lib::api::story::{QuestCollect, StoryStateTransition};
+use paddlers_shared_lib::game_clock::{self, GAME_TIME_HEADER};
 use paddlers_shared_lib::{
     api::{
         attacks::AttackDescriptor,
-        shop::{BuildingDeletion, BuildingPurchase, ProphetPurchase},
+        hobos::{HoboEviction, HoboOrdination},
+        shop::{
+            BlueprintPurchase, BuildingDeletion, BuildingPurchase, ProphetPurchase, WorkerPurchase,
+        },
         statistics::FrontendRuntimeStatistics,
-        tasks::TaskList,
+        tasks::{RoutineDefinition, TaskList},
     },
     config::Config,
 };
+use scenes::SceneCatalog;
 
 type StringErr = Result<(), String>;
 
@@ -53,8 +65,11 @@ fn main() {
     let conn: DB = (&dbpool.clone()).into();
     conn.db_scripts_by_env().expect("DB initialization failed.");
     println!("DB successfully migrated");
+    // Continue from where the clock has been advanced to before the restart
+    game_clock::sync_server_clock(&conn);
 
     let config = Config::from_env().unwrap_or(Config::default());
+    let scenes = SceneCatalog::load().expect("Loading story scenes failed");
     let origin = config.frontend_origin.clone();
     let base_url = config.game_master_service_name.clone();
 
@@ -80,12 +95,24 @@ fn main() {
             .wrap(
                 Cors::new()
                     .allowed_origin(&origin)
-                    .allowed_methods(vec!["POST"])
+                    .allowed_methods(vec!["GET", "POST"])
                     .allowed_headers(vec![header::AUTHORIZATION, header::ACCEPT])
                     .allowed_header(header::CONTENT_TYPE)
+                    .expose_headers(vec![GAME_TIME_HEADER])
                     .max_age(3600 * 24),
             )
             .wrap(actix_web::middleware::Logger::default())
+            .wrap_fn(|req, srv| {
+                // Every response tells the client the current game time, to keep its clock in sync
+                srv.call(req).map(|mut res| {
+                    let now = game_clock::server_clock_snapshot().game_time_us;
+                    res.headers_mut().insert(
+                        header::HeaderName::from_static(GAME_TIME_HEADER),
+                        now.into(),
+                    );
+                    res
+                })
+            })
             .data(ActorAddresses {
                 _game_master: gm_actor.clone(),
                 town_worker: town_worker_actor.clone(),
@@ -95,14 +122,21 @@ fn main() {
                 attack_funnel: attack_funnel.clone(),
             })
             .data(config.clone())
+            .data(scenes.clone())
             .data(dbpool.clone())
             .route("/", web::get().to(api::index))
+            .route("/clock", web::get().to(api::game_clock))
             .service(web::resource("/player/create").route(web::post().to(api::new_player)))
             .service(
                 web::resource("/shop/building")
                     .data(web::Json::<BuildingPurchase>)
                     .route(web::post().to(api::purchase_building)),
             )
+            .service(
+                web::resource("/shop/blueprint")
+                    .data(web::Json::<BlueprintPurchase>)
+                    .route(web::post().to(api::purchase_blueprint)),
+            )
             .service(
                 web::resource("/shop/building/delete")
                     .data(web::Json::<BuildingDeletion>)
@@ -113,31 +147,112 @@ fn main() {
                     .data(web::Json::<ProphetPurchase>)
                     .route(web::post().to_async(api::purchase_prophet)),
             )
+            .service(
+                web::resource("/shop/unit/worker")
+                    .data(web::Json::<WorkerPurchase>)
+                    .route(web::post().to(api::purchase_worker)),
+            )
+            .service(
+                web::resource("/hobo/evict")
+                    .data(web::Json::<HoboEviction>)
+                    .route(web::post().to(api::evict_hobo)),
+            )
+            .service(
+                web::resource("/hobo/ordain")
+                    .data(web::Json::<HoboOrdination>)
+                    .route(web::post().to(api::ordain_hobo)),
+            )
             .service(
                 web::resource("/worker/overwriteTasks")
                     .data(web::Json::<TaskList>)
                     .route(web::post().to(api::overwrite_tasks)),
             )
+            .service(
+                web::resource("/worker/routine")
+                    .data(web::Json::<RoutineDefinition>)
+                    .route(web::post().to(api::set_routine)),
+            )
             .service(
                 web::resource("/attacks/create")
                     .data(web::Json::<AttackDescriptor>)
                     .route(web::post().to_async(api::create_attack)),
             )
-            .service(
-                web::resource("/attacks/notifications/visitor_satisfied")
-                    .data(web::Json::<HoboKey>)
-                    .route(web::post().to(api::visitor_satisfied_notification)),
-            )
             .service(
                 web::resource("/report/collect")
                     .data(web::Json::<ReportCollect>)
                     .route(web::post().to_async(api::collect_report_rewards)),
             )
+            .service(
+                web::resource("/message/send")
+                    .data(web::Json::<MessageSend>)
+                    .route(web::post().to(api::send_message)),
+            )
+            .service(
+                web::resource("/message/read")
+                    .data(web::Json::<MessagesRead>)
+                    .route(web::post().to(api::mark_messages_read)),
+            )
+            .route("/story/scene/{scene}", web::get().to(api::story_scene))
+            .service(
+                web::resource("/quest/collect")
+                    .data(web::Json::<QuestCollect>)
+                    .route(web::post().to(api::collect_quest)),
+            )
             .service(
                 web::resource("/story/transition")
                     .data(web::Json::<StoryStateTransition>)
                     .route(web::post().to(api::story_transition)),
             )
+            .service(
+                web::resource("/admin/inspect/player")
+                    .data(web::Json::<admin::InspectPlayer>)
+                    .route(web::post().to(api::admin::inspect_player)),
+            )
+            .service(
+                web::resource("/admin/inspect/village")
+                    .data(web::Json::<admin::InspectVillage>)
+                    .route(web::post().to(api::admin::inspect_village)),
+            )
+            .service(
+                web::resource("/admin/grant/resources")
+                    .data(web::Json::<admin::GrantResources>)
+                    .route(web::post().to(api::admin::grant_resources)),
+            )
+            .service(
+                web::resource("/admin/grant/karma")
+                    .data(web::Json::<admin::GrantKarma>)
+                    .route(web::post().to(api::admin::grant_karma)),
+            )
+            .service(
+                web::resource("/admin/story")
+                    .data(web::Json::<admin::ForceStoryState>)
+                    .route(web::post().to(api::admin::force_story_state)),
+            )
+            .service(
+                web::resource("/admin/attack")
+                    .data(web::Json::<admin::SpawnAttack>)
+                    .route(web::post().to(api::admin::spawn_attack)),
+            )
+            .service(
+                web::resource("/admin/worker/flush")
+                    .data(web::Json::<admin::FlushTasks>)
+                    .route(web::post().to(api::admin::flush_tasks)),
+            )
+            .service(
+                web::resource("/admin/player/ban")
+                    .data(web::Json::<admin::BanPlayer>)
+                    .route(web::post().to(api::admin::ban_player)),
+            )
+            .service(
+                web::resource("/admin/player/rename")
+                    .data(web::Json::<admin::RenamePlayer>)
+                    .route(web::post().to(api::admin::rename_player)),
+            )
+            .service(
+                web::resource("/admin/clock/advance")
+                    .data(web::Json::<admin::AdvanceClock>)
+                    .route(web::post().to(api::admin::advance_clock)),
+            )
             .service(
                 web::resource("/stats")
                     .data(web::Json::<FrontendRuntimeStatistics>)
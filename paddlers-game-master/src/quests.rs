@@ -0,0 +1,118 @@
+//! Checks quest objectives and hands out quest rewards.
+//!
+//! The quests themselves are defined in `paddlers_shared_lib::story::quest`.
+
+use crate::api::shop::building_has_space;
+use crate::buildings::BuildingFactory;
+use crate::db::store::GameStore;
+use crate::town_view::TownView;
+use crate::StringErr;
+use paddlers_shared_lib::game_mechanics::town::{TOWN_X, TOWN_Y};
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::story::quest::{QuestName, QuestReward};
+
+/// Completes a quest and grants its rewards, if the player has fulfilled all objectives
+pub(crate) fn collect_quest<S: GameStore>(db: &S, player: &Player, quest: QuestName) -> StringErr {
+    let completed = db.completed_quests(player.key());
+    if !quest.is_available(player.story_state, &completed) {
+        return Err(format!("Quest {:?} is not available", quest));
+    }
+    if !quest.objectives_fulfilled(&db.quest_progress(player)) {
+        return Err(format!("Objectives of quest {:?} not fulfilled", quest));
+    }
+    let village = db
+        .player_villages(player.key())
+        .into_iter()
+        .next()
+        .ok_or("Player has no village".to_owned())?
+        .key();
+    let rewards = quest.definition().rewards;
+
+    // Presents need space in the village, check that before anything is granted
+    let presents: Vec<BuildingType> = rewards
+        .iter()
+        .filter_map(|r| match r {
+            QuestReward::Present(typ) => Some(*typ),
+            _ => None,
+        })
+        .collect();
+    let spots = free_spots(db, village, presents.len());
+    if spots.len() < presents.len() {
+        return Err("No space left in the village for presents".to_owned());
+    }
+
+    db.transaction(|| {
+        // Fails if the rewards have been collected already by a concurrent request
+        db.insert_completed_quest(player.key(), quest)
+            .map_err(|e| format!("Quest could not be completed: {}", e))?;
+
+        for (typ, pos) in presents.iter().zip(&spots) {
+            let mut building = BuildingFactory::new(*typ, *pos, village);
+            building.creation = db.now();
+            db.insert_building(&building);
+        }
+        for reward in rewards {
+            match reward {
+                QuestReward::Resources(rt, n) => db
+                    .add_resource(*rt, village, *n)
+                    .map_err(|e| format!("Granting resources failed: {}", e))?,
+                QuestReward::Karma(n) => db
+                    .add_karma(player.key(), *n)
+                    .map_err(|e| format!("Granting karma failed: {}", e))?,
+                QuestReward::Present(_) => { /* placed above */ }
+                QuestReward::Ability(ability_type) => {
+                    for worker in db.workers(village) {
+                        teach_ability(db, &worker, *ability_type);
+                    }
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Lets a worker use an ability, unless it knows it already.
+/// Workers without mana get an empty pool that regenerates, if the ability needs mana.
+pub(crate) fn teach_ability<S: GameStore>(db: &S, worker: &Worker, ability_type: AbilityType) {
+    if db.worker_ability(worker.key(), ability_type).is_some() {
+        return;
+    }
+    db.insert_ability(&NewAbility {
+        ability_type,
+        worker_id: worker.id,
+    });
+    if ability_type.mana_cost() > 0 && worker.mana.is_none() {
+        let mut worker = worker.clone();
+        worker.mana = Some(0);
+        db.update_worker(&worker);
+        db.insert_worker_flag(WorkerFlag {
+            worker_id: worker.id,
+            flag_type: WorkerFlagType::ManaRegeneration,
+            last_update: db.now(),
+        });
+    }
+}
+
+/// Finds positions in the village where a building could be placed, up to `n` of them
+fn free_spots<S: GameStore>(db: &S, village: VillageKey, n: usize) -> Vec<(usize, usize)> {
+    let town = TownView::load_village(db, village);
+    let mut spots = vec![];
+    for y in 0..TOWN_Y {
+        for x in 0..TOWN_X {
+            if spots.len() >= n {
+                return spots;
+            }
+            let buildable = town
+                .map
+                .tile_type((x, y))
+                .map_or(false, |t| t.is_buildable());
+            // Only tiles free of buildings need the more expensive check for units in the way.
+            // Presents are all of size 1x1, the exact type does not matter for the check.
+            if buildable && building_has_space(db, BuildingType::PresentA, (x, y), village).is_ok()
+            {
+                spots.push((x, y));
+            }
+        }
+    }
+    spots
+}
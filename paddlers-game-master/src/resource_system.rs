@@ -1,3 +1,4 @@
+use crate::db::store::GameStore;
 use crate::{db::DB, StringErr};
 use paddlers_shared_lib::{api::shop::*, prelude::*};
 
@@ -18,25 +19,25 @@ impl DB {
             }
         }
     }
+}
 
-    pub fn try_spend(&self, p: &Price, village: VillageKey) -> StringErr {
-        self.can_afford(p, village)?;
-        self.spend(p, village);
-        Ok(())
-    }
+pub(crate) fn try_spend<S: GameStore>(db: &S, p: &Price, village: VillageKey) -> StringErr {
+    can_afford(db, p, village)?;
+    spend(db, p, village);
+    Ok(())
+}
 
-    pub fn spend(&self, p: &Price, village: VillageKey) {
-        for (res, n) in p.0.iter() {
-            self.add_resource((*res).into(), village, -*n)
-                .expect("Unchecked spending resources");
-        }
+fn spend<S: GameStore>(db: &S, p: &Price, village: VillageKey) {
+    for (res, n) in p.0.iter() {
+        db.add_resource((*res).into(), village, -*n)
+            .expect("Unchecked spending resources");
     }
-    pub fn can_afford(&self, p: &Price, village: VillageKey) -> StringErr {
-        for (res, n) in p.0.iter() {
-            if self.resource((*res).into(), village) < *n {
-                return Err(format!("Not enough {}", res));
-            }
+}
+fn can_afford<S: GameStore>(db: &S, p: &Price, village: VillageKey) -> StringErr {
+    for (res, n) in p.0.iter() {
+        if db.resource((*res).into(), village) < *n {
+            return Err(format!("Not enough {}", res));
         }
-        Ok(())
     }
+    Ok(())
 }
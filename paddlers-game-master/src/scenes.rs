@@ -0,0 +1,53 @@
+//! Story scenes are defined in `scenes/*.json` and sent to the frontend on demand.
+//!
+//! To add a scene, add a variant to `SceneIndex` and include its file in `scene_source`.
+
+use paddlers_shared_lib::story::scene::{SceneDefinition, SceneIndex};
+use paddlers_shared_lib::strum::IntoEnumIterator;
+use std::collections::HashMap;
+
+#[derive(Clone)]
+pub(crate) struct SceneCatalog {
+    scenes: HashMap<SceneIndex, SceneDefinition>,
+}
+
+impl SceneCatalog {
+    /// Parses and validates all scenes
+    pub fn load() -> Result<Self, String> {
+        let mut scenes = HashMap::new();
+        for index in SceneIndex::iter() {
+            let scene: SceneDefinition = serde_json::from_str(scene_source(index))
+                .map_err(|e| format!("Scene {} could not be parsed: {}", index, e))?;
+            scene
+                .validate()
+                .map_err(|e| format!("Scene {} is invalid: {}", index, e))?;
+            scenes.insert(index, scene);
+        }
+        Ok(SceneCatalog { scenes })
+    }
+    pub fn get(&self, index: SceneIndex) -> Option<&SceneDefinition> {
+        self.scenes.get(&index)
+    }
+}
+
+fn scene_source(index: SceneIndex) -> &'static str {
+    match index {
+        SceneIndex::Entrance => include_str!("../../scenes/entrance.json"),
+        SceneIndex::TreesPlanted => include_str!("../../scenes/trees_planted.json"),
+        SceneIndex::QuestsCompleted => include_str!("../../scenes/quests_completed.json"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Texts of the scenes are checked together with all other texts in the frontend catalog test
+    #[test]
+    fn all_scenes_are_valid() {
+        let catalog = SceneCatalog::load().unwrap();
+        for index in SceneIndex::iter() {
+            assert!(catalog.get(index).is_some());
+        }
+    }
+}
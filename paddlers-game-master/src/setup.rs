@@ -6,13 +6,18 @@
 mod map_generation;
 mod new_player;
 
+use map_generation::PlacementStrategy;
+
 use crate::buildings::BuildingFactory;
 use crate::db::DB;
 use diesel::result::{DatabaseErrorKind, Error};
 use dotenv::dotenv;
 use paddlers_shared_lib::test_data::*;
 use paddlers_shared_lib::{
-    api::PlayerInitData, prelude::*, sql_db::run_db_migrations, story::story_state::StoryState,
+    api::{check_display_name, normalize_referral_code, PlayerInitData},
+    prelude::*,
+    sql_db::run_db_migrations,
+    story::story_state::StoryState,
 };
 use std::env;
 
@@ -21,7 +26,20 @@ pub(crate) fn initialize_new_player_account(
     uuid: uuid::Uuid,
     info: &PlayerInitData,
 ) -> Result<(), String> {
-    let result = db.new_player(info.display_name.clone(), uuid);
+    let placement = info
+        .referral_code
+        .as_ref()
+        .and_then(|code| db.player_by_referral_code(&normalize_referral_code(code)))
+        .map(|friend| PlacementStrategy::NearPlayer(friend.key()))
+        .unwrap_or_else(PlacementStrategy::from_env);
+    if let Some(player) = db.player_by_uuid(uuid) {
+        if db.player_villages(player.key()).is_empty() {
+            db.resettle_player(&player, placement);
+            return Ok(());
+        }
+    }
+    let display_name = check_display_name(&info.display_name)?;
+    let result = db.new_player(display_name.to_owned(), uuid, placement);
     if let Err(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _info)) = result {
         println!("Warning: Tried to create player account that already exists");
         Ok(())
@@ -36,14 +54,14 @@ impl DB {
     pub fn db_scripts_by_env(&self) -> Result<(), Box<dyn std::error::Error>> {
         dotenv().ok();
         if env::var("DATABASE_INIT").is_ok() {
-            let server = 1;
             run_db_migrations(self.dbconn())?;
-            self.init_map(server);
+            self.init_map();
         }
         if env::var("INSERT_TEST_DATA").is_ok() {
             if let Ok(player) = self.new_player(
                 TEST_PLAYER_NAME.to_owned(),
                 uuid::Uuid::parse_str(TEST_PLAYER_UUID).unwrap(),
+                PlacementStrategy::default(),
             ) {
                 let village = self.player_villages(player.key())[0];
                 self.add_prophet(village.key());
@@ -1,36 +1,46 @@
 //! Uses a LCG to generate a pseudo-random sequence for the streams on the map
 
 mod anarchists;
+mod placement;
 mod village_creation;
 
+pub use placement::PlacementStrategy;
+
+use crate::db::store::GameStore;
 use crate::db::DB;
 use paddlers_shared_lib::game_mechanics::map::*;
 use paddlers_shared_lib::prelude::*;
 mod lcg;
 use lcg::Lcg;
 
-const STREAMS: usize = 100;
-const ANARCHISTS: usize = 100;
+/// Seed for the map of this server (Seed = server id)
+pub(super) const SERVER_SEED: u64 = 1;
+/// Number of pairs of streams generated per map chunk
+const STREAM_PAIRS_PER_CHUNK: usize = MAP_CHUNK_W as usize / 4;
+/// Number of AI villages generated per map chunk
+const ANARCHISTS_PER_CHUNK: usize = 5;
 
-struct NewMap {
+struct NewMapChunk {
     streams: Vec<NewStream>,
 }
 
-impl NewMap {
-    /// Generates the map for a specific server (Seed = server id)
-    fn generate(seed: u64) -> NewMap {
+impl NewMapChunk {
+    /// Generates a chunk of the map for a specific server.
+    /// The result only depends on the seed and the chunk index, not on the order of generation.
+    fn generate(seed: u64, chunk: i32) -> NewMapChunk {
         let mut streams = vec![];
-        let mut lcg = Lcg::new(seed);
+        let mut lcg = Lcg::for_chunk(seed, chunk);
 
         let start_y = 5.5;
         let dx = MAP_STREAM_AREA_W;
-        for i in 0..STREAMS {
-            let b = (4 * i) as f32;
+        let (chunk_x, _) = map_chunk_range(chunk);
+        for i in 0..STREAM_PAIRS_PER_CHUNK {
+            let b = chunk_x + (4 * i) as f32;
             streams.push(new_stream((b + 1.0, start_y), dx, 20.0, &mut lcg));
             streams.push(new_stream((b + 3.0, start_y), dx, -10.0, &mut lcg));
         }
 
-        NewMap { streams }
+        NewMapChunk { streams }
     }
 }
 
@@ -62,15 +72,53 @@ fn new_stream(start: (f32, f32), max_dx: f32, max_y: f32, lcg: &mut Lcg) -> NewS
 }
 
 impl DB {
-    pub fn init_map(&self, seed: u64) {
-        let map = NewMap::generate(seed);
-
-        self.insert_streams(&map.streams);
-
-        if let Err(e) = self.generate_anarchists(ANARCHISTS, seed.wrapping_add(1)) {
-            eprintln!("Failure on anarchists spawning: {}", e);
+    pub fn init_map(&self) {
+        if let Err(e) = self.generate_map_chunk(0, SERVER_SEED) {
+            eprintln!("Failure on map generation: {}", e);
         }
         // #[cfg(debug_assertions)]
         // self.test_add_all_villages();
     }
+    /// Adds streams and AI villages for a new chunk of the map.
+    /// Does nothing if the chunk has been generated before.
+    /// The chunk is only claimed together with its content, a failure leaves it unclaimed.
+    pub(super) fn generate_map_chunk(&self, chunk: i32, seed: u64) -> Result<(), String> {
+        self.transaction(|| {
+            if !self.claim_map_chunk(chunk) {
+                return Ok(());
+            }
+            let map = NewMapChunk::generate(seed, chunk);
+            let streams = self.insert_streams(&map.streams);
+
+            let mut lcg = Lcg::for_chunk(seed.wrapping_add(1), chunk);
+            let anarchist_streams = streams.iter().step_by(2).take(ANARCHISTS_PER_CHUNK);
+            for s in anarchist_streams {
+                self.add_anarchists_village(s, &mut lcg)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_are_deterministic() {
+        let a = NewMapChunk::generate(SERVER_SEED, 3);
+        // Generating other chunks first must not change the result
+        let _ = NewMapChunk::generate(SERVER_SEED, 2);
+        let b = NewMapChunk::generate(SERVER_SEED, 3);
+        assert_eq!(a.streams.len(), 2 * STREAM_PAIRS_PER_CHUNK);
+        for (s, t) in a.streams.iter().zip(&b.streams) {
+            assert_eq!(s.start_x, t.start_x);
+            assert_eq!(s.control_points, t.control_points);
+        }
+        let (low, high) = map_chunk_range(3);
+        assert!(a
+            .streams
+            .iter()
+            .all(|s| s.start_x >= low && s.start_x < high));
+    }
 }
@@ -18,6 +18,16 @@ impl DB {
         self.generate_anarchist_hobos(HOBOS_PER_TOWN, village, lcg)?;
         Ok(())
     }
+    /// Takes a village away from its owner and turns it into an anarchist village,
+    /// like those generated together with the map
+    pub fn abandon_village(&self, village: VillageKey) -> Result<(), String> {
+        crate::db::store::GameStore::transaction(self, || {
+            self.clear_village(village).map_err(|e| e.to_string())?;
+            let mut lcg = Lcg::new(village.num() as u64);
+            self.generate_anarchist_town_content(village, &mut lcg)?;
+            Ok(())
+        })
+    }
     fn add_random_forest_to_village(&self, village: VillageKey, lcg: &mut Lcg) {
         // Two contiguous forests in the top corners
         let mut left = lcg.next_in_range(0, 2 * TOWN_X as u64 / 3);
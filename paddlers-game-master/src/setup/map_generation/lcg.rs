@@ -9,6 +9,14 @@ impl Lcg {
     pub fn new(seed: u64) -> Self {
         Lcg { val: seed }
     }
+    /// Independent sequence for each map chunk, derived from the server seed
+    pub fn for_chunk(seed: u64, chunk: i32) -> Self {
+        // Spread chunk indices over the full range, then let the LCG mix it with the seed
+        let offset = (chunk as i64 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let mut lcg = Lcg::new(seed ^ offset);
+        lcg.next();
+        lcg
+    }
     /// Returns a pseudo-random value in the specified range, inclusive start and exclusive end
     pub fn next_in_range(&mut self, start: u64, end: u64) -> u64 {
         let i = self.next().unwrap();
@@ -0,0 +1,240 @@
+//! Strategies for picking the position of a new player's village on the map.
+//!
+//! The decision itself only looks at a snapshot of the map, which keeps it
+//! independent of the database.
+
+use paddlers_shared_lib::game_mechanics::map::map_distance;
+use paddlers_shared_lib::prelude::*;
+
+/// Villages closer than this are considered neighbours when counting the population of a region
+pub(super) const NEIGHBOURHOOD_RADIUS: f32 = 5.0;
+/// Number of most recently generated chunks in which new villages are placed
+const RECENT_CHUNKS: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlacementStrategy {
+    /// As close as possible to any village of the given player
+    NearPlayer(PlayerKey),
+    /// Where the fewest villages are in the neighbourhood
+    LeastPopulated,
+    /// Far away from players with a lot of karma
+    AwayFromStrongPlayers,
+}
+
+impl Default for PlacementStrategy {
+    fn default() -> Self {
+        PlacementStrategy::LeastPopulated
+    }
+}
+
+impl PlacementStrategy {
+    /// Strategy for players without a referral, set with the environment variable `NEW_PLAYER_PLACEMENT`
+    pub fn from_env() -> Self {
+        match std::env::var("NEW_PLAYER_PLACEMENT")
+            .as_ref()
+            .map(String::as_str)
+        {
+            Ok("away_from_strong_players") => PlacementStrategy::AwayFromStrongPlayers,
+            Ok("least_populated") | Err(_) => PlacementStrategy::LeastPopulated,
+            Ok(other) => {
+                eprintln!("Unknown NEW_PLAYER_PLACEMENT {}, using default", other);
+                PlacementStrategy::default()
+            }
+        }
+    }
+}
+
+/// An empty position on a stream where a village could be placed
+#[derive(Debug, Clone, Copy)]
+pub(super) struct FreeSpot {
+    pub stream: StreamKey,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A village that is already on the map
+#[derive(Debug, Clone, Copy)]
+pub(super) struct PlacedVillage {
+    pub x: f32,
+    pub y: f32,
+    pub owner: Option<PlayerKey>,
+    /// Karma of the owner, zero for villages without owner
+    pub karma: i64,
+}
+
+/// Returns the index of the best spot according to the strategy.
+/// Ties are broken in favour of spots further left, to keep the map compact.
+pub(super) fn choose_spot(
+    strategy: PlacementStrategy,
+    spots: &[FreeSpot],
+    villages: &[PlacedVillage],
+) -> Option<usize> {
+    match strategy {
+        PlacementStrategy::NearPlayer(player) => {
+            let friend_villages: Vec<&PlacedVillage> = villages
+                .iter()
+                .filter(|v| v.owner == Some(player))
+                .collect();
+            if friend_villages.is_empty() {
+                return choose_spot(PlacementStrategy::default(), spots, villages);
+            }
+            best_spot(spots, |spot| {
+                friend_villages
+                    .iter()
+                    .map(|v| distance(spot, v))
+                    .fold(std::f32::INFINITY, f32::min)
+            })
+        }
+        PlacementStrategy::LeastPopulated => best_spot(spots, |spot| {
+            villages
+                .iter()
+                .filter(|v| distance(spot, v) < NEIGHBOURHOOD_RADIUS)
+                .count() as f32
+        }),
+        PlacementStrategy::AwayFromStrongPlayers => best_spot(spots, |spot| {
+            // Influence of a player decreases quadratically with the distance
+            villages
+                .iter()
+                .filter(|v| v.owner.is_some())
+                .map(|v| v.karma.max(0) as f32 / (1.0 + distance(spot, v).powi(2)))
+                .sum()
+        }),
+    }
+}
+
+/// Ranges of chunks (first, last) that are searched for a free spot, in order of preference.
+/// Looking at a few chunks only keeps the placement cheap, no matter how large the map grows.
+pub(super) fn candidate_chunks(friend_chunks: &[i32], last_chunk: i32) -> Vec<(i32, i32)> {
+    let mut ranges = vec![];
+    if let (Some(first), Some(last)) = (friend_chunks.iter().min(), friend_chunks.iter().max()) {
+        ranges.push(((first - 1).max(0), (last + 1).min(last_chunk)));
+    }
+    ranges.push(((last_chunk - RECENT_CHUNKS + 1).max(0), last_chunk));
+    ranges
+}
+
+/// Index of the spot with the lowest cost
+fn best_spot(spots: &[FreeSpot], cost: impl Fn(&FreeSpot) -> f32) -> Option<usize> {
+    spots
+        .iter()
+        .enumerate()
+        .map(|(i, spot)| (i, cost(spot)))
+        .fold(None, |best: Option<(usize, f32)>, (i, c)| match best {
+            Some((j, best_cost))
+                if best_cost < c || (best_cost == c && spots[j].x <= spots[i].x) =>
+            {
+                Some((j, best_cost))
+            }
+            _ => Some((i, c)),
+        })
+        .map(|(i, _)| i)
+}
+
+fn distance(spot: &FreeSpot, village: &PlacedVillage) -> f32 {
+    map_distance((spot.x, spot.y), (village.x, village.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::village_creation::village_positions;
+    use super::super::{NewMapChunk, SERVER_SEED};
+    use super::*;
+    use paddlers_shared_lib::game_mechanics::map::map_chunk_of;
+
+    /// All positions on two generated chunks, sorted from left to right
+    fn generated_spots() -> Vec<FreeSpot> {
+        let mut streams = NewMapChunk::generate(SERVER_SEED, 0).streams;
+        streams.extend(NewMapChunk::generate(SERVER_SEED, 1).streams);
+        let mut spots: Vec<FreeSpot> = streams
+            .iter()
+            .enumerate()
+            .flat_map(|(i, s)| {
+                village_positions(&s.control_points)
+                    .into_iter()
+                    .map(move |(x, y)| FreeSpot {
+                        stream: StreamKey(i as i64),
+                        x,
+                        y,
+                    })
+            })
+            .collect();
+        spots.sort_by(|a, b| (a.x, a.y).partial_cmp(&(b.x, b.y)).unwrap());
+        spots
+    }
+
+    fn occupy(
+        spots: &mut Vec<FreeSpot>,
+        index: usize,
+        owner: Option<i64>,
+        karma: i64,
+    ) -> PlacedVillage {
+        let spot = spots.remove(index);
+        PlacedVillage {
+            x: spot.x,
+            y: spot.y,
+            owner: owner.map(PlayerKey),
+            karma,
+        }
+    }
+
+    #[test]
+    fn least_populated_avoids_crowded_chunk() {
+        let mut spots = generated_spots();
+        // Every other spot in the first chunk is taken
+        let mut villages = vec![];
+        let mut i = 0;
+        while map_chunk_of(spots[i].x) == 0 {
+            villages.push(occupy(&mut spots, i, None, 0));
+            i += 1;
+        }
+        let chosen = choose_spot(PlacementStrategy::LeastPopulated, &spots, &villages).unwrap();
+        assert_eq!(map_chunk_of(spots[chosen].x), 1);
+    }
+
+    #[test]
+    fn near_player_picks_closest_spot() {
+        let mut spots = generated_spots();
+        let middle = spots.len() / 2;
+        let friend = occupy(&mut spots, middle, Some(7), 100);
+        let villages = vec![friend];
+        let chosen = choose_spot(
+            PlacementStrategy::NearPlayer(PlayerKey(7)),
+            &spots,
+            &villages,
+        )
+        .unwrap();
+        let chosen_distance = distance(&spots[chosen], &friend);
+        assert!(spots
+            .iter()
+            .all(|s| distance(s, &friend) >= chosen_distance));
+        assert!(chosen_distance < NEIGHBOURHOOD_RADIUS);
+    }
+
+    #[test]
+    fn near_unknown_player_falls_back_to_default() {
+        let spots = generated_spots();
+        let near = choose_spot(PlacementStrategy::NearPlayer(PlayerKey(7)), &spots, &[]);
+        let default = choose_spot(PlacementStrategy::default(), &spots, &[]);
+        assert_eq!(near, default);
+    }
+
+    #[test]
+    fn away_from_strong_players() {
+        let mut spots = generated_spots();
+        let strong = occupy(&mut spots, 0, Some(1), 10_000);
+        let last = spots.len() - 1;
+        let weak = occupy(&mut spots, last, Some(2), 1);
+        let villages = vec![strong, weak];
+        let chosen =
+            choose_spot(PlacementStrategy::AwayFromStrongPlayers, &spots, &villages).unwrap();
+        assert!(distance(&spots[chosen], &strong) > distance(&spots[chosen], &weak));
+    }
+
+    #[test]
+    fn candidates_are_near_friends_and_recent() {
+        assert_eq!(candidate_chunks(&[], 0), vec![(0, 0)]);
+        assert_eq!(candidate_chunks(&[], 7), vec![(6, 7)]);
+        assert_eq!(candidate_chunks(&[3, 1], 7), vec![(0, 4), (6, 7)]);
+        assert_eq!(candidate_chunks(&[7], 7), vec![(6, 7), (6, 7)]);
+    }
+}
@@ -1,37 +1,101 @@
 //! For generating villages on the map
 
+use super::placement::*;
 use crate::db::DB;
-use crate::setup::map_generation::Lcg;
+use crate::setup::map_generation::{Lcg, SERVER_SEED};
+use diesel::result::{DatabaseErrorKind, Error};
 use paddlers_shared_lib::game_mechanics::map::*;
 use paddlers_shared_lib::prelude::*;
+use std::collections::HashSet;
+
+/// How often placing a village is retried when others are placed concurrently
+const PLACEMENT_ATTEMPTS: usize = 3;
 
 impl DB {
-    pub fn add_village(&self, pid: PlayerKey) -> Result<Village, &'static str> {
-        // Find unsaturated stream
-        let streams = self.streams_to_add_village();
-        for s in &streams {
-            // Pick a position on it
-            match self.insert_village_on_stream(s, Some(pid)) {
-                Err(_) => {}
-                Ok(v) => return Ok(v),
+    /// Places a new village on a free spot picked by the strategy.
+    /// Only a few chunks are searched, if they are full the next chunk is generated.
+    pub fn add_village(
+        &self,
+        pid: PlayerKey,
+        strategy: PlacementStrategy,
+    ) -> Result<Village, String> {
+        let friend_chunks: Vec<i32> = match strategy {
+            PlacementStrategy::NearPlayer(friend) => self
+                .player_villages(friend)
+                .iter()
+                .map(|v| map_chunk_of(v.x))
+                .collect(),
+            _ => vec![],
+        };
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            let spot = self.find_village_spot(strategy, &friend_chunks)?;
+            let v = NewVillage {
+                stream_id: spot.stream.num(),
+                x: spot.x,
+                y: spot.y,
+                player_id: Some(pid.num()),
+                faith: None, // Start with default value
+            };
+            match self.insert_village(&v) {
+                Ok(village) => return Ok(village),
+                // Another village has been placed on the same spot in the meantime
+                Err(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => continue,
+                Err(e) => return Err(e.to_string()),
             }
         }
-        Err("World full: No space for another village")
+        Err("No free spot found for the village".to_owned())
     }
-    pub fn generate_anarchists(&self, n: usize, seed: u64) -> Result<(), &'static str> {
-        let mut lcg = Lcg::new(seed);
-        for i in 1..n + 1 {
-            self.add_anarchists_village(StreamKey(i as i64), &mut lcg)?;
+    fn find_village_spot(
+        &self,
+        strategy: PlacementStrategy,
+        friend_chunks: &[i32],
+    ) -> Result<FreeSpot, String> {
+        let last_chunk = self.map_chunks().last().copied();
+        if let Some(last_chunk) = last_chunk {
+            for (first, last) in candidate_chunks(friend_chunks, last_chunk) {
+                let (spots, placed) = self.map_snapshot(first, last);
+                if let Some(i) = choose_spot(strategy, &spots, &placed) {
+                    return Ok(spots[i]);
+                }
+            }
         }
-        Ok(())
+        let next_chunk = last_chunk.map(|c| c + 1).unwrap_or(0);
+        self.generate_map_chunk(next_chunk, SERVER_SEED)?;
+        let (spots, placed) = self.map_snapshot(next_chunk, next_chunk);
+        let spot = choose_spot(strategy, &spots, &placed)
+            .map(|i| spots[i])
+            .ok_or("World full: No space for another village")?;
+        Ok(spot)
+    }
+    /// Free spots in the chunks between first and last, inclusive, and the villages around them
+    fn map_snapshot(&self, first: i32, last: i32) -> (Vec<FreeSpot>, Vec<PlacedVillage>) {
+        // Villages just outside of the chunks count for the neighbourhood of spots on the border
+        let (low, _) = map_chunk_range(first);
+        let (_, high) = map_chunk_range(last);
+        let r = NEIGHBOURHOOD_RADIUS;
+        let villages = self.villages_with_owner_karma(low - r, high + r);
+        let occupied: HashSet<(i32, i32)> = villages
+            .iter()
+            .map(|(v, _)| (v.x as i32, v.y as i32))
+            .collect();
+        let spots = free_spots(&self.streams_in_chunks(first, last), &occupied);
+        let placed = villages
+            .iter()
+            .map(|(v, karma)| PlacedVillage {
+                x: v.x,
+                y: v.y,
+                owner: v.player_id.map(PlayerKey),
+                karma: karma.unwrap_or(0),
+            })
+            .collect();
+        (spots, placed)
     }
-    fn add_anarchists_village(
+    pub(super) fn add_anarchists_village(
         &self,
-        stream_id: StreamKey,
+        s: &Stream,
         lcg: &mut Lcg,
     ) -> Result<Village, &'static str> {
-        let s = self.stream(stream_id);
-        let village = self.insert_village_on_stream(&s, None)?;
+        let village = self.insert_village_on_stream(s, None)?;
         self.generate_anarchist_town_content(village.key(), lcg)?;
         Ok(village)
     }
@@ -56,9 +120,14 @@ impl DB {
         Err("Stream full: No space for another village")
     }
 
-    fn streams_to_add_village(&self) -> Vec<Stream> {
-        // Good enough for now
-        self.streams(0.0, MAP_MAX_X as f32)
+    /// Streams of all chunks between first and last, inclusive
+    fn streams_in_chunks(&self, first: i32, last: i32) -> Vec<Stream> {
+        let (low, _) = map_chunk_range(first);
+        let (_, high) = map_chunk_range(last);
+        let mut streams = self.streams(low, high);
+        streams.retain(|s| s.start_x < high);
+        streams.sort_by(|a, b| a.start_x.partial_cmp(&b.start_x).unwrap());
+        streams
     }
 
     fn map_position_empty(&self, x: f32, y: f32) -> bool {
@@ -85,7 +154,27 @@ impl DB {
     }
 }
 
-fn village_positions(stream_points: &[f32]) -> Vec<(f32, f32)> {
+/// Positions on the streams without a village, sorted from left to right
+fn free_spots(streams: &[Stream], occupied: &HashSet<(i32, i32)>) -> Vec<FreeSpot> {
+    let mut spots: Vec<FreeSpot> = streams
+        .iter()
+        .flat_map(|s| {
+            village_positions(&s.control_points)
+                .into_iter()
+                .map(move |(x, y)| FreeSpot {
+                    stream: s.key(),
+                    x,
+                    y,
+                })
+        })
+        .filter(|spot| !occupied.contains(&(spot.x as i32, spot.y as i32)))
+        .collect();
+    // Positions come out of a hash set, sort them to make the placement deterministic
+    spots.sort_by(|a, b| (a.x, a.y).partial_cmp(&(b.x, b.y)).unwrap());
+    spots
+}
+
+pub(super) fn village_positions(stream_points: &[f32]) -> Vec<(f32, f32)> {
     let mut v: std::collections::HashSet<(i32, i32)> = std::collections::HashSet::new();
     let points: Vec<(f32, f32)> = stream_points
         .chunks_exact(2)
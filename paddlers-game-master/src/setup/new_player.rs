@@ -1,20 +1,40 @@
+use super::map_generation::PlacementStrategy;
 use crate::db::DB;
 use diesel::QueryResult;
+use paddlers_shared_lib::game_clock;
 use paddlers_shared_lib::prelude::*;
+use rand::seq::SliceRandom;
+
+const REFERRAL_CODE_LEN: usize = 12;
+/// Letters and digits that are hard to confuse with each other
+const REFERRAL_CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
 
 impl DB {
-    pub(super) fn new_player(&self, display_name: String, uuid: uuid::Uuid) -> QueryResult<Player> {
+    pub(super) fn new_player(
+        &self,
+        display_name: String,
+        uuid: uuid::Uuid,
+        placement: PlacementStrategy,
+    ) -> QueryResult<Player> {
         let player = NewPlayer {
             display_name: display_name,
             karma: 0,
             uuid,
+            referral_code: new_referral_code(),
+            last_active: game_clock::now(),
         };
         let player = self.insert_player(&player)?;
-        let village = self.new_village(player.key());
+        let village = self.new_village(player.key(), placement);
 
         self.insert_hero(village.key());
         Ok(player)
     }
+    /// Gives a returning player, whose villages have been abandoned, a fresh start
+    pub(super) fn resettle_player(&self, player: &Player, placement: PlacementStrategy) {
+        self.record_activity(player);
+        let village = self.new_village(player.key(), placement);
+        self.insert_hero(village.key());
+    }
 
     fn insert_hero(&self, vid: VillageKey) -> Worker {
         let (x, y) = (5, 2);
@@ -35,7 +55,7 @@ impl DB {
             task_type: TaskType::Idle,
             x: x,
             y: y,
-            start_time: None,
+            start_time: Some(game_clock::now()),
             target_hobo_id: None,
         };
         self.insert_task(&task);
@@ -52,18 +72,20 @@ impl DB {
         self.insert_worker_flag(WorkerFlag {
             worker_id: worker.id,
             flag_type: WorkerFlagType::ManaRegeneration,
-            last_update: chrono::Utc::now().naive_utc(),
+            last_update: game_clock::now(),
         });
         self.insert_worker_flag(WorkerFlag {
             worker_id: worker.id,
             flag_type: WorkerFlagType::Work,
-            last_update: chrono::Utc::now().naive_utc(),
+            last_update: game_clock::now(),
         });
         worker
     }
 
-    fn new_village(&self, pid: PlayerKey) -> Village {
-        let village = self.add_village(pid).expect("Village insertion failed");
+    fn new_village(&self, pid: PlayerKey, strategy: PlacementStrategy) -> Village {
+        let village = self
+            .add_village(pid, strategy)
+            .expect("Village insertion failed");
         self.insert_initial_resources(village.key());
         village
     }
@@ -88,3 +110,11 @@ impl DB {
             .expect("Adding dev resources");
     }
 }
+
+/// A random code, which cannot be guessed from the player id like a plain counter could
+fn new_referral_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..REFERRAL_CODE_LEN)
+        .map(|_| *REFERRAL_CODE_CHARS.choose(&mut rng).unwrap() as char)
+        .collect()
+}
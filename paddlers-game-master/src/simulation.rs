@@ -0,0 +1,119 @@
+//! Offline simulation of the game master.
+//!
+//! Runs the same game logic as the actors but on a `MemoryStore` with a manually advanced clock.
+//! This allows testing entire scenarios without a database and without waiting in real time.
+
+mod memory_store;
+mod scenario_test;
+
+pub(crate) use memory_store::MemoryStore;
+
+use crate::db::store::GameStore;
+use crate::game_master::attack_funnel::{insert_scheduled_attack, schedule_attack, PlannedAttack};
+use crate::game_master::event::Event;
+use crate::game_master::event_queue::EventQueue;
+use crate::game_master::town_defence::{collect_report_rewards, evaluate_finished_attacks};
+use crate::worker_actions::{replace_tasks, validate_routine, validate_task_list};
+use chrono::{offset::TimeZone, Duration, Utc};
+use paddlers_shared_lib::api::tasks::{RawTask, RoutineDefinition, RoutineStep, TaskList};
+use paddlers_shared_lib::prelude::*;
+
+/// Resolution in which simulated time advances
+const TIME_STEP_MS: i64 = 100;
+
+pub(crate) struct Simulation {
+    pub store: MemoryStore,
+    events: EventQueue,
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        Simulation {
+            store: MemoryStore::new(),
+            events: EventQueue::new(),
+        }
+    }
+    /// Sends visitors without origin to a village, like the attack spawner does
+    pub fn send_visitors(&mut self, village: VillageKey, visitors: &[NewHobo]) -> Vec<Hobo> {
+        let hobos: Vec<Hobo> = visitors.iter().map(|h| self.store.insert_hobo(h)).collect();
+        let pa = PlannedAttack {
+            origin_village: None,
+            destination_village: self.store.village(village).expect("No such village"),
+            hobos: hobos.clone(),
+        };
+        let (scheduled, events) = schedule_attack(&self.store, pa);
+        insert_scheduled_attack(&self.store, &scheduled);
+        for (event, time) in events {
+            self.events.add_event(event, time);
+        }
+        hobos
+    }
+    /// Replaces the tasks of a worker, the same way as the API does for player input
+    pub fn assign_tasks(
+        &mut self,
+        worker_id: WorkerKey,
+        tasks: Vec<RawTask>,
+    ) -> Result<(), String> {
+        let tl = TaskList { worker_id, tasks };
+        let validated = validate_task_list(&self.store, &tl).map_err(|e| e.to_string())?;
+        self.store.delete_worker_routine(worker_id);
+        for upd in validated.update_tasks {
+            self.store.update_task(&upd);
+        }
+        if let Some((event, time)) = replace_tasks(
+            &self.store,
+            worker_id,
+            &validated.new_tasks,
+            validated.village_id,
+        ) {
+            self.events.add_event(event, time);
+        }
+        Ok(())
+    }
+    /// Lets a worker repeat the given steps, the same way as the API does for player input
+    pub fn assign_routine(
+        &mut self,
+        worker_id: WorkerKey,
+        steps: Vec<RoutineStep>,
+    ) -> Result<(), String> {
+        let rd = RoutineDefinition { worker_id, steps };
+        let (routine, steps) = validate_routine(&self.store, &rd)?;
+        self.store
+            .insert_worker_routine(&routine, &steps)
+            .map_err(|e| e.to_string())?;
+        let time = Utc.from_utc_datetime(&routine.next_step_time);
+        self.events
+            .add_event(Event::WorkerRoutine { worker_id }, time);
+        Ok(())
+    }
+    /// Moves the clock forward, running all events and checks that become due on the way
+    pub fn advance(&mut self, duration: Duration) {
+        let end = self.store.now() + duration;
+        while self.store.now() < end {
+            let step = std::cmp::min(Duration::milliseconds(TIME_STEP_MS), end - self.store.now());
+            self.store.advance(step);
+            let now = Utc.from_utc_datetime(&self.store.now());
+            while let Some(event) = self.events.poll_event(now) {
+                for (event, time) in event.run(&self.store) {
+                    self.events.add_event(event, time);
+                }
+            }
+            evaluate_finished_attacks(&self.store);
+        }
+    }
+    /// Collects all reports of a village, as if the player clicked on them
+    pub fn collect_reports(
+        &self,
+        village: VillageKey,
+    ) -> Vec<(VisitReport, Vec<(ResourceType, i64)>)> {
+        self.store
+            .reports(village, None)
+            .into_iter()
+            .map(|report| {
+                let rewards = self.store.rewards(report.key());
+                collect_report_rewards(&self.store, &report);
+                (report, rewards)
+            })
+            .collect()
+    }
+}
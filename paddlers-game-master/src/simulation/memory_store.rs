@@ -0,0 +1,1037 @@
+//! In-memory implementation of `GameStore` with a manually controlled clock.
+//!
+//! The queries mirror the SQL queries of `DB` closely enough for the game logic,
+//! including the ordering of results and cascading deletes.
+
+use crate::db::store::GameStore;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use diesel::result::{
+    DatabaseErrorKind,
+    Error::{DatabaseError, NotFound},
+    QueryResult,
+};
+use paddlers_shared_lib::game_clock::GameClock;
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::story::quest::{QuestName, QuestProgress};
+use paddlers_shared_lib::story::story_state::StoryState;
+use paddlers_shared_lib::strum::IntoEnumIterator;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+#[derive(Clone)]
+pub(crate) struct MemoryStore {
+    clock: Cell<GameClock>,
+    next_id: Cell<i64>,
+    players: RefCell<Vec<Player>>,
+    /// Season statistics are summed up over all seasons, keyed by player id
+    season_stats: RefCell<HashMap<i64, (i64, i64)>>,
+    villages: RefCell<Vec<Village>>,
+    resources: RefCell<HashMap<(ResourceType, i64), i64>>,
+    buildings: RefCell<Vec<Building>>,
+    workers: RefCell<Vec<Worker>>,
+    worker_flags: RefCell<Vec<WorkerFlag>>,
+    abilities: RefCell<Vec<Ability>>,
+    tasks: RefCell<Vec<Task>>,
+    worker_routines: RefCell<Vec<WorkerRoutine>>,
+    routine_tasks: RefCell<Vec<RoutineTask>>,
+    hobos: RefCell<Vec<Hobo>>,
+    effects: RefCell<Vec<Effect>>,
+    attacks: RefCell<Vec<Attack>>,
+    attacks_to_hobos: RefCell<Vec<AttackToHobo>>,
+    visit_reports: RefCell<Vec<VisitReport>>,
+    rewards: RefCell<Vec<Reward>>,
+    visit_replays: RefCell<Vec<VisitReplay>>,
+    replay_hobos: RefCell<Vec<ReplayHobo>>,
+    replay_effects: RefCell<Vec<ReplayEffect>>,
+    replay_buildings: RefCell<Vec<ReplayBuilding>>,
+    completed_quests: RefCell<Vec<(i64, QuestName)>>,
+    messages: RefCell<Vec<PlayerMessage>>,
+}
+
+impl MemoryStore {
+    /// An empty store, with the clock set to a fixed point in time
+    pub fn new() -> Self {
+        MemoryStore {
+            clock: Cell::new(GameClock::manual(
+                NaiveDate::from_ymd(2020, 6, 1).and_hms(12, 0, 0).into(),
+            )),
+            next_id: Cell::new(1),
+            players: Default::default(),
+            season_stats: Default::default(),
+            villages: Default::default(),
+            resources: Default::default(),
+            buildings: Default::default(),
+            workers: Default::default(),
+            worker_flags: Default::default(),
+            abilities: Default::default(),
+            tasks: Default::default(),
+            worker_routines: Default::default(),
+            routine_tasks: Default::default(),
+            hobos: Default::default(),
+            effects: Default::default(),
+            attacks: Default::default(),
+            attacks_to_hobos: Default::default(),
+            visit_reports: Default::default(),
+            rewards: Default::default(),
+            visit_replays: Default::default(),
+            replay_hobos: Default::default(),
+            replay_effects: Default::default(),
+            replay_buildings: Default::default(),
+            completed_quests: Default::default(),
+            messages: Default::default(),
+        }
+    }
+    pub fn advance(&self, by: Duration) {
+        let mut clock = self.clock.get();
+        clock.advance(Timestamp::from_us(0) + by);
+        self.clock.set(clock);
+    }
+    /// Resets all tables to an earlier copy of the store.
+    /// Like sequences in the database, the clock and the ids are not rolled back.
+    fn restore(&self, snapshot: MemoryStore) {
+        let MemoryStore {
+            clock: _,
+            next_id: _,
+            players,
+            season_stats,
+            villages,
+            resources,
+            buildings,
+            workers,
+            worker_flags,
+            abilities,
+            tasks,
+            worker_routines,
+            routine_tasks,
+            hobos,
+            effects,
+            attacks,
+            attacks_to_hobos,
+            visit_reports,
+            rewards,
+            visit_replays,
+            replay_hobos,
+            replay_effects,
+            replay_buildings,
+            completed_quests,
+            messages,
+        } = snapshot;
+        self.players.swap(&players);
+        self.season_stats.swap(&season_stats);
+        self.villages.swap(&villages);
+        self.resources.swap(&resources);
+        self.buildings.swap(&buildings);
+        self.workers.swap(&workers);
+        self.worker_flags.swap(&worker_flags);
+        self.abilities.swap(&abilities);
+        self.tasks.swap(&tasks);
+        self.worker_routines.swap(&worker_routines);
+        self.routine_tasks.swap(&routine_tasks);
+        self.hobos.swap(&hobos);
+        self.effects.swap(&effects);
+        self.attacks.swap(&attacks);
+        self.attacks_to_hobos.swap(&attacks_to_hobos);
+        self.visit_reports.swap(&visit_reports);
+        self.rewards.swap(&rewards);
+        self.visit_replays.swap(&visit_replays);
+        self.replay_hobos.swap(&replay_hobos);
+        self.replay_effects.swap(&replay_effects);
+        self.replay_buildings.swap(&replay_buildings);
+        self.completed_quests.swap(&completed_quests);
+        self.messages.swap(&messages);
+    }
+    fn next_id(&self) -> i64 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        id
+    }
+
+    /// Adds a new player with one village that has no resources
+    pub fn add_player_with_village(&self, display_name: &str) -> (PlayerKey, VillageKey) {
+        let player = Player {
+            id: self.next_id(),
+            uuid: uuid::Uuid::nil(),
+            karma: 0,
+            display_name: display_name.to_owned(),
+            story_state: StoryState::Initialized,
+            last_active: self.now(),
+            banned: false,
+            referral_code: format!("TEST{}", self.next_id.get()),
+        };
+        let village = Village {
+            id: self.next_id(),
+            x: self.villages.borrow().len() as f32,
+            y: 0.0,
+            stream_id: 0,
+            player_id: Some(player.id),
+            faith: 100,
+        };
+        for res in ResourceType::iter() {
+            self.resources.borrow_mut().insert((res, village.id), 0);
+        }
+        let keys = (player.key(), village.key());
+        self.players.borrow_mut().push(player);
+        self.villages.borrow_mut().push(village);
+        keys
+    }
+    /// Adds a hero that is idle at the given position
+    pub fn add_hero(&self, village: VillageKey, (x, y): (i32, i32)) -> WorkerKey {
+        let worker = Worker {
+            id: self.next_id(),
+            home: village.num(),
+            x,
+            y,
+            unit_type: UnitType::Hero,
+            color: None,
+            speed: 0.5,
+            mana: Some(10),
+            level: 1,
+            exp: 0,
+        };
+        let key = worker.key();
+        for &ability_type in &[AbilityType::Work, AbilityType::Welcome] {
+            self.abilities.borrow_mut().push(Ability {
+                ability_type,
+                worker_id: worker.id,
+                last_used: None,
+            });
+        }
+        for &flag_type in &[WorkerFlagType::ManaRegeneration, WorkerFlagType::Work] {
+            self.worker_flags.borrow_mut().push(WorkerFlag {
+                worker_id: worker.id,
+                flag_type,
+                last_update: self.now(),
+            });
+        }
+        self.workers.borrow_mut().push(worker);
+        self.insert_tasks(&[NewTask {
+            worker_id: key.num(),
+            task_type: TaskType::Idle,
+            x,
+            y,
+            start_time: None,
+            target_hobo_id: None,
+        }]);
+        key
+    }
+    pub fn player(&self, p: PlayerKey) -> Option<Player> {
+        self.players
+            .borrow()
+            .iter()
+            .find(|x| x.id == p.num())
+            .cloned()
+    }
+    pub fn set_story_state(&self, p: PlayerKey, story_state: StoryState) {
+        let mut players = self.players.borrow_mut();
+        if let Some(player) = players.iter_mut().find(|x| x.id == p.num()) {
+            player.story_state = story_state;
+        }
+    }
+    /// Total number of visitors welcomed by the player, over all seasons
+    pub fn visitors_welcomed(&self, p: PlayerKey) -> i64 {
+        self.season_stats
+            .borrow()
+            .get(&p.num())
+            .map(|(_karma, welcomed)| *welcomed)
+            .unwrap_or(0)
+    }
+    pub fn replay_hobos(&self, replay: VisitReplayKey) -> Vec<ReplayHobo> {
+        self.replay_hobos
+            .borrow()
+            .iter()
+            .filter(|h| h.replay_id == replay.num())
+            .cloned()
+            .collect()
+    }
+    pub fn replay_effects(&self, hobo: ReplayHoboKey) -> Vec<ReplayEffect> {
+        self.replay_effects
+            .borrow()
+            .iter()
+            .filter(|e| e.replay_hobo_id == hobo.num())
+            .cloned()
+            .collect()
+    }
+    pub fn replay_buildings(&self, replay: VisitReplayKey) -> Vec<ReplayBuilding> {
+        self.replay_buildings
+            .borrow()
+            .iter()
+            .filter(|b| b.replay_id == replay.num())
+            .cloned()
+            .collect()
+    }
+    pub fn all_hobos(&self) -> Vec<Hobo> {
+        self.hobos.borrow().clone()
+    }
+    pub fn received_messages(&self, p: PlayerKey) -> Vec<PlayerMessage> {
+        self.messages
+            .borrow()
+            .iter()
+            .filter(|m| m.receiver() == p)
+            .cloned()
+            .collect()
+    }
+}
+
+impl GameStore for MemoryStore {
+    fn now(&self) -> NaiveDateTime {
+        // A manual clock ignores the real time
+        self.clock.get().game_time(Timestamp::from_us(0)).into()
+    }
+    fn transaction<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce() -> Result<T, String>,
+    {
+        let snapshot = self.clone();
+        let result = f();
+        if result.is_err() {
+            self.restore(snapshot);
+        }
+        result
+    }
+    fn player_by_village(&self, vid: VillageKey) -> Option<Player> {
+        let owner = self.village(vid)?.player_id?;
+        self.player(PlayerKey(owner))
+    }
+    fn village(&self, village: VillageKey) -> Option<Village> {
+        self.villages
+            .borrow()
+            .iter()
+            .find(|v| v.id == village.num())
+            .cloned()
+    }
+    fn player_villages(&self, p: PlayerKey) -> Vec<Village> {
+        self.villages
+            .borrow()
+            .iter()
+            .filter(|v| v.player_id == Some(p.num()))
+            .cloned()
+            .collect()
+    }
+    fn all_player_villages(&self) -> Vec<Village> {
+        self.villages
+            .borrow()
+            .iter()
+            .filter(|v| v.player_id.is_some())
+            .cloned()
+            .collect()
+    }
+    fn add_karma(&self, p: PlayerKey, plus: i64) -> QueryResult<()> {
+        self.add_season_stats(p, plus, 0)?;
+        let mut players = self.players.borrow_mut();
+        let player = players
+            .iter_mut()
+            .find(|x| x.id == p.num())
+            .ok_or(NotFound)?;
+        player.karma += plus;
+        Ok(())
+    }
+    fn add_season_stats(
+        &self,
+        p: PlayerKey,
+        karma: i64,
+        visitors_welcomed: i64,
+    ) -> QueryResult<()> {
+        let mut stats = self.season_stats.borrow_mut();
+        let entry = stats.entry(p.num()).or_insert((0, 0));
+        entry.0 += karma;
+        entry.1 += visitors_welcomed;
+        Ok(())
+    }
+    fn resource(&self, r: ResourceType, v: VillageKey) -> i64 {
+        self.resources
+            .borrow()
+            .get(&(r, v.num()))
+            .cloned()
+            .unwrap_or(0)
+    }
+    fn add_resource(&self, rt: ResourceType, vk: VillageKey, plus: i64) -> QueryResult<()> {
+        let mut resources = self.resources.borrow_mut();
+        let amount = resources.get_mut(&(rt, vk.num())).ok_or(NotFound)?;
+        *amount += plus;
+        Ok(())
+    }
+    fn buildings(&self, village: VillageKey) -> Vec<Building> {
+        self.buildings
+            .borrow()
+            .iter()
+            .filter(|b| b.village_id == village.num())
+            .cloned()
+            .collect()
+    }
+    fn find_building_by_coordinates(
+        &self,
+        x: i32,
+        y: i32,
+        village: VillageKey,
+    ) -> Option<Building> {
+        self.buildings(village)
+            .into_iter()
+            .find(|b| b.x == x && b.y == y)
+    }
+    fn insert_building(&self, b: &NewBuilding) -> Building {
+        let building = Building {
+            id: self.next_id(),
+            x: b.x,
+            y: b.y,
+            building_type: b.building_type,
+            building_range: b.building_range,
+            attack_power: b.attack_power,
+            attacks_per_cycle: b.attacks_per_cycle,
+            creation: b.creation,
+            village_id: b.village_id,
+        };
+        self.buildings.borrow_mut().push(building.clone());
+        building
+    }
+    fn delete_building(&self, building: &Building) {
+        self.buildings.borrow_mut().retain(|b| b.id != building.id);
+        // Like the foreign key in the database, which is set to NULL on deletion
+        for hobo in self.hobos.borrow_mut().iter_mut() {
+            if hobo.nest == Some(building.id) {
+                hobo.nest = None;
+            }
+        }
+    }
+    fn update_building_creation(&self, building: &Building, creation: NaiveDateTime) {
+        for b in self.buildings.borrow_mut().iter_mut() {
+            if b.id == building.id {
+                b.creation = creation;
+            }
+        }
+    }
+    fn worker_priv(&self, worker_id: WorkerKey) -> Option<Worker> {
+        self.workers
+            .borrow()
+            .iter()
+            .find(|w| w.id == worker_id.num())
+            .cloned()
+    }
+    fn workers(&self, village: VillageKey) -> Vec<Worker> {
+        self.workers
+            .borrow()
+            .iter()
+            .filter(|w| w.home == village.num())
+            .cloned()
+            .collect()
+    }
+    fn insert_worker(&self, u: &NewWorker) -> Worker {
+        let worker = Worker {
+            id: self.next_id(),
+            home: u.home,
+            x: u.x,
+            y: u.y,
+            unit_type: u.unit_type,
+            color: u.color,
+            speed: u.speed,
+            mana: u.mana,
+            level: u.level,
+            exp: u.exp,
+        };
+        self.workers.borrow_mut().push(worker.clone());
+        worker
+    }
+    fn workers_with_job(&self, village: VillageKey, jobs: &[TaskType]) -> Vec<Worker> {
+        let now = self.now();
+        let tasks = self.tasks.borrow();
+        self.workers(village)
+            .into_iter()
+            .filter(|w| {
+                tasks.iter().any(|t| {
+                    t.worker_id == w.id && jobs.contains(&t.task_type) && t.start_time < now
+                })
+            })
+            .collect()
+    }
+    fn count_workers_at_pos_doing_job(
+        &self,
+        village: VillageKey,
+        x: i32,
+        y: i32,
+        job: TaskType,
+    ) -> usize {
+        let workers = self.workers(village);
+        self.tasks
+            .borrow()
+            .iter()
+            .filter(|t| t.task_type == job && t.x == x && t.y == y)
+            .filter(|t| workers.iter().any(|w| w.id == t.worker_id))
+            .count()
+    }
+    fn update_worker(&self, u: &Worker) {
+        if let Some(w) = self.workers.borrow_mut().iter_mut().find(|w| w.id == u.id) {
+            *w = u.clone();
+        }
+    }
+    fn insert_worker_flag(&self, wf: WorkerFlag) {
+        self.worker_flags.borrow_mut().push(wf);
+    }
+    fn update_worker_flag_timestamp_now(&self, w: WorkerKey, f: WorkerFlagType) {
+        let now = self.now();
+        for flag in self.worker_flags.borrow_mut().iter_mut() {
+            if flag.worker_id == w.num() && flag.flag_type == f {
+                flag.last_update = now;
+            }
+        }
+    }
+    fn update_worker_flag_timestamp(&self, w: WorkerKey, f: WorkerFlagType, ts: NaiveDateTime) {
+        for flag in self.worker_flags.borrow_mut().iter_mut() {
+            if flag.worker_id == w.num() && flag.flag_type == f {
+                flag.last_update = ts;
+            }
+        }
+    }
+    fn worker_flags(&self, worker: WorkerKey) -> Vec<WorkerFlag> {
+        self.worker_flags
+            .borrow()
+            .iter()
+            .filter(|f| f.worker_id == worker.num())
+            .cloned()
+            .collect()
+    }
+    fn add_worker_mana(&self, w: WorkerKey, plus: i32, max: i32) {
+        if let Some(worker) = self
+            .workers
+            .borrow_mut()
+            .iter_mut()
+            .find(|u| u.id == w.num())
+        {
+            worker.mana = Some(max.min(worker.mana.unwrap_or(0) + plus));
+        }
+    }
+    fn worker_ability(&self, worker_id: WorkerKey, ability_type: AbilityType) -> Option<Ability> {
+        self.abilities
+            .borrow()
+            .iter()
+            .find(|a| a.worker_id == worker_id.num() && a.ability_type == ability_type)
+            .cloned()
+    }
+    fn insert_ability(&self, a: &NewAbility) {
+        self.abilities.borrow_mut().push(Ability {
+            ability_type: a.ability_type,
+            worker_id: a.worker_id,
+            last_used: None,
+        });
+    }
+    fn update_ability_used_timestamp(&self, worker: WorkerKey, at: AbilityType) {
+        let now = self.now();
+        for a in self.abilities.borrow_mut().iter_mut() {
+            if a.worker_id == worker.num() && a.ability_type == at {
+                a.last_used = Some(now);
+            }
+        }
+    }
+    fn task(&self, task_id: TaskKey) -> Option<Task> {
+        self.tasks
+            .borrow()
+            .iter()
+            .find(|t| t.id == task_id.num())
+            .cloned()
+    }
+    fn worker_tasks(&self, worker_id: WorkerKey) -> Vec<Task> {
+        let mut tasks: Vec<Task> = self
+            .tasks
+            .borrow()
+            .iter()
+            .filter(|t| t.worker_id == worker_id.num())
+            .cloned()
+            .collect();
+        tasks.sort_by_key(|t| t.start_time);
+        tasks
+    }
+    fn past_worker_tasks(&self, worker_id: WorkerKey) -> Vec<Task> {
+        let now = self.now();
+        self.worker_tasks(worker_id)
+            .into_iter()
+            .filter(|t| t.start_time < now)
+            .collect()
+    }
+    fn current_task(&self, worker_id: WorkerKey) -> Option<Task> {
+        let now = self.now();
+        self.worker_tasks(worker_id)
+            .into_iter()
+            .find(|t| t.start_time <= now)
+    }
+    fn earliest_future_task(&self, worker_id: WorkerKey) -> Option<Task> {
+        let now = self.now();
+        self.worker_tasks(worker_id)
+            .into_iter()
+            .find(|t| t.start_time >= now)
+    }
+    fn current_and_next_task(&self, worker_id: WorkerKey) -> (Option<Task>, Option<Task>) {
+        let mut tasks = self.worker_tasks(worker_id).into_iter();
+        (tasks.next(), tasks.next())
+    }
+    fn insert_tasks(&self, tasks: &[NewTask]) -> Vec<Task> {
+        let inserted: Vec<Task> = tasks
+            .iter()
+            .map(|t| Task {
+                id: self.next_id(),
+                worker_id: t.worker_id,
+                task_type: t.task_type,
+                x: t.x,
+                y: t.y,
+                start_time: t.start_time.unwrap_or(self.now()),
+                target_hobo_id: t.target_hobo_id,
+            })
+            .collect();
+        self.tasks.borrow_mut().extend(inserted.iter().cloned());
+        inserted
+    }
+    fn update_task(&self, u: &Task) {
+        if let Some(t) = self.tasks.borrow_mut().iter_mut().find(|t| t.id == u.id) {
+            *t = u.clone();
+        }
+    }
+    fn delete_task(&self, task: &Task) {
+        self.tasks.borrow_mut().retain(|t| t.id != task.id);
+    }
+    fn flush_task_queue(&self, worker_id: WorkerKey) {
+        let now = self.now();
+        self.tasks
+            .borrow_mut()
+            .retain(|t| t.worker_id != worker_id.num() || t.start_time <= now);
+    }
+    fn worker_routine(&self, worker_id: WorkerKey) -> Option<WorkerRoutine> {
+        self.worker_routines
+            .borrow()
+            .iter()
+            .find(|r| r.worker_id == worker_id.num())
+            .cloned()
+    }
+    fn routine_tasks(&self, worker_id: WorkerKey) -> Vec<RoutineTask> {
+        let mut steps: Vec<RoutineTask> = self
+            .routine_tasks
+            .borrow()
+            .iter()
+            .filter(|t| t.worker_id == worker_id.num())
+            .cloned()
+            .collect();
+        steps.sort_by_key(|t| t.step);
+        steps
+    }
+    fn insert_worker_routine(
+        &self,
+        routine: &WorkerRoutine,
+        steps: &[RoutineTask],
+    ) -> QueryResult<()> {
+        if !self
+            .workers
+            .borrow()
+            .iter()
+            .any(|w| w.id == routine.worker_id)
+        {
+            return Err(DatabaseError(
+                DatabaseErrorKind::ForeignKeyViolation,
+                Box::new("Worker does not exist".to_owned()),
+            ));
+        }
+        self.delete_worker_routine(WorkerKey(routine.worker_id));
+        self.worker_routines.borrow_mut().push(*routine);
+        self.routine_tasks
+            .borrow_mut()
+            .extend(steps.iter().cloned());
+        Ok(())
+    }
+    fn update_worker_routine(&self, u: &WorkerRoutine) {
+        if let Some(r) = self
+            .worker_routines
+            .borrow_mut()
+            .iter_mut()
+            .find(|r| r.worker_id == u.worker_id)
+        {
+            *r = *u;
+        }
+    }
+    fn delete_worker_routine(&self, worker_id: WorkerKey) {
+        self.worker_routines
+            .borrow_mut()
+            .retain(|r| r.worker_id != worker_id.num());
+        self.routine_tasks
+            .borrow_mut()
+            .retain(|t| t.worker_id != worker_id.num());
+    }
+    fn hobo(&self, hobo_id: HoboKey) -> Option<Hobo> {
+        self.hobos
+            .borrow()
+            .iter()
+            .find(|h| h.id == hobo_id.num())
+            .cloned()
+    }
+    fn insert_hobo(&self, u: &NewHobo) -> Hobo {
+        let hobo = Hobo {
+            id: self.next_id(),
+            home: u.home,
+            color: u.color,
+            speed: u.speed,
+            hp: u.hp,
+            hurried: u.hurried,
+            nest: u.nest,
+            last_production: None,
+        };
+        self.hobos.borrow_mut().push(hobo.clone());
+        hobo
+    }
+    fn hobos(&self, village: VillageKey) -> Vec<Hobo> {
+        self.hobos
+            .borrow()
+            .iter()
+            .filter(|h| h.home == village.num())
+            .cloned()
+            .collect()
+    }
+    fn update_hobo(&self, hobo: &Hobo) {
+        if let Some(h) = self.hobos.borrow_mut().iter_mut().find(|h| h.id == hobo.id) {
+            *h = hobo.clone();
+        }
+    }
+    fn delete_hobo(&self, hobo: &Hobo) {
+        self.hobos.borrow_mut().retain(|h| h.id != hobo.id);
+        self.delete_effects_on_hobo(hobo.key());
+        self.attacks_to_hobos
+            .borrow_mut()
+            .retain(|ath| ath.hobo_id != hobo.id);
+    }
+    fn delete_effects_on_hobo(&self, hobo: HoboKey) {
+        self.effects
+            .borrow_mut()
+            .retain(|e| e.hobo_id != hobo.num());
+    }
+    fn effects_on_hobo(&self, hobo: HoboKey) -> Vec<Effect> {
+        self.effects
+            .borrow()
+            .iter()
+            .filter(|e| e.hobo_id == hobo.num())
+            .cloned()
+            .collect()
+    }
+    fn insert_effect(&self, e: &NewEffect) -> Effect {
+        let effect = Effect {
+            id: self.next_id(),
+            hobo_id: e.hobo_id,
+            attribute: e.attribute,
+            strength: e.strength,
+            start_time: e.start_time.unwrap_or(self.now()),
+        };
+        self.effects.borrow_mut().push(effect);
+        effect
+    }
+    fn attacks(&self, village: VillageKey, min_id: Option<i64>) -> Vec<Attack> {
+        let mut attacks: Vec<Attack> = self
+            .attacks
+            .borrow()
+            .iter()
+            .filter(|a| a.destination_village_id == village.num())
+            .filter(|a| a.id >= min_id.unwrap_or(0))
+            .cloned()
+            .collect();
+        attacks.sort_by_key(|a| a.arrival);
+        attacks
+    }
+    fn attack_hobos(&self, atk: AttackKey) -> Vec<Hobo> {
+        self.attacks_to_hobos
+            .borrow()
+            .iter()
+            .filter(|ath| ath.attack_id == atk.num())
+            .filter_map(|ath| self.hobo(HoboKey(ath.hobo_id)))
+            .collect()
+    }
+    fn attack_hobos_with_attack_info(&self, atk: &Attack) -> Vec<(Hobo, AttackToHobo)> {
+        self.attacks_to_hobos
+            .borrow()
+            .iter()
+            .filter(|ath| ath.attack_id == atk.id)
+            .filter_map(|ath| Some((self.hobo(HoboKey(ath.hobo_id))?, ath.clone())))
+            .collect()
+    }
+    fn attack_hobos_active_with_attack_info(&self, atk: &Attack) -> Vec<(Hobo, AttackToHobo)> {
+        self.attacks_to_hobos
+            .borrow()
+            .iter()
+            .filter(|ath| ath.attack_id == atk.id && ath.satisfied.is_none())
+            .filter_map(|ath| Some((self.hobo(HoboKey(ath.hobo_id))?, ath.clone())))
+            .collect()
+    }
+    fn attack_hobos_satisfied(&self, atk: &Attack) -> Vec<Hobo> {
+        self.attacks_to_hobos
+            .borrow()
+            .iter()
+            .filter(|ath| ath.attack_id == atk.id && ath.satisfied == Some(true))
+            .filter_map(|ath| self.hobo(HoboKey(ath.hobo_id)))
+            .collect()
+    }
+    fn resting_visitors(&self, village_id: VillageKey) -> Vec<(Hobo, AttackKey)> {
+        let now = self.now();
+        let mut visitors = vec![];
+        for atk in self.attacks(village_id, None) {
+            if atk.arrival > now {
+                continue;
+            }
+            for (hobo, _info) in self.attack_hobos_active_with_attack_info(&atk) {
+                if !hobo.hurried {
+                    visitors.push((hobo, atk.key()));
+                }
+            }
+        }
+        visitors
+    }
+    fn hobo_attack_info(&self, hid: HoboKey) -> Vec<(Attack, AttackToHobo)> {
+        let attacks = self.attacks.borrow();
+        self.attacks_to_hobos
+            .borrow()
+            .iter()
+            .filter(|ath| ath.hobo_id == hid.num())
+            .filter_map(|ath| {
+                let atk = attacks.iter().find(|a| a.id == ath.attack_id)?;
+                Some((atk.clone(), ath.clone()))
+            })
+            .collect()
+    }
+    fn insert_attack(&self, new_attack: &NewAttack) -> Attack {
+        let attack = Attack {
+            id: self.next_id(),
+            departure: new_attack.departure,
+            arrival: new_attack.arrival,
+            origin_village_id: new_attack.origin_village_id,
+            destination_village_id: new_attack.destination_village_id,
+        };
+        self.attacks.borrow_mut().push(attack.clone());
+        attack
+    }
+    fn insert_attack_to_hobo(&self, atu: &AttackToHobo) {
+        self.attacks_to_hobos.borrow_mut().push(atu.clone());
+    }
+    fn set_satisfied(&self, hid: HoboKey, aid: AttackKey, satisfied: bool) {
+        for ath in self.attacks_to_hobos.borrow_mut().iter_mut() {
+            if ath.attack_id == aid.num() && ath.hobo_id == hid.num() {
+                ath.satisfied = Some(satisfied);
+            }
+        }
+    }
+    fn release_resting_visitor(&self, hid: HoboKey, aid: AttackKey) {
+        let now = self.now();
+        for ath in self.attacks_to_hobos.borrow_mut().iter_mut() {
+            if ath.attack_id == aid.num() && ath.hobo_id == hid.num() {
+                ath.released = Some(now);
+            }
+        }
+    }
+    fn delete_attack_hobos(&self, atk: AttackKey) {
+        let hobos: Vec<i64> = self
+            .attack_hobos(atk)
+            .iter()
+            .filter(|h| h.nest.is_none())
+            .map(|h| h.id)
+            .collect();
+        self.hobos.borrow_mut().retain(|h| !hobos.contains(&h.id));
+        self.effects
+            .borrow_mut()
+            .retain(|e| !hobos.contains(&e.hobo_id));
+        self.attacks_to_hobos
+            .borrow_mut()
+            .retain(|ath| !hobos.contains(&ath.hobo_id));
+    }
+    fn delete_attack(&self, atk: &Attack) {
+        self.attacks.borrow_mut().retain(|a| a.id != atk.id);
+        self.attacks_to_hobos
+            .borrow_mut()
+            .retain(|ath| ath.attack_id != atk.id);
+    }
+    fn reports(&self, v: VillageKey, min_id: Option<i64>) -> Vec<VisitReport> {
+        let mut reports: Vec<VisitReport> = self
+            .visit_reports
+            .borrow()
+            .iter()
+            .filter(|r| r.village_id == v.num() && r.id >= min_id.unwrap_or(0))
+            .cloned()
+            .collect();
+        reports.sort_by_key(|r| std::cmp::Reverse(r.reported));
+        reports
+    }
+    fn rewards(&self, vr: VisitReportKey) -> Vec<(ResourceType, i64)> {
+        let mut sums: Vec<(ResourceType, i64)> = vec![];
+        for r in self
+            .rewards
+            .borrow()
+            .iter()
+            .filter(|r| r.visit_report_id == vr.num())
+        {
+            match sums.iter_mut().find(|(rt, _)| *rt == r.resource_type) {
+                Some((_, n)) => *n += r.amount,
+                None => sums.push((r.resource_type, r.amount)),
+            }
+        }
+        sums
+    }
+    fn insert_visit_report(&self, vr: NewVisitReport) -> VisitReport {
+        let report = VisitReport {
+            id: self.next_id(),
+            village_id: vr.village_id,
+            reported: vr.reported,
+            karma: vr.karma,
+        };
+        self.visit_reports.borrow_mut().push(report);
+        report
+    }
+    fn insert_visit_report_rewards(&self, rewards: Vec<NewReward>) {
+        for r in rewards {
+            let reward = Reward {
+                id: self.next_id(),
+                visit_report_id: r.visit_report_id,
+                resource_type: r.resource_type,
+                amount: r.amount,
+            };
+            self.rewards.borrow_mut().push(reward);
+        }
+    }
+    fn delete_visit_report(&self, obj: &VisitReport) {
+        self.visit_reports.borrow_mut().retain(|r| r.id != obj.id);
+        self.rewards
+            .borrow_mut()
+            .retain(|r| r.visit_report_id != obj.id);
+    }
+    fn visit_replays(&self, v: VillageKey) -> Vec<VisitReplay> {
+        let mut replays: Vec<VisitReplay> = self
+            .visit_replays
+            .borrow()
+            .iter()
+            .filter(|r| r.village_id == v.num())
+            .cloned()
+            .collect();
+        replays.sort_by_key(|r| std::cmp::Reverse(r.finished));
+        replays
+    }
+    fn insert_visit_replay(&self, vr: &NewVisitReplay) -> VisitReplay {
+        let replay = VisitReplay {
+            id: self.next_id(),
+            village_id: vr.village_id,
+            arrival: vr.arrival,
+            finished: vr.finished,
+        };
+        self.visit_replays.borrow_mut().push(replay);
+        replay
+    }
+    fn insert_replay_hobo(&self, hobo: &NewReplayHobo) -> ReplayHobo {
+        let hobo = ReplayHobo {
+            id: self.next_id(),
+            replay_id: hobo.replay_id,
+            color: hobo.color,
+            speed: hobo.speed,
+            hp: hobo.hp,
+            hurried: hobo.hurried,
+            released: hobo.released,
+            satisfied: hobo.satisfied,
+        };
+        self.replay_hobos.borrow_mut().push(hobo);
+        hobo
+    }
+    fn insert_replay_effects(&self, effects: Vec<NewReplayEffect>) {
+        for e in effects {
+            let effect = ReplayEffect {
+                id: self.next_id(),
+                replay_hobo_id: e.replay_hobo_id,
+                attribute: e.attribute,
+                strength: e.strength,
+                start_time: e.start_time,
+            };
+            self.replay_effects.borrow_mut().push(effect);
+        }
+    }
+    fn insert_replay_buildings(&self, buildings: Vec<NewReplayBuilding>) {
+        for b in buildings {
+            let building = ReplayBuilding {
+                id: self.next_id(),
+                replay_id: b.replay_id,
+                x: b.x,
+                y: b.y,
+                building_type: b.building_type,
+                building_range: b.building_range,
+                attack_power: b.attack_power,
+                attacks_per_cycle: b.attacks_per_cycle,
+                creation: b.creation,
+            };
+            self.replay_buildings.borrow_mut().push(building);
+        }
+    }
+    fn delete_visit_replay(&self, obj: &VisitReplay) {
+        self.visit_replays.borrow_mut().retain(|r| r.id != obj.id);
+        let hobos: Vec<i64> = self
+            .replay_hobos(obj.key())
+            .iter()
+            .map(|h| h.id)
+            .collect();
+        self.replay_hobos
+            .borrow_mut()
+            .retain(|h| h.replay_id != obj.id);
+        self.replay_effects
+            .borrow_mut()
+            .retain(|e| !hobos.contains(&e.replay_hobo_id));
+        self.replay_buildings
+            .borrow_mut()
+            .retain(|b| b.replay_id != obj.id);
+    }
+    fn completed_quests(&self, p: PlayerKey) -> Vec<QuestName> {
+        self.completed_quests
+            .borrow()
+            .iter()
+            .filter(|(player, _)| *player == p.num())
+            .map(|(_, quest)| *quest)
+            .collect()
+    }
+    fn quest_progress(&self, player: &Player) -> QuestProgress {
+        let buildings = self
+            .player_villages(player.key())
+            .into_iter()
+            .flat_map(|v| self.buildings(v.key()))
+            .map(|b| b.building_type)
+            .collect();
+        QuestProgress {
+            karma: player.karma,
+            visitors_welcomed: self.visitors_welcomed(player.key()),
+            buildings,
+        }
+    }
+    fn insert_completed_quest(&self, p: PlayerKey, quest: QuestName) -> QueryResult<()> {
+        let mut completed = self.completed_quests.borrow_mut();
+        if completed.contains(&(p.num(), quest)) {
+            return Err(DatabaseError(
+                DatabaseErrorKind::UniqueViolation,
+                Box::new("Quest completed before".to_owned()),
+            ));
+        }
+        completed.push((p.num(), quest));
+        Ok(())
+    }
+    fn message(&self, id: MessageKey) -> Option<PlayerMessage> {
+        self.messages
+            .borrow()
+            .iter()
+            .find(|m| m.id == id.num())
+            .cloned()
+    }
+    fn messages_sent_since(&self, sender: PlayerKey, since: NaiveDateTime) -> i64 {
+        self.messages
+            .borrow()
+            .iter()
+            .filter(|m| m.sender_id == Some(sender.num()) && m.sent >= since)
+            .count() as i64
+    }
+    fn insert_message(&self, msg: &NewPlayerMessage) -> PlayerMessage {
+        let msg = PlayerMessage {
+            id: self.next_id(),
+            sender_id: msg.sender_id,
+            receiver_id: msg.receiver_id,
+            body: msg.body.clone(),
+            sent: msg.sent,
+            read: false,
+        };
+        self.messages.borrow_mut().push(msg.clone());
+        msg
+    }
+    fn mark_message_read(&self, id: MessageKey) {
+        for m in self.messages.borrow_mut().iter_mut() {
+            if m.id == id.num() {
+                m.read = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,747 @@
+use super::*;
+use crate::api::messages::{message_rate_limit_reached, try_mark_messages_read, try_send_message};
+use crate::api::shop::{try_buy_blueprint, try_buy_building, try_buy_worker};
+use crate::buildings::BuildingFactory;
+use crate::game_master::attack_funnel::attack_duration;
+use crate::game_master::economy_worker::update_workers;
+use crate::game_master::event::Event;
+use crate::game_master::forestry::{chop_forest, remove_dead_trees};
+use crate::game_master::nests::{collect_nest_production, empty_nest, ordain_hobo};
+use crate::game_master::town_defence::MAX_REPLAYS_PER_VILLAGE;
+use crate::quests::collect_quest;
+use crate::town_view::TownView;
+use paddlers_shared_lib::api::messages::MessageSend;
+use paddlers_shared_lib::game_mechanics::forestry::TREE_LIFESPAN_HOURS;
+use paddlers_shared_lib::game_mechanics::hobos::hobo_speed;
+use paddlers_shared_lib::game_mechanics::prophets::prophet_cost;
+use paddlers_shared_lib::game_mechanics::town::TOWN_LANE_Y;
+use paddlers_shared_lib::game_mechanics::worker::{
+    basic_worker_speed, hero_resource_collection_per_hour,
+};
+use paddlers_shared_lib::story::quest::QuestName;
+use paddlers_shared_lib::story::story_state::StoryState;
+
+fn visitor(village: VillageKey, hp: i64, hurried: bool) -> NewHobo {
+    NewHobo {
+        hp,
+        home: village.num(),
+        color: Some(UnitColor::Yellow),
+        speed: hobo_speed(hurried),
+        hurried,
+        nest: None,
+    }
+}
+
+#[test]
+fn flowers_satisfy_resting_visitor() {
+    let mut sim = Simulation::new();
+    let (player, village) = sim.store.add_player_with_village("Tester");
+    sim.store
+        .add_resource(ResourceType::Feathers, village, 20)
+        .unwrap();
+    try_buy_building(&sim.store, BuildingType::BlueFlowers, (5, 2), village).unwrap();
+    assert_eq!(sim.store.resource(ResourceType::Feathers, village), 0);
+
+    sim.send_visitors(village, &[visitor(village, 1, false)]);
+    let arrival = sim.store.attacks(village, None)[0].arrival;
+    sim.advance(arrival - sim.store.now() + Duration::seconds(10));
+    assert_eq!(sim.store.attacks(village, None).len(), 1);
+
+    // The visitor passes the flowers and is satisfied by the time it reaches the resting place
+    sim.advance(Duration::seconds(15));
+    assert!(sim.store.attacks(village, None).is_empty());
+    let reports = sim.collect_reports(village);
+    assert_eq!(reports.len(), 1);
+    let (report, rewards) = &reports[0];
+    assert_eq!(report.karma, 1);
+    assert!(rewards.contains(&(ResourceType::Feathers, 1)));
+
+    assert!(sim.store.reports(village, None).is_empty());
+    assert_eq!(sim.store.resource(ResourceType::Feathers, village), 1);
+    assert_eq!(sim.store.player(player).unwrap().karma, 1);
+    assert_eq!(sim.store.visitors_welcomed(player), 1);
+}
+
+#[test]
+fn camo_visitor_ignores_first_flowers() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    sim.store
+        .add_resource(ResourceType::Feathers, village, 20)
+        .unwrap();
+    try_buy_building(&sim.store, BuildingType::BlueFlowers, (5, 2), village).unwrap();
+
+    let camo = NewHobo {
+        color: Some(UnitColor::Camo),
+        ..visitor(village, 1, false)
+    };
+    sim.send_visitors(village, &[camo]);
+    let arrival = sim.store.attacks(village, None)[0].arrival;
+    sim.advance(arrival - sim.store.now() + Duration::seconds(25));
+
+    // A yellow visitor would be satisfied by now, the camo visitor is still resting
+    assert_eq!(sim.store.attacks(village, None).len(), 1);
+    assert_eq!(sim.store.resting_visitors(village).len(), 1);
+    assert!(sim.collect_reports(village).is_empty());
+}
+
+#[test]
+fn satisfied_visitor_settles_in_nest() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    sim.store
+        .add_resource(ResourceType::Feathers, village, 20)
+        .unwrap();
+    try_buy_building(&sim.store, BuildingType::BlueFlowers, (5, 2), village).unwrap();
+    let nest = sim.store.insert_building(&BuildingFactory::new(
+        BuildingType::SingleNest,
+        (1, 1),
+        village,
+    ));
+
+    sim.send_visitors(village, &[visitor(village, 1, false)]);
+    let arrival = sim.store.attacks(village, None)[0].arrival;
+    sim.advance(arrival - sim.store.now() + Duration::seconds(25));
+    assert!(sim.store.attacks(village, None).is_empty());
+    let residents = sim.store.hobos(village);
+    assert_eq!(residents.len(), 1);
+    assert_eq!(residents[0].nest, Some(nest.id));
+
+    // Settled hobos produce feathers over time
+    let feathers = sim.store.resource(ResourceType::Feathers, village);
+    sim.advance(Duration::hours(1));
+    collect_nest_production(&sim.store, village);
+    assert_eq!(
+        sim.store.resource(ResourceType::Feathers, village),
+        feathers + 2
+    );
+
+    // Ordaining costs as much as a new prophet
+    let cost = prophet_cost(0);
+    assert!(ordain_hobo(&sim.store, &residents[0], &cost).is_err());
+    assert_eq!(
+        sim.store.hobo(residents[0].key()).unwrap().nest,
+        Some(nest.id)
+    );
+    for (rt, n) in &cost.0 {
+        sim.store.add_resource(*rt, village, *n).unwrap();
+    }
+    let feathers = sim.store.resource(ResourceType::Feathers, village);
+
+    // An ordained hobo frees its place for the next visitor
+    ordain_hobo(&sim.store, &residents[0], &cost).unwrap();
+    let prophet = sim.store.hobo(residents[0].key()).unwrap();
+    assert_eq!(prophet.color, Some(UnitColor::Prophet));
+    assert_eq!(prophet.nest, None);
+    assert_eq!(
+        sim.store.resource(ResourceType::Feathers, village),
+        feathers - 1000
+    );
+    assert_eq!(sim.store.resource(ResourceType::Sticks, village), 0);
+    assert!(ordain_hobo(&sim.store, &prophet, &cost).is_err());
+
+    sim.send_visitors(village, &[visitor(village, 1, false)]);
+    let arrival = sim.store.attacks(village, None)[0].arrival;
+    sim.advance(arrival - sim.store.now() + Duration::seconds(25));
+    let residents: Vec<_> = sim
+        .store
+        .hobos(village)
+        .into_iter()
+        .filter(|h| h.nest.is_some())
+        .collect();
+    assert_eq!(residents.len(), 1);
+
+    empty_nest(&sim.store, &nest);
+    sim.store.delete_building(&nest);
+    assert!(sim.store.hobos(village).iter().all(|h| h.nest.is_none()));
+    assert_eq!(sim.store.hobos(village).len(), 1);
+}
+
+#[test]
+fn satisfaction_of_passing_visitor_is_scheduled() {
+    let sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    sim.store
+        .add_resource(ResourceType::Feathers, village, 20)
+        .unwrap();
+    try_buy_building(&sim.store, BuildingType::BlueFlowers, (5, 2), village).unwrap();
+
+    let hobo = sim.store.insert_hobo(&visitor(village, 1, true));
+    let pa = PlannedAttack {
+        origin_village: None,
+        destination_village: sim.store.village(village).unwrap(),
+        hobos: vec![hobo.clone()],
+    };
+    let (scheduled, events) = schedule_attack(&sim.store, pa);
+
+    // The flowers reach the lane from the third tile on, which takes a hurried visitor 32s
+    let expected = scheduled.attack.arrival + Duration::seconds(32);
+    let check = Event::CheckVisitorHp {
+        hobo_id: hobo.key(),
+    };
+    assert!(events.contains(&(check, Utc.from_utc_datetime(&expected))));
+}
+
+#[test]
+fn unwelcomed_visitor_leaves_without_report() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+
+    sim.send_visitors(village, &[visitor(village, 5, true)]);
+    assert_eq!(sim.store.attacks(village, None).len(), 1);
+    sim.advance(Duration::minutes(5));
+
+    assert!(sim.store.attacks(village, None).is_empty());
+    assert!(sim.store.reports(village, None).is_empty());
+    assert!(sim.store.all_hobos().is_empty());
+}
+
+#[test]
+fn finished_visit_is_archived() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    sim.store
+        .add_resource(ResourceType::Feathers, village, 20)
+        .unwrap();
+    try_buy_building(&sim.store, BuildingType::BlueFlowers, (5, 2), village).unwrap();
+
+    sim.send_visitors(
+        village,
+        &[visitor(village, 1, false), visitor(village, 5, true)],
+    );
+    let arrival = sim.store.attacks(village, None)[0].arrival;
+    sim.advance(Duration::minutes(5));
+    assert!(sim.store.attacks(village, None).is_empty());
+
+    let replays = sim.store.visit_replays(village);
+    assert_eq!(replays.len(), 1);
+    assert_eq!(replays[0].arrival, arrival);
+    let hobos = sim.store.replay_hobos(replays[0].key());
+    assert_eq!(hobos.len(), 2);
+    assert!(hobos.iter().any(|h| !h.hurried && h.satisfied && h.released.is_some()));
+    assert!(hobos.iter().any(|h| h.hurried && !h.satisfied));
+    let buildings = sim.store.replay_buildings(replays[0].key());
+    assert_eq!(buildings.len(), 1);
+    assert_eq!(buildings[0].building_type, BuildingType::BlueFlowers);
+}
+
+#[test]
+fn only_latest_replays_are_kept() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+
+    let mut arrivals = vec![];
+    for _ in 0..=MAX_REPLAYS_PER_VILLAGE {
+        sim.send_visitors(village, &[visitor(village, 5, true)]);
+        arrivals.push(sim.store.attacks(village, None)[0].arrival);
+        sim.advance(Duration::minutes(5));
+    }
+
+    let replays = sim.store.visit_replays(village);
+    assert_eq!(replays.len(), MAX_REPLAYS_PER_VILLAGE);
+    // The oldest visit has been dropped, the newest comes first
+    assert!(replays.iter().all(|r| r.arrival != arrivals[0]));
+    assert_eq!(replays[0].arrival, *arrivals.last().unwrap());
+}
+
+#[test]
+fn attacks_do_not_overlap() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+
+    sim.send_visitors(
+        village,
+        &[visitor(village, 3, false), visitor(village, 3, true)],
+    );
+    sim.send_visitors(village, &[visitor(village, 3, false)]);
+
+    let attacks = sim.store.attacks(village, None);
+    assert_eq!(attacks.len(), 2);
+    assert!(attacks[1].arrival >= attacks[0].arrival + attack_duration(2));
+}
+
+#[test]
+fn building_requires_resources() {
+    let sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    sim.store
+        .add_resource(ResourceType::Feathers, village, 19)
+        .unwrap();
+
+    let result = try_buy_building(&sim.store, BuildingType::BlueFlowers, (5, 2), village);
+    assert!(result.is_err());
+    assert!(sim.store.buildings(village).is_empty());
+    assert_eq!(sim.store.resource(ResourceType::Feathers, village), 19);
+}
+
+#[test]
+fn blueprint_is_built_completely_or_not_at_all() {
+    let sim = Simulation::new();
+    let (player, village) = sim.store.add_player_with_village("Tester");
+    sim.store.add_karma(player, 1).unwrap();
+    let player = sim.store.player_by_village(village).unwrap();
+    sim.store
+        .add_resource(ResourceType::Feathers, village, 40)
+        .unwrap();
+    let blueprint = "1:F52F62T15".parse().unwrap();
+
+    // Two flowers and a tree cost 50 feathers
+    let result = try_buy_blueprint(&sim.store, &blueprint, village, &player);
+    assert!(result.is_err());
+    assert!(sim.store.buildings(village).is_empty());
+    assert_eq!(sim.store.resource(ResourceType::Feathers, village), 40);
+
+    // Parts that are already built are not paid again
+    try_buy_building(&sim.store, BuildingType::BlueFlowers, (5, 2), village).unwrap();
+    sim.store
+        .add_resource(ResourceType::Feathers, village, 10)
+        .unwrap();
+    let built = try_buy_blueprint(&sim.store, &blueprint, village, &player).unwrap();
+    assert_eq!(built, vec![BuildingType::BlueFlowers, BuildingType::Tree]);
+    assert_eq!(sim.store.buildings(village).len(), 3);
+    assert_eq!(sim.store.resource(ResourceType::Feathers, village), 0);
+}
+
+#[test]
+fn hired_workers_need_housing() {
+    let sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    sim.store
+        .add_resource(ResourceType::Feathers, village, 100)
+        .unwrap();
+    sim.store
+        .add_resource(ResourceType::Sticks, village, 80)
+        .unwrap();
+    assert!(try_buy_worker(&sim.store, village, UnitColor::Yellow).is_err());
+
+    try_buy_building(&sim.store, BuildingType::Shack, (2, 1), village).unwrap();
+    let worker = try_buy_worker(&sim.store, village, UnitColor::Yellow).unwrap();
+    assert_eq!(worker.unit_type, UnitType::Basic);
+    assert_eq!((worker.x, worker.y), (2, TOWN_LANE_Y as i32));
+    assert!(sim
+        .store
+        .worker_ability(worker.key(), AbilityType::Work)
+        .is_some());
+    assert_eq!(sim.store.worker_tasks(worker.key()).len(), 1);
+    assert_eq!(sim.store.resource(ResourceType::Feathers, village), 40);
+
+    // A shack houses two workers
+    sim.store
+        .add_resource(ResourceType::Feathers, village, 80)
+        .unwrap();
+    sim.store
+        .add_resource(ResourceType::Sticks, village, 20)
+        .unwrap();
+    try_buy_worker(&sim.store, village, UnitColor::Yellow).unwrap();
+    assert!(try_buy_worker(&sim.store, village, UnitColor::Yellow).is_err());
+    assert_eq!(sim.store.workers(village).len(), 2);
+    assert_eq!(sim.store.resource(ResourceType::Feathers, village), 80);
+    assert_eq!(sim.store.resource(ResourceType::Sticks, village), 20);
+}
+
+#[test]
+fn worker_colors_change_speed_and_collection() {
+    let sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    sim.store
+        .add_resource(ResourceType::Feathers, village, 80)
+        .unwrap();
+    sim.store
+        .add_resource(ResourceType::Sticks, village, 40)
+        .unwrap();
+    try_buy_building(&sim.store, BuildingType::Shack, (2, 1), village).unwrap();
+    let camo = try_buy_worker(&sim.store, village, UnitColor::Camo).unwrap();
+    let white = try_buy_worker(&sim.store, village, UnitColor::White).unwrap();
+    assert_eq!(camo.speed, basic_worker_speed() * 1.25);
+    assert_eq!(white.speed, basic_worker_speed());
+
+    for worker in &[&camo, &white] {
+        let mut task = sim.store.current_task(worker.key()).unwrap();
+        task.task_type = TaskType::GatherSticks;
+        sim.store.update_task(&task);
+    }
+    assert_eq!(sim.store.resource(ResourceType::Sticks, village), 0);
+    update_workers(&sim.store, village, sim.store.now() + Duration::hours(1));
+    // Camo workers gather like yellow ones, white workers gather twice as much
+    let (_, rate) = hero_resource_collection_per_hour(TaskType::GatherSticks).unwrap();
+    assert_eq!(
+        sim.store.resource(ResourceType::Sticks, village),
+        3 * rate as i64
+    );
+}
+
+#[test]
+fn worker_walks() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    let worker = sim.store.add_hero(village, (0, 0));
+    sim.advance(Duration::seconds(1));
+
+    let walk = RawTask {
+        task_type: TaskType::Walk,
+        x: 3,
+        y: 0,
+        target: None,
+    };
+    let idle = RawTask {
+        task_type: TaskType::Idle,
+        ..walk
+    };
+    sim.assign_tasks(worker, vec![walk, idle]).unwrap();
+    assert_eq!(sim.store.worker_priv(worker).unwrap().x, 0);
+
+    sim.advance(Duration::minutes(1));
+    let worker = sim.store.worker_priv(worker).unwrap();
+    assert_eq!((worker.x, worker.y), (3, 0));
+    let tasks = sim.store.worker_tasks(worker.key());
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].task_type, TaskType::Idle);
+}
+
+fn forest_size(store: &MemoryStore, village: VillageKey) -> usize {
+    TownView::load_village(store, village).state.forest_size
+}
+
+#[test]
+fn planted_tree_is_chopped_and_dies() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    let worker = sim.store.add_hero(village, (0, 0));
+    sim.advance(Duration::seconds(1));
+
+    let walk = RawTask {
+        task_type: TaskType::Walk,
+        x: 2,
+        y: 0,
+        target: None,
+    };
+    let plant = RawTask {
+        task_type: TaskType::PlantTree,
+        ..walk
+    };
+    let idle = RawTask {
+        task_type: TaskType::Idle,
+        ..walk
+    };
+    sim.assign_tasks(worker, vec![walk.clone(), plant.clone(), idle.clone()])
+        .unwrap();
+    sim.advance(Duration::minutes(1));
+    let planted = sim.store.find_building_by_coordinates(2, 0, village);
+    assert_eq!(planted.map(|b| b.building_type), Some(BuildingType::Tree));
+    assert_eq!(forest_size(&sim.store, village), 1);
+
+    // The sapling blocks the tile but the worker can walk away from it
+    assert!(sim.assign_tasks(worker, vec![plant]).is_err());
+    let back = vec![RawTask { x: 0, ..walk }, RawTask { x: 0, ..idle }];
+    sim.assign_tasks(worker, back).unwrap();
+
+    // Chopping shrinks the tree until it is gone
+    sim.advance(Duration::hours(9));
+    assert_eq!(forest_size(&sim.store, village), 4);
+    assert_eq!(chop_forest(&sim.store, village, 2), 2);
+    assert_eq!(forest_size(&sim.store, village), 2);
+    assert_eq!(chop_forest(&sim.store, village, 5), 2);
+    assert!(sim.store.buildings(village).is_empty());
+
+    // Old trees die
+    let mut sapling = BuildingFactory::new(BuildingType::Tree, (2, 0), village);
+    sapling.creation = sim.store.now();
+    let tree = sim.store.insert_building(&sapling);
+    remove_dead_trees(&sim.store, village);
+    assert_eq!(sim.store.buildings(village).len(), 1);
+    let lifespan = Duration::hours(TREE_LIFESPAN_HOURS);
+    sim.store
+        .update_building_creation(&tree, sim.store.now() - lifespan);
+    remove_dead_trees(&sim.store, village);
+    assert!(sim.store.buildings(village).is_empty());
+}
+
+#[test]
+fn quests_are_checked_and_rewarded() {
+    let sim = Simulation::new();
+    let (player, village) = sim.store.add_player_with_village("Tester");
+    let collect = |quest| collect_quest(&sim.store, &sim.store.player(player).unwrap(), quest);
+    let build = |typ, x| {
+        sim.store
+            .insert_building(&BuildingFactory::new(typ, (x, 0), village));
+    };
+
+    // Quests start after the introduction
+    build(BuildingType::Tree, 0);
+    build(BuildingType::Tree, 1);
+    build(BuildingType::Tree, 2);
+    assert!(collect(QuestName::PlantTrees).is_err());
+    sim.store
+        .set_story_state(player, StoryState::GatheringSticks);
+    assert!(collect(QuestName::WelcomeVisitors).is_err());
+    collect(QuestName::PlantTrees).unwrap();
+    assert_eq!(sim.store.resource(ResourceType::Sticks, village), 20);
+    assert!(collect(QuestName::PlantTrees).is_err());
+    assert_eq!(sim.store.resource(ResourceType::Sticks, village), 20);
+
+    assert!(collect(QuestName::WelcomeVisitors).is_err());
+    sim.store.add_season_stats(player, 0, 10).unwrap();
+    collect(QuestName::WelcomeVisitors).unwrap();
+    assert_eq!(sim.store.resource(ResourceType::Feathers, village), 10);
+    assert_eq!(sim.store.player(player).unwrap().karma, 50);
+
+    // The present is placed on a free tile
+    build(BuildingType::SawMill, 3);
+    collect(QuestName::BuildSawMill).unwrap();
+    let presents: Vec<Building> = sim
+        .store
+        .buildings(village)
+        .into_iter()
+        .filter(|b| b.building_type == BuildingType::PresentA)
+        .collect();
+    assert_eq!(presents.len(), 1);
+    assert_eq!((presents[0].x, presents[0].y), (4, 0));
+    assert_eq!(
+        sim.store.completed_quests(player),
+        vec![
+            QuestName::PlantTrees,
+            QuestName::WelcomeVisitors,
+            QuestName::BuildSawMill
+        ]
+    );
+}
+
+#[test]
+fn quests_teach_abilities_to_all_workers() {
+    let sim = Simulation::new();
+    let (player, village) = sim.store.add_player_with_village("Tester");
+    let collect = |quest| collect_quest(&sim.store, &sim.store.player(player).unwrap(), quest);
+    sim.store
+        .add_resource(ResourceType::Feathers, village, 80)
+        .unwrap();
+    sim.store
+        .add_resource(ResourceType::Sticks, village, 40)
+        .unwrap();
+    try_buy_building(&sim.store, BuildingType::Shack, (2, 1), village).unwrap();
+    let first = try_buy_worker(&sim.store, village, UnitColor::Yellow).unwrap();
+    assert!(sim
+        .store
+        .worker_ability(first.key(), AbilityType::Welcome)
+        .is_none());
+    assert_eq!(first.mana, None);
+
+    sim.store
+        .set_story_state(player, StoryState::GatheringSticks);
+    for x in 0..3 {
+        sim.store
+            .insert_building(&BuildingFactory::new(BuildingType::Tree, (x, 0), village));
+    }
+    collect(QuestName::PlantTrees).unwrap();
+    sim.store.add_season_stats(player, 0, 10).unwrap();
+    collect(QuestName::WelcomeVisitors).unwrap();
+
+    // Workers that are hired later learn the ability, too
+    let second = try_buy_worker(&sim.store, village, UnitColor::Yellow).unwrap();
+    for worker in &[first, second] {
+        assert!(sim
+            .store
+            .worker_ability(worker.key(), AbilityType::Welcome)
+            .is_some());
+        assert_eq!(sim.store.worker_priv(worker.key()).unwrap().mana, Some(0));
+    }
+}
+
+fn routine_step(task_type: TaskType, x: usize, duration: Option<i64>) -> RoutineStep {
+    RoutineStep {
+        task_type,
+        x,
+        y: 0,
+        duration,
+        condition: None,
+    }
+}
+
+#[test]
+fn routine_welcomes_resting_visitors() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    let worker = sim.store.add_hero(village, (0, 0));
+
+    // Right above the resting place, the visitor is in range
+    let welcome = RoutineStep {
+        condition: Some(TaskCondition::RestingVisitor),
+        y: 2,
+        ..routine_step(TaskType::WelcomeAbility, 4, None)
+    };
+    sim.assign_routine(
+        worker,
+        vec![
+            routine_step(TaskType::Walk, 3, None),
+            routine_step(TaskType::Idle, 3, Some(30)),
+            routine_step(TaskType::Walk, 0, None),
+            welcome,
+        ],
+    )
+    .unwrap();
+
+    // Without visitors, the welcome step is skipped and the worker keeps walking back and forth
+    sim.advance(Duration::seconds(20));
+    let tasks = sim.store.worker_tasks(worker);
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].task_type, TaskType::Idle);
+    assert_eq!(sim.store.worker_priv(worker).unwrap().mana, Some(10));
+
+    sim.send_visitors(village, &[visitor(village, 1, false)]);
+    sim.advance(Duration::minutes(10));
+    assert!(sim.store.attacks(village, None).is_empty());
+    let reports = sim.collect_reports(village);
+    assert_eq!(reports.len(), 1);
+    assert_eq!(sim.store.worker_priv(worker).unwrap().mana, Some(5));
+    assert!(sim.store.worker_routine(worker).is_some());
+}
+
+#[test]
+fn routine_walks_to_its_steps() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    let worker = sim.store.add_hero(village, (0, 0));
+    let idle = RoutineStep {
+        y: 2,
+        ..routine_step(TaskType::Idle, 2, Some(60))
+    };
+    sim.assign_routine(worker, vec![idle]).unwrap();
+    sim.advance(Duration::seconds(1));
+
+    // The step is not next to the worker, which walks there first
+    let tasks = sim.store.worker_tasks(worker);
+    assert!(tasks.iter().any(|t| t.task_type == TaskType::Walk));
+    let last = tasks.last().unwrap();
+    assert_eq!((last.task_type, last.x, last.y), (TaskType::Idle, 2, 2));
+
+    sim.advance(Duration::seconds(30));
+    let worker = sim.store.worker_priv(worker).unwrap();
+    assert_eq!((worker.x, worker.y), (2, 2));
+}
+
+#[test]
+fn visitors_out_of_range_cannot_be_welcomed() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    let worker = sim.store.add_hero(village, (0, 0));
+    sim.send_visitors(village, &[visitor(village, 1, false)]);
+    let arrival = sim.store.attacks(village, None)[0].arrival;
+    sim.advance(arrival - sim.store.now() + Duration::seconds(25));
+    let (hobo, _attack) = sim.store.resting_visitors(village).remove(0);
+
+    let welcome = RawTask {
+        task_type: TaskType::WelcomeAbility,
+        x: 0,
+        y: 0,
+        target: Some(hobo.id),
+    };
+    assert!(sim.assign_tasks(worker, vec![welcome]).is_err());
+
+    let walk = RawTask {
+        task_type: TaskType::Walk,
+        x: 4,
+        y: 0,
+        target: None,
+    };
+    let closer = vec![
+        walk,
+        RawTask { y: 2, ..walk },
+        RawTask {
+            x: 4,
+            y: 2,
+            ..welcome
+        },
+    ];
+    sim.assign_tasks(worker, closer).unwrap();
+}
+
+#[test]
+fn new_tasks_end_routine() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    let worker = sim.store.add_hero(village, (0, 0));
+    sim.assign_routine(worker, vec![routine_step(TaskType::Idle, 0, Some(60))])
+        .unwrap();
+    sim.advance(Duration::seconds(1));
+
+    let walk = RawTask {
+        task_type: TaskType::Walk,
+        x: 3,
+        y: 0,
+        target: None,
+    };
+    let idle = RawTask {
+        task_type: TaskType::Idle,
+        ..walk
+    };
+    sim.assign_tasks(worker, vec![walk, idle]).unwrap();
+    assert!(sim.store.worker_routine(worker).is_none());
+
+    sim.advance(Duration::minutes(2));
+    let worker = sim.store.worker_priv(worker).unwrap();
+    assert_eq!((worker.x, worker.y), (3, 0));
+    let tasks = sim.store.worker_tasks(worker.key());
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].task_type, TaskType::Idle);
+}
+
+#[test]
+fn routine_steps_are_validated() {
+    let mut sim = Simulation::new();
+    let (_player, village) = sim.store.add_player_with_village("Tester");
+    let worker = sim.store.add_hero(village, (0, 0));
+
+    // Working forever would never get to the next step
+    let gather = routine_step(TaskType::GatherSticks, 0, None);
+    assert!(sim.assign_routine(worker, vec![gather]).is_err());
+    // Abilities need a condition to choose their target
+    let welcome = routine_step(TaskType::WelcomeAbility, 0, None);
+    assert!(sim.assign_routine(worker, vec![welcome]).is_err());
+    assert!(sim.assign_routine(worker, vec![]).is_err());
+    assert!(sim.store.worker_routine(worker).is_none());
+}
+
+#[test]
+fn messages_are_rate_limited_and_marked_read() {
+    let mut sim = Simulation::new();
+    let (alice, alice_village) = sim.store.add_player_with_village("Alice");
+    let (bob, bob_village) = sim.store.add_player_with_village("Bob");
+    let letter = |body: &str| MessageSend {
+        recipient: bob_village,
+        body: body.to_owned(),
+    };
+
+    assert!(try_send_message(&sim.store, alice, letter("  ")).is_err());
+    let to_self = MessageSend {
+        recipient: alice_village,
+        body: "Hi".to_owned(),
+    };
+    assert!(try_send_message(&sim.store, alice, to_self).is_err());
+
+    for _ in 0..5 {
+        assert!(!message_rate_limit_reached(&sim.store, alice));
+        try_send_message(&sim.store, alice, letter("Hi Bob")).unwrap();
+    }
+    assert!(message_rate_limit_reached(&sim.store, alice));
+    assert!(!message_rate_limit_reached(&sim.store, bob));
+    // The limit is measured in game time
+    sim.advance(Duration::seconds(61));
+    assert!(!message_rate_limit_reached(&sim.store, alice));
+
+    let bobs: Vec<MessageKey> = sim
+        .store
+        .received_messages(bob)
+        .iter()
+        .map(|m| m.key())
+        .collect();
+    assert_eq!(bobs.len(), 5);
+
+    // Alice cannot mark Bob's messages, and a single foreign message rejects the whole list
+    assert!(try_mark_messages_read(&sim.store, alice, &bobs).is_err());
+    let mut with_foreign = bobs.clone();
+    with_foreign.push(MessageKey(bob.num()));
+    assert!(try_mark_messages_read(&sim.store, bob, &with_foreign).is_err());
+    assert!(bobs.iter().all(|m| !sim.store.message(*m).unwrap().read));
+
+    try_mark_messages_read(&sim.store, bob, &bobs).unwrap();
+    assert!(bobs.iter().all(|m| sim.store.message(*m).unwrap().read));
+}
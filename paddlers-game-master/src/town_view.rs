@@ -1,7 +1,8 @@
-use crate::db::DB;
-use paddlers_shared_lib::game_mechanics::forestry::tree_size;
+use crate::db::store::GameStore;
+use paddlers_shared_lib::game_mechanics::forestry::Forest;
 use paddlers_shared_lib::game_mechanics::town::*;
 use paddlers_shared_lib::prelude::*;
+use std::collections::{HashMap, VecDeque};
 
 /// Game master state of a town.
 /// It is usually loaded from the database before doing some complex checks which repeatedly require the town state.
@@ -15,13 +16,14 @@ pub struct TownView {
 }
 
 impl TownView {
-    pub(crate) fn load_village(db: &DB, village: VillageKey) -> Self {
+    pub(crate) fn load_village<S: GameStore>(db: &S, village: VillageKey) -> Self {
         let mut map = TownMap::new(TownLayout::Basic);
         let mut state = TownState::new();
-        let now = chrono::Utc::now().naive_utc();
+        let now = db.now();
 
         let buildings = db.buildings(village);
         let mut buildings_with_aura = vec![];
+        let mut forest = Forest::new();
         for b in buildings {
             let idx = (b.x as usize, b.y as usize);
             map[idx] = TownTileType::BUILDING(b.building_type);
@@ -33,11 +35,9 @@ impl TownView {
             };
             let entity_count = db.count_workers_at_pos_doing_job(village, b.x, b.y, task_type);
             state.insert(idx, TileState::new_building(b.id, capacity, entity_count));
-            let forest_supply = match b.building_type {
-                BuildingType::Tree => tree_size(now - b.creation),
-                _ => 0,
-            };
-            state.forest_size += forest_supply;
+            if b.building_type == BuildingType::Tree {
+                forest.add_tree(b.id, now - b.creation);
+            }
             if b.attacks_per_cycle.is_none()
                 && b.building_range.is_some()
                 && b.attack_power.is_some()
@@ -46,12 +46,12 @@ impl TownView {
             }
         }
 
+        state.forest_size = forest.size();
+
         let workers = db.workers(village);
         for worker in workers {
             if let Some(task) = db.current_task(worker.key()) {
-                state
-                    .register_task_begin(task.task_type)
-                    .expect("Current DB state invalid");
+                state.register_ongoing_task(task.task_type);
             } else {
                 println!("Warning: worker without task: {:?}", worker);
             }
@@ -79,11 +79,69 @@ impl TownView {
         }
         let mut pos = start;
         while pos != end {
-            if !self.map[pos].is_walkable() {
+            // Leaving the current tile is always possible, even if a tree has been planted on it
+            if pos != start && !self.map[pos].is_walkable() {
                 return false;
             }
             pos = ((pos.0 as i32 + dx) as usize, (pos.1 as i32 + dy) as usize)
         }
         true
     }
+    /// Shortest way from one tile to another, as the corners at which a worker changes direction.
+    /// The destination is the last corner, the start is not included.
+    pub(crate) fn walking_route(&self, start: TileIndex, end: TileIndex) -> Option<Vec<TileIndex>> {
+        let mut previous = HashMap::new();
+        let mut queue = VecDeque::new();
+        previous.insert(start, start);
+        queue.push_back(start);
+        while let Some(pos) = queue.pop_front() {
+            if pos == end {
+                break;
+            }
+            for next in neighbours(pos) {
+                if self.map[next].is_walkable() && !previous.contains_key(&next) {
+                    previous.insert(next, pos);
+                    queue.push_back(next);
+                }
+            }
+        }
+        previous.get(&end)?;
+
+        let mut path = vec![end];
+        let mut pos = end;
+        while pos != start {
+            pos = previous[&pos];
+            path.push(pos);
+        }
+        path.reverse();
+        // Only keep the tiles where the direction changes, each leg is a straight line
+        let mut corners = vec![];
+        for leg in path.windows(3) {
+            let (a, b, c) = (leg[0], leg[1], leg[2]);
+            if (a.0 == b.0) != (b.0 == c.0) {
+                corners.push(b);
+            }
+        }
+        if start != end {
+            corners.push(end);
+        }
+        Some(corners)
+    }
+}
+
+fn neighbours((x, y): TileIndex) -> Vec<TileIndex> {
+    let mut tiles = vec![];
+    if x > 0 {
+        tiles.push((x - 1, y));
+    }
+    if x + 1 < TOWN_X {
+        tiles.push((x + 1, y));
+    }
+    if y > 0 {
+        tiles.push((x, y - 1));
+    }
+    if y + 1 < TOWN_Y {
+        tiles.push((x, y + 1));
+    }
+    tiles
 }
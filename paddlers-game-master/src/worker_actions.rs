@@ -4,16 +4,23 @@
 //! For now, I am still don't really know how I want it to look like.
 
 mod worker_abilities;
+mod worker_routines;
 mod worker_updates;
 
+pub(crate) use worker_routines::{continue_routine, validate_routine};
+
+use crate::buildings::BuildingFactory;
+use crate::db::store::GameStore;
 use crate::db::DB;
 use crate::game_master::event::*;
+use crate::game_master::town_defence::maybe_evaluate_attack;
 use crate::game_master::town_worker::*;
 use crate::town_view::*;
 use actix::prelude::*;
 use chrono::offset::TimeZone;
 use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use paddlers_shared_lib::api::tasks::*;
+use paddlers_shared_lib::game_mechanics::town::TownTileType;
 use paddlers_shared_lib::game_mechanics::worker::*;
 use paddlers_shared_lib::prelude::*;
 use worker_abilities::*;
@@ -29,9 +36,11 @@ pub struct ValidatedTaskList {
     pub new_tasks: Vec<NewTask>,
     pub update_tasks: Vec<Task>,
     pub village_id: VillageKey,
+    /// When the worker is done with the last of the new tasks
+    pub end_time: NaiveDateTime,
 }
-pub(crate) fn validate_task_list(
-    db: &DB,
+pub(crate) fn validate_task_list<S: GameStore>(
+    db: &S,
     tl: &TaskList,
 ) -> Result<ValidatedTaskList, Box<dyn std::error::Error>> {
     let worker_id = tl.worker_id;
@@ -45,8 +54,8 @@ pub(crate) fn validate_task_list(
     let mut current_task = db
         .current_task(worker.key())
         .expect("Must have a current task");
-    let mut timestamp =
-        interrupt_task(&mut current_task, &worker).ok_or("Cannot interrupt current task.")?;
+    let mut timestamp = interrupt_task(&mut current_task, &worker, db.now())
+        .ok_or("Cannot interrupt current task.")?;
     worker.x = current_task.x;
     worker.y = current_task.y;
 
@@ -59,7 +68,13 @@ pub(crate) fn validate_task_list(
             db.hobo(HoboKey(target_id)).ok_or("No such hobo id")?;
         }
 
-        validate_ability(db, task.task_type, worker_id, timestamp)?;
+        validate_ability(
+            db,
+            task.task_type,
+            &worker,
+            task.target.map(HoboKey),
+            timestamp,
+        )?;
 
         let new_task = NewTask {
             worker_id: worker_id.num(),
@@ -78,6 +93,7 @@ pub(crate) fn validate_task_list(
         new_tasks: tasks,
         update_tasks: vec![current_task],
         village_id,
+        end_time: timestamp,
     })
 }
 pub(crate) fn replace_worker_tasks(
@@ -87,44 +103,78 @@ pub(crate) fn replace_worker_tasks(
     tasks: &[NewTask],
     village_id: VillageKey,
 ) {
+    // New orders end the routine of the worker
+    db.delete_worker_routine(worker_id);
+    if let Some((event, time)) = replace_tasks(db, worker_id, tasks, village_id) {
+        worker
+            .send(TownWorkerEventMsg(event, time))
+            .wait()
+            .expect("Send msg to actor");
+    }
+}
+/// Stores a validated routine and lets the worker begin with its first step
+pub(crate) fn start_worker_routine(
+    db: &DB,
+    worker: &Addr<TownWorker>,
+    routine: &WorkerRoutine,
+    steps: &[RoutineTask],
+) -> diesel::QueryResult<()> {
+    db.insert_worker_routine(routine, steps)?;
+    let event = Event::WorkerRoutine {
+        worker_id: WorkerKey(routine.worker_id),
+    };
+    worker
+        .send(TownWorkerEventMsg(
+            event,
+            Utc.from_utc_datetime(&routine.next_step_time),
+        ))
+        .wait()
+        .expect("Send msg to actor");
+    Ok(())
+}
+/// Replaces all future tasks of a worker and returns the event that finishes the current task
+pub(crate) fn replace_tasks<S: GameStore>(
+    db: &S,
+    worker_id: WorkerKey,
+    tasks: &[NewTask],
+    village_id: VillageKey,
+) -> Option<(Event, DateTime<Utc>)> {
     db.flush_task_queue(worker_id);
     let _inserted = db.insert_tasks(tasks);
     let current_task =
         execute_worker_tasks(db, worker_id, village_id).expect("Worker has no current task");
-    if let Some(next_task) = db.earliest_future_task(worker_id) {
+    db.earliest_future_task(worker_id).map(|next_task| {
         let event = Event::WorkerTask {
             task_id: current_task.key(),
         };
-        worker
-            .send(TownWorkerEventMsg(
-                event,
-                Utc.from_utc_datetime(&next_task.start_time),
-            ))
-            .wait()
-            .expect("Send msg to actor");
-    }
+        (event, Utc.from_utc_datetime(&next_task.start_time))
+    })
 }
 
-fn interrupt_task(current_task: &mut Task, worker: &Worker) -> Option<NaiveDateTime> {
+fn interrupt_task(
+    current_task: &mut Task,
+    worker: &Worker,
+    now: NaiveDateTime,
+) -> Option<NaiveDateTime> {
     match current_task.task_type {
         TaskType::Idle
         | TaskType::ChopTree
         | TaskType::Defend
         | TaskType::GatherSticks
-        | TaskType::CollectReward => {
-            let now = chrono::Utc::now().naive_utc();
-            Some(now)
-        }
+        | TaskType::CollectReward
+        | TaskType::PlantTree => Some(now),
         TaskType::Walk => {
             let speed = unit_speed_to_worker_tiles_per_second(worker.speed) as f64;
-            let time_so_far: Duration = Utc::now().naive_utc() - current_task.start_time;
-            let steps = (speed * time_so_far.num_microseconds().unwrap() as f64 / 1_000_000.0)
-                .ceil() as i32;
+            let time_so_far: Duration = now - current_task.start_time;
+            let dx = current_task.x - worker.x;
+            let dy = current_task.y - worker.y;
+            // A worker that already arrived cannot walk any further
+            let steps = ((speed * time_so_far.num_microseconds().unwrap() as f64 / 1_000_000.0)
+                .ceil() as i32)
+                .min(dx.abs() + dy.abs());
             let total_time = steps as f64 / speed;
             let moment = current_task.start_time
                 + chrono::Duration::microseconds((total_time * 1_000_000.0) as i64);
-            let dx = current_task.x - worker.x;
-            let dy = current_task.y - worker.y;
             let x = if dx == 0 {
                 worker.x
             } else if dx < 0 {
@@ -142,7 +192,7 @@ fn interrupt_task(current_task: &mut Task, worker: &Worker) -> Option<NaiveDateT
             // Walking must terminate earlier
             current_task.x = x;
             current_task.y = y;
-            Some(moment)
+            Some(moment.max(now))
         }
         TaskType::WelcomeAbility => {
             let cast_time = current_task.start_time + AbilityType::Welcome.busy_duration();
@@ -152,7 +202,11 @@ fn interrupt_task(current_task: &mut Task, worker: &Worker) -> Option<NaiveDateT
 }
 
 /// For the given worker, executes tasks on the DB that are due
-fn execute_worker_tasks(db: &DB, worker_id: WorkerKey, village: VillageKey) -> Option<Task> {
+fn execute_worker_tasks<S: GameStore>(
+    db: &S,
+    worker_id: WorkerKey,
+    village: VillageKey,
+) -> Option<Task> {
     let mut tasks = db.past_worker_tasks(worker_id);
     let current_task = tasks.pop();
     let mut town = TownView::load_village(db, village);
@@ -164,8 +218,8 @@ fn execute_worker_tasks(db: &DB, worker_id: WorkerKey, village: VillageKey) -> O
     current_task
 }
 
-pub(crate) fn finish_task(
-    db: &DB,
+pub(crate) fn finish_task<S: GameStore>(
+    db: &S,
     task_id: TaskKey,
     task: Option<Task>,
     town: Option<&mut TownView>,
@@ -195,18 +249,23 @@ pub(crate) fn finish_task(
     }
 }
 
-fn apply_task_to_db(db: &DB, task: &Task, worker: &mut Worker) -> Result<(), String> {
+fn apply_task_to_db<S: GameStore>(db: &S, task: &Task, worker: &mut Worker) -> Result<(), String> {
     match task.task_type {
         TaskType::WelcomeAbility => {
             let a = AbilityType::Welcome;
             let (attribute, strength) = a.apply();
+            let target = task.target().ok_or("Ability must have a target")?;
             let ne = NewEffect {
-                hobo_id: task.target().ok_or("Ability must have a target")?.num(),
+                hobo_id: target.num(),
                 attribute,
                 strength: Some(strength),
-                start_time: None, // default = now
+                start_time: Some(db.now()),
             };
             db.insert_effect(&ne);
+            // The visitor may be satisfied now, which is not covered by the checks scheduled for the visit
+            for (atk, _info) in db.hobo_attack_info(target) {
+                maybe_evaluate_attack(db, &atk, db.now());
+            }
             db.update_ability_used_timestamp(WorkerKey(worker.id), a);
             *worker.mana.as_mut().unwrap() -= AbilityType::Welcome.mana_cost();
         }
@@ -228,6 +287,21 @@ fn apply_task_to_db(db: &DB, task: &Task, worker: &mut Worker) -> Result<(), Str
                 return Err(format!("No reward to collect at {},{}", task.x, task.y));
             }
         }
+        TaskType::PlantTree => {
+            if db
+                .find_building_by_coordinates(task.x, task.y, worker.home())
+                .is_some()
+            {
+                return Err(format!("No space to plant a tree at {},{}", task.x, task.y));
+            }
+            let mut sapling = BuildingFactory::new(
+                BuildingType::Tree,
+                (task.x as usize, task.y as usize),
+                worker.home(),
+            );
+            sapling.creation = db.now();
+            db.insert_building(&sapling);
+        }
         _ => { /* NOP */ }
     }
     Ok(())
@@ -266,6 +340,12 @@ fn simulate_finish_task<T: WorkerAction>(
             town.state.remove(&index);
             Ok(Duration::milliseconds(0))
         }
+        TaskType::PlantTree => {
+            // The sapling blocks the tile for later tasks, its size is negligible
+            let index = (task.x() as usize, task.y() as usize);
+            town.map[index] = TownTileType::BUILDING(BuildingType::Tree);
+            Ok(Duration::milliseconds(0))
+        }
         TaskType::Defend => Err("Task not implemented".to_owned()),
     }
 }
@@ -278,6 +358,13 @@ fn simulate_begin_task<T: WorkerAction>(
 ) -> Result<(), String> {
     match task.task_type() {
         TaskType::Idle | TaskType::Walk | TaskType::CollectReward => Ok(()),
+        TaskType::PlantTree => {
+            let index = (task.x() as usize, task.y() as usize);
+            match town.map.tile_type(index) {
+                Some(tile) if tile.is_buildable() => Ok(()),
+                _ => Err("Cannot plant a tree here".to_owned()),
+            }
+        }
         TaskType::GatherSticks | TaskType::ChopTree => {
             town.state
                 .register_task_begin(*task.task_type())
@@ -1,4 +1,4 @@
-use crate::db::DB;
+use crate::db::store::GameStore;
 use crate::town_view::*;
 use chrono::Duration;
 use paddlers_shared_lib::game_mechanics::{town::*, worker::*};
@@ -39,18 +39,26 @@ pub fn worker_into_building(
     tile_state.try_add_entity().map_err(|e| e.to_string())?;
     Ok(())
 }
-pub(super) fn validate_ability(
-    db: &DB,
+/// Where unhurried visitors wait to be welcomed
+pub(super) const RESTING_PLACE: TileIndex = (TOWN_RESTING_X, TOWN_LANE_Y);
+
+/// Checks that the worker can use the ability of the task right now, on the target if there is one
+pub(super) fn validate_ability<S: GameStore>(
+    db: &S,
     task_type: TaskType,
-    worker_id: WorkerKey,
+    worker: &Worker,
+    target: Option<HoboKey>,
     now: chrono::NaiveDateTime,
 ) -> Result<(), String> {
     if let Some(ability_type) = AbilityType::from_task(&task_type) {
-        // TODO: Range checks
-        // let range = ability_type.range();
-        // TODO: Take movement of visitor into account
-
-        if let Some(a) = db.worker_ability(worker_id, ability_type) {
+        if let Some(hobo) = target {
+            let from = (worker.x as usize, worker.y as usize);
+            let to = visitor_tile(db, worker.home(), hobo, from);
+            if !in_ability_range(ability_type, from, to) {
+                return Err("Visitor is out of range".to_owned());
+            }
+        }
+        if let Some(a) = db.worker_ability(worker.key(), ability_type) {
             if let Some(last_used) = a.last_used {
                 let free_to_use = last_used + ability_type.cooldown();
                 if free_to_use > now {
@@ -63,3 +71,28 @@ pub(super) fn validate_ability(
     }
     Ok(())
 }
+
+pub(super) fn in_ability_range(ability: AbilityType, from: TileIndex, to: TileIndex) -> bool {
+    let range = ability.range();
+    distance2(from, to) <= range * range
+}
+
+/// The tile on which a visitor can be reached.
+/// Only resting visitors stay on one tile, the others are assumed to swim past the worker on the lane.
+fn visitor_tile<S: GameStore>(
+    db: &S,
+    village: VillageKey,
+    hobo: HoboKey,
+    worker_tile: TileIndex,
+) -> TileIndex {
+    // TODO: Take movement of visitor into account
+    let resting = db
+        .resting_visitors(village)
+        .iter()
+        .any(|(visitor, _)| visitor.key() == hobo);
+    if resting {
+        RESTING_PLACE
+    } else {
+        (worker_tile.0, TOWN_LANE_Y)
+    }
+}
@@ -0,0 +1,233 @@
+//! Routines are cycles of tasks that a worker repeats until it gets new orders.
+//!
+//! Only one step of a routine is turned into a concrete `Task` at a time, when the step begins.
+//! This way, conditions are checked against the village as it is at that moment.
+
+use super::worker_abilities::{in_ability_range, validate_ability, RESTING_PLACE};
+use super::{interrupt_task, replace_tasks, validate_task_list};
+use crate::db::store::GameStore;
+use crate::game_master::event::Event;
+use crate::town_view::TownView;
+use chrono::offset::TimeZone;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use paddlers_shared_lib::api::tasks::*;
+use paddlers_shared_lib::game_mechanics::town::{TOWN_X, TOWN_Y};
+use paddlers_shared_lib::prelude::*;
+
+/// Shortest time a worker spends on a step, which prevents routines from spinning
+const MIN_STEP_SECONDS: i64 = 1;
+/// How long a worker waits before checking its routine again, when no step could be performed
+const ROUTINE_RETRY_SECONDS: i64 = 60;
+
+/// Checks a routine sent by a player and converts it for storage.
+/// The first step is due immediately.
+pub(crate) fn validate_routine<S: GameStore>(
+    db: &S,
+    routine: &RoutineDefinition,
+) -> Result<(WorkerRoutine, Vec<RoutineTask>), String> {
+    let worker_id = routine.worker_id;
+    if routine.steps.is_empty() {
+        return Err("Routine has no steps".to_owned());
+    }
+    if routine.steps.len() > MAX_ROUTINE_STEPS {
+        return Err(format!("Routine has more than {} steps", MAX_ROUTINE_STEPS));
+    }
+    db.worker_priv(worker_id).ok_or("Worker does not exist")?;
+
+    let mut steps = vec![];
+    for (i, step) in routine.steps.iter().enumerate() {
+        if step.x >= TOWN_X || step.y >= TOWN_Y {
+            return Err(format!("Step {} is outside of the town", i));
+        }
+        match step.duration {
+            Some(seconds) if seconds < 0 => {
+                return Err(format!("Step {} has a negative duration", i));
+            }
+            _ => {}
+        }
+        match step.task_type {
+            TaskType::Walk => {}
+            TaskType::Idle | TaskType::GatherSticks | TaskType::ChopTree => {
+                if step.duration.unwrap_or(0) == 0 {
+                    return Err(format!("Step {} must have a duration", i));
+                }
+            }
+            TaskType::WelcomeAbility => {
+                if step.condition.is_none() {
+                    return Err(format!("Step {} needs a condition to pick a target", i));
+                }
+            }
+            TaskType::CollectReward | TaskType::PlantTree | TaskType::Defend => {
+                return Err(format!("{} cannot be part of a routine", step.task_type));
+            }
+        }
+        if let Some(ability) = AbilityType::from_task(&step.task_type) {
+            db.worker_ability(worker_id, ability)
+                .ok_or("Worker does not have this ability")?;
+        }
+        steps.push(RoutineTask {
+            worker_id: worker_id.num(),
+            step: i as i32,
+            task_type: step.task_type,
+            x: step.x as i32,
+            y: step.y as i32,
+            duration: step.duration,
+            task_condition: step.condition,
+        });
+    }
+    let routine = WorkerRoutine {
+        worker_id: worker_id.num(),
+        next_step: 0,
+        next_step_time: db.now(),
+    };
+    Ok((routine, steps))
+}
+
+/// Begins the next step of a routine that can be performed, if the routine is due.
+/// Returns the event that continues the routine afterwards, and the event that finishes the interrupted task.
+pub(crate) fn continue_routine<S: GameStore>(
+    db: &S,
+    worker_id: WorkerKey,
+) -> Vec<(Event, DateTime<Utc>)> {
+    let mut routine = match db.worker_routine(worker_id) {
+        Some(routine) => routine,
+        None => return vec![],
+    };
+    let now = db.now();
+    if routine.next_step_time > now {
+        // Outdated event of a routine that has been replaced in the meantime
+        return vec![];
+    }
+    let steps = db.routine_tasks(worker_id);
+    if steps.is_empty() {
+        db.delete_worker_routine(worker_id);
+        return vec![];
+    }
+
+    let mut events = vec![];
+    let mut step_end = None;
+    for _ in 0..steps.len() {
+        let step = &steps[routine.next_step as usize % steps.len()];
+        routine.next_step = (routine.next_step + 1) % steps.len() as i32;
+        match begin_step(db, worker_id, step) {
+            Ok(Some((end, task_event))) => {
+                step_end = Some(end);
+                events.extend(task_event);
+                break;
+            }
+            Ok(None) => { /* Condition not met, try the next step */ }
+            Err(e) => println!("Skipping step {} of routine: {}", step.step, e),
+        }
+    }
+    routine.next_step_time = match step_end {
+        Some(end) => end.max(now + Duration::seconds(MIN_STEP_SECONDS)),
+        None => now + Duration::seconds(ROUTINE_RETRY_SECONDS),
+    };
+    db.update_worker_routine(&routine);
+    events.push((
+        Event::WorkerRoutine { worker_id },
+        Utc.from_utc_datetime(&routine.next_step_time),
+    ));
+    events
+}
+
+/// Replaces the tasks of the worker with the task described by the step, walking there first if necessary.
+/// Returns when the worker is done with the step and the event that finishes the interrupted task,
+/// or None if the step should be skipped right now.
+fn begin_step<S: GameStore>(
+    db: &S,
+    worker_id: WorkerKey,
+    step: &RoutineTask,
+) -> Result<Option<(NaiveDateTime, Option<(Event, DateTime<Utc>)>)>, Box<dyn std::error::Error>> {
+    let worker = db.worker_priv(worker_id).ok_or("Worker does not exist")?;
+    let destination = (step.x as usize, step.y as usize);
+    let target = match step.task_condition {
+        Some(condition) => match condition_target(db, worker.home(), condition, step) {
+            Some(hobo) => Some(hobo),
+            None => return Ok(None),
+        },
+        None => None,
+    };
+    if step.task_type == TaskType::WelcomeAbility {
+        // Waiting for mana and cooldown is part of the routine, not an error
+        let mut at_step = worker.clone();
+        at_step.x = step.x;
+        at_step.y = step.y;
+        let mana = worker.mana.unwrap_or(0);
+        if mana < AbilityType::Welcome.mana_cost()
+            || validate_ability(db, step.task_type, &at_step, target, db.now()).is_err()
+        {
+            return Ok(None);
+        }
+    }
+
+    // The worker starts where it is when the current task is interrupted
+    let mut current_task = db
+        .current_task(worker_id)
+        .ok_or("Worker has no current task")?;
+    interrupt_task(&mut current_task, &worker, db.now()).ok_or("Cannot interrupt current task.")?;
+    let start = (current_task.x as usize, current_task.y as usize);
+    let town = TownView::load_village(db, worker.home());
+    let route = town
+        .walking_route(start, destination)
+        .ok_or("Step cannot be reached")?;
+    let mut tasks: Vec<RawTask> = route
+        .into_iter()
+        .map(|(x, y)| RawTask {
+            task_type: TaskType::Walk,
+            x,
+            y,
+            target: None,
+        })
+        .collect();
+    if step.task_type != TaskType::Walk || tasks.is_empty() {
+        tasks.push(RawTask {
+            task_type: step.task_type,
+            x: destination.0,
+            y: destination.1,
+            target: target
+                .filter(|_| step.task_type == TaskType::WelcomeAbility)
+                .map(|hobo| hobo.num()),
+        });
+    }
+    let tl = TaskList { worker_id, tasks };
+    let validated = validate_task_list(db, &tl)?;
+    for upd in &validated.update_tasks {
+        db.update_task(upd);
+    }
+    // The interrupted task is finished when the worker actually stops with it
+    let task_event = replace_tasks(db, worker_id, &validated.new_tasks, validated.village_id);
+    let duration = Duration::seconds(step.duration.unwrap_or(0));
+    Ok(Some((validated.end_time + duration, task_event)))
+}
+
+/// Looks for a visitor in the village that satisfies the condition and that can be reached from the step
+fn condition_target<S: GameStore>(
+    db: &S,
+    village: VillageKey,
+    condition: TaskCondition,
+    step: &RoutineTask,
+) -> Option<HoboKey> {
+    // Conditions only look at resting visitors, which all wait at the same place
+    if let Some(ability) = AbilityType::from_task(&step.task_type) {
+        let from = (step.x as usize, step.y as usize);
+        if ability == AbilityType::Welcome && !in_ability_range(ability, from, RESTING_PLACE) {
+            return None;
+        }
+    }
+    let mut visitors = db
+        .resting_visitors(village)
+        .into_iter()
+        .map(|(hobo, _attack)| hobo.key());
+    match condition {
+        TaskCondition::RestingVisitor => visitors.next(),
+        TaskCondition::UnwelcomedVisitor => {
+            let (welcome, _strength) = AbilityType::Welcome.apply();
+            visitors.find(|hobo| {
+                db.effects_on_hobo(*hobo)
+                    .iter()
+                    .all(|effect| effect.attribute != welcome)
+            })
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Player activity levels.
+//!
+//! Villages of inactive players are simulated less frequently and no longer attacked by anarchists.
+//! After a long time without any activity, villages are abandoned and taken over by anarchists.
+
+use chrono::{Duration, NaiveDateTime};
+
+/// Minimal time between two updates of the last activity of a player
+pub const ACTIVITY_RECORD_INTERVAL_MINUTES: i64 = 10;
+pub const INACTIVE_AFTER_DAYS: i64 = 7;
+pub const ABANDONED_AFTER_DAYS: i64 = 60;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ActivityLevel {
+    Active,
+    Inactive,
+    Abandoned,
+}
+
+impl ActivityLevel {
+    pub fn of(last_active: NaiveDateTime, now: NaiveDateTime) -> Self {
+        let idle = now - last_active;
+        if idle >= Duration::days(ABANDONED_AFTER_DAYS) {
+            ActivityLevel::Abandoned
+        } else if idle >= Duration::days(INACTIVE_AFTER_DAYS) {
+            ActivityLevel::Inactive
+        } else {
+            ActivityLevel::Active
+        }
+    }
+}
+
+/// Whether the stored last activity is outdated enough to be worth a DB update
+pub fn should_record_activity(last_active: NaiveDateTime, now: NaiveDateTime) -> bool {
+    now - last_active >= Duration::minutes(ACTIVITY_RECORD_INTERVAL_MINUTES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn activity_levels() {
+        let now = NaiveDate::from_ymd(2020, 6, 10).and_hms(12, 0, 0);
+        let level = |days| ActivityLevel::of(now - Duration::days(days), now);
+        assert_eq!(level(0), ActivityLevel::Active);
+        assert_eq!(level(INACTIVE_AFTER_DAYS - 1), ActivityLevel::Active);
+        assert_eq!(level(INACTIVE_AFTER_DAYS), ActivityLevel::Inactive);
+        assert_eq!(level(ABANDONED_AFTER_DAYS - 1), ActivityLevel::Inactive);
+        assert_eq!(level(ABANDONED_AFTER_DAYS), ActivityLevel::Abandoned);
+    }
+}
@@ -1,6 +1,9 @@
+pub mod admin;
 pub mod attacks;
 pub mod error;
+pub mod hobos;
 pub mod keys;
+pub mod messages;
 pub mod reports;
 pub mod shop;
 pub mod statistics;
@@ -12,4 +15,30 @@ use serde::*;
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PlayerInitData {
     pub display_name: String,
+    /// Code of the player that invited the new player, the new village is placed close to theirs
+    #[serde(default)]
+    pub referral_code: Option<String>,
+}
+
+/// Longest display name a player can have, in characters
+pub const MAX_DISPLAY_NAME_LENGTH: usize = 50;
+
+/// Trims the name and checks that it can be used as display name of a player
+pub fn check_display_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        Err("Empty name".to_owned())
+    } else if name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+        Err(format!(
+            "Name too long, at most {} characters allowed",
+            MAX_DISPLAY_NAME_LENGTH
+        ))
+    } else {
+        Ok(name)
+    }
+}
+
+/// Referral codes are not case sensitive, this brings them into the form in which they are stored
+pub fn normalize_referral_code(code: &str) -> String {
+    code.trim().to_uppercase()
 }
@@ -0,0 +1,98 @@
+//! Shared data for network transmission of administrative requests.
+//!
+//! These requests are only accepted from users with the admin role.
+
+use crate::prelude::*;
+use crate::story::story_state::StoryState;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Name of the realm role that grants access to the admin API
+pub const ADMIN_ROLE: &str = "paddlers-admin";
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum PlayerSelector {
+    Id(PlayerKey),
+    Name(String),
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct InspectPlayer {
+    pub player: PlayerSelector,
+}
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct InspectVillage {
+    pub village: VillageKey,
+}
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct GrantResources {
+    pub village: VillageKey,
+    pub resources: Vec<(ResourceType, i64)>,
+}
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct GrantKarma {
+    pub player: PlayerKey,
+    pub karma: i64,
+}
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ForceStoryState {
+    pub player: PlayerKey,
+    pub story_state: StoryState,
+}
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SpawnAttack {
+    pub village: VillageKey,
+    pub visitors: Vec<AttackingVisitor>,
+}
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AttackingVisitor {
+    pub hp: i64,
+    pub color: UnitColor,
+    pub hurried: bool,
+}
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct FlushTasks {
+    pub worker: WorkerKey,
+}
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BanPlayer {
+    pub player: PlayerKey,
+    /// False to lift a ban
+    pub banned: bool,
+}
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RenamePlayer {
+    pub player: PlayerKey,
+    pub display_name: String,
+}
+/// Moves the game clock of the game master forward, for dev servers
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AdvanceClock {
+    pub seconds: i64,
+}
+
+/// Response to `InspectPlayer`
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PlayerReport {
+    pub id: PlayerKey,
+    pub display_name: String,
+    pub karma: i64,
+    pub story_state: StoryState,
+    pub last_active: NaiveDateTime,
+    pub banned: bool,
+    pub villages: Vec<VillageReport>,
+}
+/// Response to `InspectVillage`
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct VillageReport {
+    pub id: VillageKey,
+    pub owner: Option<PlayerKey>,
+    pub x: f32,
+    pub y: f32,
+    pub faith: i16,
+    pub resources: Vec<(ResourceType, i64)>,
+    pub workers: Vec<WorkerKey>,
+    pub hobos: usize,
+    pub buildings: usize,
+    pub ongoing_attacks: usize,
+}
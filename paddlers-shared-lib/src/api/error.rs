@@ -3,6 +3,7 @@
 #[repr(u8)]
 pub enum PadlApiError {
     PlayerNotCreated = 1,
+    PlayerBanned = 2,
 }
 
 impl std::error::Error for PadlApiError {}
@@ -10,6 +11,7 @@ impl std::fmt::Display for PadlApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             PadlApiError::PlayerNotCreated => write!(f, "The player is not in the database."),
+            PadlApiError::PlayerBanned => write!(f, "The player has been banned."),
         }
     }
 }
@@ -18,6 +20,7 @@ impl PadlApiError {
     pub fn try_from_num(i: u8) -> Option<Self> {
         match i {
             1 => Some(PadlApiError::PlayerNotCreated),
+            2 => Some(PadlApiError::PlayerBanned),
             _ => None,
         }
     }
@@ -0,0 +1,14 @@
+use crate::api::keys::HoboKey;
+use serde::{Deserialize, Serialize};
+
+/// Sends a hobo that has settled in a nest of the village away for good
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HoboEviction {
+    pub hobo: HoboKey,
+}
+
+/// Turns a hobo that has settled in a nest of the village into a prophet, which frees its place in the nest
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HoboOrdination {
+    pub hobo: HoboKey,
+}
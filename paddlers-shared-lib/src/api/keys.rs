@@ -55,7 +55,10 @@ object_key!(Attack, AttackKey);
 object_key!(Building, BuildingKey);
 object_key!(Hobo, HoboKey);
 object_key!(Player, PlayerKey);
+object_key!(PlayerMessage, MessageKey);
+object_key!(ReplayHobo, ReplayHoboKey);
 object_key!(Stream, StreamKey);
 object_key!(Task, TaskKey);
+object_key!(VisitReplay, VisitReplayKey);
 object_key!(VisitReport, VisitReportKey);
 object_key!(Worker, WorkerKey);
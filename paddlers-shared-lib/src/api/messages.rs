@@ -0,0 +1,19 @@
+//! Shared data for network transmission of messages between players
+
+use crate::api::keys::{MessageKey, VillageKey};
+use serde::{Deserialize, Serialize};
+
+/// Longest accepted message body, in characters
+pub const MAX_MESSAGE_LENGTH: usize = 1000;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MessageSend {
+    /// Messages are addressed to the owner of a village, which is what players see on the map
+    pub recipient: VillageKey,
+    pub body: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MessagesRead {
+    pub messages: Vec<MessageKey>,
+}
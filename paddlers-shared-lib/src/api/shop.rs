@@ -30,6 +30,7 @@ impl Cost for BuildingType {
             BuildingType::TripleNest => {
                 vec![(ResourceType::Feathers, 60), (ResourceType::Sticks, 60)]
             }
+            BuildingType::Shack => vec![(ResourceType::Feathers, 20), (ResourceType::Sticks, 40)],
         }
     }
 }
@@ -42,6 +43,14 @@ pub struct BuildingPurchase {
     pub y: usize,
 }
 
+/// Builds all parts of a blueprint that are missing in the village, or nothing at all
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BlueprintPurchase {
+    pub village: VillageKey,
+    /// See [Blueprint](crate::game_mechanics::town::blueprint::Blueprint) for the format
+    pub blueprint: String,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct BuildingDeletion {
     pub village: VillageKey,
@@ -53,3 +62,9 @@ pub struct BuildingDeletion {
 pub struct ProphetPurchase {
     pub village: VillageKey,
 }
+
+/// Hires a basic worker, which needs a free place in a housing building of the village
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorkerPurchase {
+    pub village: VillageKey,
+}
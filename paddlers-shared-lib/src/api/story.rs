@@ -1,5 +1,6 @@
 //! Shared data for network transmission for story
 
+use crate::story::quest::QuestName;
 use crate::story::story_state::StoryState;
 use serde::{Deserialize, Serialize};
 
@@ -8,3 +9,8 @@ pub struct StoryStateTransition {
     pub before: StoryState,
     pub after: StoryState,
 }
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuestCollect {
+    pub quest: QuestName,
+}
@@ -39,3 +39,26 @@ impl RawTask {
         }
     }
 }
+
+/// Longest accepted routine, in steps
+pub const MAX_ROUTINE_STEPS: usize = 16;
+
+/// Replaces the orders of a worker with a routine, which it repeats until it gets a new `TaskList`
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RoutineDefinition {
+    pub worker_id: WorkerKey,
+    pub steps: Vec<RoutineStep>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RoutineStep {
+    pub task_type: TaskType,
+    pub x: usize,
+    pub y: usize,
+    /// Seconds the worker stays with the task before it moves on to the next step
+    #[serde(default)]
+    pub duration: Option<i64>,
+    /// Steps with a condition are skipped while it is not met
+    #[serde(default)]
+    pub condition: Option<TaskCondition>,
+}
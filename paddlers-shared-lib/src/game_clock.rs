@@ -0,0 +1,256 @@
+//! Game time, which does not necessarily pass at the speed of real time.
+//!
+//! A `GameClock` maps real time to game time. It runs at real time in production,
+//! at a scaled speed on fast-forward dev servers, or only moves when stepped manually.
+//! The clock never reads the system time itself, the caller provides the current real time.
+//! That way, the same clock works in the browser and on the server.
+//!
+//! Server processes share one clock per process, accessible through `now()`.
+//! It is configured with the environment variables `GAME_CLOCK` and `GAME_CLOCK_EPOCH`.
+//! Manual advances are stored in the database, from where all processes pick them up.
+
+use crate::shared_types::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// Response header in which the game master sends its current game time, in microseconds since the UNIX epoch
+pub const GAME_TIME_HEADER: &str = "x-game-time";
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ClockMode {
+    /// Game time passes like real time
+    RealTime,
+    /// Game time passes faster (or slower) than real time by the given factor
+    Scaled(f64),
+    /// Game time only passes when the clock is advanced explicitly
+    Manual,
+}
+
+/// Snapshot of a clock, sent over the network to let other clocks follow it
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ClockSync {
+    /// Game time in microseconds since the UNIX epoch
+    pub game_time_us: i64,
+    pub mode: ClockMode,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GameClock {
+    mode: ClockMode,
+    /// Game time and real time at the same moment, from which on the game time is extrapolated
+    game_origin: Timestamp,
+    real_origin: Timestamp,
+}
+
+impl GameClock {
+    pub fn real_time() -> Self {
+        GameClock {
+            mode: ClockMode::RealTime,
+            game_origin: Timestamp::from_us(0),
+            real_origin: Timestamp::from_us(0),
+        }
+    }
+    /// Game time equals real time at `epoch` and then runs `speed` times as fast
+    pub fn scaled(speed: f64, epoch: Timestamp) -> Self {
+        GameClock {
+            mode: ClockMode::Scaled(speed),
+            game_origin: epoch,
+            real_origin: epoch,
+        }
+    }
+    /// A clock that stands still at `start` until it is advanced
+    pub fn manual(start: Timestamp) -> Self {
+        GameClock {
+            mode: ClockMode::Manual,
+            game_origin: start,
+            real_origin: Timestamp::from_us(0),
+        }
+    }
+    pub fn mode(&self) -> ClockMode {
+        self.mode
+    }
+    /// Game seconds passing per real second
+    pub fn speed(&self) -> f64 {
+        match self.mode {
+            ClockMode::RealTime => 1.0,
+            ClockMode::Scaled(speed) => speed,
+            ClockMode::Manual => 0.0,
+        }
+    }
+    pub fn game_time(&self, real_now: Timestamp) -> Timestamp {
+        let real_elapsed = (real_now - self.real_origin).micros() as f64;
+        self.game_origin + Timestamp::from_us((real_elapsed * self.speed()) as i64)
+    }
+    /// Shifts the game time by the given amount, in any mode
+    pub fn advance(&mut self, by: Timestamp) {
+        self.game_origin = self.game_origin + by;
+    }
+    pub fn snapshot(&self, real_now: Timestamp) -> ClockSync {
+        ClockSync {
+            game_time_us: self.game_time(real_now).micros(),
+            mode: self.mode,
+        }
+    }
+    /// Continue from a snapshot of another clock, which has been taken at `real_now`
+    pub fn sync(&mut self, snapshot: &ClockSync, real_now: Timestamp) {
+        self.mode = snapshot.mode;
+        self.game_origin = Timestamp::from_us(snapshot.game_time_us);
+        self.real_origin = real_now;
+    }
+}
+
+impl std::str::FromStr for ClockMode {
+    type Err = String;
+    /// Parses `real`, `manual` or `scaled:<speed>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some("real"), None) => Ok(ClockMode::RealTime),
+            (Some("manual"), None) => Ok(ClockMode::Manual),
+            (Some("scaled"), Some(speed)) => speed
+                .parse()
+                .ok()
+                .filter(|speed: &f64| *speed > 0.0)
+                .map(ClockMode::Scaled)
+                .ok_or(format!("Invalid clock speed: {}", speed)),
+            _ => Err(format!("Invalid clock mode: {}", s)),
+        }
+    }
+}
+
+#[cfg(feature = "sql_db")]
+mod server_clock {
+    use super::*;
+    use crate::sql::GameDB;
+    use chrono::NaiveDateTime;
+    use once_cell::sync::OnceCell;
+    use std::sync::RwLock;
+
+    struct ServerClock {
+        clock: GameClock,
+        /// Total advance from the database that has been applied to the clock
+        advanced_us: i64,
+    }
+
+    static SERVER_CLOCK: OnceCell<RwLock<ServerClock>> = OnceCell::new();
+
+    fn real_now() -> Timestamp {
+        chrono::Utc::now().naive_utc().into()
+    }
+    fn server_clock() -> &'static RwLock<ServerClock> {
+        SERVER_CLOCK.get_or_init(|| {
+            RwLock::new(ServerClock {
+                clock: clock_from_env(),
+                advanced_us: 0,
+            })
+        })
+    }
+    fn clock_from_env() -> GameClock {
+        dotenv::dotenv().ok();
+        let mode = match std::env::var("GAME_CLOCK") {
+            Ok(s) => s.parse().unwrap_or_else(|e| {
+                eprintln!("{}, falling back to real time", e);
+                ClockMode::RealTime
+            }),
+            Err(_) => ClockMode::RealTime,
+        };
+        if mode == ClockMode::RealTime {
+            return GameClock::real_time();
+        }
+        // All processes must use the same epoch to agree on the game time,
+        // also across restarts.
+        let epoch = std::env::var("GAME_CLOCK_EPOCH")
+            .ok()
+            .and_then(|s| NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S").ok());
+        match (mode, epoch) {
+            (ClockMode::Scaled(speed), Some(epoch)) => GameClock::scaled(speed, epoch.into()),
+            (ClockMode::Manual, Some(epoch)) => GameClock::manual(epoch.into()),
+            _ => {
+                eprintln!("Scaled and manual clocks require GAME_CLOCK_EPOCH (e.g. 2020-06-15T00:00:00), falling back to real time");
+                GameClock::real_time()
+            }
+        }
+    }
+
+    /// Current game time of this server process
+    pub fn now() -> NaiveDateTime {
+        server_clock()
+            .read()
+            .expect("Clock poisoned")
+            .clock
+            .game_time(real_now())
+            .into()
+    }
+    /// Moves the clock to the total advance stored in the database.
+    /// Every process calls this to follow advances made by the game master.
+    pub fn sync_server_clock<D: GameDB>(db: &D) {
+        let total_us = db
+            .shared_clock_advance()
+            .num_microseconds()
+            .expect("Clock advance out of range");
+        let mut server = server_clock().write().expect("Clock poisoned");
+        let delta = total_us - server.advanced_us;
+        if delta != 0 {
+            server.clock.advance(Timestamp::from_us(delta));
+            server.advanced_us = total_us;
+        }
+    }
+    pub fn server_clock_snapshot() -> ClockSync {
+        server_clock()
+            .read()
+            .expect("Clock poisoned")
+            .clock
+            .snapshot(real_now())
+    }
+}
+#[cfg(feature = "sql_db")]
+pub use server_clock::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled_clock() {
+        let epoch = Timestamp::from_seconds(1_000);
+        let clock = GameClock::scaled(10.0, epoch);
+        assert_eq!(clock.game_time(epoch), epoch);
+        assert_eq!(
+            clock.game_time(epoch + Timestamp::from_seconds(3)),
+            epoch + Timestamp::from_seconds(30)
+        );
+    }
+
+    #[test]
+    fn manual_clock() {
+        let start = Timestamp::from_seconds(1_000);
+        let mut clock = GameClock::manual(start);
+        assert_eq!(clock.game_time(Timestamp::from_seconds(5_000)), start);
+        clock.advance(Timestamp::from_seconds(60));
+        assert_eq!(
+            clock.game_time(Timestamp::from_seconds(9_000)),
+            start + Timestamp::from_seconds(60)
+        );
+    }
+
+    #[test]
+    fn synced_clock_follows() {
+        let server = GameClock::scaled(2.0, Timestamp::from_seconds(0));
+        let snapshot = server.snapshot(Timestamp::from_seconds(100));
+        // The local real time is off by 7s
+        let mut client = GameClock::real_time();
+        client.sync(&snapshot, Timestamp::from_seconds(107));
+        assert_eq!(
+            client.game_time(Timestamp::from_seconds(112)),
+            server.game_time(Timestamp::from_seconds(105))
+        );
+    }
+
+    #[test]
+    fn parse_mode() {
+        assert_eq!("real".parse(), Ok(ClockMode::RealTime));
+        assert_eq!("manual".parse(), Ok(ClockMode::Manual));
+        assert_eq!("scaled:10".parse(), Ok(ClockMode::Scaled(10.0)));
+        assert!("scaled:0".parse::<ClockMode>().is_err());
+        assert!("fast".parse::<ClockMode>().is_err());
+    }
+}
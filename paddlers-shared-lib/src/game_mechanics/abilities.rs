@@ -13,7 +13,9 @@ impl AbilityType {
     pub fn from_task(task: &TaskType) -> Option<AbilityType> {
         match task {
             TaskType::WelcomeAbility => Some(AbilityType::Welcome),
-            TaskType::ChopTree | TaskType::GatherSticks => Some(AbilityType::Work),
+            TaskType::ChopTree | TaskType::GatherSticks | TaskType::PlantTree => {
+                Some(AbilityType::Work)
+            }
             _ => None,
         }
     }
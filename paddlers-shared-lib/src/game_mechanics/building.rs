@@ -26,6 +26,21 @@ impl BuildingType {
             _ => 0,
         }
     }
+    /// How many hired workers can live in the building
+    pub fn housing_capacity(&self) -> usize {
+        match self {
+            BuildingType::Shack => 2,
+            _ => 0,
+        }
+    }
+    /// How many hobos can settle in the building
+    pub fn nest_capacity(&self) -> usize {
+        match self {
+            BuildingType::SingleNest => 1,
+            BuildingType::TripleNest => 3,
+            _ => 0,
+        }
+    }
 }
 
 impl BuildingType {
@@ -53,6 +68,7 @@ impl BuildingType {
             BuildingType::Tree => karma >= 1,
             BuildingType::SingleNest => karma >= 500,
             BuildingType::TripleNest => karma >= 3000,
+            BuildingType::Shack => karma >= 50,
         }
     }
     /// Buildings that may be available at the default shop, regardless of player restrictions
@@ -68,6 +84,7 @@ impl BuildingType {
             BuildingType::Temple,
             BuildingType::SingleNest,
             BuildingType::TripleNest,
+            BuildingType::Shack,
         ]
         .iter()
     }
@@ -82,8 +99,9 @@ impl BuildingType {
             BuildingType::SawMill => true,
             BuildingType::Temple => false,
             BuildingType::Tree => true,
-            BuildingType::SingleNest => false, // false for now, to avoid problems with associated hobos
-            BuildingType::TripleNest => false, // false for now, to avoid problems with associated hobos
+            BuildingType::SingleNest => true,
+            BuildingType::TripleNest => true,
+            BuildingType::Shack => false, // false for now, to avoid homeless workers
         }
     }
 }
@@ -1,13 +1,101 @@
+#[cfg(test)]
+mod forestry_test;
+
 use crate::models::TaskType;
 use chrono::Duration;
 
+/// Trees die of old age after this many hours, which removes them from the forest
+pub const TREE_LIFESPAN_HOURS: i64 = 24 * 14;
+/// Size of a fully grown tree
+pub const MAX_TREE_SIZE: usize = 10;
+
+/// Size of a tree of the given age, dead trees have no size left
 pub fn tree_size(age: Duration) -> usize {
     match age.num_hours() {
         h if h < 1 => 1,
         h if h < 4 => 2,
         h if h <= 45 => 3 + h as usize / 9,
         h if h < 72 => 9,
-        _ => 10,
+        h if h < TREE_LIFESPAN_HOURS => MAX_TREE_SIZE,
+        _ => 0,
+    }
+}
+
+pub fn tree_is_dead(age: Duration) -> bool {
+    age.num_hours() >= TREE_LIFESPAN_HOURS
+}
+
+/// The age at which a tree has just grown to the given size
+fn tree_age_for_size(size: usize) -> Duration {
+    let size = size.min(MAX_TREE_SIZE);
+    (0..TREE_LIFESPAN_HOURS)
+        .map(Duration::hours)
+        .find(|age| tree_size(*age) >= size)
+        .unwrap_or(Duration::zero())
+}
+
+/// The trees of a town, identified by `I`.
+///
+/// Frontend and game-master both derive the forest size from this model, which keeps them in agreement.
+/// Chopping sets a tree back in its growth, as if it had been planted later, until nothing is left of it.
+#[derive(Debug, Clone)]
+pub struct Forest<I> {
+    trees: Vec<(I, Duration)>,
+}
+
+impl<I: Copy> Forest<I> {
+    pub fn new() -> Self {
+        Forest { trees: vec![] }
+    }
+    /// Adds a tree that has been growing for the given time
+    pub fn add_tree(&mut self, id: I, age: Duration) {
+        self.trees.push((id, age));
+    }
+    /// Combined size of all living trees
+    pub fn size(&self) -> usize {
+        self.trees.iter().map(|(_, age)| tree_size(*age)).sum()
+    }
+    /// Trees that have died of old age and should be removed
+    pub fn dead_trees(&self) -> Vec<I> {
+        self.trees
+            .iter()
+            .filter(|(_, age)| tree_is_dead(*age))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+    /// Chops down up to `amount` of forest size, biggest trees first.
+    /// Returns how much has been chopped and the new age of every affected tree, None for trees that are gone.
+    pub fn chop(&mut self, amount: usize) -> (usize, Vec<(I, Option<Duration>)>) {
+        let mut order: Vec<usize> = (0..self.trees.len())
+            .filter(|i| !tree_is_dead(self.trees[*i].1))
+            .collect();
+        order.sort_by_key(|i| std::cmp::Reverse(tree_size(self.trees[*i].1)));
+
+        let mut chopped = 0;
+        let mut changes = vec![];
+        let mut felled = vec![];
+        for i in order {
+            if chopped == amount {
+                break;
+            }
+            let (id, age) = self.trees[i];
+            let size = tree_size(age);
+            let cut = size.min(amount - chopped);
+            chopped += cut;
+            if cut == size {
+                felled.push(i);
+                changes.push((id, None));
+            } else {
+                let new_age = tree_age_for_size(size - cut);
+                self.trees[i].1 = new_age;
+                changes.push((id, Some(new_age)));
+            }
+        }
+        felled.sort();
+        for i in felled.into_iter().rev() {
+            self.trees.remove(i);
+        }
+        (chopped, changes)
     }
 }
 
@@ -0,0 +1,49 @@
+use super::*;
+
+#[test]
+fn trees_grow_and_die() {
+    assert_eq!(tree_size(Duration::minutes(30)), 1);
+    assert_eq!(tree_size(Duration::hours(9)), 4);
+    assert_eq!(tree_size(Duration::hours(100)), MAX_TREE_SIZE);
+    assert!(!tree_is_dead(Duration::hours(100)));
+    assert!(tree_is_dead(Duration::hours(TREE_LIFESPAN_HOURS)));
+    assert_eq!(tree_size(Duration::hours(TREE_LIFESPAN_HOURS)), 0);
+
+    let mut forest = Forest::new();
+    forest.add_tree(1, Duration::hours(100));
+    forest.add_tree(2, Duration::hours(TREE_LIFESPAN_HOURS + 1));
+    assert_eq!(forest.size(), MAX_TREE_SIZE);
+    assert_eq!(forest.dead_trees(), vec![2]);
+}
+
+#[test]
+fn chopping_shrinks_biggest_tree_first() {
+    let mut forest = Forest::new();
+    forest.add_tree(1, Duration::hours(9));
+    forest.add_tree(2, Duration::hours(100));
+    assert_eq!(forest.size(), 14);
+
+    let (chopped, changes) = forest.chop(3);
+    assert_eq!(chopped, 3);
+    assert_eq!(changes.len(), 1);
+    let (id, age) = changes[0];
+    assert_eq!(id, 2);
+    assert_eq!(tree_size(age.unwrap()), 7);
+    assert_eq!(forest.size(), 11);
+}
+
+#[test]
+fn chopping_removes_trees() {
+    let mut forest = Forest::new();
+    forest.add_tree(1, Duration::hours(9));
+    forest.add_tree(2, Duration::minutes(5));
+    forest.add_tree(3, Duration::hours(TREE_LIFESPAN_HOURS));
+
+    let (chopped, changes) = forest.chop(6);
+    assert_eq!(chopped, 5);
+    assert_eq!(changes, vec![(1, None), (2, None)]);
+    assert_eq!(forest.size(), 0);
+    // Dead trees are not chopped, they are removed separately
+    assert_eq!(forest.dead_trees(), vec![3]);
+    assert_eq!(forest.chop(1), (0, vec![]));
+}
@@ -1,5 +1,20 @@
 // TODO [0.1.5] Move to better place, maybe a separate specification / balancing crate.
 
+use crate::models::UnitColor;
+
+/// Speed of visitors that are in a hurry and walk straight through a town
+pub const HURRIED_HOBO_SPEED: f32 = 0.0625;
+/// Speed of visitors that take their time and rest in a town
+pub const UNHURRIED_HOBO_SPEED: f32 = 0.25;
+
+pub fn hobo_speed(hurried: bool) -> f32 {
+    if hurried {
+        HURRIED_HOBO_SPEED
+    } else {
+        UNHURRIED_HOBO_SPEED
+    }
+}
+
 pub struct HoboLevel(usize);
 
 impl HoboLevel {
@@ -35,3 +50,57 @@ impl HoboLevel {
         self.hurried_anarchist_hp_range().1
     }
 }
+
+/// Stat modifiers of visitors, depending on their color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HoboColorStats {
+    /// Number of auras the visitor swims through without noticing them
+    pub ignored_auras: usize,
+    /// Welcoming effects on the visitor are divided by this
+    pub welcome_divisor: i32,
+    /// Multiplies the feathers left behind by the satisfied visitor
+    pub feathers_factor: i64,
+}
+
+pub fn hobo_color_stats(color: UnitColor) -> HoboColorStats {
+    match color {
+        UnitColor::Yellow | UnitColor::Prophet => HoboColorStats {
+            ignored_auras: 0,
+            welcome_divisor: 1,
+            feathers_factor: 1,
+        },
+        UnitColor::Camo => HoboColorStats {
+            ignored_auras: 1,
+            welcome_divisor: 1,
+            feathers_factor: 2,
+        },
+        UnitColor::White => HoboColorStats {
+            ignored_auras: 0,
+            welcome_divisor: 2,
+            feathers_factor: 3,
+        },
+    }
+}
+
+impl HoboColorStats {
+    /// Satisfaction resulting from the sum of all effects on the visitor
+    pub fn effective_effects(&self, effects_strength: i32) -> i32 {
+        effects_strength / self.welcome_divisor
+    }
+}
+
+/// Feathers produced by a hobo that has settled in a nest of the village
+pub fn settled_hobo_feathers_per_hour(color: UnitColor) -> i32 {
+    2 * hobo_color_stats(color).feathers_factor as i32
+}
+
+/// Feathers a satisfied visitor leaves behind in the town it visited
+/// TODO [0.1.5]
+pub fn visitor_feathers(hp: i64, speed: f32, hurried: bool, color: UnitColor) -> i64 {
+    let f = if hurried {
+        (1.0 + hp as f32 * speed / 4.0).log2().floor()
+    } else {
+        (1.0 + hp as f32 / 16.0).log2().ceil()
+    };
+    f as i64 * hobo_color_stats(color).feathers_factor
+}
@@ -1,7 +1,18 @@
 pub const MAP_H: u32 = 11;
-pub const MAP_MAX_X: u32 = 20;
 pub const MAP_STREAM_AREA_W: f32 = 5.0;
+/// Width of a map chunk. The map is generated and loaded in chunks.
+pub const MAP_CHUNK_W: u32 = 20;
 
 pub fn map_distance(a: (f32, f32), b: (f32, f32)) -> f32 {
     ((a.0 - b.0) * (a.0 - b.0) + (a.1 - b.1) * (a.1 - b.1)).sqrt()
 }
+
+/// Index of the chunk which includes the given x coordinate
+pub fn map_chunk_of(x: f32) -> i32 {
+    (x / MAP_CHUNK_W as f32).floor() as i32
+}
+/// Range of x coordinates covered by a chunk, inclusive start and exclusive end
+pub fn map_chunk_range(chunk: i32) -> (f32, f32) {
+    let w = MAP_CHUNK_W as f32;
+    (chunk as f32 * w, (chunk + 1) as f32 * w)
+}
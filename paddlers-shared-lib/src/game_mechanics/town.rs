@@ -1,9 +1,12 @@
+pub mod blueprint;
 pub mod defence;
 pub mod town_layout;
 
 pub use defence::{IAttackingHobo, IDefendingTown};
 pub use town_layout::{ITownLayout, ITownLayoutMarker, TownLayout};
 
+#[cfg(test)]
+mod blueprint_test;
 #[cfg(test)]
 mod defence_test;
 
@@ -136,7 +139,8 @@ impl<I: Eq + std::hash::Hash + Clone + Copy + std::fmt::Debug> TownState<I> {
         self.tiles.get_mut(tile)
     }
     pub fn has_supply_for_additional_worker(&self, task: TaskType) -> bool {
-        let supply = self.forest_size - self.forest_usage;
+        // Chopping and dying trees can shrink the forest below what is in use
+        let supply = self.forest_size.saturating_sub(self.forest_usage);
         let required = task.required_forest_size();
         supply >= required
     }
@@ -149,6 +153,10 @@ impl<I: Eq + std::hash::Hash + Clone + Copy + std::fmt::Debug> TownState<I> {
             Err(TownError::NotEnoughSupply)
         }
     }
+    /// Registers a task that has already begun, it keeps its share of the forest even if the forest has shrunk since
+    pub fn register_ongoing_task(&mut self, task: TaskType) {
+        self.forest_usage += task.required_forest_size();
+    }
     pub fn register_task_end(&mut self, task: TaskType) -> Result<(), TownError> {
         let required = task.required_forest_size();
         if self.forest_usage >= required {
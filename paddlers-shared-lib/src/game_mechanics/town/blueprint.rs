@@ -0,0 +1,128 @@
+//! Blueprints are compact, shareable descriptions of a town layout.
+//!
+//! A blueprint string starts with a version prefix, followed by three characters per building:
+//! One letter for the building type and one digit each for the x and y coordinate.
+//! For example, `1:F52T16` describes blue flowers at (5,2) and a tree at (1,6).
+//!
+//! The temple is not part of blueprints, its position is decided by the story.
+
+use super::{TileIndex, TOWN_LANE_Y, TOWN_X, TOWN_Y};
+use crate::api::shop::{Cost, Price};
+use crate::models::{BuildingType, ResourceType};
+use std::collections::HashMap;
+
+const BLUEPRINT_VERSION: &str = "1:";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Blueprint {
+    pub buildings: Vec<(BuildingType, TileIndex)>,
+}
+
+impl Blueprint {
+    /// Builds a blueprint from the buildings of a town, leaving out everything that cannot be part of a blueprint
+    pub fn from_buildings(buildings: impl IntoIterator<Item = (BuildingType, TileIndex)>) -> Self {
+        let mut buildings: Vec<_> = buildings
+            .into_iter()
+            .filter(|(bt, _)| blueprint_code(*bt).is_some())
+            .collect();
+        buildings.sort_by_key(|(_, (x, y))| (*x, *y));
+        Blueprint { buildings }
+    }
+    /// Parts of the blueprint which are not yet present in the town with the given buildings
+    pub fn missing_parts(
+        &self,
+        existing: &[(BuildingType, TileIndex)],
+    ) -> Vec<(BuildingType, TileIndex)> {
+        self.buildings
+            .iter()
+            .filter(|part| !existing.contains(part))
+            .cloned()
+            .collect()
+    }
+    /// Summed up price of the given parts of a blueprint
+    pub fn total_price(parts: &[(BuildingType, TileIndex)]) -> Price {
+        let mut total: HashMap<ResourceType, i64> = HashMap::new();
+        for (bt, _) in parts {
+            for (res, n) in bt.cost() {
+                *total.entry(res).or_insert(0) += n;
+            }
+        }
+        let mut price: Vec<_> = total.into_iter().collect();
+        price.sort_by_key(|(res, _)| *res as usize);
+        Price(price)
+    }
+}
+
+impl std::fmt::Display for Blueprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", BLUEPRINT_VERSION)?;
+        for (bt, (x, y)) in &self.buildings {
+            if let Some(code) = blueprint_code(*bt) {
+                write!(f, "{}{}{}", code, x, y)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Blueprint {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if !s.starts_with(BLUEPRINT_VERSION) {
+            return Err("Unknown blueprint version".to_owned());
+        }
+        let body = &s[BLUEPRINT_VERSION.len()..];
+        let chars: Vec<char> = body.chars().collect();
+        if chars.len() % 3 != 0 {
+            return Err("Blueprint is incomplete".to_owned());
+        }
+        let mut buildings = vec![];
+        for part in chars.chunks(3) {
+            let bt = building_from_code(part[0])
+                .ok_or(format!("Unknown building in blueprint: {}", part[0]))?;
+            let x = part[1].to_digit(10).ok_or("Invalid x coordinate")? as usize;
+            let y = part[2].to_digit(10).ok_or("Invalid y coordinate")? as usize;
+            if x >= TOWN_X || y >= TOWN_Y || y == TOWN_LANE_Y {
+                return Err(format!("Cannot build at {}|{}", x, y));
+            }
+            if buildings.iter().any(|(_, pos)| *pos == (x, y)) {
+                return Err(format!("Blueprint uses {}|{} twice", x, y));
+            }
+            buildings.push((bt, (x, y)));
+        }
+        Ok(Blueprint { buildings })
+    }
+}
+
+fn blueprint_code(bt: BuildingType) -> Option<char> {
+    match bt {
+        BuildingType::BlueFlowers => Some('F'),
+        BuildingType::RedFlowers => Some('R'),
+        BuildingType::Tree => Some('T'),
+        BuildingType::BundlingStation => Some('B'),
+        BuildingType::SawMill => Some('S'),
+        BuildingType::PresentA => Some('A'),
+        BuildingType::PresentB => Some('P'),
+        BuildingType::SingleNest => Some('N'),
+        BuildingType::TripleNest => Some('M'),
+        BuildingType::Shack => Some('H'),
+        BuildingType::Temple => None,
+    }
+}
+
+fn building_from_code(c: char) -> Option<BuildingType> {
+    match c {
+        'F' => Some(BuildingType::BlueFlowers),
+        'R' => Some(BuildingType::RedFlowers),
+        'T' => Some(BuildingType::Tree),
+        'B' => Some(BuildingType::BundlingStation),
+        'S' => Some(BuildingType::SawMill),
+        'A' => Some(BuildingType::PresentA),
+        'P' => Some(BuildingType::PresentB),
+        'N' => Some(BuildingType::SingleNest),
+        'M' => Some(BuildingType::TripleNest),
+        'H' => Some(BuildingType::Shack),
+        _ => None,
+    }
+}
@@ -0,0 +1,43 @@
+use super::blueprint::*;
+use crate::models::{BuildingType, ResourceType};
+
+#[test]
+fn blueprint_round_trip() {
+    let blueprint = Blueprint::from_buildings(vec![
+        (BuildingType::Tree, (1, 6)),
+        (BuildingType::Temple, (4, 4)),
+        (BuildingType::BlueFlowers, (5, 2)),
+    ]);
+    let exported = blueprint.to_string();
+    assert_eq!(exported, "1:T16F52");
+    assert_eq!(exported.parse::<Blueprint>(), Ok(blueprint));
+}
+
+#[test]
+fn invalid_blueprints_are_rejected() {
+    assert!("F52".parse::<Blueprint>().is_err());
+    assert!("1:F5".parse::<Blueprint>().is_err());
+    assert!("1:X52".parse::<Blueprint>().is_err());
+    assert!("1:F53".parse::<Blueprint>().is_err());
+    assert!("1:F92".parse::<Blueprint>().is_err());
+    assert!("1:F52T52".parse::<Blueprint>().is_err());
+}
+
+#[test]
+fn missing_parts_and_price() {
+    let blueprint: Blueprint = "1:F52F62T16".parse().unwrap();
+    let existing = vec![
+        (BuildingType::BlueFlowers, (5, 2)),
+        (BuildingType::Tree, (1, 5)),
+    ];
+    let missing = blueprint.missing_parts(&existing);
+    assert_eq!(
+        missing,
+        vec![
+            (BuildingType::BlueFlowers, (6, 2)),
+            (BuildingType::Tree, (1, 6))
+        ]
+    );
+    let price = Blueprint::total_price(&missing);
+    assert_eq!(price.0, vec![(ResourceType::Feathers, 30)]);
+}
@@ -5,6 +5,8 @@
 //! The frontend and the backend can therefore use his computation by implementing the traits.
 use super::town_layout::ITownLayout;
 use super::{TOWN_RESTING_X, TOWN_X};
+use crate::game_mechanics::hobos::{hobo_color_stats, HoboColorStats};
+use crate::models::UnitColor;
 use crate::shared_types::*;
 
 /// Provides information about a hobo currently attacking
@@ -16,8 +18,17 @@ pub trait IAttackingHobo {
     fn arrival(&self) -> Timestamp;
     fn released(&self) -> Option<Timestamp>;
     fn effects_strength(&self) -> i32;
+    fn color(&self) -> UnitColor;
 
     // PROVIDED
+    fn color_stats(&self) -> HoboColorStats {
+        hobo_color_stats(self.color())
+    }
+    /// Satisfaction from effects on the hobo, after applying the modifiers of its color
+    fn effective_effects_strength(&self) -> i32 {
+        self.color_stats()
+            .effective_effects(self.effects_strength())
+    }
     /// Returns the duration it takes the hobo to reach the resting place, after having reached the town.
     fn time_until_resting(&self) -> Timestamp {
         Self::s_time_until_resting(self.speed())
@@ -28,6 +39,14 @@ pub trait IAttackingHobo {
     }
 }
 
+/// Orders auras that a hobo touches for the first time on the same tile, weakest first.
+/// This decides which of them are ignored by colors that ignore the first auras.
+/// Only the effect is compared because the frontend and the game master use different aura ids,
+/// ties are therefore irrelevant for the satisfaction of the hobo.
+pub fn order_touched_together<ID>(auras: &mut [(ID, i32)]) {
+    auras.sort_by_key(|aura| aura.1);
+}
+
 /// Trait for town information required to perform hp computations
 pub trait IDefendingTown: ITownLayout {
     // TO IMPLEMENT
@@ -40,8 +59,47 @@ pub trait IDefendingTown: ITownLayout {
             .max_hp()
             .saturating_sub(self.total_damage(attacker, now) as u32)
     }
+    /// The moment at which the attacker reaches 0 HP, assuming the town and the effects on the attacker do not change anymore.
+    /// Only the way into the town is considered, which for unhurried hobos ends at the resting place.
+    fn satisfaction_time<HOBO: IAttackingHobo>(&self, attacker: &HOBO) -> Option<Timestamp> {
+        let max_hp = attacker.max_hp() as i32;
+        let mut damage = attacker.effective_effects_strength();
+        let mut ignored = attacker.color_stats().ignored_auras;
+        if damage >= max_hp {
+            return Some(attacker.arrival());
+        }
+        let tiles = if attacker.hurried() {
+            self.path_straight_through()
+        } else {
+            self.path_to_rest_place()
+        };
+        let mut touched = vec![];
+        let mut t = attacker.arrival();
+        let t_per_tile = Timestamp::from_float_seconds(1.0 / attacker.speed());
+        for tile in tiles {
+            let mut new_auras: Vec<_> = self
+                .auras_in_range(tile, t)
+                .into_iter()
+                .filter(|aura| !touched.contains(aura))
+                .collect();
+            order_touched_together(&mut new_auras);
+            for aura in new_auras {
+                if ignored > 0 {
+                    ignored -= 1;
+                } else {
+                    damage += aura.1;
+                }
+                touched.push(aura);
+            }
+            if damage >= max_hp {
+                return Some(t);
+            }
+            t = t + t_per_tile;
+        }
+        None
+    }
     fn total_damage<HOBO: IAttackingHobo>(&self, attacker: &HOBO, now: Timestamp) -> i32 {
-        self.aura_damage(attacker, now) + attacker.effects_strength()
+        self.aura_damage(attacker, now) + attacker.effective_effects_strength()
     }
 
     fn hobo_left_town<HOBO: IAttackingHobo>(&self, attacker: &HOBO, now: Timestamp) -> bool {
@@ -62,13 +120,29 @@ pub trait IDefendingTown: ITownLayout {
         }
     }
     fn aura_damage<HOBO: IAttackingHobo>(&self, attacker: &HOBO, now: Timestamp) -> i32 {
-        let auras = self.touched_auras(attacker, now);
+        let auras = self.affecting_auras(attacker, now);
         let dmg = Self::damage(&auras);
         dmg
     }
+    /// The touched auras that have an effect on the attacker, some colors ignore the first few.
+    fn affecting_auras<HOBO: IAttackingHobo>(
+        &self,
+        attacker: &HOBO,
+        now: Timestamp,
+    ) -> Vec<(Self::AuraId, i32)> {
+        let ignored = attacker.color_stats().ignored_auras;
+        let mut auras: Vec<_> = self
+            .touched_auras(attacker, now)
+            .into_iter()
+            .skip(ignored)
+            .collect();
+        auras.sort();
+        auras
+    }
     fn damage(auras: &[(Self::AuraId, i32)]) -> i32 {
         auras.iter().fold(0, |acc, aura| acc + aura.1)
     }
+    /// All auras the attacker has been in range of, in the order they were touched first
     fn touched_auras<HOBO: IAttackingHobo>(
         &self,
         attacker: &HOBO,
@@ -97,9 +171,13 @@ pub trait IDefendingTown: ITownLayout {
                 auras.append(&mut self.touched_auras_on_path(released, now, attacker, &tiles));
             }
         }
-        auras.sort();
-        auras.dedup();
-        auras
+        let mut out = vec![];
+        for aura in auras {
+            if !out.contains(&aura) {
+                out.push(aura);
+            }
+        }
+        out
     }
     fn touched_auras_on_path<HOBO: IAttackingHobo>(
         &self,
@@ -115,11 +193,16 @@ pub trait IDefendingTown: ITownLayout {
             if t > max_t {
                 break;
             }
-            out.append(&mut self.auras_in_range(tile, t));
+            let mut in_range = self.auras_in_range(tile, t);
+            in_range.sort();
+            order_touched_together(&mut in_range);
+            for aura in in_range {
+                if !out.contains(&aura) {
+                    out.push(aura);
+                }
+            }
             t = t + t_per_tile;
         }
-        out.sort();
-        out.dedup();
         out
     }
     /// The timestamp when the resting place was left by a non-hurried hobo. May differ from hobo.released
@@ -1,6 +1,7 @@
 use super::defence::*;
 use super::town_layout::*;
 use super::*;
+use crate::models::UnitColor;
 use crate::shared_types::Timestamp;
 use std::collections::HashMap;
 
@@ -11,6 +12,7 @@ struct TestHobo {
     arrival: Timestamp,
     released: Option<Timestamp>,
     effects_strength: i32,
+    color: UnitColor,
 }
 struct TestTown {
     building_auras: HashMap<TownLayoutIndex, Vec<TestAura>>,
@@ -125,6 +127,101 @@ fn unhurried_hobo_released() {
     assert_eq!(hobo_hp_left, 97);
 }
 
+#[test]
+fn satisfaction_time_matches_hp() {
+    let mut hobo = TestHobo::new();
+    hobo.max_hp = 5;
+    let mut town = TestTown::new();
+    town.add_aura(TestAura::new(3), &[(7, Y)]);
+    town.add_aura(TestAura::new(3), &[(2, Y)]);
+
+    // 2s per tile, (2, Y) is the seventh tile on the way through
+    let t = town.satisfaction_time(&hobo);
+    assert_eq!(t, Some(Timestamp::from_seconds(12)));
+    assert_eq!(town.hp_left(&hobo, Timestamp::from_seconds(11)), 2);
+    assert_eq!(town.hp_left(&hobo, Timestamp::from_seconds(12)), 0);
+
+    // Unhurried hobos do not reach the second aura before resting
+    hobo.hurried = false;
+    assert_eq!(town.satisfaction_time(&hobo), None);
+
+    hobo.effects_strength = 2;
+    assert_eq!(
+        town.satisfaction_time(&hobo),
+        Some(Timestamp::from_seconds(2))
+    );
+    hobo.effects_strength = 5;
+    assert_eq!(town.satisfaction_time(&hobo), Some(hobo.arrival));
+}
+
+#[test]
+fn camo_hobo_ignores_first_aura() {
+    let mut hobo = TestHobo::new();
+    hobo.max_hp = 5;
+    hobo.color = UnitColor::Camo;
+    let mut town = TestTown::new();
+    town.add_aura(TestAura::new(3), &[(7, Y)]);
+    town.add_aura(TestAura::new(2), &[(8, Y)]);
+    town.add_aura(TestAura::new(4), &[(2, Y)]);
+
+    let now = Timestamp::from_seconds(100);
+    // The aura at (8, Y) is touched first and therefore ignored
+    assert_eq!(town.touched_auras(&hobo, now).len(), 3);
+    assert_eq!(town.aura_damage(&hobo, now), 7);
+    assert_eq!(
+        town.satisfaction_time(&hobo),
+        Some(Timestamp::from_seconds(12))
+    );
+
+    hobo.color = UnitColor::Yellow;
+    assert_eq!(town.aura_damage(&hobo, now), 9);
+    assert_eq!(
+        town.satisfaction_time(&hobo),
+        Some(Timestamp::from_seconds(2))
+    );
+}
+
+#[test]
+fn camo_hobo_ignores_weakest_of_simultaneous_auras() {
+    let mut hobo = TestHobo::new();
+    hobo.max_hp = 5;
+    hobo.color = UnitColor::Camo;
+    let now = Timestamp::from_seconds(100);
+
+    // The result must not depend on the ids, which differ between frontend and game master
+    for strengths in &[[4, 1], [1, 4]] {
+        let mut town = TestTown::new();
+        town.add_aura(TestAura::new(strengths[0]), &[(8, Y)]);
+        town.add_aura(TestAura::new(strengths[1]), &[(8, Y)]);
+        assert_eq!(town.aura_damage(&hobo, now), 4);
+        assert_eq!(town.satisfaction_time(&hobo), None);
+        hobo.effects_strength = 1;
+        assert_eq!(
+            town.satisfaction_time(&hobo),
+            Some(Timestamp::from_seconds(0))
+        );
+        hobo.effects_strength = 0;
+    }
+}
+
+#[test]
+fn white_hobo_needs_twice_the_welcome() {
+    let mut hobo = TestHobo::new();
+    hobo.max_hp = 4;
+    hobo.hurried = false;
+    hobo.effects_strength = 4;
+    hobo.color = UnitColor::White;
+    let town = TestTown::new();
+
+    let now = Timestamp::from_seconds(100);
+    assert_eq!(town.hp_left(&hobo, now), 2);
+    assert_eq!(town.satisfaction_time(&hobo), None);
+
+    hobo.effects_strength = 8;
+    assert_eq!(town.hp_left(&hobo, now), 0);
+    assert_eq!(town.satisfaction_time(&hobo), Some(hobo.arrival));
+}
+
 impl TestHobo {
     fn new() -> Self {
         TestHobo {
@@ -134,6 +231,7 @@ impl TestHobo {
             arrival: Timestamp::from_seconds(0),
             released: None,
             effects_strength: 0,
+            color: UnitColor::Yellow,
         }
     }
 }
@@ -157,6 +255,9 @@ impl IAttackingHobo for TestHobo {
     fn effects_strength(&self) -> i32 {
         self.effects_strength
     }
+    fn color(&self) -> UnitColor {
+        self.color
+    }
 }
 impl ITownLayoutMarker for TestTown {
     const LAYOUT: TownLayout = TownLayout::Basic;
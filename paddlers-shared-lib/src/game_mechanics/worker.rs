@@ -1,4 +1,5 @@
-use crate::models::{ResourceType, TaskType};
+use crate::api::shop::Price;
+use crate::models::{ResourceType, TaskType, UnitColor};
 
 pub const fn unit_speed_to_worker_tiles_per_second(base_speed: f32) -> f32 {
     base_speed
@@ -12,6 +13,44 @@ pub const fn hero_mana_regeneration_per_hour() -> i32 {
     10
 }
 
+/// Speed of hired workers, they have no mana and only know how to work
+pub const fn basic_worker_speed() -> f32 {
+    0.4
+}
+
+pub fn basic_worker_cost() -> Price {
+    Price(vec![
+        (ResourceType::Feathers, 40),
+        (ResourceType::Sticks, 20),
+    ])
+}
+
+/// Stat modifiers of workers, depending on their color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorkerColorStats {
+    pub speed_factor: f32,
+    /// Multiplies the resources collected while working
+    pub collection_factor: i32,
+}
+
+/// Heroes have no color and use the stats of yellow workers
+pub fn worker_color_stats(color: Option<UnitColor>) -> WorkerColorStats {
+    match color {
+        None | Some(UnitColor::Yellow) | Some(UnitColor::Prophet) => WorkerColorStats {
+            speed_factor: 1.0,
+            collection_factor: 1,
+        },
+        Some(UnitColor::Camo) => WorkerColorStats {
+            speed_factor: 1.25,
+            collection_factor: 1,
+        },
+        Some(UnitColor::White) => WorkerColorStats {
+            speed_factor: 1.0,
+            collection_factor: 2,
+        },
+    }
+}
+
 pub fn hero_resource_collection_per_hour(task: TaskType) -> Option<(ResourceType, i32)> {
     match task {
         TaskType::ChopTree => Some((ResourceType::Logs, 5)),
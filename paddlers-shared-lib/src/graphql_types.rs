@@ -30,6 +30,15 @@ impl From<GqlTimestamp> for Timestamp {
     }
 }
 
+/// Integer that does not fit into the 32 bit Int of GraphQL
+#[derive(Serialize, Deserialize)]
+pub struct GqlBigInt(pub i64);
+impl GqlBigInt {
+    pub fn from_string(s: &String) -> Option<GqlBigInt> {
+        s.parse::<i64>().ok().map(GqlBigInt)
+    }
+}
+
 #[cfg(feature = "graphql")]
 juniper::graphql_scalar!(GqlTimestamp {
     description: "Micro second precision timestamp"
@@ -47,3 +56,21 @@ juniper::graphql_scalar!(GqlTimestamp {
         <String as juniper::ParseScalarValue>::from_str(value)
     }
 });
+
+#[cfg(feature = "graphql")]
+juniper::graphql_scalar!(GqlBigInt {
+    description: "64 bit integer, transmitted as string"
+
+    resolve(&self) -> Value {
+        Value::scalar(self.0.to_string())
+    }
+
+    from_input_value(v: &InputValue) -> Option<GqlBigInt> {
+        v.as_scalar_value::<String>()
+            .and_then(GqlBigInt::from_string)
+    }
+
+    from_str<'a>(value: ScalarToken<'a>) -> juniper::ParseScalarResult<'a, juniper::DefaultScalarValue> {
+        <String as juniper::ParseScalarValue>::from_str(value)
+    }
+});
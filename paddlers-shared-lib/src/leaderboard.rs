@@ -0,0 +1,80 @@
+//! Leaderboard categories and the schedule of seasonal leaderboards.
+//!
+//! Seasonal categories only count what happened since the start of the current season.
+//! Seasons are numbered weeks, starting on Mondays at 00:00 UTC. A new season
+//! starts with empty scores, old seasons are kept in the database for reference.
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+/// Number of entries shown on a single leaderboard page
+pub const LEADERBOARD_PAGE_SIZE: i64 = 20;
+/// Upper limit for the number of entries that can be requested at once
+pub const LEADERBOARD_MAX_PAGE_SIZE: i64 = 100;
+pub const SEASON_LENGTH_DAYS: i64 = 7;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "enum_utils", derive(EnumIter, Display))]
+#[cfg_attr(feature = "graphql", derive(juniper::GraphQLEnum))]
+pub enum LeaderboardCategory {
+    /// Total karma collected over all time
+    Karma,
+    /// Karma collected in the current season
+    SeasonKarma,
+    /// Number of villages currently owned
+    Villages,
+    /// Number of visitors that left satisfied in the current season
+    VisitorsWelcomed,
+    /// Number of prophets currently owned
+    Prophets,
+}
+
+impl LeaderboardCategory {
+    pub fn is_seasonal(&self) -> bool {
+        match self {
+            Self::SeasonKarma | Self::VisitorsWelcomed => true,
+            Self::Karma | Self::Villages | Self::Prophets => false,
+        }
+    }
+}
+
+/// Index of the season which includes the given point in time
+pub fn season_of(t: NaiveDateTime) -> i32 {
+    let since_start = t - seasons_epoch();
+    since_start
+        .num_seconds()
+        .div_euclid(season_length().num_seconds()) as i32
+}
+
+pub fn season_start(season: i32) -> NaiveDateTime {
+    seasons_epoch() + season_length() * season
+}
+
+pub fn season_end(season: i32) -> NaiveDateTime {
+    season_start(season + 1)
+}
+
+/// The first Monday after the Unix epoch
+fn seasons_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd(1970, 1, 5).and_hms(0, 0, 0)
+}
+
+fn season_length() -> Duration {
+    Duration::days(SEASON_LENGTH_DAYS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seasons_start_on_monday() {
+        // 2020-06-01 was a Monday
+        let monday = NaiveDate::from_ymd(2020, 6, 1).and_hms(0, 0, 0);
+        let season = season_of(monday);
+        assert_eq!(season_start(season), monday);
+        assert_eq!(season_of(monday - Duration::seconds(1)), season - 1);
+        assert_eq!(season_of(monday + Duration::days(6)), season);
+        assert_eq!(season_end(season), monday + Duration::days(7));
+    }
+}
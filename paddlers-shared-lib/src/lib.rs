@@ -20,10 +20,13 @@ pub extern crate strum;
 #[macro_use]
 pub mod macros;
 
+pub mod activity;
 pub mod api;
 pub mod config;
 pub mod display;
+pub mod game_clock;
 pub mod graphql_types;
+pub mod leaderboard;
 pub mod models;
 pub mod prelude;
 pub mod shared_types;
@@ -5,10 +5,14 @@ use chrono::NaiveDateTime;
 
 // Reexport
 #[cfg(feature = "sql_db")]
+pub use crate::story::quest::Quest_name;
+#[cfg(feature = "sql_db")]
 pub use crate::story::story_state::Story_state_type;
 #[cfg(feature = "sql_db")]
 pub use resources::dsl;
 
+#[cfg(feature = "sql_db")]
+use crate::story::quest::QuestName;
 #[cfg(feature = "sql_db")]
 use crate::story::story_state::StoryState;
 #[cfg(feature = "sql_db")]
@@ -16,8 +20,10 @@ use ::diesel_derive_enum::DbEnum;
 
 #[cfg(feature = "sql_db")]
 use super::schema::{
-    abilities, attacks, attacks_to_hobos, buildings, effects, hobos, players, resources, rewards,
-    streams, tasks, villages, visit_reports, worker_flags, workers,
+    abilities, attacks, attacks_to_hobos, buildings, completed_quests, effects, hobos, messages,
+    player_season_stats, players, replay_buildings, replay_effects, replay_hobos, resources,
+    rewards, routine_tasks, streams, tasks, villages, visit_replays, visit_reports, worker_flags,
+    worker_routines, workers,
 };
 
 #[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
@@ -48,6 +54,10 @@ pub struct Player {
     pub karma: i64,
     pub display_name: String,
     pub story_state: StoryState,
+    pub last_active: NaiveDateTime,
+    pub banned: bool,
+    /// Code to invite other players, their villages are placed close to the own villages
+    pub referral_code: String,
 }
 
 #[cfg(feature = "sql_db")]
@@ -57,10 +67,41 @@ pub struct NewPlayer {
     pub uuid: uuid::Uuid,
     pub karma: i64,
     pub display_name: String,
+    pub referral_code: String,
+    pub last_active: NaiveDateTime,
+}
+
+#[cfg(feature = "sql_db")]
+#[derive(Debug, Queryable, Insertable, Clone, Copy)]
+#[table_name = "player_season_stats"]
+/// Scores of a player in a single leaderboard season
+pub struct PlayerSeasonStats {
+    pub player_id: i64,
+    pub season: i32,
+    pub karma: i64,
+    pub visitors_welcomed: i64,
+}
+
+#[cfg(feature = "sql_db")]
+#[derive(Debug, Queryable, Clone, Copy)]
+/// A quest of which the player has collected the rewards
+pub struct CompletedQuest {
+    pub player_id: i64,
+    pub quest: QuestName,
+    pub completed: NaiveDateTime,
 }
 
 #[cfg(feature = "sql_db")]
-#[derive(Debug, Queryable, Identifiable, AsChangeset)]
+#[derive(Insertable, Debug)]
+#[table_name = "completed_quests"]
+pub struct NewCompletedQuest {
+    pub player_id: i64,
+    pub quest: QuestName,
+    pub completed: NaiveDateTime,
+}
+
+#[cfg(feature = "sql_db")]
+#[derive(Debug, Queryable, Identifiable, AsChangeset, Clone)]
 pub struct Worker {
     pub id: i64,
     pub home: i64,
@@ -100,6 +141,8 @@ pub struct Hobo {
     /// If in a hurry, hobos will not stop in a town they are visiting but swim through directly
     pub hurried: bool,
     pub nest: Option<i64>,
+    /// For hobos that have settled in a nest, the time up to which their production has been collected
+    pub last_production: Option<NaiveDateTime>,
 }
 
 #[cfg(feature = "sql_db")]
@@ -115,7 +158,7 @@ pub struct NewHobo {
 }
 
 #[cfg(feature = "sql_db")]
-#[derive(Debug, Queryable, Identifiable)]
+#[derive(Debug, Queryable, Identifiable, Clone)]
 pub struct Attack {
     pub id: i64,
     pub departure: NaiveDateTime,
@@ -136,7 +179,7 @@ pub struct NewAttack {
 }
 
 #[cfg(feature = "sql_db")]
-#[derive(Debug, Queryable, Insertable)]
+#[derive(Debug, Queryable, Insertable, Clone)]
 #[table_name = "attacks_to_hobos"]
 pub struct AttackToHobo {
     pub attack_id: i64,
@@ -162,10 +205,11 @@ pub enum BuildingType {
     Temple,
     SingleNest,
     TripleNest,
+    Shack,
 }
 
 #[cfg(feature = "sql_db")]
-#[derive(Queryable, Debug)]
+#[derive(Queryable, Debug, Clone)]
 pub struct Building {
     pub id: i64,
     pub x: i32,
@@ -224,6 +268,20 @@ pub enum TaskType {
     ChopTree,
     WelcomeAbility,
     CollectReward,
+    PlantTree,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "enum_utils", derive(EnumIter, Display))]
+#[cfg_attr(feature = "graphql", derive(juniper::GraphQLEnum))]
+#[cfg_attr(feature = "sql_db", DieselType = "Task_condition", derive(DbEnum))]
+/// Condition under which a step of a routine is performed, otherwise the step is skipped.
+/// Abilities used in a routine pick their target according to the condition.
+pub enum TaskCondition {
+    /// Any unhurried visitor is resting in the town
+    RestingVisitor,
+    /// A resting visitor has not been welcomed by anyone, yet
+    UnwelcomedVisitor,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, AsChangeset)]
@@ -250,6 +308,30 @@ pub struct NewTask {
     pub target_hobo_id: Option<i64>,
 }
 
+#[derive(Debug, Clone, Copy, Queryable, Identifiable, Insertable, AsChangeset)]
+#[cfg(feature = "sql_db")]
+#[primary_key(worker_id)]
+/// A cycle of tasks the worker repeats, until the player gives it new orders
+pub struct WorkerRoutine {
+    pub worker_id: i64,
+    pub next_step: i32,
+    pub next_step_time: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable)]
+#[cfg(feature = "sql_db")]
+/// Template for a task of a routine, it is turned into a concrete `Task` whenever the routine reaches it
+pub struct RoutineTask {
+    pub worker_id: i64,
+    pub step: i32,
+    pub task_type: TaskType,
+    pub x: i32,
+    pub y: i32,
+    /// Seconds the worker stays with this task, in addition to the time the task itself takes
+    pub duration: Option<i64>,
+    pub task_condition: Option<TaskCondition>,
+}
+
 #[derive(Debug, Clone, Queryable, Identifiable)]
 #[cfg(feature = "sql_db")]
 pub struct Stream {
@@ -380,6 +462,7 @@ pub struct VisitReport {
 pub struct NewVisitReport {
     pub village_id: i64,
     pub karma: i64,
+    pub reported: NaiveDateTime,
 }
 
 #[derive(Debug, Clone, Copy, Queryable)]
@@ -399,3 +482,122 @@ pub struct NewReward {
     pub resource_type: ResourceType,
     pub amount: i64,
 }
+
+#[derive(Debug, Clone, Copy, Queryable, Identifiable)]
+#[cfg(feature = "sql_db")]
+/// Archive of a finished visit, with enough information to simulate it again
+pub struct VisitReplay {
+    pub id: i64,
+    pub village_id: i64,
+    pub arrival: NaiveDateTime,
+    pub finished: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[cfg(feature = "sql_db")]
+#[table_name = "visit_replays"]
+pub struct NewVisitReplay {
+    pub village_id: i64,
+    pub arrival: NaiveDateTime,
+    pub finished: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, Queryable, Identifiable)]
+#[cfg(feature = "sql_db")]
+/// Copy of a hobo as it was when its visit ended
+pub struct ReplayHobo {
+    pub id: i64,
+    pub replay_id: i64,
+    pub color: Option<UnitColor>,
+    pub speed: f32,
+    pub hp: i64,
+    pub hurried: bool,
+    pub released: Option<NaiveDateTime>,
+    pub satisfied: bool,
+}
+
+#[derive(Insertable, Debug)]
+#[cfg(feature = "sql_db")]
+#[table_name = "replay_hobos"]
+pub struct NewReplayHobo {
+    pub replay_id: i64,
+    pub color: Option<UnitColor>,
+    pub speed: f32,
+    pub hp: i64,
+    pub hurried: bool,
+    pub released: Option<NaiveDateTime>,
+    pub satisfied: bool,
+}
+
+#[derive(Debug, Clone, Copy, Queryable)]
+#[cfg(feature = "sql_db")]
+pub struct ReplayEffect {
+    pub id: i64,
+    pub replay_hobo_id: i64,
+    pub attribute: HoboAttributeType,
+    pub strength: Option<i32>,
+    pub start_time: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[cfg(feature = "sql_db")]
+#[table_name = "replay_effects"]
+pub struct NewReplayEffect {
+    pub replay_hobo_id: i64,
+    pub attribute: HoboAttributeType,
+    pub strength: Option<i32>,
+    pub start_time: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Queryable)]
+#[cfg(feature = "sql_db")]
+/// Copy of a building in the town that has been visited
+pub struct ReplayBuilding {
+    pub id: i64,
+    pub replay_id: i64,
+    pub x: i32,
+    pub y: i32,
+    pub building_type: BuildingType,
+    pub building_range: Option<f32>,
+    pub attack_power: Option<i32>,
+    pub attacks_per_cycle: Option<i32>,
+    pub creation: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[cfg(feature = "sql_db")]
+#[table_name = "replay_buildings"]
+pub struct NewReplayBuilding {
+    pub replay_id: i64,
+    pub x: i32,
+    pub y: i32,
+    pub building_type: BuildingType,
+    pub building_range: Option<f32>,
+    pub attack_power: Option<i32>,
+    pub attacks_per_cycle: Option<i32>,
+    pub creation: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Queryable, Identifiable)]
+#[cfg(feature = "sql_db")]
+#[table_name = "messages"]
+/// A letter written by one player to another
+pub struct PlayerMessage {
+    pub id: i64,
+    /// None if the sender has been deleted in the meantime
+    pub sender_id: Option<i64>,
+    pub receiver_id: i64,
+    pub body: String,
+    pub sent: NaiveDateTime,
+    pub read: bool,
+}
+
+#[derive(Insertable, Debug)]
+#[cfg(feature = "sql_db")]
+#[table_name = "messages"]
+pub struct NewPlayerMessage {
+    pub sender_id: Option<i64>,
+    pub receiver_id: i64,
+    pub body: String,
+    pub sent: NaiveDateTime,
+}
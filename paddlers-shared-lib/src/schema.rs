@@ -51,6 +51,17 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    completed_quests (player_id, quest) {
+        player_id -> Int8,
+        quest -> Quest_name,
+        completed -> Timestamp,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::models::*;
@@ -76,6 +87,43 @@ table! {
         hp -> Int8,
         hurried -> Bool,
         nest -> Nullable<Int8>,
+        last_production -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    map_chunks (id) {
+        id -> Int4,
+        generated -> Timestamp,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    messages (id) {
+        id -> Int8,
+        sender_id -> Nullable<Int8>,
+        receiver_id -> Int8,
+        body -> Text,
+        sent -> Timestamp,
+        read -> Bool,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    player_season_stats (player_id, season) {
+        player_id -> Int8,
+        season -> Int4,
+        karma -> Int8,
+        visitors_welcomed -> Int8,
     }
 }
 
@@ -89,6 +137,55 @@ table! {
         karma -> Int8,
         display_name -> Varchar,
         story_state -> Story_state_type,
+        last_active -> Timestamp,
+        banned -> Bool,
+        referral_code -> Varchar,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    replay_buildings (id) {
+        id -> Int8,
+        replay_id -> Int8,
+        x -> Int4,
+        y -> Int4,
+        building_type -> Building_type,
+        building_range -> Nullable<Float4>,
+        attack_power -> Nullable<Int4>,
+        attacks_per_cycle -> Nullable<Int4>,
+        creation -> Timestamp,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    replay_effects (id) {
+        id -> Int8,
+        replay_hobo_id -> Int8,
+        attribute -> Hobo_attribute_type,
+        strength -> Nullable<Int4>,
+        start_time -> Timestamp,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    replay_hobos (id) {
+        id -> Int8,
+        replay_id -> Int8,
+        color -> Nullable<Unit_color>,
+        speed -> Float4,
+        hp -> Int8,
+        hurried -> Bool,
+        released -> Nullable<Timestamp>,
+        satisfied -> Bool,
     }
 }
 
@@ -115,6 +212,31 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    routine_tasks (worker_id, step) {
+        worker_id -> Int8,
+        step -> Int4,
+        task_type -> Task_type,
+        x -> Int4,
+        y -> Int4,
+        duration -> Nullable<Int8>,
+        task_condition -> Nullable<Task_condition>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    shared_clock (id) {
+        id -> Int4,
+        advanced_us -> Int8,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::models::*;
@@ -167,6 +289,18 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    visit_replays (id) {
+        id -> Int8,
+        village_id -> Int8,
+        arrival -> Timestamp,
+        finished -> Timestamp,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::models::*;
@@ -178,6 +312,17 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    worker_routines (worker_id) {
+        worker_id -> Int8,
+        next_step -> Int4,
+        next_step_time -> Timestamp,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::models::*;
@@ -200,17 +345,26 @@ joinable!(abilities -> workers (worker_id));
 joinable!(attacks_to_hobos -> attacks (attack_id));
 joinable!(attacks_to_hobos -> hobos (hobo_id));
 joinable!(buildings -> villages (village_id));
+joinable!(completed_quests -> players (player_id));
 joinable!(effects -> hobos (hobo_id));
 joinable!(hobos -> buildings (nest));
 joinable!(hobos -> villages (home));
+joinable!(messages -> players (receiver_id));
+joinable!(player_season_stats -> players (player_id));
+joinable!(replay_buildings -> visit_replays (replay_id));
+joinable!(replay_effects -> replay_hobos (replay_hobo_id));
+joinable!(replay_hobos -> visit_replays (replay_id));
 joinable!(resources -> villages (village_id));
 joinable!(rewards -> visit_reports (visit_report_id));
+joinable!(routine_tasks -> worker_routines (worker_id));
 joinable!(tasks -> hobos (target_hobo_id));
 joinable!(tasks -> workers (worker_id));
 joinable!(villages -> players (player_id));
 joinable!(villages -> streams (stream_id));
+joinable!(visit_replays -> villages (village_id));
 joinable!(visit_reports -> villages (village_id));
 joinable!(worker_flags -> workers (worker_id));
+joinable!(worker_routines -> workers (worker_id));
 joinable!(workers -> villages (home));
 
 allow_tables_to_appear_in_same_query!(
@@ -218,15 +372,26 @@ allow_tables_to_appear_in_same_query!(
     attacks,
     attacks_to_hobos,
     buildings,
+    completed_quests,
     effects,
     hobos,
+    map_chunks,
+    messages,
+    player_season_stats,
     players,
+    replay_buildings,
+    replay_effects,
+    replay_hobos,
     resources,
     rewards,
+    routine_tasks,
+    shared_clock,
     streams,
     tasks,
     villages,
+    visit_replays,
     visit_reports,
     worker_flags,
+    worker_routines,
     workers,
 );
@@ -85,3 +85,12 @@ impl From<NaiveDateTime> for Timestamp {
         Timestamp::from_us(other.timestamp() * 1_000_000 + other.timestamp_subsec_micros() as i64)
     }
 }
+impl From<Timestamp> for NaiveDateTime {
+    fn from(other: Timestamp) -> Self {
+        let us = other.micros();
+        NaiveDateTime::from_timestamp(
+            us.div_euclid(1_000_000),
+            us.rem_euclid(1_000_000) as u32 * 1000,
+        )
+    }
+}
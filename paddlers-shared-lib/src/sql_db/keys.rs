@@ -28,6 +28,21 @@ impl VisitReport {
     }
 }
 
+impl VisitReplay {
+    pub fn village(&self) -> VillageKey {
+        VillageKey(self.village_id)
+    }
+}
+
+impl PlayerMessage {
+    pub fn sender(&self) -> Option<PlayerKey> {
+        self.sender_id.map(PlayerKey)
+    }
+    pub fn receiver(&self) -> PlayerKey {
+        PlayerKey(self.receiver_id)
+    }
+}
+
 impl Attack {
     pub fn destination(&self) -> VillageKey {
         VillageKey(self.destination_village_id)
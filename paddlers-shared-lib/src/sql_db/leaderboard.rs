@@ -0,0 +1,72 @@
+//! Raw SQL for leaderboard rankings.
+//!
+//! Diesel 1.4 has no proper support for GROUP BY, hence the scores are computed in plain SQL.
+//! Each category defines a score table with columns (player_id, score).
+//! Players without any score in a category do not show up in its ranking.
+
+use crate::leaderboard::LeaderboardCategory;
+use diesel::sql_types::BigInt;
+
+#[derive(QueryableByName, Debug, Clone, Copy)]
+pub struct ScoreRow {
+    #[sql_type = "BigInt"]
+    pub player_id: i64,
+    #[sql_type = "BigInt"]
+    pub score: i64,
+}
+
+#[derive(QueryableByName, Debug, Clone, Copy)]
+pub(super) struct RankRow {
+    #[sql_type = "BigInt"]
+    pub rank: i64,
+}
+
+/// SQL selecting (player_id, score) for a category.
+/// Seasonal categories expect the season as bind parameter $1.
+fn scores(category: LeaderboardCategory) -> &'static str {
+    match category {
+        LeaderboardCategory::Karma => "SELECT id AS player_id, karma AS score FROM players",
+        LeaderboardCategory::SeasonKarma => {
+            "SELECT player_id, karma AS score FROM player_season_stats WHERE season = $1"
+        }
+        LeaderboardCategory::VisitorsWelcomed => {
+            "SELECT player_id, visitors_welcomed AS score FROM player_season_stats WHERE season = $1"
+        }
+        LeaderboardCategory::Villages => {
+            "SELECT player_id, COUNT(*) AS score FROM villages \
+             WHERE player_id IS NOT NULL GROUP BY player_id"
+        }
+        LeaderboardCategory::Prophets => {
+            "SELECT v.player_id AS player_id, COUNT(*) AS score FROM hobos h \
+             JOIN villages v ON h.home = v.id \
+             WHERE h.color = 'prophet' AND v.player_id IS NOT NULL GROUP BY v.player_id"
+        }
+    }
+}
+
+/// Binds: $1 season, $2 limit, $3 offset
+///
+/// The season is always bound, even for categories that do not use it.
+pub(super) fn ranking_query(category: LeaderboardCategory) -> String {
+    format!(
+        "SELECT player_id, score FROM ({}) AS scores \
+         ORDER BY score DESC, player_id LIMIT $2 OFFSET $3",
+        scores(category)
+    )
+}
+
+/// Binds: $1 season, $2 player_id
+///
+/// Uses the same order as `ranking_query`, hence ties between players are broken by player id.
+/// The rank is counted from the players ahead, which the indices in the same order can answer
+/// without sorting the entire ranking. No row is returned if the player has no score.
+pub(super) fn rank_query(category: LeaderboardCategory) -> String {
+    // The scores are not put in a CTE, which would be materialized and hide the indices
+    format!(
+        "SELECT 1 + (SELECT COUNT(*) FROM ({0}) AS ahead \
+             WHERE ahead.score > mine.score \
+             OR (ahead.score = mine.score AND ahead.player_id < mine.player_id)) AS rank \
+         FROM ({0}) AS mine WHERE mine.player_id = $2",
+        scores(category)
+    )
+}
@@ -4,6 +4,7 @@ use dotenv::dotenv;
 use std::env;
 
 pub mod keys;
+pub mod leaderboard;
 pub mod sql;
 
 embed_migrations!();
@@ -1,5 +1,9 @@
+use super::leaderboard;
+use crate::activity::should_record_activity;
+use crate::leaderboard::LeaderboardCategory;
 use crate::prelude::*;
 use crate::schema::*;
+use crate::story::quest::{QuestName, QuestProgress};
 use diesel::prelude::*;
 
 pub trait GameDB {
@@ -19,6 +23,38 @@ pub trait GameDB {
             .optional()
             .expect("Error loading data")
     }
+    fn player_by_referral_code(&self, code: &str) -> Option<Player> {
+        players::table
+            .filter(players::referral_code.eq(code))
+            .first(self.dbconn())
+            .optional()
+            .expect("Error loading data")
+    }
+    fn player_by_name(&self, display_name: &str) -> Option<Player> {
+        players::table
+            .filter(players::display_name.eq(display_name))
+            .first(self.dbconn())
+            .optional()
+            .expect("Error loading data")
+    }
+    /// Updates the last activity of a player, unless it has been updated only recently
+    fn record_activity(&self, player: &Player) {
+        let now = crate::game_clock::now();
+        if should_record_activity(player.last_active, now) {
+            diesel::update(players::table.find(player.id))
+                .set(players::last_active.eq(now))
+                .execute(self.dbconn())
+                .expect("Error updating activity");
+        }
+    }
+    /// Total amount by which the game clock has been advanced manually
+    fn shared_clock_advance(&self) -> chrono::Duration {
+        let us = shared_clock::table
+            .select(shared_clock::advanced_us)
+            .first(self.dbconn())
+            .expect("Error loading data");
+        chrono::Duration::microseconds(us)
+    }
     fn player_by_village(&self, vid: VillageKey) -> Option<Player> {
         villages::table
             .filter(villages::id.eq(vid.num()))
@@ -77,7 +113,7 @@ pub trait GameDB {
             .inner_join(tasks::table)
             .filter(workers::home.eq(village.num()))
             .filter(tasks::task_type.eq_any(jobs))
-            .filter(tasks::start_time.lt(diesel::dsl::now.at_time_zone("UTC")))
+            .filter(tasks::start_time.lt(crate::game_clock::now()))
             .select(workers::all_columns)
             .distinct()
             .load::<Worker>(self.dbconn())
@@ -175,7 +211,7 @@ pub trait GameDB {
             // condition for "resting"
             .filter(hobos::hurried.eq(false))
             .filter(attacks_to_hobos::satisfied.is_null())
-            .filter(attacks::arrival.le(diesel::dsl::now.at_time_zone("UTC")))
+            .filter(attacks::arrival.le(crate::game_clock::now()))
             //
             .order_by(attacks::arrival.asc())
             .select((hobos::all_columns, attacks::id))
@@ -256,7 +292,7 @@ pub trait GameDB {
     fn past_worker_tasks(&self, worker_id: WorkerKey) -> Vec<Task> {
         let results = tasks::table
             .filter(tasks::worker_id.eq(worker_id.num()))
-            .filter(tasks::start_time.lt(diesel::dsl::now.at_time_zone("UTC")))
+            .filter(tasks::start_time.lt(crate::game_clock::now()))
             .order(tasks::start_time.asc())
             .limit(500)
             .load::<Task>(self.dbconn())
@@ -266,7 +302,7 @@ pub trait GameDB {
     fn earliest_future_task(&self, worker_id: WorkerKey) -> Option<Task> {
         tasks::table
             .filter(tasks::worker_id.eq(worker_id.num()))
-            .filter(tasks::start_time.ge(diesel::dsl::now.at_time_zone("UTC")))
+            .filter(tasks::start_time.ge(crate::game_clock::now()))
             .order(tasks::start_time.asc())
             .first(self.dbconn())
             .optional()
@@ -290,7 +326,7 @@ pub trait GameDB {
     fn current_task(&self, worker_id: WorkerKey) -> Option<Task> {
         tasks::table
             .filter(tasks::worker_id.eq(worker_id.num()))
-            .filter(tasks::start_time.le(diesel::dsl::now.at_time_zone("UTC")))
+            .filter(tasks::start_time.le(crate::game_clock::now()))
             .order(tasks::start_time.asc())
             .first(self.dbconn())
             .optional()
@@ -303,6 +339,20 @@ pub trait GameDB {
             .optional()
             .expect("Error loading task")
     }
+    fn worker_routine(&self, worker_id: WorkerKey) -> Option<WorkerRoutine> {
+        worker_routines::table
+            .find(worker_id.num())
+            .first(self.dbconn())
+            .optional()
+            .expect("Error loading worker routine")
+    }
+    fn routine_tasks(&self, worker_id: WorkerKey) -> Vec<RoutineTask> {
+        routine_tasks::table
+            .filter(routine_tasks::worker_id.eq(worker_id.num()))
+            .order_by(routine_tasks::step.asc())
+            .load(self.dbconn())
+            .expect("Error loading routine tasks")
+    }
     fn stream(&self, id: StreamKey) -> Stream {
         let result = streams::table.find(id.num()).first(self.dbconn());
         match result {
@@ -320,6 +370,14 @@ pub trait GameDB {
             .expect("Error loading data");
         results
     }
+    /// Indices of all generated map chunks, in ascending order
+    fn map_chunks(&self) -> Vec<i32> {
+        map_chunks::table
+            .select(map_chunks::id)
+            .order(map_chunks::id.asc())
+            .load(self.dbconn())
+            .expect("Error loading data")
+    }
     fn village(&self, village: VillageKey) -> Option<Village> {
         villages::table
             .find(village.num())
@@ -365,6 +423,24 @@ pub trait GameDB {
             .expect("Error loading data");
         results
     }
+    /// Villages between low_x and high_x with the karma of their owner, if they have one
+    fn villages_with_owner_karma(&self, low_x: f32, high_x: f32) -> Vec<(Village, Option<i64>)> {
+        villages::table
+            .left_join(players::table)
+            .filter(villages::x.ge(low_x))
+            .filter(villages::x.le(high_x))
+            .select((villages::all_columns, players::karma.nullable()))
+            .load(self.dbconn())
+            .expect("Error loading data")
+    }
+    /// All villages owned by a player, together with the last activity of the owner
+    fn player_villages_with_activity(&self) -> Vec<(Village, chrono::NaiveDateTime)> {
+        villages::table
+            .inner_join(players::table)
+            .select((villages::all_columns, players::last_active))
+            .load(self.dbconn())
+            .expect("Error loading data")
+    }
     fn village_hobos(&self, v: VillageKey) -> Vec<Hobo> {
         let results = hobos::table
             .filter(hobos::home.eq(v.num()))
@@ -484,4 +560,135 @@ pub trait GameDB {
             .load::<(ResourceType, i64)>(self.dbconn())
             .expect("Error loading rewards")
     }
+    /// Archived visits of a village, latest first
+    fn visit_replays(&self, v: VillageKey) -> Vec<VisitReplay> {
+        visit_replays::table
+            .filter(visit_replays::village_id.eq(v.num()))
+            .order_by(visit_replays::finished.desc())
+            .load::<VisitReplay>(self.dbconn())
+            .expect("Error loading visit replays")
+    }
+    fn replay_hobos(&self, replay: VisitReplayKey) -> Vec<ReplayHobo> {
+        replay_hobos::table
+            .filter(replay_hobos::replay_id.eq(replay.num()))
+            .order_by(replay_hobos::id)
+            .load::<ReplayHobo>(self.dbconn())
+            .expect("Error loading replay hobos")
+    }
+    fn replay_effects(&self, hobo: ReplayHoboKey) -> Vec<ReplayEffect> {
+        replay_effects::table
+            .filter(replay_effects::replay_hobo_id.eq(hobo.num()))
+            .load::<ReplayEffect>(self.dbconn())
+            .expect("Error loading replay effects")
+    }
+    fn replay_buildings(&self, replay: VisitReplayKey) -> Vec<ReplayBuilding> {
+        replay_buildings::table
+            .filter(replay_buildings::replay_id.eq(replay.num()))
+            .load::<ReplayBuilding>(self.dbconn())
+            .expect("Error loading replay buildings")
+    }
+    fn message(&self, id: MessageKey) -> Option<PlayerMessage> {
+        messages::table
+            .find(id.num())
+            .first(self.dbconn())
+            .optional()
+            .expect("Error loading message")
+    }
+    fn messages(&self, receiver: PlayerKey, min_id: Option<i64>) -> Vec<PlayerMessage> {
+        messages::table
+            .filter(messages::receiver_id.eq(receiver.num()))
+            .filter(messages::id.ge(min_id.unwrap_or(0)))
+            .order_by(messages::sent.desc())
+            .limit(500)
+            .load::<PlayerMessage>(self.dbconn())
+            .expect("Error loading messages")
+    }
+    fn unread_messages_count(&self, receiver: PlayerKey) -> i64 {
+        messages::table
+            .filter(messages::receiver_id.eq(receiver.num()))
+            .filter(messages::read.eq(false))
+            .select(diesel::dsl::count(messages::id))
+            .first(self.dbconn())
+            .expect("Error in look up")
+    }
+    fn messages_sent_since(&self, sender: PlayerKey, since: chrono::NaiveDateTime) -> i64 {
+        messages::table
+            .filter(messages::sender_id.eq(sender.num()))
+            .filter(messages::sent.ge(since))
+            .select(diesel::dsl::count(messages::id))
+            .first(self.dbconn())
+            .expect("Error in look up")
+    }
+    fn completed_quests(&self, player: PlayerKey) -> Vec<QuestName> {
+        completed_quests::table
+            .filter(completed_quests::player_id.eq(player.num()))
+            .select(completed_quests::quest)
+            .load(self.dbconn())
+            .expect("Error loading completed quests")
+    }
+    /// Everything quest objectives are checked against, collected over all villages and seasons
+    fn quest_progress(&self, player: &Player) -> QuestProgress {
+        let visitors_welcomed = player_season_stats::table
+            .filter(player_season_stats::player_id.eq(player.id))
+            .select(player_season_stats::visitors_welcomed)
+            .load::<i64>(self.dbconn())
+            .expect("Error loading season stats")
+            .into_iter()
+            .sum();
+        let buildings = buildings::table
+            .inner_join(villages::table)
+            .filter(villages::player_id.eq(player.id))
+            .select(buildings::building_type)
+            .load(self.dbconn())
+            .expect("Error loading buildings");
+        QuestProgress {
+            karma: player.karma,
+            visitors_welcomed,
+            buildings,
+        }
+    }
+    /// Returns (player, score) pairs of a leaderboard, sorted from best to worst
+    fn leaderboard(
+        &self,
+        category: LeaderboardCategory,
+        season: i32,
+        start_index: i64,
+        limit: i64,
+    ) -> Vec<(Player, i64)> {
+        let rows = diesel::sql_query(leaderboard::ranking_query(category))
+            .bind::<diesel::sql_types::Integer, _>(season)
+            .bind::<diesel::sql_types::BigInt, _>(limit)
+            .bind::<diesel::sql_types::BigInt, _>(start_index)
+            .load::<leaderboard::ScoreRow>(self.dbconn())
+            .expect("Error loading leaderboard");
+        let ids: Vec<i64> = rows.iter().map(|row| row.player_id).collect();
+        let players = players::table
+            .filter(players::id.eq_any(ids))
+            .load::<Player>(self.dbconn())
+            .expect("Error loading data");
+        rows.into_iter()
+            .filter_map(|row| {
+                players
+                    .iter()
+                    .find(|p| p.id == row.player_id)
+                    .map(|p| (p.clone(), row.score))
+            })
+            .collect()
+    }
+    /// Position of a player in a leaderboard, starting at 1.
+    /// None if the player has no score in that category.
+    fn leaderboard_rank(
+        &self,
+        category: LeaderboardCategory,
+        season: i32,
+        player: PlayerKey,
+    ) -> Option<i64> {
+        diesel::sql_query(leaderboard::rank_query(category))
+            .bind::<diesel::sql_types::Integer, _>(season)
+            .bind::<diesel::sql_types::BigInt, _>(player.num())
+            .get_result::<leaderboard::RankRow>(self.dbconn())
+            .optional()
+            .expect("Error in look up")
+            .map(|row| row.rank)
+    }
 }
@@ -1 +1,3 @@
+pub mod quest;
+pub mod scene;
 pub mod story_state;
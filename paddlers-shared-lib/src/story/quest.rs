@@ -0,0 +1,194 @@
+//! Quests guide players once they are through the introduction story.
+//!
+//! A quest becomes available when the player has reached its story state and completed the preceding quest.
+//! Objectives are checked by the game master when the player collects the rewards.
+//! Completed quests are stored in the database.
+
+use super::scene::SceneIndex;
+use super::story_state::StoryState;
+use crate::models::{AbilityType, BuildingType, ResourceType};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "sql_db")]
+use ::diesel_derive_enum::DbEnum;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "enum_utils", derive(EnumIter, Display))]
+#[cfg_attr(feature = "graphql", derive(juniper::GraphQLEnum))]
+#[cfg_attr(feature = "sql_db", DieselType = "Quest_name", derive(DbEnum))]
+pub enum QuestName {
+    PlantTrees,
+    WelcomeVisitors,
+    BuildSawMill,
+    GatherKarma,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuestObjective {
+    /// Have at least this many buildings of a type in the villages of the player
+    Build(BuildingType, i64),
+    /// Total number of visitors welcomed, over all seasons
+    WelcomeVisitors(i64),
+    /// Total karma of the player
+    Karma(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuestReward {
+    Resources(ResourceType, i64),
+    Karma(i64),
+    /// A present building placed in the village of the player, free of charge
+    Present(BuildingType),
+    /// Teaches an ability to all workers of the player, including those hired later
+    Ability(AbilityType),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct QuestDefinition {
+    pub unlocked_at: StoryState,
+    pub after: Option<QuestName>,
+    pub objectives: &'static [QuestObjective],
+    pub rewards: &'static [QuestReward],
+    /// Scene shown after the rewards have been collected
+    pub follow_up: Option<SceneIndex>,
+}
+
+/// Snapshot of everything quest objectives are checked against
+#[derive(Debug, Clone, Default)]
+pub struct QuestProgress {
+    pub karma: i64,
+    pub visitors_welcomed: i64,
+    pub buildings: Vec<BuildingType>,
+}
+
+impl QuestName {
+    pub fn definition(self) -> QuestDefinition {
+        match self {
+            QuestName::PlantTrees => QuestDefinition {
+                unlocked_at: StoryState::GatheringSticks,
+                after: None,
+                objectives: &[QuestObjective::Build(BuildingType::Tree, 3)],
+                rewards: &[QuestReward::Resources(ResourceType::Sticks, 20)],
+                follow_up: Some(SceneIndex::TreesPlanted),
+            },
+            QuestName::WelcomeVisitors => QuestDefinition {
+                unlocked_at: StoryState::GatheringSticks,
+                after: Some(QuestName::PlantTrees),
+                objectives: &[QuestObjective::WelcomeVisitors(10)],
+                rewards: &[
+                    QuestReward::Resources(ResourceType::Feathers, 10),
+                    QuestReward::Karma(50),
+                    QuestReward::Ability(AbilityType::Welcome),
+                ],
+                follow_up: None,
+            },
+            QuestName::BuildSawMill => QuestDefinition {
+                unlocked_at: StoryState::GatheringSticks,
+                after: Some(QuestName::WelcomeVisitors),
+                objectives: &[QuestObjective::Build(BuildingType::SawMill, 1)],
+                rewards: &[
+                    QuestReward::Resources(ResourceType::Logs, 10),
+                    QuestReward::Present(BuildingType::PresentA),
+                ],
+                follow_up: None,
+            },
+            QuestName::GatherKarma => QuestDefinition {
+                unlocked_at: StoryState::GatheringSticks,
+                after: Some(QuestName::BuildSawMill),
+                objectives: &[
+                    QuestObjective::WelcomeVisitors(50),
+                    QuestObjective::Karma(1000),
+                ],
+                rewards: &[
+                    QuestReward::Resources(ResourceType::Feathers, 50),
+                    QuestReward::Present(BuildingType::PresentB),
+                ],
+                follow_up: Some(SceneIndex::QuestsCompleted),
+            },
+        }
+    }
+    /// Quests that are not completed but could be, given the current story state and completed quests
+    pub fn is_available(self, story_state: StoryState, completed: &[QuestName]) -> bool {
+        let def = self.definition();
+        !completed.contains(&self)
+            && story_state >= def.unlocked_at
+            && def.after.map_or(true, |q| completed.contains(&q))
+    }
+    /// Abilities taught by the rewards of the completed quests
+    pub fn learned_abilities(completed: &[QuestName]) -> Vec<AbilityType> {
+        let mut abilities = vec![];
+        for quest in completed {
+            for reward in quest.definition().rewards {
+                if let QuestReward::Ability(ability) = reward {
+                    if !abilities.contains(ability) {
+                        abilities.push(*ability);
+                    }
+                }
+            }
+        }
+        abilities
+    }
+    pub fn objectives_fulfilled(self, progress: &QuestProgress) -> bool {
+        self.definition()
+            .objectives
+            .iter()
+            .all(|o| o.is_fulfilled(progress))
+    }
+}
+
+impl QuestObjective {
+    pub fn current(&self, progress: &QuestProgress) -> i64 {
+        match self {
+            QuestObjective::Build(typ, _) => {
+                progress.buildings.iter().filter(|b| *b == typ).count() as i64
+            }
+            QuestObjective::WelcomeVisitors(_) => progress.visitors_welcomed,
+            QuestObjective::Karma(_) => progress.karma,
+        }
+    }
+    pub fn target(&self) -> i64 {
+        match self {
+            QuestObjective::Build(_, n)
+            | QuestObjective::WelcomeVisitors(n)
+            | QuestObjective::Karma(n) => *n,
+        }
+    }
+    pub fn is_fulfilled(&self, progress: &QuestProgress) -> bool {
+        self.current(progress) >= self.target()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quest_availability() {
+        let state = StoryState::GatheringSticks;
+        assert!(QuestName::PlantTrees.is_available(state, &[]));
+        assert!(!QuestName::PlantTrees.is_available(StoryState::TreePlanted, &[]));
+        assert!(!QuestName::WelcomeVisitors.is_available(state, &[]));
+        let done = [QuestName::PlantTrees];
+        assert!(!QuestName::PlantTrees.is_available(state, &done));
+        assert!(QuestName::WelcomeVisitors.is_available(state, &done));
+    }
+
+    #[test]
+    fn quest_objectives() {
+        let mut progress = QuestProgress {
+            karma: 2000,
+            visitors_welcomed: 50,
+            buildings: vec![
+                BuildingType::Tree,
+                BuildingType::Tree,
+                BuildingType::SawMill,
+            ],
+        };
+        assert!(!QuestName::PlantTrees.objectives_fulfilled(&progress));
+        assert!(QuestName::GatherKarma.objectives_fulfilled(&progress));
+        progress.buildings.push(BuildingType::Tree);
+        assert!(QuestName::PlantTrees.objectives_fulfilled(&progress));
+        progress.karma = 999;
+        assert!(!QuestName::GatherKarma.objectives_fulfilled(&progress));
+    }
+}
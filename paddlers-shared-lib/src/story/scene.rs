@@ -0,0 +1,229 @@
+//! Definitions of story scenes, as they are stored in `scenes/*.json`.
+//!
+//! The game master serves these definitions and the frontend displays them in the dialogue view.
+//! Texts are referenced by keys of the gettext catalogs in `texts/`.
+
+use super::story_state::StoryState;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "enum_utils", derive(EnumIter, Display))]
+pub enum SceneIndex {
+    Entrance,
+    TreesPlanted,
+    QuestsCompleted,
+}
+
+/// Slides are referenced (within a scene) by their index.
+pub type SlideIndex = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StoryAction {
+    OpenScene(SceneIndex, SlideIndex),
+    StoryProgress(StoryState),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SceneDefinition {
+    pub slides: Vec<SlideDefinition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SlideDefinition {
+    pub text_key: String,
+    pub sprite: SceneSprite,
+    #[serde(default)]
+    pub buttons: Vec<SlideButtonDefinition>,
+    #[serde(default)]
+    pub back_button: bool,
+    #[serde(default)]
+    pub next_button: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SlideButtonDefinition {
+    pub text_key: String,
+    #[serde(default)]
+    pub next_slide: Option<SlideIndex>,
+    #[serde(default)]
+    pub next_view: Option<SceneView>,
+    #[serde(default)]
+    pub actions: Vec<StoryAction>,
+}
+
+/// Images that can be shown next to the text of a slide
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SceneSprite {
+    RogerLarge,
+    RogerLargeAstonished,
+    RogerLargeCelebrating,
+    RogerLargeObedient,
+    RogerLargeSad,
+}
+
+/// Views the player can be sent to when leaving a scene
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SceneView {
+    Town,
+    Map,
+    Leaderboard,
+    IncomingVisitors,
+    Letters,
+    Messages,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneError {
+    NoSlides,
+    EmptyTextKey(SlideIndex),
+    NoBackSlide(SlideIndex),
+    NoNextSlide(SlideIndex),
+    SlideOutOfRange(SlideIndex, SlideIndex),
+    DeadEnd(SlideIndex),
+    Unreachable(SlideIndex),
+}
+
+impl SceneDefinition {
+    /// Checks that all slides are reachable in the scene and that players can always continue.
+    pub fn validate(&self) -> Result<(), SceneError> {
+        let n = self.slides.len();
+        if n == 0 {
+            return Err(SceneError::NoSlides);
+        }
+        for (i, slide) in self.slides.iter().enumerate() {
+            if slide.text_key.is_empty() || slide.buttons.iter().any(|b| b.text_key.is_empty()) {
+                return Err(SceneError::EmptyTextKey(i));
+            }
+            if slide.back_button && i == 0 {
+                return Err(SceneError::NoBackSlide(i));
+            }
+            if slide.next_button && i + 1 == n {
+                return Err(SceneError::NoNextSlide(i));
+            }
+            for button in &slide.buttons {
+                if let Some(next) = button.next_slide {
+                    if next >= n {
+                        return Err(SceneError::SlideOutOfRange(i, next));
+                    }
+                }
+            }
+            if !slide.back_button && !slide.next_button && slide.buttons.is_empty() {
+                return Err(SceneError::DeadEnd(i));
+            }
+        }
+        if let Some(i) = self.unreachable_slides().first() {
+            return Err(SceneError::Unreachable(*i));
+        }
+        Ok(())
+    }
+    /// Slides that cannot be reached from the first slide by any of the buttons.
+    /// All referenced slides must exist.
+    fn unreachable_slides(&self) -> Vec<SlideIndex> {
+        let mut reached = vec![false; self.slides.len()];
+        let mut todo = vec![0];
+        while let Some(i) = todo.pop() {
+            if reached[i] {
+                continue;
+            }
+            reached[i] = true;
+            let slide = &self.slides[i];
+            if slide.back_button {
+                todo.push(i - 1);
+            }
+            if slide.next_button {
+                todo.push(i + 1);
+            }
+            todo.extend(slide.buttons.iter().filter_map(|b| b.next_slide));
+        }
+        (0..reached.len()).filter(|i| !reached[*i]).collect()
+    }
+    /// All keys of texts displayed in the scene
+    pub fn text_keys(&self) -> impl Iterator<Item = &str> {
+        self.slides.iter().flat_map(|slide| {
+            std::iter::once(slide.text_key.as_str())
+                .chain(slide.buttons.iter().map(|b| b.text_key.as_str()))
+        })
+    }
+}
+
+impl std::fmt::Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneError::NoSlides => write!(f, "Scene has no slides"),
+            SceneError::EmptyTextKey(i) => write!(f, "Slide {} has an empty text key", i),
+            SceneError::NoBackSlide(i) => {
+                write!(f, "Slide {} has a back button but is the first slide", i)
+            }
+            SceneError::NoNextSlide(i) => {
+                write!(f, "Slide {} has a next button but is the last slide", i)
+            }
+            SceneError::SlideOutOfRange(i, next) => {
+                write!(f, "Button on slide {} leads to missing slide {}", i, next)
+            }
+            SceneError::DeadEnd(i) => write!(f, "Slide {} has no buttons", i),
+            SceneError::Unreachable(i) => write!(f, "Slide {} cannot be reached", i),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slide(back_button: bool, next_button: bool) -> SlideDefinition {
+        SlideDefinition {
+            text_key: "text".to_owned(),
+            sprite: SceneSprite::RogerLarge,
+            buttons: vec![],
+            back_button,
+            next_button,
+        }
+    }
+
+    #[test]
+    fn validate_scene() {
+        let mut scene = SceneDefinition {
+            slides: vec![slide(false, true), slide(true, false)],
+        };
+        assert_eq!(scene.validate(), Ok(()));
+
+        scene.slides[1].next_button = true;
+        assert_eq!(scene.validate(), Err(SceneError::NoNextSlide(1)));
+
+        scene.slides[1] = slide(false, false);
+        assert_eq!(scene.validate(), Err(SceneError::DeadEnd(1)));
+
+        scene.slides[1].buttons.push(SlideButtonDefinition {
+            text_key: "button".to_owned(),
+            next_slide: Some(2),
+            next_view: None,
+            actions: vec![],
+        });
+        assert_eq!(scene.validate(), Err(SceneError::SlideOutOfRange(1, 2)));
+    }
+
+    #[test]
+    fn validate_reachability() {
+        let mut scene = SceneDefinition {
+            slides: vec![slide(false, true), slide(true, true), slide(true, false)],
+        };
+        scene.slides[2].buttons.push(SlideButtonDefinition {
+            text_key: "button".to_owned(),
+            next_slide: None,
+            next_view: None,
+            actions: vec![],
+        });
+        assert_eq!(scene.validate(), Ok(()));
+
+        // Going back to the second slide is not enough to reach it
+        scene.slides[0].next_button = false;
+        scene.slides[0].buttons = scene.slides[2].buttons.clone();
+        assert_eq!(scene.validate(), Err(SceneError::Unreachable(1)));
+
+        scene.slides[0].buttons[0].next_slide = Some(2);
+        assert_eq!(scene.validate(), Ok(()));
+    }
+}
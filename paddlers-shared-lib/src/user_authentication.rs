@@ -1,3 +1,4 @@
+use crate::api::admin::ADMIN_ROLE;
 use crate::config::Config;
 use jsonwebtoken::*;
 use once_cell::sync::OnceCell;
@@ -8,6 +9,15 @@ static RSA_PUB_KEY: OnceCell<Vec<u8>> = OnceCell::new();
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
     sub: String,
+    #[serde(default)]
+    realm_access: RealmAccess,
+}
+
+/// Roles assigned to the user in Keycloak
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RealmAccess {
+    #[serde(default)]
+    roles: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -15,6 +25,7 @@ struct Claims {
 pub struct PadlUser {
     /// Minimal authenticated user identity. Must remain unique among all services.
     pub uuid: uuid::Uuid,
+    admin: bool,
     private: (),
 }
 
@@ -34,7 +45,22 @@ impl PadlUser {
         let uuid = uuid::Uuid::parse_str(&token_parsed.claims.sub)
             .map_err(|_| AuthenticationError::InvalidSubject)?;
 
-        Ok(PadlUser { uuid, private: () })
+        let admin = token_parsed
+            .claims
+            .realm_access
+            .roles
+            .iter()
+            .any(|role| role == ADMIN_ROLE);
+
+        Ok(PadlUser {
+            uuid,
+            admin,
+            private: (),
+        })
+    }
+    /// The user has the role required for the admin API
+    pub fn is_admin(&self) -> bool {
+        self.admin
     }
 }
 